@@ -137,6 +137,8 @@ fn bench_config_validation(c: &mut Criterion) {
             config.worker = WorkerConfig::Cpu {
                 threads: 4,
                 batch_size: 100_000,
+                thread_priority: Default::default(),
+                midstate: true,
             };
             config
         }),
@@ -148,6 +150,10 @@ fn bench_config_validation(c: &mut Criterion) {
                 max_connections: 100,
                 difficulty: StratumDifficulty::Block,
                 rate_ms: 1000,
+                always_clean_jobs: false,
+                job_history_size: 4,
+                session_idle_timeout_secs: 600,
+                extranonce1_size: 4,
             };
             config
         }),
@@ -242,6 +248,8 @@ fn bench_worker_config_creation(c: &mut Criterion) {
             black_box(WorkerConfig::Cpu {
                 threads: 4,
                 batch_size: 100_000,
+                thread_priority: Default::default(),
+                midstate: true,
             });
         });
     });
@@ -254,6 +262,10 @@ fn bench_worker_config_creation(c: &mut Criterion) {
                 max_connections: 100,
                 difficulty: StratumDifficulty::Block,
                 rate_ms: 1000,
+                always_clean_jobs: false,
+                job_history_size: 4,
+                session_idle_timeout_secs: 600,
+                extranonce1_size: 4,
             });
         });
     });
@@ -287,6 +299,8 @@ fn bench_config_merging(c: &mut Criterion) {
             config.worker = WorkerConfig::Cpu {
                 threads: 8,
                 batch_size: 50_000,
+                thread_priority: Default::default(),
+                midstate: true,
             };
             config
         },
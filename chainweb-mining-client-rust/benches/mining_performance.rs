@@ -1,6 +1,6 @@
 //! Performance benchmarks for mining operations
 
-use chainweb_mining_client::core::{ChainId, Nonce, Target, Work};
+use chainweb_mining_client::core::{ChainId, Midstate, Nonce, Target, Work};
 use chainweb_mining_client::utils::units;
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use rand::Rng;
@@ -40,6 +40,39 @@ fn bench_hash_computation(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_midstate_hashing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("midstate_hashing");
+    const NONCES: u64 = 1024;
+    group.throughput(Throughput::Elements(NONCES));
+
+    let mut rng = rand::rng();
+    let mut data = [0u8; 286];
+    rng.fill(&mut data);
+
+    // Inject every nonce and hash the whole header
+    let mut work = Work::from_bytes(data);
+    group.bench_function("full_header", |b| {
+        b.iter(|| {
+            for nonce in 0..NONCES {
+                work.set_nonce(Nonce::new(nonce));
+                black_box(work.hash());
+            }
+        });
+    });
+
+    // Compress the prefix once and only the final block per nonce
+    group.bench_function("midstate", |b| {
+        b.iter(|| {
+            let midstate = Midstate::new(&data);
+            for nonce in 0..NONCES {
+                black_box(midstate.hash(nonce));
+            }
+        });
+    });
+
+    group.finish();
+}
+
 fn bench_nonce_operations(c: &mut Criterion) {
     let mut group = c.benchmark_group("nonce_operations");
 
@@ -209,6 +242,7 @@ fn bench_unit_parsing(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_hash_computation,
+    bench_midstate_hashing,
     bench_nonce_operations,
     bench_target_checking,
     bench_mining_simulation,
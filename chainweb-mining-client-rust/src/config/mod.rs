@@ -1,13 +1,45 @@
 //! Configuration management for the mining client
 
-use crate::error::{Error, Result};
-use crate::protocol::http_pool::get_config_client;
+use crate::error::{Error, NetworkError, Result};
+use crate::protocol::http_pool::{get_config_client, parse_http_header};
+use crate::protocol::retry::RetryPolicy;
 use crate::utils::units;
 use crate::workers::WorkerType;
 use clap::Parser;
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+mod node_url;
+mod schema;
+
+pub use node_url::{DEFAULT_NODE_PORT, DEFAULT_TLS_PORT, NodeUrl, normalize_interface};
+pub use schema::{SchemaFormat, config_schema, example_config};
+
+/// Default upper bound for a stratum share period in seconds
+///
+/// Longer periods are almost always a mistyped fixed difficulty and would
+/// leave miners without feedback for hours.
+pub const DEFAULT_STRATUM_MAX_PERIOD: f64 = 3600.0;
+
+/// Default easiest difficulty level (leading zero bits of the target) a
+/// stratum session is handed in period mode
+pub const DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL: u8 = 42;
+
+/// Default factor by which a stratum session's hash rate has to change for
+/// its difficulty to be recalculated from the latest shares only
+pub const DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR: f64 = 3.0;
+
+/// Default number of external worker processes that may run at once
+pub const DEFAULT_EXTERNAL_MAX_CONCURRENT: usize = 1;
+
+/// Worker used when neither the command line nor a config file selects one,
+/// as in the Haskell client
+pub const DEFAULT_WORKER: &str = "stratum";
 
 /// Stratum difficulty setting
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,9 +53,37 @@ pub enum StratumDifficulty {
     Period(f64),
 }
 
+impl StratumDifficulty {
+    /// Parse a difficulty setting, rejecting share periods above `max_period` seconds
+    pub fn parse_with_max_period(s: &str, max_period: f64) -> Result<Self> {
+        let difficulty = s.parse()?;
+        if let StratumDifficulty::Period(period) = difficulty {
+            check_stratum_period(period, max_period)?;
+        }
+        Ok(difficulty)
+    }
+}
+
+/// Reject share periods that are longer than `max_period` seconds
+fn check_stratum_period(period: f64, max_period: f64) -> Result<()> {
+    if period > max_period {
+        return Err(Error::config_invalid_value(
+            "stratum_difficulty",
+            period.to_string(),
+            format!(
+                "\"block\", a fixed difficulty between 0 and 256 (leading zero bits), or a share period of at most {}s (see --stratum-max-period)",
+                max_period
+            ),
+        ));
+    }
+    Ok(())
+}
+
 impl FromStr for StratumDifficulty {
     type Err = Error;
 
+    /// Parse without an upper bound on the period; use
+    /// [`StratumDifficulty::parse_with_max_period`] for user input
     fn from_str(s: &str) -> Result<Self> {
         match s {
             "block" => Ok(StratumDifficulty::Block),
@@ -34,7 +94,7 @@ impl FromStr for StratumDifficulty {
                         return Err(Error::config_invalid_value(
                             "stratum_difficulty",
                             difficulty.to_string(),
-                            "value between 0 and 256"
+                            "value between 0 and 256",
                         ));
                     }
                     Ok(StratumDifficulty::Fixed(difficulty as u8))
@@ -44,15 +104,435 @@ impl FromStr for StratumDifficulty {
                         return Err(Error::config_invalid_value(
                             "stratum_difficulty",
                             period.to_string(),
-                            "positive number (seconds)"
+                            "positive number (seconds)",
                         ));
                     }
                     Ok(StratumDifficulty::Period(period))
                 } else {
-                    Err(Error::config_invalid_value("stratum_difficulty", n, "number, 'block', or period in seconds"))
+                    Err(Error::config_invalid_value(
+                        "stratum_difficulty",
+                        n,
+                        "number, 'block', or period in seconds",
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Serialization format of a printed configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigFormat {
+    /// YAML, as the Haskell client prints it
+    #[default]
+    Yaml,
+    /// Pretty-printed JSON
+    Json,
+    /// TOML
+    Toml,
+}
+
+impl FromStr for ConfigFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "yaml" => Ok(ConfigFormat::Yaml),
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            _ => Err(Error::config_invalid_value(
+                "print_config_format",
+                s,
+                "yaml, json or toml",
+            )),
+        }
+    }
+}
+
+/// Encoding used when submitting solved work to the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmitEncoding {
+    /// Raw 286 byte header (`application/octet-stream`)
+    #[default]
+    Binary,
+    /// Base64url encoded header as a JSON string (`application/json`)
+    Json,
+}
+
+impl FromStr for SubmitEncoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "binary" => Ok(SubmitEncoding::Binary),
+            "json" => Ok(SubmitEncoding::Json),
+            _ => Err(Error::config_invalid_value(
+                "submit_encoding",
+                s,
+                "json or binary",
+            )),
+        }
+    }
+}
+
+/// Share difficulty semantics of the stratum server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StratumMode {
+    /// Only blocks count: sessions mine at the block difficulty and shares
+    /// that don't meet the block target are rejected
+    Solo,
+    /// Shares below the block difficulty give miners steady feedback; the
+    /// share difficulty follows each session's hash rate unless a fixed
+    /// difficulty or share period is configured
+    Pool,
+}
+
+/// Share period in seconds of pool mode without a configured difficulty
+pub const DEFAULT_POOL_SHARE_PERIOD: f64 = 10.0;
+
+impl StratumMode {
+    /// Difficulty used in this mode in place of the `configured` one
+    pub fn difficulty(self, configured: &StratumDifficulty) -> StratumDifficulty {
+        match (self, configured) {
+            (StratumMode::Solo, _) => StratumDifficulty::Block,
+            (StratumMode::Pool, StratumDifficulty::Block) => {
+                StratumDifficulty::Period(DEFAULT_POOL_SHARE_PERIOD)
+            }
+            (StratumMode::Pool, configured) => configured.clone(),
+        }
+    }
+}
+
+impl FromStr for StratumMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "solo" => Ok(StratumMode::Solo),
+            "pool" => Ok(StratumMode::Pool),
+            _ => Err(Error::config_invalid_value(
+                "stratum_mode",
+                s,
+                "solo or pool",
+            )),
+        }
+    }
+}
+
+/// What to do once no configured node is reachable any more
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeLossPolicy {
+    /// Keep retrying with backoff until a node comes back
+    #[default]
+    Retry,
+    /// Exit with an error, e.g. so an orchestrator restarts the client
+    Exit,
+}
+
+impl FromStr for NodeLossPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "retry" => Ok(NodeLossPolicy::Retry),
+            "exit" => Ok(NodeLossPolicy::Exit),
+            _ => Err(Error::config_invalid_value(
+                "on_node_loss",
+                s,
+                "exit or retry",
+            )),
+        }
+    }
+}
+
+/// OS scheduling priority of mining threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadPriority {
+    /// Leave the priority unchanged
+    #[default]
+    Normal,
+    /// Below normal, interactive work takes precedence
+    Low,
+    /// Only run when the machine is otherwise idle
+    Idle,
+}
+
+impl FromStr for ThreadPriority {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "normal" => Ok(ThreadPriority::Normal),
+            "low" => Ok(ThreadPriority::Low),
+            "idle" => Ok(ThreadPriority::Idle),
+            _ => Err(Error::config_invalid_value(
+                "thread_priority",
+                s,
+                "normal, low or idle",
+            )),
+        }
+    }
+}
+
+/// Most threads derived from the number of detected cores
+///
+/// Core detection in some containers reports the cores of the whole host; an
+/// explicit thread count is not capped.
+pub const MAX_AUTO_THREADS: usize = 512;
+
+/// Number of CPU mining threads, fixed or relative to the number of cores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadCount {
+    /// This many threads
+    Fixed(usize),
+    /// One thread per core
+    Auto,
+    /// This percentage (1-100) of the cores, rounded, at least one thread
+    Percent(u8),
+}
+
+impl ThreadCount {
+    /// Number of threads on a machine with `cores` cores
+    ///
+    /// The detected cores are clamped to between 1 and [`MAX_AUTO_THREADS`],
+    /// as detection may report none or implausibly many.
+    pub fn resolve(self, cores: usize) -> usize {
+        let cores = cores.clamp(1, MAX_AUTO_THREADS);
+        match self {
+            ThreadCount::Fixed(threads) => threads,
+            ThreadCount::Auto => cores,
+            ThreadCount::Percent(percent) => ((cores * percent as usize + 50) / 100).max(1),
+        }
+    }
+}
+
+impl FromStr for ThreadCount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::config_invalid_value(
+                "thread_count",
+                s,
+                "a number of threads, auto or auto:P% with P from 1 to 100",
+            )
+        };
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(ThreadCount::Auto);
+        }
+        match s.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("auto:") => {
+                let percent: u8 = s[5..]
+                    .trim()
+                    .strip_suffix('%')
+                    .and_then(|percent| percent.trim().parse().ok())
+                    .ok_or_else(invalid)?;
+                if !(1..=100).contains(&percent) {
+                    return Err(invalid());
                 }
+                Ok(ThreadCount::Percent(percent))
+            }
+            _ => s.parse().map(ThreadCount::Fixed).map_err(|_| invalid()),
+        }
+    }
+}
+
+/// A mining account and the public key guarding it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiningAccount {
+    /// Account receiving the mining rewards
+    pub account: String,
+    /// Public key of the account
+    #[serde(alias = "publicKey")]
+    pub public_key: String,
+}
+
+impl MiningAccount {
+    /// Parse `PUBLIC_KEY` or `k:PUBLIC_KEY` (account `k:PUBLIC_KEY`) or
+    /// `ACCOUNT=PUBLIC_KEY`
+    pub fn parse(s: &str) -> Self {
+        match s.rsplit_once('=') {
+            Some((account, public_key)) => Self {
+                account: account.to_string(),
+                public_key: public_key.to_string(),
+            },
+            None if s.starts_with("k:") => Self {
+                account: s.to_string(),
+                public_key: s[2..].to_string(),
+            },
+            None => Self {
+                account: format!("k:{}", s),
+                public_key: s.to_string(),
+            },
+        }
+    }
+
+    /// Check that the public key is a hex ed25519 key, that a `k:` account
+    /// belongs to it and that a `w:` account is a well-formed keyset
+    /// principal
+    pub fn validate(&self) -> Result<()> {
+        const KEY_HINT: &str =
+            "an ed25519 public key of 64 hex characters (create one with --generate-key)";
+        if self.public_key.len() != 64 {
+            return Err(Error::config_invalid_value(
+                "public_key",
+                &self.public_key,
+                format!("{}, not {} characters", KEY_HINT, self.public_key.len()),
+            ));
+        }
+        if let Some(c) = self.public_key.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(Error::config_invalid_value(
+                "public_key",
+                &self.public_key,
+                format!("{}, without the non-hex character {:?}", KEY_HINT, c),
+            ));
+        }
+
+        if self.account.is_empty() {
+            return Err(Error::config_invalid_value(
+                "account",
+                "",
+                "non-empty account name",
+            ));
+        }
+        if let Some(key) = self.account.strip_prefix("k:")
+            && key != self.public_key
+        {
+            return Err(Error::config_invalid_value(
+                "account",
+                &self.account,
+                format!("k:{} to match its public key", self.public_key),
+            ));
+        }
+        if let Some(principal) = self.account.strip_prefix("w:") {
+            // w:<base64url keyset hash>:<predicate>
+            let well_formed = principal.split_once(':').is_some_and(|(hash, predicate)| {
+                hash.len() == 43
+                    && hash
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                    && !predicate.is_empty()
+            });
+            if !well_formed {
+                return Err(Error::config_invalid_value(
+                    "account",
+                    &self.account,
+                    "w:<base64url keyset hash of 43 characters>:<predicate>, e.g. w:...:keys-all",
+                ));
             }
         }
+        Ok(())
+    }
+}
+
+/// Keyset guarding the mining account, sent as is in work requests in
+/// place of the single public key keyset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinerKeyset {
+    /// Public keys of the keyset
+    pub keys: Vec<String>,
+    /// Predicate over the keys, e.g. `keys-all`, `keys-any` or `keys-2`
+    pub pred: String,
+}
+
+impl MinerKeyset {
+    /// Parse a JSON keyset object such as `{"keys": ["..."], "pred": "keys-all"}`
+    pub fn from_json(json: &str, source: &str) -> Result<Self> {
+        let keyset: MinerKeyset = serde_json::from_str(json).map_err(|e| {
+            Error::config_parse_error(
+                source,
+                format!("expected a JSON keyset object with keys and pred: {}", e),
+            )
+        })?;
+        if keyset.keys.is_empty() {
+            return Err(Error::config_invalid_value(
+                "keys",
+                "[]",
+                "at least one public key",
+            ));
+        }
+        if keyset.pred.is_empty() {
+            return Err(Error::config_invalid_value(
+                "pred",
+                "",
+                "a predicate such as keys-all, keys-any or keys-2",
+            ));
+        }
+        Ok(keyset)
+    }
+
+    /// Read a JSON keyset from `path`
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            Error::config(format!(
+                "Failed to read miner keyset file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_json(&json, &path.to_string_lossy())
+    }
+}
+
+/// When to move on to the next mining account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AccountRotation {
+    /// After this many solutions accepted by the node
+    Blocks(u64),
+    /// After this many seconds
+    Interval(u64),
+}
+
+impl FromStr for AccountRotation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::config_invalid_value(
+                "account_rotation",
+                s,
+                "blocks:N or interval:SECS with a positive number",
+            )
+        };
+        let (kind, count) = s.split_once(':').ok_or_else(invalid)?;
+        let count: u64 = count.trim().parse().map_err(|_| invalid())?;
+        if count == 0 {
+            return Err(invalid());
+        }
+        match kind.trim().to_lowercase().as_str() {
+            "blocks" => Ok(AccountRotation::Blocks(count)),
+            "interval" => Ok(AccountRotation::Interval(count)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl std::fmt::Display for AccountRotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountRotation::Blocks(blocks) => write!(f, "blocks:{}", blocks),
+            AccountRotation::Interval(secs) => write!(f, "interval:{}", secs),
+        }
+    }
+}
+
+impl TryFrom<String> for AccountRotation {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<AccountRotation> for String {
+    fn from(rotation: AccountRotation) -> Self {
+        rotation.to_string()
     }
 }
 
@@ -88,6 +568,13 @@ pub struct Args {
     #[clap(long = "monitoring-status", help = "Show monitoring status and exit")]
     pub monitoring_status: bool,
 
+    /// Run internal consistency checks and exit
+    #[clap(
+        long = "selftest",
+        help = "Run internal consistency checks (hashing, targets, config, workers) and exit nonzero if any fails"
+    )]
+    pub selftest: bool,
+
     /// Print the parsed configuration to standard out and exit
     #[clap(
         long = "print-config-as",
@@ -103,11 +590,29 @@ pub struct Args {
     )]
     pub print_config: bool,
 
+    /// Serialization format of the printed configuration
+    #[clap(
+        long = "print-config-format",
+        value_name = "yaml|json|toml",
+        help = "Serialization format of the printed configuration, implies --print-config unless --print-config-as is given [default: yaml]"
+    )]
+    pub print_config_format: Option<String>,
+
+    /// Print a description of the configuration format and exit
+    #[clap(
+        long = "config-schema",
+        value_name = "json-schema|example",
+        num_args = 0..=1,
+        default_missing_value = "json-schema",
+        help = "Print a JSON Schema or a commented example of the configuration format and exit"
+    )]
+    pub config_schema: Option<String>,
+
     /// Configuration file in YAML or JSON format
     #[clap(
         long = "config-file",
         value_name = "FILE",
-        help = "Configuration file in YAML or JSON format. If more than a single config file option is present files are loaded in the order in which they appear on the command line."
+        help = "Configuration file in YAML or JSON format, or - to read it from standard input. If more than a single config file option is present files are loaded in the order in which they appear on the command line."
     )]
     pub config_file: Vec<PathBuf>,
 
@@ -119,14 +624,22 @@ pub struct Args {
     )]
     pub hash_rate: Option<String>,
 
-    /// Node to which to connect
+    /// Node to which to connect; repeat for fallback nodes
     #[clap(
         short = 'n',
         long = "node",
         value_name = "DOMAIN:PORT",
-        help = "node to which to connect"
+        help = "node to which to connect. Repeat to add fallback nodes, which are used in the given order when the previous ones keep failing"
     )]
-    pub node: Option<String>,
+    pub node: Vec<String>,
+
+    /// Nodes receiving every solution in addition to the active node
+    #[clap(
+        long = "relay-node",
+        value_name = "DOMAIN:PORT",
+        help = "node to which solutions are also submitted, concurrently with the active node; a solution counts as accepted once any node accepts it. Repeat for several relays"
+    )]
+    pub relay_node: Vec<String>,
 
     /// Use TLS to connect to node
     #[clap(short = 't', long = "tls", help = "use TLS to connect to node")]
@@ -164,13 +677,61 @@ pub struct Args {
     )]
     pub account: Option<String>,
 
+    /// Further accounts to rotate through after the primary one
+    #[clap(
+        long = "rotation-account",
+        value_name = "[ACCOUNT=]PUBLIC_KEY",
+        help = "account to rotate to after the primary one (default account: public-key prefixed with 'k:'). Repeat for more accounts"
+    )]
+    pub rotation_account: Vec<String>,
+
+    /// Account receiving the rewards of a single chain
+    #[clap(
+        long = "chain-account",
+        value_name = "CHAIN=[ACCOUNT=]PUBLIC_KEY",
+        help = "account for the mining rewards of CHAIN instead of the primary one (default account: public-key prefixed with 'k:'). Repeat for more chains"
+    )]
+    pub chain_account: Vec<String>,
+
+    /// When to rotate to the next mining account
+    #[clap(
+        long = "account-rotation",
+        help = "rotate mining accounts after N accepted solutions (blocks:N) or every SECS seconds (interval:SECS)"
+    )]
+    pub account_rotation: Option<String>,
+
     /// Number of concurrent mining threads
     #[clap(
         short = 'c',
         long = "thread-count",
-        help = "number of concurrent mining threads"
+        value_name = "N|auto|auto:P%",
+        help = "number of concurrent mining threads: a fixed number, auto for all cores or auto:P% for P percent of the cores [default: 2]"
     )]
-    pub thread_count: Option<usize>,
+    pub thread_count: Option<String>,
+
+    /// OS priority of CPU mining threads
+    #[clap(
+        long = "thread-priority",
+        value_name = "PRIORITY",
+        help = "OS scheduling priority of CPU mining threads: normal, low or idle [default: normal]"
+    )]
+    pub thread_priority: Option<String>,
+
+    /// Nonce budget of CPU mining per work
+    #[clap(
+        long = "max-nonces-per-work",
+        value_name = "N",
+        help = "Fetch fresh work once the CPU threads searched N nonces of the current work without a solution, 0 disables [default: 0]"
+    )]
+    pub max_nonces_per_work: Option<u64>,
+
+    /// Memory limit of CPU mining buffers
+    #[clap(
+        long = "max-memory-mb",
+        value_name = "MB",
+        help = "Shrink the CPU batch size so the per-thread batch buffers fit in MB megabytes, 0 disables [default: 0]"
+    )]
+    pub max_memory_mb: Option<u64>,
 
     /// Generate a new key pair and exit
     #[clap(long = "generate-key", help = "Generate a new key pair and exit")]
@@ -180,6 +741,30 @@ pub struct Args {
     #[clap(long = "no-generate-key", help = "unset flag generate-key")]
     pub no_generate_key: bool,
 
+    /// Output format for --generate-key and --monitoring-status
+    #[clap(
+        long = "format",
+        value_name = "FORMAT",
+        help = "Output format for --generate-key: plain (default), json, yaml, or env (shell export lines); for --monitoring-status: text (default) or json"
+    )]
+    pub format: Option<String>,
+
+    /// Write the key pair generated by --generate-key to a file
+    #[clap(
+        long = "key-file",
+        value_name = "FILE",
+        help = "Write the key pair generated by --generate-key to FILE (mode 0600) instead of standard out"
+    )]
+    pub key_file: Option<PathBuf>,
+
+    /// JSON keyset sent in work requests
+    #[clap(
+        long = "miner-keyset-file",
+        value_name = "FILE",
+        help = "Request work for the JSON keyset in FILE ({\"keys\": [...], \"pred\": ...}) instead of the public key alone"
+    )]
+    pub miner_keyset_file: Option<PathBuf>,
+
     /// Level at which log messages are written to the console
     #[clap(
         short = 'l',
@@ -189,15 +774,48 @@ pub struct Args {
     )]
     pub log_level: Option<String>,
 
+    /// Only log errors
+    #[clap(
+        short = 'q',
+        long = "quiet",
+        conflicts_with = "verbose",
+        help = "Only log errors, shorthand for --log-level error"
+    )]
+    pub quiet: bool,
+
+    /// Log more, repeatable
+    #[clap(
+        short = 'v',
+        long = "verbose",
+        action = clap::ArgAction::Count,
+        help = "Log more: -v logs at debug, -vv at trace level. --log-level takes precedence"
+    )]
+    pub verbose: u8,
+
+    /// Minimum interval between hashrate log lines
+    #[clap(
+        long = "hashrate-log-interval-secs",
+        value_name = "SECS",
+        help = "Log the hashrate at most every SECS seconds; every sample is still recorded for monitoring [default: 10]"
+    )]
+    pub hashrate_log_interval_secs: Option<u64>,
+
     /// The type of mining worker that is used
     #[clap(
         short = 'w',
         long = "worker",
         value_name = "cpu|gpu|external|simulation|stratum|constant-delay|on-demand",
-        help = "The type of mining worker that is used"
+        help = "The type of mining worker that is used [default: stratum]"
     )]
     pub worker: Option<String>,
 
+    /// Require the worker type to be selected
+    #[clap(
+        long = "strict-config",
+        help = "Fail instead of falling back to the stratum worker when neither --worker nor a config file selects one"
+    )]
+    pub strict_config: bool,
+
     /// Command that is used to call an external worker
     #[clap(
         long = "external-worker-cmd",
@@ -205,6 +823,14 @@ pub struct Args {
     )]
     pub external_worker_cmd: Option<String>,
 
+    /// Maximum number of concurrently running external worker processes
+    #[clap(
+        long = "external-worker-max-concurrent",
+        value_name = "COUNT",
+        help = "Maximum number of external worker processes running at once; new work cancels superseded processes and waits for them to exit [default: 1]"
+    )]
+    pub external_worker_max_concurrent: Option<usize>,
+
     /// The port on which the stratum server listens
     #[clap(
         long = "stratum-port",
@@ -226,6 +852,38 @@ pub struct Args {
     )]
     pub stratum_difficulty: Option<String>,
 
+    /// Upper bound for a period based stratum difficulty
+    #[clap(
+        long = "stratum-max-period",
+        value_name = "SECS",
+        help = "Longest share period in seconds accepted for --stratum-difficulty [default: 3600]"
+    )]
+    pub stratum_max_period: Option<f64>,
+
+    /// Easiest difficulty level handed to stratum sessions in period mode
+    #[clap(
+        long = "stratum-min-difficulty-level",
+        value_name = "LEVEL",
+        help = "Easiest share difficulty, in leading zero bits of the target (1-254), that a period based --stratum-difficulty goes down to [default: 42]"
+    )]
+    pub stratum_min_difficulty_level: Option<u8>,
+
+    /// Hash rate change that resets a stratum session's difficulty
+    #[clap(
+        long = "stratum-hashrate-shift-factor",
+        value_name = "FACTOR",
+        help = "Recalculate a stratum session's period based difficulty from its latest shares alone once its hash rate rose or fell by more than this factor, e.g. after a rig reconfiguration; 0 disables [default: 3]"
+    )]
+    pub stratum_hashrate_shift_factor: Option<f64>,
+
+    /// Share difficulty semantics of the stratum server
+    #[clap(
+        long = "stratum-mode",
+        value_name = "solo|pool",
+        help = "solo: shares must meet the block target, --stratum-difficulty can only be \"block\". pool: shares below the block target are accepted, with the difficulty adjusted to each miner's hash rate unless --stratum-difficulty sets one. Without a mode, --stratum-difficulty applies as given"
+    )]
+    pub stratum_mode: Option<String>,
+
     /// Rate (in milliseconds) at which a stratum worker thread emits jobs
     #[clap(
         short = 's',
@@ -234,6 +892,52 @@ pub struct Args {
     )]
     pub stratum_rate: Option<u64>,
 
+    /// Always mark stratum jobs as clean
+    #[clap(
+        long = "stratum-always-clean-jobs",
+        help = "Always set clean_jobs in mining.notify, even when only the block time changed. Use for miners that misbehave otherwise."
+    )]
+    pub stratum_always_clean_jobs: bool,
+
+    /// Number of recent stratum jobs accepted for submits
+    #[clap(
+        long = "stratum-job-history",
+        help = "Number of recent stratum jobs (including the current one) that shares may be submitted against [default: 4]"
+    )]
+    pub stratum_job_history: Option<usize>,
+
+    /// Disconnect stratum sessions that submit no shares
+    #[clap(
+        long = "stratum-session-idle-timeout",
+        value_name = "SECONDS",
+        help = "Disconnect stratum sessions that submit no shares for this many seconds, 0 disables [default: 600]"
+    )]
+    pub stratum_session_idle_timeout: Option<u64>,
+
+    /// Size of the pool-assigned extranonce1 in bytes
+    #[clap(
+        long = "stratum-extranonce1-size",
+        value_name = "BYTES",
+        help = "Bytes of the 8 byte nonce assigned by the server as extranonce1 (1-7); miners get the remaining bytes as extranonce2 [default: 4]"
+    )]
+    pub stratum_extranonce1_size: Option<u8>,
+
+    /// Audit file of accepted stratum shares
+    #[clap(
+        long = "stratum-audit-file",
+        value_name = "FILE",
+        help = "Append every accepted stratum share to FILE as a JSON line (worker, session, job, nonce, difficulty, block)"
+    )]
+    pub stratum_audit_file: Option<PathBuf>,
+
+    /// Size at which the stratum audit file is rotated
+    #[clap(
+        long = "stratum-audit-max-size",
+        value_name = "MB",
+        help = "Rotate the stratum audit file once it exceeds this many megabytes, 0 disables [default: 100]"
+    )]
+    pub stratum_audit_max_size: Option<u64>,
+
     /// Time at which a constant-delay worker emits blocks
     #[clap(
         long = "constant-delay-block-time",
@@ -255,32 +959,364 @@ pub struct Args {
     )]
     pub on_demand_port: Option<u16>,
 
+    /// Window in which repeated on-demand trigger ids are ignored
+    #[clap(
+        long = "on-demand-replay-window-secs",
+        value_name = "SECS",
+        help = "Seconds during which an on-demand request repeating an earlier Idempotency-Key is rejected [default: 300]"
+    )]
+    pub on_demand_replay_window_secs: Option<u64>,
+
+    /// Minimum interval between on-demand triggers
+    #[clap(
+        long = "on-demand-min-interval-ms",
+        value_name = "MS",
+        help = "Reject on-demand requests arriving less than MS milliseconds after the previous one [default: 0, no limit]"
+    )]
+    pub on_demand_min_interval_ms: Option<u64>,
+
     /// Default HTTP timeout in microseconds
     #[clap(
         long = "default-http-timeout",
         help = "default HTTP timeout in microseconds"
     )]
     pub default_http_timeout: Option<u64>,
-}
 
-/// Main configuration structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    /// Node configuration
-    pub node: NodeConfig,
+    /// Encoding for solution submissions
+    #[clap(
+        long = "submit-encoding",
+        value_name = "binary|json",
+        help = "Encoding for submitting solved work: binary (raw header, default) or json. Binary falls back to json for nodes that don't accept it"
+    )]
+    pub submit_encoding: Option<String>,
 
-    /// Mining configuration
-    pub mining: MiningConfig,
+    /// Chains not to mine on
+    #[clap(
+        long = "exclude-chains",
+        value_name = "CHAINS",
+        value_delimiter = ',',
+        help = "Comma separated chain ids not to mine on, e.g. 3,7,12. Checked against the node's chain count on startup"
+    )]
+    pub exclude_chains: Vec<u16>,
 
-    /// Worker configuration
-    pub worker: WorkerConfig,
+    /// Behavior once every node is unreachable
+    #[clap(
+        long = "on-node-loss",
+        value_name = "exit|retry",
+        help = "What to do once every node's circuit breaker is open: retry with backoff (default) or exit with a nonzero code"
+    )]
+    pub on_node_loss: Option<String>,
 
-    /// Logging configuration
-    pub logging: LoggingConfig,
-}
+    /// Chainweb version used in mining URLs
+    #[clap(
+        long = "network",
+        value_name = "VERSION",
+        help = "Chainweb version used in the mining API path (e.g. mainnet01, testnet04), overriding the version reported by the node"
+    )]
+    pub network: Option<String>,
 
-/// Flat configuration structure (Haskell-compatible)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+    /// DNS cache TTL for node hosts
+    #[clap(
+        long = "dns-refresh-secs",
+        value_name = "SECS",
+        help = "Cache node DNS resolutions for SECS seconds and re-resolve them periodically, reconnecting when an address changes (uses the system resolver on every connection if unset)"
+    )]
+    pub dns_refresh_secs: Option<u64>,
+
+    /// Timeout of work requests
+    #[clap(
+        long = "work-fetch-timeout-secs",
+        value_name = "SECS",
+        help = "Give up on a work request after SECS seconds and ask again [default: the request timeout, at most 10]"
+    )]
+    pub work_fetch_timeout_secs: Option<u64>,
+
+    /// Timeout of solution submissions
+    #[clap(
+        long = "submit-timeout-secs",
+        value_name = "SECS",
+        help = "Give up on a solution submission after SECS seconds [default: the request timeout]"
+    )]
+    pub submit_timeout_secs: Option<u64>,
+
+    /// Extra headers for node requests
+    #[clap(
+        long = "http-header",
+        value_name = "NAME: VALUE",
+        help = "Extra header sent with every node request, e.g. \"X-Miner-Rig: rig-7\". Can be given multiple times"
+    )]
+    pub http_header: Vec<String>,
+
+    /// Validate the system clock against an NTP server at startup
+    #[clap(
+        long = "check-clock",
+        help = "Compare the system clock against an NTP server at startup and refuse to mine if the skew exceeds --max-clock-skew-secs"
+    )]
+    pub check_clock: bool,
+
+    /// NTP server used by --check-clock
+    #[clap(
+        long = "ntp-server",
+        value_name = "HOST[:PORT]",
+        help = "NTP server used by --check-clock [default: pool.ntp.org]"
+    )]
+    pub ntp_server: Option<String>,
+
+    /// Maximum tolerated clock skew for --check-clock
+    #[clap(
+        long = "max-clock-skew-secs",
+        help = "Maximum tolerated difference between the system clock and the NTP server in seconds [default: 10]"
+    )]
+    pub max_clock_skew_secs: Option<u64>,
+
+    /// Watch the mining loop for stalls
+    #[clap(
+        long = "watchdog",
+        help = "Raise a critical alert when the hash count or work updates stop advancing"
+    )]
+    pub watchdog: bool,
+
+    /// Hash count stall threshold for --watchdog
+    #[clap(
+        long = "watchdog-hash-timeout-secs",
+        value_name = "SECS",
+        help = "Seconds the hash count may stand still before --watchdog reports a stall [default: 300]"
+    )]
+    pub watchdog_hash_timeout_secs: Option<u64>,
+
+    /// Work update stall threshold for --watchdog
+    #[clap(
+        long = "watchdog-work-timeout-secs",
+        value_name = "SECS",
+        help = "Seconds without a work update before --watchdog reports a stall [default: 300]"
+    )]
+    pub watchdog_work_timeout_secs: Option<u64>,
+
+    /// Abort on a watchdog stall
+    #[clap(
+        long = "watchdog-abort",
+        help = "Abort the process when the watchdog reports a stall, so an orchestrator can restart it (implies --watchdog)"
+    )]
+    pub watchdog_abort: bool,
+
+    /// Alert on implausibly long stretches without a solution
+    #[clap(
+        long = "dead-mans-switch",
+        help = "Raise a critical alert when no solution was found for far longer than expected at the hash rate and difficulty mined, which points at a broken hasher or target"
+    )]
+    pub dead_mans_switch: bool,
+
+    /// Dry spell threshold for --dead-mans-switch
+    #[clap(
+        long = "dead-mans-switch-multiple",
+        value_name = "N",
+        help = "Expected blocks without a solution before --dead-mans-switch trips; a working miner gets there with a chance of e^-N [default: 20]"
+    )]
+    pub dead_mans_switch_multiple: Option<f64>,
+
+    /// Stop mining when the dead man's switch trips
+    #[clap(
+        long = "dead-mans-switch-halt",
+        help = "Exit with a nonzero code when --dead-mans-switch trips (implies --dead-mans-switch)"
+    )]
+    pub dead_mans_switch_halt: bool,
+
+    /// GPU temperature at which mining pauses
+    #[clap(
+        long = "gpu-max-temp-c",
+        value_name = "CELSIUS",
+        help = "Pause GPU mining while the hottest GPU, as reported by the driver's hwmon sensor, is above this temperature in °C"
+    )]
+    pub gpu_max_temp_c: Option<f64>,
+
+    /// GPU temperature at which paused mining resumes
+    #[clap(
+        long = "gpu-resume-temp-c",
+        value_name = "CELSIUS",
+        help = "Resume GPU mining paused by --gpu-max-temp-c once the GPU has cooled below this temperature in °C [default: 10 below --gpu-max-temp-c]"
+    )]
+    pub gpu_resume_temp_c: Option<f64>,
+
+    /// Upper bound of the random delay before the first node request
+    #[clap(
+        long = "startup-jitter-ms",
+        value_name = "N",
+        help = "Sleep a random time between 0 and N milliseconds before the first request to the node, spreading load when many miners start at once"
+    )]
+    pub startup_jitter_ms: Option<u64>,
+
+    /// Skip re-checking solutions before submission
+    #[clap(
+        long = "no-verify-solution",
+        help = "Submit solutions without re-checking their hash against the target"
+    )]
+    pub no_verify_solution: bool,
+
+    /// Probe the configured nodes and print their latency
+    #[clap(
+        long = "ping",
+        help = "Measure the round-trip latency to each configured node and exit"
+    )]
+    pub ping: bool,
+
+    /// File or named pipe receiving each solution as a JSON line
+    #[clap(
+        long = "solution-sink",
+        value_name = "PATH",
+        help = "Append each found solution as a JSON line to PATH (a file or named pipe), in addition to submitting it"
+    )]
+    pub solution_sink: Option<PathBuf>,
+
+    /// Don't submit solutions to the node
+    #[clap(
+        long = "no-submit",
+        help = "Don't submit solutions to the node; requires --solution-sink"
+    )]
+    pub no_submit: bool,
+
+    /// Easy target level replacing the node's target, for testing
+    #[clap(
+        long = "test-target-level",
+        value_name = "LEVEL",
+        help = "Mine real work against an easy target of LEVEL leading zero bits instead of the node's target, for testing; requires --no-submit"
+    )]
+    pub test_target_level: Option<u8>,
+
+    /// Socket serving mining events as JSON lines
+    #[clap(
+        long = "event-socket",
+        value_name = "ADDR",
+        help = "Serve work, preemption, solution and alert events as JSON lines on ADDR (host:port or unix:PATH)"
+    )]
+    pub event_socket: Option<String>,
+
+    /// Submission latency alert threshold
+    #[clap(
+        long = "max-submit-latency-ms",
+        value_name = "MS",
+        help = "Raise an alert when submitting a solution takes longer than MS milliseconds [default: 2000]"
+    )]
+    pub max_submit_latency_ms: Option<f64>,
+
+    /// Webhook receiving monitoring alerts
+    #[clap(
+        long = "alert-webhook",
+        value_name = "URL",
+        help = "POST monitoring alerts (severity, category, message, context) as JSON to URL"
+    )]
+    pub alert_webhook: Option<String>,
+
+    /// Minimum severity of alerts sent to the webhook
+    #[clap(
+        long = "alert-min-severity",
+        value_name = "SEVERITY",
+        help = "Only send alerts of at least this severity to --alert-webhook: info, warning, critical or emergency [default: warning]"
+    )]
+    pub alert_min_severity: Option<String>,
+
+    /// Hashrate warm-up window in seconds
+    #[clap(
+        long = "hashrate-warmup-secs",
+        help = "Seconds after mining starts during which CPU/GPU hashrate samples are not reported [default: 5]"
+    )]
+    pub hashrate_warmup_secs: Option<u64>,
+
+    /// Work polling interval once the update stream is lost
+    #[clap(
+        long = "fallback-poll-interval-secs",
+        help = "Seconds between polls for new work once the update stream can't be re-established [default: 10]"
+    )]
+    pub fallback_poll_interval_secs: Option<u64>,
+
+    /// Maximum number of concurrent work fetches
+    #[clap(
+        long = "max-concurrent-work-fetches",
+        help = "Maximum number of work requests in flight at once, shared across chains [default: 4]"
+    )]
+    pub max_concurrent_work_fetches: Option<usize>,
+
+    /// Confirm accepted blocks against the node's cut
+    #[clap(
+        long = "confirm-blocks",
+        help = "Check some time after submission whether accepted blocks are on the winning chain or were orphaned"
+    )]
+    pub confirm_blocks: bool,
+
+    /// Delay before confirming an accepted block
+    #[clap(
+        long = "confirm-delay-secs",
+        help = "Seconds between submitting a block and polling the cut to confirm it [default: 60]"
+    )]
+    pub confirm_delay_secs: Option<u64>,
+}
+
+/// Setting of a flag with a `--no-*` negation, `None` if neither was given
+///
+/// The negation wins when both are given, so it reliably overrides a
+/// setting from a config file or an alias that sets the flag.
+fn flag_setting(set: bool, unset: bool) -> Option<bool> {
+    if unset {
+        Some(false)
+    } else if set {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+impl Args {
+    /// TLS setting requested explicitly via `--tls`/`--no-tls` (`--no-tls` wins)
+    pub fn explicit_tls(&self) -> Option<bool> {
+        flag_setting(self.tls, self.no_tls)
+    }
+
+    /// Setting requested explicitly via `--insecure`/`--no-insecure`
+    /// (`--no-insecure` wins)
+    pub fn explicit_insecure(&self) -> Option<bool> {
+        flag_setting(self.insecure, self.no_insecure)
+    }
+
+    /// Whether `--generate-key` was given and not undone by `--no-generate-key`
+    pub fn generate_key_requested(&self) -> bool {
+        flag_setting(self.generate_key, self.no_generate_key).unwrap_or(false)
+    }
+
+    /// Log level from `--log-level`, or else from `--quiet`/`--verbose`
+    pub fn effective_log_level(&self) -> Option<String> {
+        if let Some(level) = &self.log_level {
+            return Some(level.clone());
+        }
+        match (self.quiet, self.verbose) {
+            (true, _) => Some("error".to_string()),
+            (false, 0) => None,
+            (false, 1) => Some("debug".to_string()),
+            (false, _) => Some("trace".to_string()),
+        }
+    }
+}
+
+/// Main configuration structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Node configuration
+    pub node: NodeConfig,
+
+    /// Mining configuration
+    pub mining: MiningConfig,
+
+    /// Worker configuration
+    pub worker: WorkerConfig,
+
+    /// Logging configuration
+    pub logging: LoggingConfig,
+
+    /// Whether no worker was selected, so the [`DEFAULT_WORKER`] is used
+    #[serde(skip)]
+    pub worker_defaulted: bool,
+}
+
+/// Flat configuration structure (Haskell-compatible)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlatConfig {
     /// Chainweb node URL
     #[serde(rename = "node")]
@@ -300,15 +1336,30 @@ pub struct FlatConfig {
     /// Number of mining threads
     #[serde(rename = "threadCount")]
     pub thread_count: Option<usize>,
+    /// OS priority of CPU mining threads
+    #[serde(rename = "threadPriority")]
+    pub thread_priority: Option<String>,
+    /// Nonces searched per work before fetching fresh work
+    #[serde(rename = "maxNoncesPerWork")]
+    pub max_nonces_per_work: Option<u64>,
+    /// Memory limit of CPU mining buffers in megabytes
+    #[serde(rename = "maxMemoryMb")]
+    pub max_memory_mb: Option<u64>,
     /// Log level (debug, info, warn, error)
     #[serde(rename = "logLevel")]
     pub log_level: Option<String>,
+    /// Minimum interval between hashrate log lines in seconds
+    #[serde(rename = "hashrateLogIntervalSecs")]
+    pub hashrate_log_interval_secs: Option<u64>,
     /// Worker type
     #[serde(rename = "worker")]
     pub worker: Option<String>,
     /// External worker command
     #[serde(rename = "externalWorkerCommand")]
     pub external_worker_command: Option<String>,
+    /// Maximum number of concurrently running external worker processes
+    #[serde(rename = "externalWorkerMaxConcurrent")]
+    pub external_worker_max_concurrent: Option<usize>,
     /// Stratum server port
     #[serde(rename = "stratumPort")]
     pub stratum_port: Option<u16>,
@@ -318,9 +1369,36 @@ pub struct FlatConfig {
     /// Stratum difficulty setting
     #[serde(rename = "stratumDifficulty")]
     pub stratum_difficulty: Option<String>,
+    /// Upper bound for a period based stratum difficulty in seconds
+    #[serde(rename = "stratumMaxPeriod")]
+    pub stratum_max_period: Option<f64>,
+    /// Easiest difficulty level of a period based stratum difficulty
+    #[serde(rename = "stratumMinDifficultyLevel")]
+    pub stratum_min_difficulty_level: Option<u8>,
+    /// Hash rate change factor that resets a stratum session's difficulty
+    #[serde(rename = "stratumHashrateShiftFactor")]
+    pub stratum_hashrate_shift_factor: Option<f64>,
     /// Stratum job rate
     #[serde(rename = "stratumRate")]
     pub stratum_rate: Option<u64>,
+    /// Always mark stratum jobs as clean
+    #[serde(rename = "stratumAlwaysCleanJobs")]
+    pub stratum_always_clean_jobs: Option<bool>,
+    /// Number of recent stratum jobs accepted for submits
+    #[serde(rename = "stratumJobHistory")]
+    pub stratum_job_history: Option<usize>,
+    /// Idle timeout for stratum sessions in seconds
+    #[serde(rename = "stratumSessionIdleTimeout")]
+    pub stratum_session_idle_timeout: Option<u64>,
+    /// Size of the stratum extranonce1 in bytes
+    #[serde(rename = "stratumExtranonce1Size")]
+    pub stratum_extranonce1_size: Option<u8>,
+    /// Audit file of accepted stratum shares
+    #[serde(rename = "stratumAuditFile")]
+    pub stratum_audit_file: Option<PathBuf>,
+    /// Size in megabytes at which the stratum audit file is rotated
+    #[serde(rename = "stratumAuditMaxSize")]
+    pub stratum_audit_max_size: Option<u64>,
     /// Simulated hash rate
     #[serde(rename = "hashRate")]
     pub hash_rate: Option<f64>,
@@ -336,6 +1414,33 @@ pub struct FlatConfig {
     /// Default HTTP timeout in milliseconds
     #[serde(rename = "defaultHTTPTimeout")]
     pub default_http_timeout: Option<u64>,
+    /// Hashrate warm-up window in seconds
+    #[serde(rename = "hashrateWarmupSecs")]
+    pub hashrate_warmup_secs: Option<u64>,
+    /// Work polling interval once the update stream is lost
+    #[serde(rename = "fallbackPollIntervalSecs")]
+    pub fallback_poll_interval_secs: Option<u64>,
+    /// Maximum number of concurrent work fetches
+    #[serde(rename = "maxConcurrentWorkFetches")]
+    pub max_concurrent_work_fetches: Option<usize>,
+    /// JSON keyset file sent in work requests
+    #[serde(rename = "minerKeysetFile")]
+    pub miner_keyset_file: Option<PathBuf>,
+    /// Confirm accepted blocks against the node's cut
+    #[serde(rename = "confirmBlocks")]
+    pub confirm_blocks: Option<bool>,
+    /// Delay before confirming an accepted block
+    #[serde(rename = "confirmDelaySecs")]
+    pub confirm_delay_secs: Option<u64>,
+    /// Further accounts to rotate through
+    #[serde(rename = "rotationAccounts")]
+    pub rotation_accounts: Option<Vec<MiningAccount>>,
+    /// Accounts of single chains, keyed by chain id
+    #[serde(rename = "chainAccounts")]
+    pub chain_accounts: Option<BTreeMap<String, String>>,
+    /// When to rotate to the next mining account
+    #[serde(rename = "accountRotation")]
+    pub account_rotation: Option<AccountRotation>,
 }
 
 /// Node connection configuration
@@ -358,9 +1463,60 @@ pub struct NodeConfig {
 
     /// Chain ID to mine on (optional, will use all chains if not specified)
     pub chain_id: Option<u16>,
+
+    /// Chains not to mine on; when no chain ID is set, the first remaining chain is mined
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_chains: Vec<u16>,
+
+    /// Fallback nodes in order of preference, used when the primary keeps failing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_urls: Vec<String>,
+
+    /// Nodes receiving every solution in addition to the active node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relay_urls: Vec<String>,
+
+    /// Encoding used for solution submissions
+    #[serde(default)]
+    pub submit_encoding: SubmitEncoding,
+
+    /// What to do once no node is reachable any more
+    #[serde(default)]
+    pub on_node_loss: NodeLossPolicy,
+
+    /// Chainweb version used in mining URLs (detected from the node if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+
+    /// Seconds node DNS resolutions are cached before they are refreshed,
+    /// reconnecting when an address changes (no caching if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_refresh_secs: Option<u64>,
+
+    /// Timeout of work requests in seconds (the request timeout, at most
+    /// 10s, if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_fetch_timeout_secs: Option<u64>,
+
+    /// Timeout of solution submissions in seconds (the request timeout if
+    /// not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submit_timeout_secs: Option<u64>,
+
+    /// Extra `NAME: VALUE` headers sent with every node request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub http_headers: Vec<String>,
 }
 
 impl NodeConfig {
+    /// Parse the extra request headers
+    pub fn header_map(&self) -> Result<HeaderMap> {
+        self.http_headers
+            .iter()
+            .map(|header| parse_http_header(header))
+            .collect()
+    }
+
     /// Merge another node config into this one
     fn merge(&mut self, other: NodeConfig) {
         // Determine if we should update TLS before consuming the URL
@@ -388,6 +1544,52 @@ impl NodeConfig {
         if other.chain_id.is_some() {
             self.chain_id = other.chain_id;
         }
+
+        // Excluded chains: a non-empty list replaces the current one
+        if !other.exclude_chains.is_empty() {
+            self.exclude_chains = other.exclude_chains;
+        }
+
+        // Fallback nodes: a non-empty list replaces the current one
+        if !other.fallback_urls.is_empty() {
+            self.fallback_urls = other.fallback_urls;
+        }
+        if !other.relay_urls.is_empty() {
+            self.relay_urls = other.relay_urls;
+        }
+
+        // Submit encoding: use other if it's not the default
+        if other.submit_encoding != SubmitEncoding::default() {
+            self.submit_encoding = other.submit_encoding;
+        }
+
+        // Node loss policy: use other if it's not the default
+        if other.on_node_loss != NodeLossPolicy::default() {
+            self.on_node_loss = other.on_node_loss;
+        }
+
+        // Network: use other if specified, otherwise keep current
+        if other.network.is_some() {
+            self.network = other.network;
+        }
+
+        // DNS refresh: use other if specified, otherwise keep current
+        if other.dns_refresh_secs.is_some() {
+            self.dns_refresh_secs = other.dns_refresh_secs;
+        }
+
+        // Request specific timeouts: use other if specified, otherwise keep current
+        if other.work_fetch_timeout_secs.is_some() {
+            self.work_fetch_timeout_secs = other.work_fetch_timeout_secs;
+        }
+        if other.submit_timeout_secs.is_some() {
+            self.submit_timeout_secs = other.submit_timeout_secs;
+        }
+
+        // Headers: a non-empty list replaces the current one
+        if !other.http_headers.is_empty() {
+            self.http_headers = other.http_headers;
+        }
     }
 }
 
@@ -403,6 +1605,70 @@ pub struct MiningConfig {
     /// Update interval in seconds
     #[serde(default = "default_update_interval")]
     pub update_interval_secs: u64,
+
+    /// Seconds after mining starts during which CPU/GPU hashrate samples are suppressed
+    #[serde(default = "default_hashrate_warmup")]
+    pub hashrate_warmup_secs: u64,
+
+    /// Seconds between polls for new work once the update stream is lost
+    #[serde(default = "default_fallback_poll_interval")]
+    pub fallback_poll_interval_secs: u64,
+
+    /// Maximum number of work fetches in flight at once, shared across chains
+    #[serde(default = "default_max_concurrent_work_fetches")]
+    pub max_concurrent_work_fetches: usize,
+
+    /// Check some time after submission whether accepted blocks are on the
+    /// winning chain or were orphaned
+    #[serde(default)]
+    pub confirm_blocks: bool,
+
+    /// Seconds between submitting a block and polling the cut to confirm it
+    #[serde(default = "default_confirm_delay")]
+    pub confirm_delay_secs: u64,
+
+    /// Further accounts to rotate through after the primary one
+    #[serde(default)]
+    pub rotation_accounts: Vec<MiningAccount>,
+
+    /// When to rotate to the next account (no rotation if unset)
+    #[serde(default)]
+    pub account_rotation: Option<AccountRotation>,
+
+    /// Account receiving the rewards of a chain, keyed by chain id, as
+    /// `[ACCOUNT=]PUBLIC_KEY` or `k:PUBLIC_KEY`; other chains use the accounts above
+    #[serde(default)]
+    pub chain_accounts: BTreeMap<String, String>,
+
+    /// Keyset sent in work requests instead of the public key alone
+    #[serde(default)]
+    pub keyset: Option<MinerKeyset>,
+}
+
+impl MiningConfig {
+    /// The primary account followed by the rotation accounts
+    pub fn accounts(&self) -> Vec<MiningAccount> {
+        let primary = MiningAccount {
+            account: self.account.clone(),
+            public_key: self.public_key.clone(),
+        };
+        std::iter::once(primary)
+            .chain(self.rotation_accounts.iter().cloned())
+            .collect()
+    }
+
+    /// The accounts of single chains, keyed by chain id
+    pub fn chain_accounts(&self) -> Result<BTreeMap<u16, MiningAccount>> {
+        self.chain_accounts
+            .iter()
+            .map(|(chain, account)| {
+                let chain = chain.trim().parse().map_err(|_| {
+                    Error::config_invalid_value("chain_accounts", chain, "a chain id")
+                })?;
+                Ok((chain, MiningAccount::parse(account)))
+            })
+            .collect()
+    }
 }
 
 impl MiningConfig {
@@ -420,6 +1686,33 @@ impl MiningConfig {
         if other.update_interval_secs != default_update_interval() {
             self.update_interval_secs = other.update_interval_secs;
         }
+        if other.hashrate_warmup_secs != default_hashrate_warmup() {
+            self.hashrate_warmup_secs = other.hashrate_warmup_secs;
+        }
+        if other.fallback_poll_interval_secs != default_fallback_poll_interval() {
+            self.fallback_poll_interval_secs = other.fallback_poll_interval_secs;
+        }
+        if other.max_concurrent_work_fetches != default_max_concurrent_work_fetches() {
+            self.max_concurrent_work_fetches = other.max_concurrent_work_fetches;
+        }
+        if other.confirm_blocks {
+            self.confirm_blocks = true;
+        }
+        if other.confirm_delay_secs != default_confirm_delay() {
+            self.confirm_delay_secs = other.confirm_delay_secs;
+        }
+        if !other.rotation_accounts.is_empty() {
+            self.rotation_accounts = other.rotation_accounts;
+        }
+        if other.account_rotation.is_some() {
+            self.account_rotation = other.account_rotation;
+        }
+        if !other.chain_accounts.is_empty() {
+            self.chain_accounts = other.chain_accounts;
+        }
+        if other.keyset.is_some() {
+            self.keyset = other.keyset;
+        }
     }
 }
 
@@ -435,6 +1728,20 @@ pub enum WorkerConfig {
         /// Batch size for nonce checking
         #[serde(default = "default_batch_size")]
         batch_size: u64,
+        /// OS scheduling priority of the mining threads
+        #[serde(default)]
+        thread_priority: ThreadPriority,
+        /// Hash each nonce from a midstate over the fixed part of the work
+        /// header instead of using the SIMD miners (off by default)
+        #[serde(default)]
+        midstate: bool,
+        /// Nonces searched per work before fetching fresh work (0 = unlimited)
+        #[serde(default)]
+        max_nonces_per_work: u64,
+        /// Memory limit of the batch buffers in megabytes; the batch size is
+        /// reduced to fit (0 = unlimited)
+        #[serde(default)]
+        max_memory_mb: u64,
     },
 
     /// GPU worker configuration
@@ -471,6 +1778,9 @@ pub enum WorkerConfig {
         /// Timeout in seconds
         #[serde(default = "default_external_timeout")]
         timeout_secs: u64,
+        /// Maximum number of processes running at once
+        #[serde(default = "default_external_max_concurrent")]
+        max_concurrent: usize,
     },
 
     /// Stratum server configuration
@@ -485,12 +1795,47 @@ pub enum WorkerConfig {
         /// Max connections
         #[serde(default = "default_max_connections")]
         max_connections: usize,
-        /// Difficulty setting
-        #[serde(default = "default_stratum_difficulty")]
+        /// Difficulty setting (left out when serialized if it's the block
+        /// difficulty, whose `null` TOML can't represent)
+        #[serde(
+            default = "default_stratum_difficulty",
+            skip_serializing_if = "is_block_difficulty"
+        )]
         difficulty: StratumDifficulty,
+        /// Longest share period in seconds accepted for a period difficulty
+        #[serde(default = "default_stratum_max_period")]
+        max_period_secs: f64,
+        /// Easiest difficulty level (leading zero bits) of a period difficulty
+        #[serde(default = "default_stratum_min_difficulty_level")]
+        min_difficulty_level: u8,
+        /// Hash rate change factor after which a period difficulty is
+        /// recalculated from the latest shares only (0 disables)
+        #[serde(default = "default_stratum_hashrate_shift_factor")]
+        hashrate_shift_factor: f64,
+        /// Solo or pool difficulty semantics (difficulty applies as given if unset)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<StratumMode>,
         /// Job emission rate in milliseconds
         #[serde(default = "default_stratum_rate")]
         rate_ms: u64,
+        /// Always set `clean_jobs`, even for time-only job updates
+        #[serde(default)]
+        always_clean_jobs: bool,
+        /// Number of recent jobs (including the current one) accepted for submits
+        #[serde(default = "default_stratum_job_history")]
+        job_history_size: usize,
+        /// Disconnect sessions that submit no shares for this many seconds (0 disables)
+        #[serde(default = "default_stratum_session_idle_timeout")]
+        session_idle_timeout_secs: u64,
+        /// Bytes of the nonce assigned as extranonce1; extranonce2 gets the rest
+        #[serde(default = "default_stratum_extranonce1_size")]
+        extranonce1_size: u8,
+        /// Append-only audit file of accepted shares (disabled if unset)
+        #[serde(default)]
+        audit_file: Option<PathBuf>,
+        /// Size in megabytes at which the audit file is rotated (0 disables)
+        #[serde(default = "default_stratum_audit_max_size")]
+        audit_max_size_mb: u64,
     },
 
     /// Simulation worker configuration
@@ -516,6 +1861,13 @@ pub enum WorkerConfig {
         /// Listen address
         #[serde(default = "default_on_demand_host")]
         host: String,
+        /// Seconds during which a request repeating an earlier trigger id
+        /// is rejected
+        #[serde(default = "default_on_demand_replay_window")]
+        replay_window_secs: u64,
+        /// Minimum milliseconds between requests (0 for no limit)
+        #[serde(default)]
+        min_interval_ms: u64,
     },
 }
 
@@ -532,6 +1884,10 @@ pub struct LoggingConfig {
 
     /// Log to file
     pub file: Option<PathBuf>,
+
+    /// Minimum interval between hashrate log lines in seconds
+    #[serde(default = "default_hashrate_log_interval")]
+    pub hashrate_log_interval_secs: u64,
 }
 
 impl LoggingConfig {
@@ -551,6 +1907,10 @@ impl LoggingConfig {
         if other.file.is_some() {
             self.file = other.file;
         }
+
+        if other.hashrate_log_interval_secs != default_hashrate_log_interval() {
+            self.hashrate_log_interval_secs = other.hashrate_log_interval_secs;
+        }
     }
 }
 
@@ -567,6 +1927,22 @@ fn default_update_interval() -> u64 {
     5
 }
 
+fn default_hashrate_warmup() -> u64 {
+    crate::workers::DEFAULT_HASHRATE_WARMUP.as_secs()
+}
+
+fn default_fallback_poll_interval() -> u64 {
+    10
+}
+
+fn default_max_concurrent_work_fetches() -> usize {
+    crate::protocol::fetch_limit::DEFAULT_MAX_CONCURRENT_WORK_FETCHES
+}
+
+fn default_confirm_delay() -> u64 {
+    crate::protocol::confirmation::DEFAULT_CONFIRM_DELAY.as_secs()
+}
+
 fn default_batch_size() -> u64 {
     100_000
 }
@@ -579,6 +1955,10 @@ fn default_external_timeout() -> u64 {
     60
 }
 
+fn default_external_max_concurrent() -> usize {
+    DEFAULT_EXTERNAL_MAX_CONCURRENT
+}
+
 fn default_stratum_port() -> u16 {
     3333
 }
@@ -595,10 +1975,72 @@ fn default_stratum_difficulty() -> StratumDifficulty {
     StratumDifficulty::Block
 }
 
+fn is_block_difficulty(difficulty: &StratumDifficulty) -> bool {
+    matches!(difficulty, StratumDifficulty::Block)
+}
+
+fn default_stratum_max_period() -> f64 {
+    DEFAULT_STRATUM_MAX_PERIOD
+}
+
+fn default_stratum_min_difficulty_level() -> u8 {
+    DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL
+}
+
+fn default_stratum_hashrate_shift_factor() -> f64 {
+    DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR
+}
+
 fn default_stratum_rate() -> u64 {
     1000
 }
 
+/// Validate fallback or relay node addresses, keeping an explicit scheme so
+/// that each node can decide on TLS independently
+fn parse_fallback_nodes(nodes: impl IntoIterator<Item = String>) -> Result<Vec<String>> {
+    nodes
+        .into_iter()
+        .map(|node| {
+            let url = NodeUrl::parse(&node)?;
+            Ok(match url.scheme_tls() {
+                Some(true) => format!("https://{}", url.authority()),
+                Some(false) => format!("http://{}", url.authority()),
+                None => url.authority(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `--chain-account` values (`CHAIN=[ACCOUNT=]PUBLIC_KEY`) into
+/// accounts keyed by chain
+fn parse_chain_accounts(chain_accounts: &[String]) -> Result<BTreeMap<String, String>> {
+    chain_accounts
+        .iter()
+        .map(|entry| {
+            let (chain, account) = entry.split_once('=').ok_or_else(|| {
+                Error::config_invalid_value("chain_account", entry, "CHAIN=[ACCOUNT=]PUBLIC_KEY")
+            })?;
+            Ok((chain.trim().to_string(), account.to_string()))
+        })
+        .collect()
+}
+
+fn default_stratum_job_history() -> usize {
+    4
+}
+
+fn default_stratum_session_idle_timeout() -> u64 {
+    600
+}
+
+fn default_stratum_extranonce1_size() -> u8 {
+    4
+}
+
+fn default_stratum_audit_max_size() -> u64 {
+    100
+}
+
 fn default_on_demand_port() -> u16 {
     1917
 }
@@ -607,6 +2049,10 @@ fn default_on_demand_host() -> String {
     "0.0.0.0".to_string()
 }
 
+fn default_on_demand_replay_window() -> u64 {
+    300
+}
+
 fn default_log_level() -> String {
     "info".to_string()
 }
@@ -615,6 +2061,10 @@ fn default_log_format() -> String {
     "plain".to_string()
 }
 
+fn default_hashrate_log_interval() -> u64 {
+    10
+}
+
 fn default_workgroup_size() -> u32 {
     256
 }
@@ -623,16 +2073,29 @@ fn default_workgroup_count() -> u32 {
     1024
 }
 
-
 fn default_enable_monitoring() -> bool {
     true
 }
 
+/// `--config-file` value that reads the configuration from standard input
+const STDIN_CONFIG: &str = "-";
+
+/// Attempts at fetching a config URL before giving up
+const CONFIG_FETCH_ATTEMPTS: usize = 3;
+
+/// First and longest wait between config URL fetch attempts
+const CONFIG_FETCH_BASE_DELAY: Duration = Duration::from_millis(200);
+const CONFIG_FETCH_MAX_DELAY: Duration = Duration::from_secs(2);
+
 impl Config {
-    /// Load configuration from file or URL
+    /// Load configuration from file or URL; `-` reads standard input
     pub fn from_file(path: &PathBuf) -> Result<Self> {
         let path_str = path.to_string_lossy();
 
+        if path_str == STDIN_CONFIG {
+            return Self::from_reader(std::io::stdin().lock(), "<stdin>");
+        }
+
         // Check if it's a URL
         if path_str.starts_with("http://") || path_str.starts_with("https://") {
             return Self::from_url(&path_str);
@@ -644,40 +2107,136 @@ impl Config {
         Self::from_contents(&contents, &path_str)
     }
 
-    /// Load configuration from URL (HTTP/HTTPS)
-    pub fn from_url(url: &str) -> Result<Self> {
-        // Use blocking approach for synchronous interface
-        let runtime = tokio::runtime::Runtime::new()
-            .map_err(|e| Error::config(format!("Failed to create async runtime: {}", e)))?;
+    /// Load configuration from a reader, auto-detecting the format
+    pub fn from_reader(mut reader: impl std::io::Read, source: &str) -> Result<Self> {
+        let mut contents = String::new();
+        reader
+            .read_to_string(&mut contents)
+            .map_err(|e| Error::config(format!("Failed to read config from {}: {}", source, e)))?;
 
-        runtime.block_on(Self::from_url_async(url))
+        if contents.trim().is_empty() {
+            return Err(Error::config(format!("Config from {} is empty", source)));
+        }
+
+        Self::from_contents(&contents, source)
     }
 
-    /// Load configuration from URL asynchronously
-    pub async fn from_url_async(url: &str) -> Result<Self> {
-        let client = get_config_client()
-            .map_err(|e| Error::config(format!("Failed to get HTTP client: {}", e)))?;
+    /// Load configuration from file or URL asynchronously, like
+    /// [`from_file`](Self::from_file) but for callers inside a runtime
+    pub async fn load_async(path: &Path) -> Result<Self> {
+        let path_str = path.to_string_lossy();
 
-        let response =
-            client.get(url).send().await.map_err(|e| {
-                Error::config(format!("Failed to fetch config from {}: {}", url, e))
-            })?;
+        if path_str == STDIN_CONFIG {
+            return tokio::task::spawn_blocking(|| {
+                Self::from_reader(std::io::stdin().lock(), "<stdin>")
+            })
+            .await
+            .map_err(|e| Error::config(format!("Failed to read config from <stdin>: {}", e)))?;
+        }
 
-        if !response.status().is_success() {
-            return Err(Error::config(format!(
-                "HTTP error {} when fetching config from {}",
-                response.status(),
-                url
-            )));
+        if path_str.starts_with("http://") || path_str.starts_with("https://") {
+            return Self::from_url_async(&path_str).await;
         }
 
-        let contents = response.text().await.map_err(|e| {
-            Error::config(format!("Failed to read response body from {}: {}", url, e))
-        })?;
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| Error::config(format!("Failed to read config file: {}", e)))?;
+
+        Self::from_contents(&contents, &path_str)
+    }
+
+    /// Load configuration from URL (HTTP/HTTPS)
+    ///
+    /// Blocks the calling thread. Inside a runtime the fetch runs on that
+    /// runtime if it is multi-threaded, or else on a thread of its own, as a
+    /// nested runtime can't be started.
+    pub fn from_url(url: &str) -> Result<Self> {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(Self::from_url_async(url)))
+            }
+            Ok(_) => std::thread::scope(|scope| {
+                scope
+                    .spawn(|| Self::from_url_blocking(url))
+                    .join()
+                    .map_err(|_| Error::config(format!("Fetching config from {} panicked", url)))?
+            }),
+            Err(_) => Self::from_url_blocking(url),
+        }
+    }
+
+    /// Fetch the configuration at `url` on a runtime of its own
+    fn from_url_blocking(url: &str) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create async runtime: {}", e)))?;
+
+        runtime.block_on(Self::from_url_async(url))
+    }
+
+    /// Load configuration from URL asynchronously
+    ///
+    /// Connection failures and server errors are retried a few times with
+    /// backoff; the resulting error tells an unreachable URL, a non-success
+    /// status and an unparsable config apart.
+    pub async fn from_url_async(url: &str) -> Result<Self> {
+        let client = get_config_client()
+            .map_err(|e| Error::config(format!("Failed to get HTTP client: {}", e)))?;
+
+        let policy = RetryPolicy::new(
+            CONFIG_FETCH_ATTEMPTS,
+            CONFIG_FETCH_BASE_DELAY,
+            CONFIG_FETCH_MAX_DELAY,
+        );
+        let attempts = AtomicUsize::new(0);
+        let contents = policy
+            .execute(|| async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                let response = client
+                    .get(url)
+                    .send()
+                    .await
+                    .map_err(|e| Error::network_connection_failed(url, Box::new(e)))?;
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(Error::network_http_error(
+                        url,
+                        status.as_u16(),
+                        status.canonical_reason().unwrap_or("config fetch failed"),
+                    ));
+                }
+                response
+                    .text()
+                    .await
+                    .map_err(|e| Error::network_connection_failed(url, Box::new(e)))
+            })
+            .await
+            .map_err(|e| match e {
+                Error::Network(NetworkError::HttpError { status, .. }) => {
+                    Error::config_fetch_status(url, status)
+                }
+                e => {
+                    Error::config_fetch_failed(url, attempts.load(Ordering::Relaxed), e.to_string())
+                }
+            })?;
 
         Self::from_contents(&contents, url)
     }
 
+    /// Serialize the configuration in `format`, readable by
+    /// [`from_contents`](Self::from_contents)
+    pub fn render(&self, format: ConfigFormat) -> Result<String> {
+        let serialized = match format {
+            ConfigFormat::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(self)
+                .map(|json| json + "\n")
+                .map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string(self).map_err(|e| e.to_string()),
+        };
+        serialized.map_err(|e| Error::config(format!("Failed to serialize config: {}", e)))
+    }
+
     /// Parse configuration from contents with format detection
     pub fn from_contents(contents: &str, source: &str) -> Result<Self> {
         // Try to determine format from URL extension or content
@@ -778,14 +2337,8 @@ impl Config {
 
     /// Convert from flat (Haskell-style) config
     fn from_flat_config(flat: FlatConfig) -> Result<Self> {
-        let node_url = flat.node.unwrap_or_else(|| "localhost:1848".to_string());
-        let use_tls = flat
-            .use_tls
-            .unwrap_or_else(|| !node_url.starts_with("http://"));
-        let clean_url = node_url
-            .trim_start_matches("https://")
-            .trim_start_matches("http://")
-            .to_string();
+        let node_url = NodeUrl::parse(flat.node.as_deref().unwrap_or("localhost:1848"))?;
+        let use_tls = node_url.use_tls(flat.use_tls);
 
         let public_key = flat
             .public_key
@@ -793,11 +2346,20 @@ impl Config {
 
         let account = flat.account.unwrap_or_else(|| format!("k:{}", public_key));
 
-        let worker_name = flat.worker.as_deref().unwrap_or("stratum");
+        let worker_name = flat.worker.as_deref().unwrap_or(DEFAULT_WORKER);
         let worker_config = match worker_name {
             "cpu" => WorkerConfig::Cpu {
                 threads: flat.thread_count.unwrap_or(2),
                 batch_size: default_batch_size(),
+                thread_priority: flat
+                    .thread_priority
+                    .as_deref()
+                    .map(ThreadPriority::from_str)
+                    .transpose()?
+                    .unwrap_or_default(),
+                midstate: false,
+                max_nonces_per_work: flat.max_nonces_per_work.unwrap_or(0),
+                max_memory_mb: flat.max_memory_mb.unwrap_or(0),
             },
             "external" => WorkerConfig::External {
                 command: flat
@@ -806,18 +2368,51 @@ impl Config {
                 args: vec![],
                 env: vec![],
                 timeout_secs: default_external_timeout(),
+                max_concurrent: flat
+                    .external_worker_max_concurrent
+                    .unwrap_or(DEFAULT_EXTERNAL_MAX_CONCURRENT),
             },
             "stratum" => WorkerConfig::Stratum {
                 port: flat.stratum_port.unwrap_or(1917),
-                host: flat.stratum_interface.unwrap_or_else(|| "*".to_string()),
+                host: normalize_interface(flat.stratum_interface.as_deref().unwrap_or("*")),
                 max_connections: default_max_connections(),
                 difficulty: flat
                     .stratum_difficulty
                     .as_deref()
-                    .map(StratumDifficulty::from_str)
+                    .map(|s| {
+                        StratumDifficulty::parse_with_max_period(
+                            s,
+                            flat.stratum_max_period
+                                .unwrap_or(DEFAULT_STRATUM_MAX_PERIOD),
+                        )
+                    })
                     .transpose()?
                     .unwrap_or(StratumDifficulty::Block),
+                max_period_secs: flat
+                    .stratum_max_period
+                    .unwrap_or(DEFAULT_STRATUM_MAX_PERIOD),
+                min_difficulty_level: flat
+                    .stratum_min_difficulty_level
+                    .unwrap_or(DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL),
+                hashrate_shift_factor: flat
+                    .stratum_hashrate_shift_factor
+                    .unwrap_or(DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR),
+                mode: None,
                 rate_ms: flat.stratum_rate.unwrap_or(1000),
+                always_clean_jobs: flat.stratum_always_clean_jobs.unwrap_or(false),
+                job_history_size: flat
+                    .stratum_job_history
+                    .unwrap_or_else(default_stratum_job_history),
+                session_idle_timeout_secs: flat
+                    .stratum_session_idle_timeout
+                    .unwrap_or_else(default_stratum_session_idle_timeout),
+                extranonce1_size: flat
+                    .stratum_extranonce1_size
+                    .unwrap_or_else(default_stratum_extranonce1_size),
+                audit_file: flat.stratum_audit_file.clone(),
+                audit_max_size_mb: flat
+                    .stratum_audit_max_size
+                    .unwrap_or_else(default_stratum_audit_max_size),
             },
             "simulation" => WorkerConfig::Simulation {
                 hash_rate: flat.hash_rate.unwrap_or(1_000_000.0),
@@ -827,7 +2422,9 @@ impl Config {
             },
             "on-demand" => WorkerConfig::OnDemand {
                 port: flat.on_demand_port.unwrap_or(1917),
-                host: flat.on_demand_interface.unwrap_or_else(|| "*".to_string()),
+                host: normalize_interface(flat.on_demand_interface.as_deref().unwrap_or("*")),
+                replay_window_secs: default_on_demand_replay_window(),
+                min_interval_ms: 0,
             },
             _ => {
                 return Err(Error::config(format!(
@@ -839,7 +2436,7 @@ impl Config {
 
         Ok(Config {
             node: NodeConfig {
-                url: clean_url,
+                url: node_url.authority(),
                 use_tls,
                 insecure: flat.insecure.unwrap_or(false),
                 timeout_secs: flat
@@ -847,30 +2444,77 @@ impl Config {
                     .map(|us| us / 1_000_000)
                     .unwrap_or(30),
                 chain_id: None,
+                exclude_chains: Vec::new(),
+                fallback_urls: Vec::new(),
+                relay_urls: Vec::new(),
+                submit_encoding: SubmitEncoding::default(),
+                on_node_loss: NodeLossPolicy::default(),
+                network: None,
+                dns_refresh_secs: None,
+                work_fetch_timeout_secs: None,
+                submit_timeout_secs: None,
+                http_headers: Vec::new(),
             },
             mining: MiningConfig {
                 account,
                 public_key,
                 update_interval_secs: default_update_interval(),
+                hashrate_warmup_secs: flat
+                    .hashrate_warmup_secs
+                    .unwrap_or_else(default_hashrate_warmup),
+                fallback_poll_interval_secs: flat
+                    .fallback_poll_interval_secs
+                    .unwrap_or_else(default_fallback_poll_interval),
+                max_concurrent_work_fetches: flat
+                    .max_concurrent_work_fetches
+                    .unwrap_or_else(default_max_concurrent_work_fetches),
+                confirm_blocks: flat.confirm_blocks.unwrap_or(false),
+                confirm_delay_secs: flat
+                    .confirm_delay_secs
+                    .unwrap_or_else(default_confirm_delay),
+                rotation_accounts: flat.rotation_accounts.unwrap_or_default(),
+                account_rotation: flat.account_rotation,
+                chain_accounts: flat.chain_accounts.unwrap_or_default(),
+                keyset: flat
+                    .miner_keyset_file
+                    .as_deref()
+                    .map(MinerKeyset::from_file)
+                    .transpose()?,
             },
             worker: worker_config,
             logging: LoggingConfig {
                 level: flat.log_level.unwrap_or_else(|| "info".to_string()),
                 format: default_log_format(),
                 file: None,
+                hashrate_log_interval_secs: flat
+                    .hashrate_log_interval_secs
+                    .unwrap_or_else(default_hashrate_log_interval),
             },
+            worker_defaulted: flat.worker.is_none(),
         })
     }
 
     /// Create configuration from command-line arguments
     pub fn from_args(args: Args) -> Result<Self> {
         // Handle special flags that cause early exit
-        if args.generate_key {
+        if args.generate_key_requested() {
             // This should be handled in main.rs
             return Err(Error::config("Key generation should be handled in main"));
         }
+        let log_level = args.effective_log_level();
 
         // Load from config files if specified
+        if args
+            .config_file
+            .iter()
+            .filter(|path| path.as_os_str() == STDIN_CONFIG)
+            .count()
+            > 1
+        {
+            return Err(Error::config(
+                "Standard input (-) can only be used as one config file",
+            ));
+        }
         if !args.config_file.is_empty() {
             let mut config: Option<Config> = None;
             for path in &args.config_file {
@@ -887,14 +2531,20 @@ impl Config {
             if let Some(mut config) = config {
                 // Apply CLI overrides
                 config.apply_args(&args)?;
+                Self::check_worker_selected(&config, args.strict_config)?;
+                config.validate_accounts()?;
                 return Ok(config);
             }
         }
 
         // Build from CLI args
-        let node_url = args
-            .node
+        let explicit_tls = args.explicit_tls();
+        let insecure = args.explicit_insecure().unwrap_or(false);
+        let mut nodes = args.node.into_iter();
+        let node_url = nodes
+            .next()
             .ok_or_else(|| Error::config("Node URL is required (use -n or --node)"))?;
+        let fallback_urls = parse_fallback_nodes(nodes)?;
 
         let public_key = args
             .public_key
@@ -904,27 +2554,42 @@ impl Config {
         let account = args.account.unwrap_or_else(|| format!("k:{}", public_key));
 
         // Determine TLS usage
-        let use_tls = if args.no_tls {
-            false
-        } else if args.tls {
-            true
-        } else {
-            node_url.starts_with("https://") || !node_url.starts_with("http://")
-        };
-
-        let insecure = args.insecure && !args.no_insecure;
-
-        let clean_url = node_url
-            .trim_start_matches("https://")
-            .trim_start_matches("http://")
-            .to_string();
+        let node_url = NodeUrl::parse(&node_url)?;
+        let use_tls = node_url.use_tls(explicit_tls);
+
+        let submit_encoding = args
+            .submit_encoding
+            .as_deref()
+            .map(SubmitEncoding::from_str)
+            .transpose()?
+            .unwrap_or_default();
+        let on_node_loss = args
+            .on_node_loss
+            .as_deref()
+            .map(NodeLossPolicy::from_str)
+            .transpose()?
+            .unwrap_or_default();
 
         // Parse worker type
-        let worker_name = args.worker.as_deref().unwrap_or("stratum");
+        let worker_name = args.worker.as_deref().unwrap_or(DEFAULT_WORKER);
         let worker_config = match worker_name {
             "cpu" => WorkerConfig::Cpu {
-                threads: args.thread_count.unwrap_or(2),
+                threads: args
+                    .thread_count
+                    .as_deref()
+                    .map(ThreadCount::from_str)
+                    .transpose()?
+                    .map_or(2, |count| count.resolve(num_cpus::get())),
                 batch_size: default_batch_size(),
+                thread_priority: args
+                    .thread_priority
+                    .as_deref()
+                    .map(ThreadPriority::from_str)
+                    .transpose()?
+                    .unwrap_or_default(),
+                midstate: false,
+                max_nonces_per_work: args.max_nonces_per_work.unwrap_or(0),
+                max_memory_mb: args.max_memory_mb.unwrap_or(0),
             },
             "external" => WorkerConfig::External {
                 command: args.external_worker_cmd.ok_or_else(|| {
@@ -935,18 +2600,51 @@ impl Config {
                 args: vec![],
                 env: vec![],
                 timeout_secs: default_external_timeout(),
+                max_concurrent: args
+                    .external_worker_max_concurrent
+                    .unwrap_or(DEFAULT_EXTERNAL_MAX_CONCURRENT),
             },
             "stratum" => WorkerConfig::Stratum {
                 port: args.stratum_port.unwrap_or(1917),
-                host: args.stratum_interface.unwrap_or_else(|| "*".to_string()),
+                host: normalize_interface(args.stratum_interface.as_deref().unwrap_or("*")),
                 max_connections: default_max_connections(),
                 difficulty: args
                     .stratum_difficulty
                     .as_deref()
-                    .map(StratumDifficulty::from_str)
+                    .map(|s| {
+                        StratumDifficulty::parse_with_max_period(
+                            s,
+                            args.stratum_max_period
+                                .unwrap_or(DEFAULT_STRATUM_MAX_PERIOD),
+                        )
+                    })
                     .transpose()?
                     .unwrap_or(StratumDifficulty::Block),
+                max_period_secs: args
+                    .stratum_max_period
+                    .unwrap_or(DEFAULT_STRATUM_MAX_PERIOD),
+                min_difficulty_level: args
+                    .stratum_min_difficulty_level
+                    .unwrap_or(DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL),
+                hashrate_shift_factor: args
+                    .stratum_hashrate_shift_factor
+                    .unwrap_or(DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR),
+                mode: args.stratum_mode.as_deref().map(str::parse).transpose()?,
                 rate_ms: args.stratum_rate.unwrap_or(1000),
+                always_clean_jobs: args.stratum_always_clean_jobs,
+                job_history_size: args
+                    .stratum_job_history
+                    .unwrap_or_else(default_stratum_job_history),
+                session_idle_timeout_secs: args
+                    .stratum_session_idle_timeout
+                    .unwrap_or_else(default_stratum_session_idle_timeout),
+                extranonce1_size: args
+                    .stratum_extranonce1_size
+                    .unwrap_or_else(default_stratum_extranonce1_size),
+                audit_file: args.stratum_audit_file.clone(),
+                audit_max_size_mb: args
+                    .stratum_audit_max_size
+                    .unwrap_or_else(default_stratum_audit_max_size),
             },
             "simulation" => {
                 let hash_rate = args
@@ -962,7 +2660,11 @@ impl Config {
             },
             "on-demand" => WorkerConfig::OnDemand {
                 port: args.on_demand_port.unwrap_or(1917),
-                host: args.on_demand_interface.unwrap_or_else(|| "*".to_string()),
+                host: normalize_interface(args.on_demand_interface.as_deref().unwrap_or("*")),
+                replay_window_secs: args
+                    .on_demand_replay_window_secs
+                    .unwrap_or_else(default_on_demand_replay_window),
+                min_interval_ms: args.on_demand_min_interval_ms.unwrap_or(0),
             },
             _ => {
                 return Err(Error::config(format!(
@@ -974,57 +2676,125 @@ impl Config {
 
         let config = Config {
             node: NodeConfig {
-                url: clean_url,
+                url: node_url.authority(),
                 use_tls,
                 insecure,
                 timeout_secs: default_timeout(),
                 chain_id: None, // Will mine on all chains by default
+                exclude_chains: args.exclude_chains,
+                fallback_urls,
+                relay_urls: parse_fallback_nodes(args.relay_node)?,
+                submit_encoding,
+                on_node_loss,
+                network: args.network,
+                dns_refresh_secs: args.dns_refresh_secs,
+                work_fetch_timeout_secs: args.work_fetch_timeout_secs,
+                submit_timeout_secs: args.submit_timeout_secs,
+                http_headers: args.http_header,
             },
             mining: MiningConfig {
                 account,
                 public_key,
                 update_interval_secs: default_update_interval(),
+                hashrate_warmup_secs: args
+                    .hashrate_warmup_secs
+                    .unwrap_or_else(default_hashrate_warmup),
+                fallback_poll_interval_secs: args
+                    .fallback_poll_interval_secs
+                    .unwrap_or_else(default_fallback_poll_interval),
+                max_concurrent_work_fetches: args
+                    .max_concurrent_work_fetches
+                    .unwrap_or_else(default_max_concurrent_work_fetches),
+                confirm_blocks: args.confirm_blocks,
+                confirm_delay_secs: args
+                    .confirm_delay_secs
+                    .unwrap_or_else(default_confirm_delay),
+                rotation_accounts: args
+                    .rotation_account
+                    .iter()
+                    .map(|account| MiningAccount::parse(account))
+                    .collect(),
+                account_rotation: args
+                    .account_rotation
+                    .as_deref()
+                    .map(str::parse)
+                    .transpose()?,
+                chain_accounts: parse_chain_accounts(&args.chain_account)?,
+                keyset: args
+                    .miner_keyset_file
+                    .as_deref()
+                    .map(MinerKeyset::from_file)
+                    .transpose()?,
             },
             worker: worker_config,
             logging: LoggingConfig {
-                level: args.log_level.unwrap_or_else(|| "info".to_string()),
+                level: log_level.unwrap_or_else(|| "info".to_string()),
                 format: default_log_format(),
                 file: None,
+                hashrate_log_interval_secs: args
+                    .hashrate_log_interval_secs
+                    .unwrap_or_else(default_hashrate_log_interval),
             },
+            worker_defaulted: args.worker.is_none(),
         };
+        Self::check_worker_selected(&config, args.strict_config)?;
 
         config.validate()?;
+        config.validate_accounts()?;
         Ok(config)
     }
 
     /// Apply command-line arguments to existing config
     fn apply_args(&mut self, args: &Args) -> Result<()> {
         // Override node settings
-        if let Some(node) = &args.node {
-            let use_tls = if args.no_tls {
-                false
-            } else if args.tls {
-                true
-            } else {
-                node.starts_with("https://") || !node.starts_with("http://")
-            };
-            self.node.url = node
-                .trim_start_matches("https://")
-                .trim_start_matches("http://")
-                .to_string();
+        if let Some((node, fallbacks)) = args.node.split_first() {
+            let node_url = NodeUrl::parse(node)?;
+            self.node.url = node_url.authority();
+            self.node.use_tls = node_url.use_tls(args.explicit_tls());
+            if !fallbacks.is_empty() {
+                self.node.fallback_urls = parse_fallback_nodes(fallbacks.iter().cloned())?;
+            }
+        } else if let Some(use_tls) = args.explicit_tls() {
             self.node.use_tls = use_tls;
         }
+        if !args.relay_node.is_empty() {
+            self.node.relay_urls = parse_fallback_nodes(args.relay_node.iter().cloned())?;
+        }
+
+        if !args.exclude_chains.is_empty() {
+            self.node.exclude_chains = args.exclude_chains.clone();
+        }
 
-        if args.tls && !args.no_tls {
-            self.node.use_tls = true;
-        } else if args.no_tls {
-            self.node.use_tls = false;
+        if let Some(encoding) = &args.submit_encoding {
+            self.node.submit_encoding = encoding.parse()?;
         }
 
-        if args.insecure && !args.no_insecure {
-            self.node.insecure = true;
-        } else if args.no_insecure {
-            self.node.insecure = false;
+        if let Some(policy) = &args.on_node_loss {
+            self.node.on_node_loss = policy.parse()?;
+        }
+
+        if let Some(network) = &args.network {
+            self.node.network = Some(network.clone());
+        }
+
+        if let Some(secs) = args.dns_refresh_secs {
+            self.node.dns_refresh_secs = Some(secs);
+        }
+
+        if let Some(secs) = args.work_fetch_timeout_secs {
+            self.node.work_fetch_timeout_secs = Some(secs);
+        }
+
+        if let Some(secs) = args.submit_timeout_secs {
+            self.node.submit_timeout_secs = Some(secs);
+        }
+
+        if !args.http_header.is_empty() {
+            self.node.http_headers = args.http_header.clone();
+        }
+
+        if let Some(insecure) = args.explicit_insecure() {
+            self.node.insecure = insecure;
         }
 
         // Override mining settings
@@ -1040,8 +2810,42 @@ impl Config {
         }
 
         // Override logging
-        if let Some(log_level) = &args.log_level {
-            self.logging.level = log_level.clone();
+        if let Some(warmup) = args.hashrate_warmup_secs {
+            self.mining.hashrate_warmup_secs = warmup;
+        }
+        if let Some(interval) = args.fallback_poll_interval_secs {
+            self.mining.fallback_poll_interval_secs = interval;
+        }
+        if let Some(limit) = args.max_concurrent_work_fetches {
+            self.mining.max_concurrent_work_fetches = limit;
+        }
+        if args.confirm_blocks {
+            self.mining.confirm_blocks = true;
+        }
+        if let Some(delay) = args.confirm_delay_secs {
+            self.mining.confirm_delay_secs = delay;
+        }
+        if !args.rotation_account.is_empty() {
+            self.mining.rotation_accounts = args
+                .rotation_account
+                .iter()
+                .map(|account| MiningAccount::parse(account))
+                .collect();
+        }
+        if let Some(rotation) = &args.account_rotation {
+            self.mining.account_rotation = Some(rotation.parse()?);
+        }
+        if !args.chain_account.is_empty() {
+            self.mining.chain_accounts = parse_chain_accounts(&args.chain_account)?;
+        }
+        if let Some(path) = &args.miner_keyset_file {
+            self.mining.keyset = Some(MinerKeyset::from_file(path)?);
+        }
+        if let Some(log_level) = args.effective_log_level() {
+            self.logging.level = log_level;
+        }
+        if let Some(interval) = args.hashrate_log_interval_secs {
+            self.logging.hashrate_log_interval_secs = interval;
         }
 
         // Override worker config based on worker type
@@ -1053,6 +2857,17 @@ impl Config {
         Ok(())
     }
 
+    /// With `--strict-config`, fail unless the worker was selected
+    fn check_worker_selected(config: &Config, strict: bool) -> Result<()> {
+        if strict && config.worker_defaulted {
+            return Err(Error::config(format!(
+                "No worker selected (use --worker or set it in a config file); --strict-config disables the {} default",
+                DEFAULT_WORKER
+            )));
+        }
+        Ok(())
+    }
+
     /// Merge another config into this one
     /// Fields from 'other' will override fields in 'self' where they differ
     pub fn merge(&mut self, other: Config) {
@@ -1065,18 +2880,99 @@ impl Config {
         // Worker configuration: use the other worker config if it's different
         // This is complex to merge properly, so we replace it entirely
         self.worker = other.worker;
+        self.worker_defaulted = other.worker_defaulted;
 
         // Merge logging configuration
         self.logging.merge(other.logging);
     }
 
+    /// Check the format of the mining account and public key and of every
+    /// rotation and chain account
+    ///
+    /// Runs on the final configuration, before connecting to a node, so a
+    /// mistyped key fails here instead of as a rejected work request. Chains
+    /// are checked against the node's chain count on startup.
+    pub fn validate_accounts(&self) -> Result<()> {
+        self.mining
+            .accounts()
+            .iter()
+            .try_for_each(MiningAccount::validate)?;
+        self.mining
+            .chain_accounts()?
+            .values()
+            .try_for_each(MiningAccount::validate)
+    }
+
     /// Validate configuration
     pub fn validate(&self) -> Result<()> {
-        // Validate chain ID if specified
-        if let Some(chain_id) = self.node.chain_id {
-            if chain_id > 19 {
-                return Err(Error::config("Chain ID must be between 0 and 19"));
+        // The chain id and excluded chains are checked against the node's
+        // chain count on startup
+        if let Some(chain_id) = self.node.chain_id
+            && self.node.exclude_chains.contains(&chain_id)
+        {
+            return Err(Error::config_invalid_value(
+                "chain_id",
+                chain_id.to_string(),
+                "a chain that is not in exclude_chains",
+            ));
+        }
+
+        if self.node.dns_refresh_secs == Some(0) {
+            return Err(Error::config_invalid_value(
+                "dns_refresh_secs",
+                "0",
+                "at least 1",
+            ));
+        }
+        if self.node.work_fetch_timeout_secs == Some(0) {
+            return Err(Error::config_invalid_value(
+                "work_fetch_timeout_secs",
+                "0",
+                "at least 1",
+            ));
+        }
+        if self.node.submit_timeout_secs == Some(0) {
+            return Err(Error::config_invalid_value(
+                "submit_timeout_secs",
+                "0",
+                "at least 1",
+            ));
+        }
+        self.node.header_map()?;
+
+        if self.mining.max_concurrent_work_fetches == 0 {
+            return Err(Error::config_invalid_value(
+                "max_concurrent_work_fetches",
+                "0",
+                "at least 1",
+            ));
+        }
+
+        match (
+            &self.mining.account_rotation,
+            self.mining.rotation_accounts.is_empty(),
+        ) {
+            (Some(_), false) => self.validate_accounts()?,
+            (Some(rotation), true) => {
+                return Err(Error::config(format!(
+                    "Account rotation {} needs at least one rotation account",
+                    rotation
+                )));
+            }
+            (None, false) => {
+                return Err(Error::config_missing_field("account_rotation"));
             }
+            (None, true) => {}
+        }
+        if self.mining.keyset.is_some() && !self.mining.rotation_accounts.is_empty() {
+            return Err(Error::config(
+                "A miner keyset guards a single account and can't be combined with rotation accounts",
+            ));
+        }
+        if self.mining.keyset.is_some() && !self.mining.chain_accounts.is_empty() {
+            return Err(Error::config(
+                "A miner keyset guards a single account and can't be combined with chain accounts",
+            ));
         }
 
         // Validate worker config
@@ -1086,7 +2982,11 @@ impl Config {
                     return Err(Error::config("Batch size must be greater than 0"));
                 }
             }
-            WorkerConfig::Gpu { batch_size, workgroup_size, .. } => {
+            WorkerConfig::Gpu {
+                batch_size,
+                workgroup_size,
+                ..
+            } => {
                 if *batch_size == 0 {
                     return Err(Error::config("GPU batch size must be greater than 0"));
                 }
@@ -1094,16 +2994,71 @@ impl Config {
                     return Err(Error::config("GPU workgroup size must be greater than 0"));
                 }
             }
-            WorkerConfig::External { command, .. } => {
+            WorkerConfig::External {
+                command,
+                max_concurrent,
+                ..
+            } => {
                 // Command validation would happen at runtime
                 if command.is_empty() {
                     return Err(Error::config("External command cannot be empty"));
                 }
+                if *max_concurrent == 0 {
+                    return Err(Error::config_invalid_value(
+                        "external_worker_max_concurrent",
+                        max_concurrent.to_string(),
+                        "at least 1 process",
+                    ));
+                }
             }
-            WorkerConfig::Stratum { port, .. } => {
+            WorkerConfig::Stratum {
+                port,
+                extranonce1_size,
+                difficulty,
+                max_period_secs,
+                min_difficulty_level,
+                hashrate_shift_factor,
+                mode,
+                ..
+            } => {
                 if *port == 0 {
                     return Err(Error::config("Stratum port must be greater than 0"));
                 }
+                if *max_period_secs <= 0.0 {
+                    return Err(Error::config_invalid_value(
+                        "stratum_max_period",
+                        max_period_secs.to_string(),
+                        "positive number (seconds)",
+                    ));
+                }
+                if let StratumDifficulty::Period(period) = difficulty {
+                    check_stratum_period(*period, *max_period_secs)?;
+                }
+                // Level 0 takes every hash as a share, no hash meets level 255
+                if !(1..=254).contains(min_difficulty_level) {
+                    return Err(Error::config_invalid_value(
+                        "stratum_min_difficulty_level",
+                        min_difficulty_level.to_string(),
+                        "level from 1 to 254 (leading zero bits)",
+                    ));
+                }
+                if !(*hashrate_shift_factor == 0.0 || *hashrate_shift_factor > 1.0)
+                    || !hashrate_shift_factor.is_finite()
+                {
+                    return Err(Error::config_invalid_value(
+                        "stratum_hashrate_shift_factor",
+                        hashrate_shift_factor.to_string(),
+                        "factor greater than 1, or 0 to disable",
+                    ));
+                }
+                if *mode == Some(StratumMode::Solo)
+                    && !matches!(difficulty, StratumDifficulty::Block)
+                {
+                    return Err(Error::config(
+                        "Solo stratum mode mines at the block difficulty, --stratum-difficulty must be \"block\"",
+                    ));
+                }
+                crate::workers::stratum::nonce_split(*extranonce1_size)?;
             }
             WorkerConfig::Simulation { hash_rate } => {
                 if *hash_rate <= 0.0 {
@@ -1137,6 +3092,192 @@ impl Config {
             WorkerConfig::OnDemand { .. } => WorkerType::OnDemand,
         }
     }
+
+    /// Multi-line summary of the resolved configuration, logged at startup
+    ///
+    /// The public key is redacted to its first characters.
+    pub fn startup_summary(&self) -> String {
+        let mut lines = Vec::new();
+
+        let tls = match (self.node.use_tls, self.node.insecure) {
+            (true, true) => "tls, insecure",
+            (true, false) => "tls",
+            (false, _) => "no tls",
+        };
+        lines.push(format!("  node:     {} ({})", self.node.url, tls));
+        if !self.node.fallback_urls.is_empty() {
+            lines.push(format!(
+                "  fallback: {}",
+                self.node.fallback_urls.join(", ")
+            ));
+        }
+        if !self.node.relay_urls.is_empty() {
+            lines.push(format!("  relays:   {}", self.node.relay_urls.join(", ")));
+        }
+        if let Some(network) = &self.node.network {
+            lines.push(format!("  network:  {}", network));
+        }
+        if !self.node.http_headers.is_empty() {
+            lines.push(format!("  headers:  {}", self.node.http_headers.join(", ")));
+        }
+        lines.push(format!(
+            "  chain:    {}",
+            self.node
+                .chain_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| match self.node.exclude_chains.as_slice() {
+                    [] => "all chains".to_string(),
+                    excluded => format!(
+                        "all chains except {}",
+                        excluded
+                            .iter()
+                            .map(u16::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                })
+        ));
+        lines.push(format!("  account:  {}", self.mining.account));
+        lines.push(format!(
+            "  key:      {}",
+            redact_key(&self.mining.public_key)
+        ));
+        if let Some(rotation) = &self.mining.account_rotation {
+            lines.push(format!(
+                "  rotation: {} accounts, {}",
+                self.mining.rotation_accounts.len() + 1,
+                rotation
+            ));
+        }
+        for (chain, account) in self.mining.chain_accounts().unwrap_or_default() {
+            lines.push(format!("  chain {}:  {}", chain, account.account));
+        }
+        lines.push(format!(
+            "  worker:   {} ({})",
+            self.worker_type().name(),
+            self.worker_summary()
+        ));
+
+        let log_file = self
+            .logging
+            .file
+            .as_ref()
+            .map(|file| format!(", file {}", file.display()))
+            .unwrap_or_default();
+        lines.push(format!(
+            "  logging:  {} ({}{})",
+            self.logging.level, self.logging.format, log_file
+        ));
+        lines.push(format!(
+            "  features: monitoring, submit {}, timeout {}s{}",
+            match self.node.submit_encoding {
+                SubmitEncoding::Binary => "binary",
+                SubmitEncoding::Json => "json",
+            },
+            self.node.timeout_secs,
+            match &self.worker {
+                WorkerConfig::Gpu {
+                    enable_monitoring: true,
+                    ..
+                } => ", gpu monitoring",
+                _ => "",
+            }
+        ));
+
+        format!("Configuration:\n{}", lines.join("\n"))
+    }
+
+    /// Key parameters of the configured worker
+    fn worker_summary(&self) -> String {
+        match &self.worker {
+            WorkerConfig::Cpu {
+                threads,
+                batch_size,
+                thread_priority,
+                ..
+            } => {
+                let threads = if *threads == 0 {
+                    "auto".to_string()
+                } else {
+                    threads.to_string()
+                };
+                match thread_priority {
+                    ThreadPriority::Normal => format!("threads {}, batch {}", threads, batch_size),
+                    priority => format!(
+                        "threads {}, batch {}, priority {}",
+                        threads,
+                        batch_size,
+                        format!("{:?}", priority).to_lowercase()
+                    ),
+                }
+            }
+            WorkerConfig::Gpu {
+                device_index,
+                batch_size,
+                ..
+            } => format!(
+                "device {}, batch {}",
+                device_index
+                    .map(|i| i.to_string())
+                    .unwrap_or_else(|| "auto".to_string()),
+                batch_size
+            ),
+            WorkerConfig::External {
+                command,
+                timeout_secs,
+                max_concurrent,
+                ..
+            } => {
+                format!(
+                    "command {}, timeout {}s, at most {} at once",
+                    command, timeout_secs, max_concurrent
+                )
+            }
+            WorkerConfig::Stratum {
+                port,
+                host,
+                difficulty,
+                max_connections,
+                mode,
+                ..
+            } => {
+                let difficulty =
+                    match mode.map_or(difficulty.clone(), |mode| mode.difficulty(difficulty)) {
+                        StratumDifficulty::Block => "block".to_string(),
+                        StratumDifficulty::Fixed(zeros) => zeros.to_string(),
+                        StratumDifficulty::Period(secs) => format!("period {}s", secs),
+                    };
+                let difficulty = match mode {
+                    Some(StratumMode::Solo) => format!("{} (solo)", difficulty),
+                    Some(StratumMode::Pool) => format!("{} (pool)", difficulty),
+                    None => difficulty,
+                };
+                format!(
+                    "{}:{}, difficulty {}, max {} connections",
+                    host, port, difficulty, max_connections
+                )
+            }
+            WorkerConfig::Simulation { hash_rate } => {
+                format!("hash rate {} H/s", hash_rate)
+            }
+            WorkerConfig::ConstantDelay { block_time_secs } => {
+                format!("block time {}s", block_time_secs)
+            }
+            WorkerConfig::OnDemand { port, host, .. } => format!("{}:{}", host, port),
+        }
+    }
+}
+
+/// Shorten a public key to a recognisable prefix for logs
+fn redact_key(key: &str) -> String {
+    const VISIBLE: usize = 8;
+    if key.is_empty() {
+        return "<none>".to_string();
+    }
+    match key.char_indices().nth(VISIBLE) {
+        Some((end, _)) => format!("{}...", &key[..end]),
+        None => "<redacted>".to_string(),
+    }
 }
 
 impl Default for Config {
@@ -1148,21 +3289,55 @@ impl Default for Config {
                 insecure: false,
                 timeout_secs: 30,
                 chain_id: Some(0),
+                exclude_chains: Vec::new(),
+                fallback_urls: Vec::new(),
+                relay_urls: Vec::new(),
+                submit_encoding: SubmitEncoding::default(),
+                on_node_loss: NodeLossPolicy::default(),
+                network: None,
+                dns_refresh_secs: None,
+                work_fetch_timeout_secs: None,
+                submit_timeout_secs: None,
+                http_headers: Vec::new(),
             },
             mining: MiningConfig {
                 account: "miner".to_string(),
                 public_key: "".to_string(),
                 update_interval_secs: 5,
+                hashrate_warmup_secs: default_hashrate_warmup(),
+                fallback_poll_interval_secs: default_fallback_poll_interval(),
+                max_concurrent_work_fetches: default_max_concurrent_work_fetches(),
+                confirm_blocks: false,
+                confirm_delay_secs: default_confirm_delay(),
+                rotation_accounts: Vec::new(),
+                account_rotation: None,
+                chain_accounts: BTreeMap::new(),
+                keyset: None,
             },
-            worker: WorkerConfig::Cpu {
-                threads: 0,
-                batch_size: 100_000,
+            worker: WorkerConfig::Stratum {
+                port: default_stratum_port(),
+                host: default_stratum_host(),
+                max_connections: default_max_connections(),
+                difficulty: default_stratum_difficulty(),
+                max_period_secs: default_stratum_max_period(),
+                min_difficulty_level: default_stratum_min_difficulty_level(),
+                hashrate_shift_factor: default_stratum_hashrate_shift_factor(),
+                mode: None,
+                rate_ms: default_stratum_rate(),
+                always_clean_jobs: false,
+                job_history_size: default_stratum_job_history(),
+                session_idle_timeout_secs: default_stratum_session_idle_timeout(),
+                extranonce1_size: default_stratum_extranonce1_size(),
+                audit_file: None,
+                audit_max_size_mb: default_stratum_audit_max_size(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: "plain".to_string(),
                 file: None,
+                hashrate_log_interval_secs: default_hashrate_log_interval(),
             },
+            worker_defaulted: true,
         }
     }
 }
@@ -1171,6 +3346,8 @@ impl Default for Config {
 mod tests {
     use super::*;
 
+    const TEST_KEY: &str = "368820f80c324bbc7c2b0610688a7da43e39f91d118732671cd9c7500ff43cca";
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -1186,15 +3363,19 @@ mod tests {
         // Valid config
         assert!(config.validate().is_ok());
 
-        // Invalid chain ID
-        config.node.chain_id = Some(20);
+        // No work fetches allowed
+        config.mining.max_concurrent_work_fetches = 0;
         assert!(config.validate().is_err());
-        config.node.chain_id = Some(0);
+        config.mining.max_concurrent_work_fetches = 4;
 
         // Invalid batch size
         config.worker = WorkerConfig::Cpu {
             threads: 4,
             batch_size: 0,
+            thread_priority: ThreadPriority::Normal,
+            midstate: true,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
         };
         assert!(config.validate().is_err());
     }
@@ -1205,6 +3386,10 @@ mod tests {
             worker: WorkerConfig::Cpu {
                 threads: 4,
                 batch_size: 1000,
+                thread_priority: ThreadPriority::Normal,
+                midstate: true,
+                max_nonces_per_work: 0,
+                max_memory_mb: 0,
             },
             ..Default::default()
         };
@@ -1216,11 +3401,1054 @@ mod tests {
                 host: "localhost".to_string(),
                 max_connections: 100,
                 difficulty: StratumDifficulty::Block,
+                mode: None,
+                max_period_secs: DEFAULT_STRATUM_MAX_PERIOD,
+                min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+                hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
                 rate_ms: 1000,
+                always_clean_jobs: false,
+                job_history_size: 4,
+                session_idle_timeout_secs: 600,
+                extranonce1_size: 4,
+                audit_file: None,
+                audit_max_size_mb: 100,
+            },
+            ..Default::default()
+        };
+        assert_eq!(config.worker_type(), WorkerType::Stratum);
+    }
+
+    #[test]
+    fn test_config_from_reader() {
+        let yaml = "node: api.chainweb.com\npublicKey: abc123\nworker: cpu\nthreadCount: 2\n";
+        let config = Config::from_reader(std::io::Cursor::new(yaml), "<stdin>").unwrap();
+        assert_eq!(config.node.url, "api.chainweb.com");
+        assert_eq!(config.mining.public_key, "abc123");
+        assert!(matches!(
+            config.worker,
+            WorkerConfig::Cpu { threads: 2, .. }
+        ));
+
+        let json = r#"{"node": "api.chainweb.com", "publicKey": "abc123", "worker": "cpu"}"#;
+        let config = Config::from_reader(json.as_bytes(), "<stdin>").unwrap();
+        assert_eq!(config.mining.public_key, "abc123");
+
+        assert!(Config::from_reader(std::io::empty(), "<stdin>").is_err());
+    }
+
+    #[test]
+    fn test_stdin_config_only_once() {
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--config-file",
+            "-",
+            "--config-file",
+            "-",
+        ]);
+        assert!(Config::from_args(args).is_err());
+    }
+
+    #[test]
+    fn test_startup_summary() {
+        let mut config = Config {
+            worker: WorkerConfig::Cpu {
+                threads: 4,
+                batch_size: 1000,
+                thread_priority: ThreadPriority::Normal,
+                midstate: true,
+                max_nonces_per_work: 0,
+                max_memory_mb: 0,
             },
             ..Default::default()
         };
+        config.mining.public_key =
+            "f89ef46927f506c70b6a58fd322450a936311dc6ac91f4ec3d8ef949608dbf1f".to_string();
+
+        let summary = config.startup_summary();
+        assert!(summary.contains("node:     api.chainweb.com (tls)"));
+        assert!(summary.contains("worker:   cpu (threads 4, batch 1000)"));
+        assert!(summary.contains("key:      f89ef469..."));
+        assert!(!summary.contains(&config.mining.public_key));
+
+        config.mining.public_key.clear();
+        assert!(config.startup_summary().contains("key:      <none>"));
+    }
+
+    #[test]
+    fn test_repeated_node_args() {
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "primary.example:1848",
+            "--node",
+            "http://backup.example:1848",
+            "--node",
+            "backup2.example",
+            "--public-key",
+            TEST_KEY,
+        ]);
+        let config = Config::from_args(args).unwrap();
+        assert_eq!(config.node.url, "primary.example:1848");
+        assert_eq!(
+            config.node.fallback_urls,
+            vec!["http://backup.example:1848", "backup2.example"]
+        );
+    }
+
+    #[test]
+    fn test_worker_defaults_to_stratum() {
+        assert_eq!(Config::default().worker_type(), WorkerType::Stratum);
+
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "localhost:1848",
+            "--public-key",
+            TEST_KEY,
+        ]);
+        let config = Config::from_args(args).unwrap();
         assert_eq!(config.worker_type(), WorkerType::Stratum);
+        assert!(config.worker_defaulted);
+
+        let flat =
+            Config::from_contents("node: localhost:1848\npublicKey: abc\n", "flat.yaml").unwrap();
+        assert_eq!(flat.worker_type(), WorkerType::Stratum);
+        assert!(flat.worker_defaulted);
+
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "localhost:1848",
+            "--public-key",
+            TEST_KEY,
+            "--worker",
+            "stratum",
+        ]);
+        assert!(!Config::from_args(args).unwrap().worker_defaulted);
+    }
+
+    #[test]
+    fn test_public_key_format_is_checked() {
+        let from_args = |key: &str, account: Option<&str>| {
+            let mut argv = vec![
+                "chainweb-mining-client",
+                "--node",
+                "localhost:1848",
+                "--public-key",
+                key,
+            ];
+            if let Some(account) = account {
+                argv.extend(["--account", account]);
+            }
+            Config::from_args(Args::parse_from(argv))
+        };
+
+        let config = from_args(TEST_KEY, None).unwrap();
+        assert_eq!(config.mining.account, format!("k:{}", TEST_KEY));
+
+        let err = from_args(&TEST_KEY[..40], None).unwrap_err().to_string();
+        assert!(err.contains("64 hex characters"), "{}", err);
+        assert!(err.contains("not 40 characters"), "{}", err);
+
+        let non_hex = format!("{}xyz", &TEST_KEY[..61]);
+        let err = from_args(&non_hex, None).unwrap_err().to_string();
+        assert!(err.contains("non-hex character 'x'"), "{}", err);
+
+        let keyset = "w:2Vwz0-jW6Y3tE-E85y1GLKfMU8DySGU1zXL6VPOYkxM:keys-all";
+        assert!(from_args(TEST_KEY, Some(keyset)).is_ok());
+        assert!(from_args(TEST_KEY, Some("miner")).is_ok());
+        let err = from_args(TEST_KEY, Some("w:short:keys-all"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("w:<base64url keyset hash"), "{}", err);
+        assert!(from_args(TEST_KEY, Some(&format!("k:{}", &TEST_KEY[1..]))).is_err());
+    }
+
+    fn test_config_yaml() -> String {
+        let mut config = Config::default();
+        config.mining.public_key = TEST_KEY.to_string();
+        config.render(ConfigFormat::Yaml).unwrap()
+    }
+
+    /// Serve a config at `/config.yaml` of a mock server
+    async fn serve_config(server: &mut mockito::Server) -> (mockito::Mock, String) {
+        let mock = server
+            .mock("GET", "/config.yaml")
+            .with_body(test_config_yaml())
+            .create_async()
+            .await;
+        (mock, format!("{}/config.yaml", server.url()))
+    }
+
+    #[tokio::test]
+    async fn test_load_async_within_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, test_config_yaml()).unwrap();
+        let config = Config::load_async(&path).await.unwrap();
+        assert_eq!(config.mining.public_key, TEST_KEY);
+
+        let mut server = mockito::Server::new_async().await;
+        let (_mock, url) = serve_config(&mut server).await;
+        let config = Config::load_async(Path::new(&url)).await.unwrap();
+        assert_eq!(config.mining.public_key, TEST_KEY);
+    }
+
+    #[tokio::test]
+    async fn test_from_url_within_current_thread_runtime() {
+        let mut server = mockito::Server::new_async().await;
+        let (_mock, url) = serve_config(&mut server).await;
+        let config = Config::from_url(&url).unwrap();
+        assert_eq!(config.mining.public_key, TEST_KEY);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_from_url_within_multi_thread_runtime() {
+        let mut server = mockito::Server::new_async().await;
+        let (_mock, url) = serve_config(&mut server).await;
+        let config = Config::from_url(&url).unwrap();
+        assert_eq!(config.mining.public_key, TEST_KEY);
+    }
+
+    #[test]
+    fn test_miner_keyset_file_is_loaded_and_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let from_file = |json: &str| {
+            let path = dir.path().join("keyset.json");
+            std::fs::write(&path, json).unwrap();
+            Config::from_args(Args::parse_from([
+                "chainweb-mining-client",
+                "--node",
+                "localhost:1848",
+                "--public-key",
+                TEST_KEY,
+                "--miner-keyset-file",
+                path.to_str().unwrap(),
+            ]))
+        };
+
+        let config = from_file(r#"{"keys": ["key1", "key2"], "pred": "keys-any"}"#).unwrap();
+        let keyset = config.mining.keyset.unwrap();
+        assert_eq!(keyset.keys, ["key1", "key2"]);
+        assert_eq!(keyset.pred, "keys-any");
+
+        let err = from_file(r#"{"keys": ["key1"]}"#).unwrap_err().to_string();
+        assert!(err.contains("keys and pred"), "{}", err);
+        assert!(from_file(r#"{"keys": [], "pred": "keys-all"}"#).is_err());
+        assert!(from_file(r#"{"keys": ["key1"], "pred": ""}"#).is_err());
+    }
+
+    #[test]
+    fn test_strict_config_requires_worker() {
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "localhost:1848",
+            "--public-key",
+            TEST_KEY,
+            "--strict-config",
+        ]);
+        let err = Config::from_args(args).unwrap_err();
+        assert!(err.to_string().contains("No worker selected"), "{}", err);
+
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "localhost:1848",
+            "--public-key",
+            TEST_KEY,
+            "--strict-config",
+            "--worker",
+            "cpu",
+        ]);
+        let config = Config::from_args(args).unwrap();
+        assert_eq!(config.worker_type(), WorkerType::Cpu);
+        assert!(!config.worker_defaulted);
+    }
+
+    #[test]
+    fn test_negation_flags_override_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let flat = dir.path().join("flat.yaml");
+        std::fs::write(
+            &flat,
+            format!("node: api.chainweb.com\nuseTls: true\ninsecure: true\npublicKey: {}\nworker: cpu\n", TEST_KEY),
+        )
+        .unwrap();
+        let mut nested = Config::default();
+        nested.mining.public_key = TEST_KEY.to_string();
+        nested.node.use_tls = true;
+        nested.node.insecure = true;
+        let nested_path = dir.path().join("nested.yaml");
+        std::fs::write(&nested_path, nested.render(ConfigFormat::Yaml).unwrap()).unwrap();
+
+        for path in [&flat, &nested_path] {
+            let load = |extra: &[&str]| {
+                let mut argv = vec![
+                    "chainweb-mining-client",
+                    "--config-file",
+                    path.to_str().unwrap(),
+                ];
+                argv.extend_from_slice(extra);
+                Config::from_args(Args::parse_from(argv)).unwrap()
+            };
+            let config = load(&[]);
+            assert!(config.node.use_tls && config.node.insecure);
+
+            let config = load(&["--no-tls", "--no-insecure"]);
+            assert!(!config.node.use_tls);
+            assert!(!config.node.insecure);
+
+            // Also with the node replaced on the command line, and over the
+            // positive flags
+            let config = load(&[
+                "--node",
+                "localhost:1848",
+                "--tls",
+                "--no-tls",
+                "--insecure",
+                "--no-insecure",
+            ]);
+            assert!(!config.node.use_tls);
+            assert!(!config.node.insecure);
+        }
+
+        // Without a config file the negations win the same way
+        let config = Config::from_args(Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "https://api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+            "--tls",
+            "--no-tls",
+            "--insecure",
+            "--no-insecure",
+        ]))
+        .unwrap();
+        assert!(!config.node.use_tls);
+        assert!(!config.node.insecure);
+
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--generate-key",
+            "--no-generate-key",
+        ]);
+        assert!(!args.generate_key_requested());
+        assert!(
+            Args::parse_from(["chainweb-mining-client", "--generate-key"]).generate_key_requested()
+        );
+    }
+
+    #[test]
+    fn test_relay_node_arg() {
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "primary.example:1848",
+            "--relay-node",
+            "relay1.example:1848",
+            "--relay-node",
+            "https://relay2.example",
+            "--public-key",
+            TEST_KEY,
+        ]);
+        let config = Config::from_args(args).unwrap();
+        assert!(config.node.fallback_urls.is_empty());
+        assert_eq!(
+            config.node.relay_urls,
+            vec!["relay1.example:1848", "https://relay2.example"]
+        );
+        assert!(
+            config
+                .startup_summary()
+                .contains("relays:   relay1.example:1848")
+        );
+    }
+
+    #[test]
+    fn test_network_arg() {
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+        ]);
+        assert_eq!(Config::from_args(args).unwrap().node.network, None);
+
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+            "--network",
+            "testnet04",
+        ]);
+        let config = Config::from_args(args).unwrap();
+        assert_eq!(config.node.network.as_deref(), Some("testnet04"));
+    }
+
+    #[test]
+    fn test_log_level_shorthands() {
+        let level = |extra: &[&str]| {
+            let base = [
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                TEST_KEY,
+            ];
+            let args = Args::parse_from(base.iter().chain(extra));
+            Config::from_args(args).unwrap().logging.level
+        };
+        assert_eq!(level(&[]), "info");
+        assert_eq!(level(&["-q"]), "error");
+        assert_eq!(level(&["--quiet"]), "error");
+        assert_eq!(level(&["-v"]), "debug");
+        assert_eq!(level(&["-vv"]), "trace");
+        assert_eq!(level(&["--verbose", "--verbose", "--verbose"]), "trace");
+        assert_eq!(level(&["-vv", "--log-level", "warn"]), "warn");
+        assert_eq!(level(&["-q", "--log-level", "info"]), "info");
+
+        let conflict = Args::try_parse_from(["chainweb-mining-client", "-q", "-v"]);
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn test_dns_refresh_arg() {
+        let base = [
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+        ];
+        let args = Args::parse_from(base.into_iter().chain(["--dns-refresh-secs", "300"]));
+        let mut config = Config::from_args(args).unwrap();
+        assert_eq!(config.node.dns_refresh_secs, Some(300));
+        assert!(config.validate().is_ok());
+
+        config.node.dns_refresh_secs = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_request_timeout_args() {
+        let base = [
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+        ];
+        let config = Config::from_args(Args::parse_from(base)).unwrap();
+        assert_eq!(config.node.work_fetch_timeout_secs, None);
+        assert_eq!(config.node.submit_timeout_secs, None);
+
+        let args = Args::parse_from(base.into_iter().chain([
+            "--work-fetch-timeout-secs",
+            "3",
+            "--submit-timeout-secs",
+            "60",
+        ]));
+        let mut config = Config::from_args(args).unwrap();
+        assert_eq!(config.node.work_fetch_timeout_secs, Some(3));
+        assert_eq!(config.node.submit_timeout_secs, Some(60));
+        assert!(config.validate().is_ok());
+
+        config.node.work_fetch_timeout_secs = Some(0);
+        assert!(config.validate().is_err());
+        config.node.work_fetch_timeout_secs = None;
+        config.node.submit_timeout_secs = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_http_header_arg() {
+        let base = [
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+        ];
+        let args = Args::parse_from(base.into_iter().chain([
+            "--http-header",
+            "X-Miner-Rig: rig-7",
+            "--http-header",
+            "X-Pool: east",
+        ]));
+        let mut config = Config::from_args(args).unwrap();
+        assert!(config.validate().is_ok());
+        let headers = config.node.header_map().unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-miner-rig"], "rig-7");
+        assert_eq!(headers["x-pool"], "east");
+
+        config.node.http_headers.push("X-Missing-Value".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_exclude_chains_arg() {
+        let base = [
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+        ];
+        let args = Args::parse_from(base.into_iter().chain(["--exclude-chains", "3,7,12"]));
+        let mut config = Config::from_args(args).unwrap();
+        assert_eq!(config.node.exclude_chains, vec![3, 7, 12]);
+        assert!(
+            config
+                .startup_summary()
+                .contains("all chains except 3, 7, 12")
+        );
+
+        // The configured chain can't also be excluded
+        config.node.chain_id = Some(1);
+        assert!(config.validate().is_ok());
+        config.node.chain_id = Some(7);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_on_node_loss_arg() {
+        let base = [
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+        ];
+        let config = Config::from_args(Args::parse_from(base)).unwrap();
+        assert_eq!(config.node.on_node_loss, NodeLossPolicy::Retry);
+
+        let args = Args::parse_from(base.into_iter().chain(["--on-node-loss", "exit"]));
+        assert_eq!(
+            Config::from_args(args).unwrap().node.on_node_loss,
+            NodeLossPolicy::Exit
+        );
+
+        let args = Args::parse_from(base.into_iter().chain(["--on-node-loss", "restart"]));
+        assert!(Config::from_args(args).is_err());
+    }
+
+    #[test]
+    fn test_rendered_config_round_trips() {
+        let mut config = Config::from_args(Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+            "--worker",
+            "cpu",
+            "--thread-count",
+            "3",
+        ]))
+        .unwrap();
+        config
+            .mining
+            .chain_accounts
+            .insert("5".to_string(), format!("k:{}", TEST_KEY));
+
+        for (format, source) in [
+            (ConfigFormat::Yaml, "config.yaml"),
+            (ConfigFormat::Json, "config.json"),
+            (ConfigFormat::Toml, "config.toml"),
+        ] {
+            let rendered = config.render(format).unwrap();
+            let parsed = Config::from_contents(&rendered, source).unwrap_or_else(|e| {
+                panic!("{:?} output doesn't parse: {}\n{}", format, e, rendered)
+            });
+            assert_eq!(parsed.node.url, "api.chainweb.com");
+            assert_eq!(parsed.mining.public_key, TEST_KEY);
+            assert_eq!(parsed.mining.chain_accounts, config.mining.chain_accounts);
+            assert!(matches!(
+                parsed.worker,
+                WorkerConfig::Cpu { threads: 3, .. }
+            ));
+            // Serializing the parsed config again gives the same output
+            assert_eq!(parsed.render(format).unwrap(), rendered);
+        }
+        assert!(
+            config
+                .render(ConfigFormat::Json)
+                .unwrap()
+                .starts_with("{\n  \"")
+        );
+        assert_eq!("TOML".parse::<ConfigFormat>().unwrap(), ConfigFormat::Toml);
+        assert!("xml".parse::<ConfigFormat>().is_err());
+    }
+
+    #[test]
+    fn test_thread_count_parsing() {
+        assert_eq!("auto".parse::<ThreadCount>().unwrap(), ThreadCount::Auto);
+        assert_eq!(
+            "auto:50%".parse::<ThreadCount>().unwrap(),
+            ThreadCount::Percent(50)
+        );
+        assert_eq!("6".parse::<ThreadCount>().unwrap(), ThreadCount::Fixed(6));
+        for invalid in ["auto:0%", "auto:101%", "auto:50", "auto:%", "half", "-1"] {
+            assert!(
+                invalid.parse::<ThreadCount>().is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+
+        // Resolved on a machine with 8 cores
+        assert_eq!(ThreadCount::Auto.resolve(8), 8);
+        assert_eq!(ThreadCount::Percent(50).resolve(8), 4);
+        assert_eq!(ThreadCount::Percent(75).resolve(6), 5);
+        assert_eq!(ThreadCount::Percent(1).resolve(8), 1);
+        assert_eq!(ThreadCount::Fixed(3).resolve(8), 3);
+    }
+
+    #[test]
+    fn test_thread_count_arg() {
+        let parse = |count: &str| {
+            Config::from_args(Args::parse_from([
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                TEST_KEY,
+                "--worker",
+                "cpu",
+                "--thread-count",
+                count,
+            ]))
+        };
+        let threads = |config: Config| match config.worker {
+            WorkerConfig::Cpu { threads, .. } => threads,
+            _ => unreachable!(),
+        };
+        assert_eq!(threads(parse("3").unwrap()), 3);
+        let cores = ThreadCount::Auto.resolve(num_cpus::get());
+        assert_eq!(threads(parse("auto").unwrap()), cores);
+        assert_eq!(threads(parse("auto:100%").unwrap()), cores);
+        assert!(parse("auto:200%").is_err());
+
+        // Degenerate core counts
+        assert_eq!(ThreadCount::Auto.resolve(0), 1);
+        assert_eq!(ThreadCount::Percent(50).resolve(0), 1);
+        assert_eq!(ThreadCount::Auto.resolve(100_000), MAX_AUTO_THREADS);
+        assert_eq!(
+            ThreadCount::Percent(50).resolve(100_000),
+            MAX_AUTO_THREADS / 2
+        );
+        assert_eq!(ThreadCount::Fixed(2048).resolve(4), 2048);
+    }
+
+    #[test]
+    fn test_thread_priority_arg() {
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "api.chainweb.com",
+            "--public-key",
+            TEST_KEY,
+            "--worker",
+            "cpu",
+            "--thread-priority",
+            "idle",
+        ]);
+        let config = Config::from_args(args).unwrap();
+        assert!(matches!(
+            config.worker,
+            WorkerConfig::Cpu {
+                thread_priority: ThreadPriority::Idle,
+                ..
+            }
+        ));
+        assert!(config.startup_summary().contains("priority idle"));
+    }
+
+    #[test]
+    fn test_account_rotation_args() {
+        let key1 = "a".repeat(64);
+        let key2 = "b".repeat(64);
+        let parse = |extra: &[&str]| {
+            let mut args = vec![
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                &key1,
+            ];
+            args.extend_from_slice(extra);
+            Config::from_args(Args::parse_from(args))
+        };
+
+        let rotation = format!("pool={}", key2);
+        let config = parse(&[
+            "--rotation-account",
+            &rotation,
+            "--account-rotation",
+            "blocks:10",
+        ])
+        .unwrap();
+        assert_eq!(
+            config.mining.account_rotation,
+            Some(AccountRotation::Blocks(10))
+        );
+        let accounts = config.mining.accounts();
+        assert_eq!(accounts[0].account, format!("k:{}", key1));
+        assert_eq!(
+            accounts[1],
+            MiningAccount {
+                account: "pool".to_string(),
+                public_key: key2.clone()
+            }
+        );
+        assert!(
+            config
+                .startup_summary()
+                .contains("rotation: 2 accounts, blocks:10")
+        );
+
+        // Every account is checked, and rotation needs both a policy and accounts
+        assert!(
+            parse(&[
+                "--rotation-account",
+                "not-a-key",
+                "--account-rotation",
+                "blocks:10"
+            ])
+            .is_err()
+        );
+        let wrong_k = format!("k:{}={}", key1, key2);
+        assert!(
+            parse(&[
+                "--rotation-account",
+                &wrong_k,
+                "--account-rotation",
+                "interval:60"
+            ])
+            .is_err()
+        );
+        assert!(parse(&["--rotation-account", &key2]).is_err());
+        assert!(parse(&["--account-rotation", "interval:60"]).is_err());
+        assert!(
+            parse(&[
+                "--rotation-account",
+                &key2,
+                "--account-rotation",
+                "blocks:0"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_chain_accounts() {
+        let key1 = "a".repeat(64);
+        let key2 = "b".repeat(64);
+        let parse = |extra: &[&str]| {
+            let mut args = vec![
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                &key1,
+            ];
+            args.extend_from_slice(extra);
+            Config::from_args(Args::parse_from(args))
+        };
+
+        let k_account = format!("5=k:{}", key2);
+        let named_account = format!("7=pool={}", key2);
+        let config = parse(&[
+            "--chain-account",
+            &k_account,
+            "--chain-account",
+            &named_account,
+        ])
+        .unwrap();
+        let accounts = config.mining.chain_accounts().unwrap();
+        assert_eq!(
+            accounts[&5],
+            MiningAccount {
+                account: format!("k:{}", key2),
+                public_key: key2.clone()
+            }
+        );
+        assert_eq!(
+            accounts[&7],
+            MiningAccount {
+                account: "pool".to_string(),
+                public_key: key2.clone()
+            }
+        );
+        assert!(!accounts.contains_key(&0));
+        assert!(config.startup_summary().contains("chain 7:  pool"));
+
+        // Chains and accounts are both checked
+        assert!(parse(&["--chain-account", &format!("x=k:{}", key2)]).is_err());
+        assert!(parse(&["--chain-account", "5=not-a-key"]).is_err());
+        assert!(parse(&["--chain-account", &format!("5=k:{}={}", key1, key2)]).is_err());
+        assert!(parse(&["--chain-account", &key2]).is_err());
+
+        let yaml = format!(
+            "node:\n  url: api.chainweb.com\nmining:\n  account: k:{key1}\n  public_key: {key1}\n  chain_accounts:\n    \"3\": k:{key2}\nworker:\n  type: cpu\n  threads: 1\nlogging:\n  level: info\n"
+        );
+        let config = Config::from_contents(&yaml, "config.yaml").unwrap();
+        config.validate_accounts().unwrap();
+        assert_eq!(config.mining.chain_accounts().unwrap()[&3].public_key, key2);
+    }
+
+    #[test]
+    fn test_midstate_is_opt_in() {
+        let midstate = |worker: &str| {
+            let yaml = format!(
+                "node:\n  url: api.chainweb.com\nmining:\n  account: k:{key}\n  public_key: {key}\nworker:\n  type: cpu\n  threads: 1\n{worker}logging:\n  level: info\n",
+                key = TEST_KEY
+            );
+            match Config::from_contents(&yaml, "config.yaml").unwrap().worker {
+                WorkerConfig::Cpu { midstate, .. } => midstate,
+                other => panic!("unexpected worker {:?}", other),
+            }
+        };
+        assert!(!midstate(""));
+        assert!(midstate("  midstate: true\n"));
+
+        let args = Args::parse_from([
+            "chainweb-mining-client",
+            "--node",
+            "localhost:1848",
+            "--worker",
+            "cpu",
+            "--public-key",
+            TEST_KEY,
+        ]);
+        assert!(matches!(
+            Config::from_args(args).unwrap().worker,
+            WorkerConfig::Cpu {
+                midstate: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_stratum_period_bounds() {
+        assert!(matches!(
+            StratumDifficulty::parse_with_max_period("30.5", DEFAULT_STRATUM_MAX_PERIOD),
+            Ok(StratumDifficulty::Period(p)) if p == 30.5
+        ));
+        assert!(
+            StratumDifficulty::parse_with_max_period("3600.0", DEFAULT_STRATUM_MAX_PERIOD).is_ok()
+        );
+        assert!(matches!(
+            StratumDifficulty::parse_with_max_period("1000000", DEFAULT_STRATUM_MAX_PERIOD),
+            Err(Error::Config(
+                crate::error::ConfigError::InvalidValue { .. }
+            ))
+        ));
+
+        // Fixed difficulties and block are unaffected by the bound
+        assert!(matches!(
+            StratumDifficulty::parse_with_max_period("40", 1.0),
+            Ok(StratumDifficulty::Fixed(40))
+        ));
+
+        let stratum_args = |extra: &[&str]| {
+            let mut argv = vec![
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                TEST_KEY,
+                "--worker",
+                "stratum",
+            ];
+            argv.extend_from_slice(extra);
+            Config::from_args(Args::parse_from(argv))
+        };
+        assert!(matches!(
+            stratum_args(&["--stratum-difficulty", "7200.0"]),
+            Err(Error::Config(
+                crate::error::ConfigError::InvalidValue { .. }
+            ))
+        ));
+        let config = stratum_args(&[
+            "--stratum-difficulty",
+            "7200.0",
+            "--stratum-max-period",
+            "86400",
+        ])
+        .unwrap();
+        assert!(matches!(
+            config.worker,
+            WorkerConfig::Stratum { difficulty: StratumDifficulty::Period(p), .. } if p == 7200.0
+        ));
+
+        // Nested configs are checked on validation
+        let config = Config {
+            worker: WorkerConfig::Stratum {
+                port: 3333,
+                host: "localhost".to_string(),
+                max_connections: 100,
+                difficulty: StratumDifficulty::Period(1e6),
+                mode: None,
+                max_period_secs: DEFAULT_STRATUM_MAX_PERIOD,
+                min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+                hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+                rate_ms: 1000,
+                always_clean_jobs: false,
+                job_history_size: 4,
+                session_idle_timeout_secs: 600,
+                extranonce1_size: 4,
+                audit_file: None,
+                audit_max_size_mb: 100,
+            },
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_stratum_mode() {
+        assert!(matches!(
+            "solo".parse::<StratumMode>(),
+            Ok(StratumMode::Solo)
+        ));
+        assert!(matches!(
+            "pool".parse::<StratumMode>(),
+            Ok(StratumMode::Pool)
+        ));
+        assert!("shared".parse::<StratumMode>().is_err());
+
+        let stratum_args = |extra: &[&str]| {
+            let mut argv = vec![
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                TEST_KEY,
+                "--worker",
+                "stratum",
+            ];
+            argv.extend_from_slice(extra);
+            Config::from_args(Args::parse_from(argv))
+                .and_then(|config| config.validate().map(|_| config))
+        };
+        let config = stratum_args(&["--stratum-mode", "solo"]).unwrap();
+        assert!(matches!(
+            config.worker,
+            WorkerConfig::Stratum {
+                mode: Some(StratumMode::Solo),
+                ..
+            }
+        ));
+        // Solo shares are blocks, so any other difficulty contradicts the mode
+        assert!(stratum_args(&["--stratum-mode", "solo", "--stratum-difficulty", "30"]).is_err());
+
+        assert!(matches!(
+            StratumMode::Pool.difficulty(&StratumDifficulty::Block),
+            StratumDifficulty::Period(p) if p == DEFAULT_POOL_SHARE_PERIOD
+        ));
+        assert!(matches!(
+            StratumMode::Pool.difficulty(&StratumDifficulty::Fixed(30)),
+            StratumDifficulty::Fixed(30)
+        ));
+        assert!(stratum_args(&["--stratum-mode", "pool", "--stratum-difficulty", "30"]).is_ok());
+    }
+
+    #[test]
+    fn test_stratum_min_difficulty_level() {
+        let stratum_args = |extra: &[&str]| {
+            let mut argv = vec![
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                TEST_KEY,
+                "--worker",
+                "stratum",
+            ];
+            argv.extend_from_slice(extra);
+            Config::from_args(Args::parse_from(argv))
+                .and_then(|config| config.validate().map(|_| config))
+        };
+        let level = |config: Config| match config.worker {
+            WorkerConfig::Stratum {
+                min_difficulty_level,
+                ..
+            } => min_difficulty_level,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            level(stratum_args(&[]).unwrap()),
+            DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL
+        );
+        assert_eq!(
+            level(stratum_args(&["--stratum-min-difficulty-level", "12"]).unwrap()),
+            12
+        );
+        assert!(stratum_args(&["--stratum-min-difficulty-level", "0"]).is_err());
+        assert!(stratum_args(&["--stratum-min-difficulty-level", "255"]).is_err());
+
+        let shift_factor = |config: Config| match config.worker {
+            WorkerConfig::Stratum {
+                hashrate_shift_factor,
+                ..
+            } => hashrate_shift_factor,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            shift_factor(stratum_args(&[]).unwrap()),
+            DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR
+        );
+        assert_eq!(
+            shift_factor(stratum_args(&["--stratum-hashrate-shift-factor", "2"]).unwrap()),
+            2.0
+        );
+        assert_eq!(
+            shift_factor(stratum_args(&["--stratum-hashrate-shift-factor", "0"]).unwrap()),
+            0.0
+        );
+        assert!(stratum_args(&["--stratum-hashrate-shift-factor", "1"]).is_err());
+        assert!(stratum_args(&["--stratum-hashrate-shift-factor=-2"]).is_err());
+    }
+
+    #[test]
+    fn test_external_worker_max_concurrent() {
+        let external_args = |extra: &[&str]| {
+            let mut argv = vec![
+                "chainweb-mining-client",
+                "--node",
+                "api.chainweb.com",
+                "--public-key",
+                TEST_KEY,
+                "--worker",
+                "external",
+                "--external-worker-cmd",
+                "/usr/local/bin/miner",
+            ];
+            argv.extend_from_slice(extra);
+            Config::from_args(Args::parse_from(argv))
+                .and_then(|config| config.validate().map(|_| config))
+        };
+        let max_concurrent = |config: Config| match config.worker {
+            WorkerConfig::External { max_concurrent, .. } => max_concurrent,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            max_concurrent(external_args(&[]).unwrap()),
+            DEFAULT_EXTERNAL_MAX_CONCURRENT
+        );
+        assert_eq!(
+            max_concurrent(external_args(&["--external-worker-max-concurrent", "3"]).unwrap()),
+            3
+        );
+        assert!(external_args(&["--external-worker-max-concurrent", "0"]).is_err());
     }
 
     #[test]
@@ -0,0 +1,237 @@
+//! Typed node URL parsing and host normalization
+
+use crate::error::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Default port of the chainweb node service API without TLS
+pub const DEFAULT_NODE_PORT: u16 = 1848;
+
+/// Default HTTPS port
+pub const DEFAULT_TLS_PORT: u16 = 443;
+
+/// A chainweb node address parsed from user input
+///
+/// Accepts `host`, `host:port`, `http://host[:port]` and `https://host[:port]`
+/// (trailing slashes are ignored). The scheme, when present, decides whether
+/// TLS is used; otherwise TLS is implied, matching the Haskell client default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeUrl {
+    host: String,
+    port: Option<u16>,
+    scheme_tls: Option<bool>,
+}
+
+impl NodeUrl {
+    /// Parse a node address from user input
+    pub fn parse(input: &str) -> Result<Self> {
+        let trimmed = input.trim();
+        let lower = trimmed.to_ascii_lowercase();
+        let (rest, scheme_tls) = if lower.starts_with("https://") {
+            (&trimmed["https://".len()..], Some(true))
+        } else if lower.starts_with("http://") {
+            (&trimmed["http://".len()..], Some(false))
+        } else if lower.contains("://") {
+            return Err(invalid(input, "unsupported URL scheme"));
+        } else {
+            (trimmed, None)
+        };
+
+        let authority = rest.trim_end_matches('/');
+        if authority.contains('/') {
+            return Err(invalid(input, "node address must not contain a path"));
+        }
+
+        let (host, port) =
+            split_host_port(authority).ok_or_else(|| invalid(input, "invalid port"))?;
+        if host.is_empty() {
+            return Err(invalid(input, "missing host"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            scheme_tls,
+        })
+    }
+
+    /// Host name or address, without port
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Port connected to with the TLS setting implied by the input
+    pub fn port(&self) -> u16 {
+        self.port_for(self.implies_tls())
+    }
+
+    /// Port connected to: the explicit one, or else 443/1848 depending on
+    /// `use_tls`
+    pub fn port_for(&self, use_tls: bool) -> u16 {
+        self.port.unwrap_or(if use_tls {
+            DEFAULT_TLS_PORT
+        } else {
+            DEFAULT_NODE_PORT
+        })
+    }
+
+    /// Port given explicitly in the input, if any
+    pub fn explicit_port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// TLS setting given by an explicit `http://` or `https://` scheme
+    pub fn scheme_tls(&self) -> Option<bool> {
+        self.scheme_tls
+    }
+
+    /// Whether TLS is implied by the input (defaults to `true` without a scheme)
+    pub fn implies_tls(&self) -> bool {
+        self.scheme_tls.unwrap_or(true)
+    }
+
+    /// Resolve TLS usage: explicit `--tls`/`--no-tls` style settings win over the scheme
+    pub fn use_tls(&self, explicit: Option<bool>) -> bool {
+        explicit.unwrap_or_else(|| self.implies_tls())
+    }
+
+    /// Address as stored in the configuration (`host` or `host:port`, no scheme)
+    pub fn authority(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{}", self.host, port),
+            None => self.host.clone(),
+        }
+    }
+
+    /// Address requests go to with `use_tls`; without TLS the node port
+    /// is spelled out, as it isn't the HTTP default
+    pub fn authority_for(&self, use_tls: bool) -> String {
+        if use_tls {
+            self.authority()
+        } else {
+            format!("{}:{}", self.host, self.port_for(false))
+        }
+    }
+}
+
+impl FromStr for NodeUrl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for NodeUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.authority())
+    }
+}
+
+/// Normalize a bind interface: `*` (any interface) becomes `0.0.0.0`
+pub fn normalize_interface(host: &str) -> String {
+    match host.trim() {
+        "*" | "" => "0.0.0.0".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn invalid(input: &str, reason: &str) -> Error {
+    Error::config_invalid_value(
+        "node",
+        input,
+        format!("host[:port] or http(s)://host[:port] ({})", reason),
+    )
+}
+
+/// Split `host[:port]`, keeping bracketed IPv6 literals intact
+fn split_host_port(authority: &str) -> Option<(&str, Option<u16>)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let end = rest.find(']')?;
+        let host = &authority[..end + 2];
+        return match &rest[end + 1..] {
+            "" => Some((host, None)),
+            port => Some((host, Some(port.strip_prefix(':')?.parse().ok()?))),
+        };
+    }
+
+    match authority.rsplit_once(':') {
+        Some((host, port)) => Some((host, Some(port.parse().ok()?))),
+        None => Some((authority, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localhost_with_port() {
+        let url = NodeUrl::parse("localhost:1848").unwrap();
+        assert_eq!(url.host(), "localhost");
+        assert_eq!(url.port(), 1848);
+        assert_eq!(url.scheme_tls(), None);
+        assert!(url.implies_tls());
+        assert_eq!(url.authority(), "localhost:1848");
+    }
+
+    #[test]
+    fn test_https_url() {
+        let url = NodeUrl::parse("https://api.chainweb.com").unwrap();
+        assert_eq!(url.host(), "api.chainweb.com");
+        assert_eq!(url.port(), 443);
+        assert_eq!(url.explicit_port(), None);
+        assert_eq!(url.scheme_tls(), Some(true));
+        assert_eq!(url.authority(), "api.chainweb.com");
+    }
+
+    #[test]
+    fn test_http_url() {
+        let url = NodeUrl::parse("http://host").unwrap();
+        assert_eq!(url.host(), "host");
+        assert_eq!(url.port(), 1848);
+        assert_eq!(url.port_for(true), 443);
+        assert_eq!(url.authority_for(false), "host:1848");
+        assert_eq!(url.authority_for(true), "host");
+        assert!(!url.implies_tls());
+        assert!(url.use_tls(Some(true)));
+        assert_eq!(url.to_string(), "host");
+    }
+
+    #[test]
+    fn test_bare_host() {
+        let url = NodeUrl::parse("api.chainweb.com").unwrap();
+        assert_eq!(url.host(), "api.chainweb.com");
+        assert_eq!(url.port(), 443);
+        assert!(url.use_tls(None));
+        assert!(!url.use_tls(Some(false)));
+        assert_eq!(url.port_for(false), 1848);
+    }
+
+    #[test]
+    fn test_scheme_and_trailing_slash() {
+        let url = NodeUrl::parse("HTTPS://node.example:8443/").unwrap();
+        assert_eq!(url.authority(), "node.example:8443");
+        assert_eq!(url.port(), 8443);
+
+        let url = NodeUrl::parse("[::1]:1848").unwrap();
+        assert_eq!(url.host(), "[::1]");
+        assert_eq!(url.port(), 1848);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert!(NodeUrl::parse("").is_err());
+        assert!(NodeUrl::parse("https://").is_err());
+        assert!(NodeUrl::parse("host:notaport").is_err());
+        assert!(NodeUrl::parse("host:70000").is_err());
+        assert!(NodeUrl::parse("ftp://host").is_err());
+        assert!(NodeUrl::parse("http://host/chainweb").is_err());
+    }
+
+    #[test]
+    fn test_normalize_interface() {
+        assert_eq!(normalize_interface("*"), "0.0.0.0");
+        assert_eq!(normalize_interface("127.0.0.1"), "127.0.0.1");
+    }
+}
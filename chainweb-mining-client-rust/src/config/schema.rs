@@ -0,0 +1,709 @@
+//! Machine-readable description of the configuration format
+//!
+//! Two renderings are available: a JSON Schema (draft 2020-12) covering both
+//! the nested and the flat (Haskell-compatible) layouts, and a commented
+//! example config listing every field with its default. Defaults are taken
+//! from the same functions serde uses, so both stay in sync with parsing.
+
+use super::*;
+use crate::protocol::chainweb::KNOWN_NETWORKS;
+use serde_json::{Value, json};
+
+/// Output format of `--config-schema`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaFormat {
+    /// JSON Schema document
+    #[default]
+    JsonSchema,
+    /// Commented example config in YAML
+    Example,
+}
+
+impl FromStr for SchemaFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json-schema" => Ok(SchemaFormat::JsonSchema),
+            "example" => Ok(SchemaFormat::Example),
+            _ => Err(Error::config_invalid_value(
+                "config_schema",
+                s,
+                "json-schema or example",
+            )),
+        }
+    }
+}
+
+impl SchemaFormat {
+    /// Render the schema in this format
+    pub fn render(self) -> Result<String> {
+        match self {
+            SchemaFormat::JsonSchema => Ok(serde_json::to_string_pretty(&config_schema())? + "\n"),
+            SchemaFormat::Example => Ok(example_config()),
+        }
+    }
+}
+
+fn field(kind: &str, description: &str) -> Value {
+    json!({ "type": kind, "description": description })
+}
+
+fn field_with_default(kind: &str, description: &str, default: impl Into<Value>) -> Value {
+    json!({ "type": kind, "description": description, "default": default.into() })
+}
+
+fn rotation_accounts_schema() -> Value {
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "account": field("string", "Miner account"),
+                "public_key": field("string", "Public key"),
+            },
+            "required": ["account", "public_key"],
+        },
+        "description": "Accounts to rotate through after the primary one",
+        "default": [],
+    })
+}
+
+fn account_rotation_schema() -> Value {
+    json!({
+        "type": ["string", "null"],
+        "pattern": "^(blocks|interval):[1-9][0-9]*$",
+        "description": "When to rotate accounts: blocks:N (accepted solutions) or interval:SECS (no rotation if unset)",
+        "default": null,
+    })
+}
+
+fn chain_accounts_schema() -> Value {
+    json!({
+        "type": "object",
+        "propertyNames": { "pattern": "^[0-9]+$" },
+        "additionalProperties": { "type": "string" },
+        "description": "Account receiving the rewards of a chain, keyed by chain id, as [ACCOUNT=]PUBLIC_KEY or k:PUBLIC_KEY; other chains use the primary account",
+        "default": {},
+    })
+}
+
+fn keyset_schema() -> Value {
+    json!({
+        "type": ["object", "null"],
+        "properties": {
+            "keys": {
+                "type": "array",
+                "items": { "type": "string" },
+                "minItems": 1,
+                "description": "Public keys of the keyset",
+            },
+            "pred": { "type": "string", "description": "Predicate over the keys, e.g. keys-all, keys-any or keys-2" },
+        },
+        "required": ["keys", "pred"],
+        "description": "Keyset sent in work requests instead of the public key alone",
+        "default": null,
+    })
+}
+
+fn worker_variant(name: &str, description: &str, properties: Value, required: &[&str]) -> Value {
+    let mut properties = properties;
+    properties["type"] = json!({ "const": name });
+    let mut required: Vec<&str> = required.to_vec();
+    required.insert(0, "type");
+    json!({
+        "title": name,
+        "description": description,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn worker_schema() -> Value {
+    let difficulty = json!({
+        "description": "null for the block difficulty, an integer for a fixed number of leading zero bits, or a float for a target share period in seconds",
+        "oneOf": [
+            { "type": "null" },
+            { "type": "integer", "minimum": 0, "maximum": 255 },
+            { "type": "number", "exclusiveMinimum": 0 }
+        ],
+        "default": null,
+    });
+
+    json!({
+        "description": "Worker that produces solutions, selected by `type`",
+        "oneOf": [
+            worker_variant("cpu", "Mine on the CPU", json!({
+                "threads": field("integer", "Number of threads (0 = all cores)"),
+                "batch_size": field_with_default("integer", "Batch size for nonce checking", default_batch_size()),
+                "thread_priority": { "enum": ["normal", "low", "idle"], "description": "OS scheduling priority of the mining threads", "default": "normal" },
+                "midstate": field_with_default("boolean", "Hash each nonce from a midstate over the fixed part of the work header instead of using the SIMD miners", false),
+                "max_nonces_per_work": field_with_default("integer", "Nonces searched per work before fetching fresh work (0 = unlimited)", 0),
+                "max_memory_mb": field_with_default("integer", "Memory limit of the batch buffers in megabytes; the batch size is reduced to fit (0 = unlimited)", 0),
+            }), &["threads"]),
+            worker_variant("gpu", "Mine on a GPU", json!({
+                "device_index": field("integer", "Device index to use (unset = auto-select best device)"),
+                "workgroup_size": field_with_default("integer", "Workgroup size (number of threads per workgroup)", default_workgroup_size()),
+                "workgroup_count": field_with_default("integer", "Number of workgroups to dispatch", default_workgroup_count()),
+                "batch_size": field_with_default("integer", "Maximum nonces to process in one batch", default_gpu_batch_size()),
+                "enable_monitoring": field_with_default("boolean", "Enable GPU performance monitoring", default_enable_monitoring()),
+            }), &[]),
+            worker_variant("external", "Delegate mining to an external program", json!({
+                "command": field("string", "Command to execute"),
+                "args": { "type": "array", "items": { "type": "string" }, "description": "Command arguments", "default": [] },
+                "env": {
+                    "type": "array",
+                    "items": { "type": "array", "prefixItems": [{ "type": "string" }, { "type": "string" }], "minItems": 2, "maxItems": 2 },
+                    "description": "Environment variables as [name, value] pairs",
+                    "default": [],
+                },
+                "timeout_secs": field_with_default("integer", "Timeout in seconds", default_external_timeout()),
+                "max_concurrent": { "type": "integer", "minimum": 1, "description": "Maximum number of processes running at once", "default": default_external_max_concurrent() },
+            }), &["command"]),
+            worker_variant("stratum", "Serve work to ASICs over Stratum", json!({
+                "port": field_with_default("integer", "Listen port", default_stratum_port()),
+                "host": field_with_default("string", "Listen address", default_stratum_host()),
+                "max_connections": field_with_default("integer", "Max connections", default_max_connections()),
+                "difficulty": difficulty,
+                "mode": { "enum": ["solo", "pool", null], "description": "solo: shares must meet the block target; pool: shares below it are accepted and the block difficulty becomes a period difficulty", "default": null },
+                "max_period_secs": field_with_default("number", "Longest share period in seconds accepted for a period difficulty", default_stratum_max_period()),
+                "min_difficulty_level": { "type": "integer", "minimum": 1, "maximum": 254, "description": "Easiest difficulty level (leading zero bits of the target) of a period difficulty", "default": default_stratum_min_difficulty_level() },
+                "hashrate_shift_factor": field_with_default("number", "Hash rate change factor after which a period difficulty is recalculated from the latest shares only (0 disables)", default_stratum_hashrate_shift_factor()),
+                "rate_ms": field_with_default("integer", "Job emission rate in milliseconds", default_stratum_rate()),
+                "always_clean_jobs": field_with_default("boolean", "Always set clean_jobs, even for time-only job updates", false),
+                "job_history_size": field_with_default("integer", "Number of recent jobs (including the current one) accepted for submits", default_stratum_job_history()),
+                "session_idle_timeout_secs": field_with_default("integer", "Disconnect sessions that submit no shares for this many seconds (0 disables)", default_stratum_session_idle_timeout()),
+                "extranonce1_size": { "type": "integer", "minimum": 1, "maximum": 7, "description": "Bytes of the nonce assigned as extranonce1; extranonce2 gets the rest", "default": default_stratum_extranonce1_size() },
+                "audit_file": { "type": ["string", "null"], "description": "Append-only audit file of accepted shares (disabled if unset)", "default": null },
+                "audit_max_size_mb": field_with_default("integer", "Size in megabytes at which the audit file is rotated (0 disables)", default_stratum_audit_max_size()),
+            }), &[]),
+            worker_variant("simulation", "Simulate mining at a fixed hash rate", json!({
+                "hash_rate": field("number", "Target hash rate (hashes per second)"),
+            }), &["hash_rate"]),
+            worker_variant("constant-delay", "Solve every block after a fixed delay", json!({
+                "block_time_secs": field("integer", "Block time in seconds"),
+            }), &["block_time_secs"]),
+            worker_variant("on-demand", "Solve blocks when requested over HTTP", json!({
+                "port": field_with_default("integer", "Listen port", default_on_demand_port()),
+                "host": field_with_default("string", "Listen address", default_on_demand_host()),
+                "replay_window_secs": field_with_default("integer", "Seconds during which a request repeating an earlier Idempotency-Key is rejected", default_on_demand_replay_window()),
+                "min_interval_ms": field_with_default("integer", "Minimum milliseconds between requests (0 for no limit)", 0),
+            }), &[]),
+        ],
+    })
+}
+
+fn nested_schema() -> Value {
+    json!({
+        "title": "Nested configuration",
+        "type": "object",
+        "properties": {
+            "node": {
+                "description": "Node connection configuration",
+                "type": "object",
+                "properties": {
+                    "url": field("string", "Node URL (host[:port], optionally with http:// or https://)"),
+                    "use_tls": field_with_default("boolean", "Use TLS", default_true()),
+                    "insecure": field_with_default("boolean", "Allow insecure TLS connections", false),
+                    "timeout_secs": field_with_default("integer", "Request timeout in seconds", default_timeout()),
+                    "chain_id": { "type": ["integer", "null"], "minimum": 0, "description": "Chain ID to mine on (all chains if unset; checked against the node's chain count)" },
+                    "exclude_chains": { "type": "array", "items": { "type": "integer", "minimum": 0 }, "description": "Chains not to mine on (checked against the node's chain count)", "default": [] },
+                    "fallback_urls": { "type": "array", "items": { "type": "string" }, "description": "Fallback nodes in order of preference, used when the primary keeps failing", "default": [] },
+                    "relay_urls": { "type": "array", "items": { "type": "string" }, "description": "Nodes receiving every solution in addition to the active node; a solution counts as accepted once any node accepts it", "default": [] },
+                    "submit_encoding": { "enum": ["binary", "json"], "description": "Encoding used for solution submissions", "default": "binary" },
+                    "on_node_loss": { "enum": ["retry", "exit"], "description": "What to do once no node is reachable: retry with backoff or exit with an error", "default": "retry" },
+                    "network": { "type": ["string", "null"], "description": "Chainweb version used in mining URLs (detected from the node if unset)", "examples": KNOWN_NETWORKS },
+                    "dns_refresh_secs": { "type": ["integer", "null"], "minimum": 1, "description": "Seconds node DNS resolutions are cached before they are refreshed, reconnecting when an address changes (no caching if unset)" },
+                    "work_fetch_timeout_secs": { "type": ["integer", "null"], "minimum": 1, "description": "Timeout of work requests in seconds; a slow node is asked again (the request timeout, at most 10, if unset)" },
+                    "submit_timeout_secs": { "type": ["integer", "null"], "minimum": 1, "description": "Timeout of solution submissions in seconds (the request timeout if unset)" },
+                    "http_headers": { "type": "array", "items": { "type": "string", "pattern": "^[^:]+:.*$" }, "description": "Extra NAME: VALUE headers sent with every node request", "default": [] },
+                },
+                "required": ["url"],
+            },
+            "mining": {
+                "description": "Mining configuration",
+                "type": "object",
+                "properties": {
+                    "account": field("string", "Miner account"),
+                    "public_key": field("string", "Public key"),
+                    "update_interval_secs": field_with_default("integer", "Update interval in seconds", default_update_interval()),
+                    "hashrate_warmup_secs": field_with_default("integer", "Seconds after mining starts during which CPU/GPU hashrate samples are suppressed", default_hashrate_warmup()),
+                    "fallback_poll_interval_secs": field_with_default("integer", "Seconds between polls for new work once the update stream is lost", default_fallback_poll_interval()),
+                    "max_concurrent_work_fetches": field_with_default("integer", "Maximum number of work fetches in flight at once, shared across chains", default_max_concurrent_work_fetches()),
+                    "confirm_blocks": field_with_default("boolean", "Check whether accepted blocks are on the winning chain or were orphaned", false),
+                    "confirm_delay_secs": field_with_default("integer", "Seconds between submitting a block and polling the cut to confirm it", default_confirm_delay()),
+                    "rotation_accounts": rotation_accounts_schema(),
+                    "account_rotation": account_rotation_schema(),
+                    "chain_accounts": chain_accounts_schema(),
+                    "keyset": keyset_schema(),
+                },
+                "required": ["account", "public_key"],
+            },
+            "worker": worker_schema(),
+            "logging": {
+                "description": "Logging configuration",
+                "type": "object",
+                "properties": {
+                    "level": field_with_default("string", "Log level", default_log_level()),
+                    "format": { "enum": ["plain", "json"], "description": "Log format", "default": default_log_format() },
+                    "file": { "type": ["string", "null"], "description": "Log to file" },
+                    "hashrate_log_interval_secs": field_with_default("integer", "Minimum seconds between hashrate log lines; every sample is still recorded for monitoring", default_hashrate_log_interval()),
+                },
+            },
+        },
+        "required": ["node", "mining", "worker", "logging"],
+    })
+}
+
+fn flat_schema() -> Value {
+    let mut schema = json!({
+        "title": "Flat configuration (Haskell-compatible)",
+        "type": "object",
+        "properties": {
+            "node": field_with_default("string", "Chainweb node URL", "localhost:1848"),
+            "useTls": field("boolean", "Use TLS (derived from the node URL if unset)"),
+            "insecure": field_with_default("boolean", "Allow insecure TLS connections", false),
+            "publicKey": field("string", "Public key"),
+            "account": field("string", "Miner account (defaults to k:<publicKey>)"),
+            "threadCount": field_with_default("integer", "Number of CPU mining threads", 2),
+            "threadPriority": { "enum": ["normal", "low", "idle"], "description": "OS scheduling priority of CPU mining threads", "default": "normal" },
+            "maxNoncesPerWork": field_with_default("integer", "Nonces the CPU threads search per work before fetching fresh work (0 = unlimited)", 0),
+            "maxMemoryMb": field_with_default("integer", "Memory limit of the CPU batch buffers in megabytes; the batch size is reduced to fit (0 = unlimited)", 0),
+            "logLevel": field_with_default("string", "Log level", default_log_level()),
+            "hashrateLogIntervalSecs": field_with_default("integer", "Minimum seconds between hashrate log lines", default_hashrate_log_interval()),
+            "worker": {
+                "enum": ["cpu", "external", "stratum", "simulation", "constant-delay", "on-demand"],
+                "description": "Worker type",
+                "default": DEFAULT_WORKER,
+            },
+            "externalWorkerCommand": field("string", "Command of the external worker"),
+            "externalWorkerMaxConcurrent": field_with_default("integer", "Maximum number of external worker processes running at once", default_external_max_concurrent()),
+            "hashRate": field_with_default("number", "Simulated hash rate (hashes per second)", 1_000_000.0),
+            "constantDelayBlockTime": field_with_default("integer", "Constant delay block time in seconds", 30),
+            "onDemandInterface": field_with_default("string", "On-demand listen interface (* = all)", "*"),
+            "onDemandPort": field_with_default("integer", "On-demand listen port", 1917),
+            "defaultHTTPTimeout": field_with_default("integer", "HTTP timeout in microseconds", default_timeout() * 1_000_000),
+            "hashrateWarmupSecs": field_with_default("integer", "Hashrate warm-up window in seconds", default_hashrate_warmup()),
+            "fallbackPollIntervalSecs": field_with_default("integer", "Seconds between polls for new work once the update stream is lost", default_fallback_poll_interval()),
+            "maxConcurrentWorkFetches": field_with_default("integer", "Maximum number of work fetches in flight at once, shared across chains", default_max_concurrent_work_fetches()),
+            "confirmBlocks": field_with_default("boolean", "Check whether accepted blocks are on the winning chain or were orphaned", false),
+            "confirmDelaySecs": field_with_default("integer", "Seconds between submitting a block and polling the cut to confirm it", default_confirm_delay()),
+            "rotationAccounts": rotation_accounts_schema(),
+            "accountRotation": account_rotation_schema(),
+            "chainAccounts": chain_accounts_schema(),
+            "minerKeysetFile": {
+                "type": ["string", "null"],
+                "description": "JSON keyset file ({\"keys\": [...], \"pred\": ...}) sent in work requests instead of the public key alone",
+                "default": null,
+            },
+        },
+        "required": ["publicKey"],
+    });
+
+    // Kept in a separate literal to stay within the json! macro recursion limit
+    let stratum = json!({
+        "stratumPort": field_with_default("integer", "Stratum listen port", 1917),
+        "stratumInterface": field_with_default("string", "Stratum listen interface (* = all)", "*"),
+        "stratumDifficulty": field_with_default("string", "\"block\", fixed leading zero bits, or target share period in seconds", "block"),
+        "stratumMaxPeriod": field_with_default("number", "Longest stratum share period in seconds accepted for stratumDifficulty", default_stratum_max_period()),
+        "stratumMinDifficultyLevel": field_with_default("integer", "Easiest stratum difficulty level (leading zero bits, 1-254) of a period based stratumDifficulty", default_stratum_min_difficulty_level()),
+        "stratumHashrateShiftFactor": field_with_default("number", "Stratum hash rate change factor after which a period based stratumDifficulty is recalculated from the latest shares only (0 disables)", default_stratum_hashrate_shift_factor()),
+        "stratumRate": field_with_default("integer", "Stratum job emission rate in milliseconds", default_stratum_rate()),
+        "stratumAlwaysCleanJobs": field_with_default("boolean", "Always set clean_jobs, even for time-only job updates", false),
+        "stratumJobHistory": field_with_default("integer", "Number of recent stratum jobs accepted for submits", default_stratum_job_history()),
+        "stratumSessionIdleTimeout": field_with_default("integer", "Disconnect stratum sessions idle for this many seconds (0 disables)", default_stratum_session_idle_timeout()),
+        "stratumExtranonce1Size": field_with_default("integer", "Bytes of the nonce assigned as stratum extranonce1 (1-7)", default_stratum_extranonce1_size()),
+        "stratumAuditFile": { "type": ["string", "null"], "description": "Append-only audit file of accepted stratum shares (disabled if unset)", "default": null },
+        "stratumAuditMaxSize": field_with_default("integer", "Size in megabytes at which the stratum audit file is rotated (0 disables)", default_stratum_audit_max_size()),
+    });
+    if let (Some(properties), Value::Object(stratum)) =
+        (schema["properties"].as_object_mut(), stratum)
+    {
+        properties.extend(stratum);
+    }
+    schema
+}
+
+/// JSON Schema of the config file, accepting either the nested or the flat layout
+pub fn config_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "chainweb-mining-client configuration",
+        "description": "Config files are YAML, JSON or TOML. The nested layout is tried first, then the flat (Haskell-compatible) layout.",
+        "oneOf": [nested_schema(), flat_schema()],
+    })
+}
+
+/// Fully commented example config (nested layout, YAML) with every field set
+/// to its default
+pub fn example_config() -> String {
+    format!(
+        r#"# chainweb-mining-client example configuration
+#
+# Files may be YAML, JSON or TOML. This is the nested layout; a flat,
+# Haskell-compatible layout with camelCase keys is accepted as well:
+#
+#   node: api.chainweb.com
+#   publicKey: <public key>
+#   worker: stratum
+#   stratumPort: 1917
+#
+# Run with --config-schema json-schema for the complete list of keys.
+
+node:
+  # Node URL (host[:port], optionally with http:// or https://)
+  url: localhost:1848
+  # Use TLS
+  use_tls: {use_tls}
+  # Allow insecure TLS connections
+  insecure: false
+  # Request timeout in seconds
+  timeout_secs: {timeout_secs}
+  # Chain ID to mine on (all chains if unset)
+  chain_id: null
+  # Chains not to mine on, e.g. [3, 7, 12]
+  exclude_chains: []
+  # Fallback nodes in order of preference, used when the primary keeps failing
+  fallback_urls: []
+  # Nodes receiving every solution in addition to the active node; a
+  # solution counts as accepted once any node accepts it
+  relay_urls: []
+  # Encoding used for solution submissions (binary or json)
+  submit_encoding: binary
+  # What to do once no node is reachable: retry with backoff, or exit so an
+  # orchestrator can restart the client
+  on_node_loss: retry
+  # Chainweb version used in mining URLs, e.g. mainnet01 or testnet04
+  # (detected from the node if unset)
+  network: null
+  # Seconds node DNS resolutions are cached before they are re-resolved;
+  # clients reconnect when an address changes (no caching if unset)
+  dns_refresh_secs: null
+  # Timeout of work requests in seconds; a slow node is asked again rather
+  # than waited for (the request timeout, at most 10, if unset)
+  work_fetch_timeout_secs: null
+  # Timeout of solution submissions in seconds (the request timeout if unset)
+  submit_timeout_secs: null
+  # Extra headers sent with every node request, e.g. ["X-Miner-Rig: rig-7"]
+  http_headers: []
+
+mining:
+  # Miner account
+  account: k:0000000000000000000000000000000000000000000000000000000000000000
+  # Public key
+  public_key: "0000000000000000000000000000000000000000000000000000000000000000"
+  # Update interval in seconds
+  update_interval_secs: {update_interval_secs}
+  # Seconds after mining starts during which CPU/GPU hashrate samples are suppressed
+  hashrate_warmup_secs: {hashrate_warmup_secs}
+  # Seconds between polls for new work once the update stream is lost
+  fallback_poll_interval_secs: {fallback_poll_interval_secs}
+  # Maximum number of work fetches in flight at once, shared across chains
+  max_concurrent_work_fetches: {max_concurrent_work_fetches}
+  # Check some time after submission whether accepted blocks are on the
+  # winning chain or were orphaned
+  confirm_blocks: false
+  # Seconds between submitting a block and polling the cut to confirm it
+  confirm_delay_secs: {confirm_delay_secs}
+  # Accounts to rotate through after the one above, each with account and public_key
+  rotation_accounts: []
+  # When to rotate accounts: blocks:N (accepted solutions) or interval:SECS
+  # (no rotation if unset)
+  account_rotation: null
+  # Accounts receiving the rewards of single chains, keyed by chain id, e.g.
+  # {{ "5": "k:<public key>" }}; other chains use the accounts above
+  chain_accounts: {{}}
+  # Keyset sent in work requests instead of the public key alone, e.g.
+  # {{ keys: [<public key>, ...], pred: keys-2 }}
+  keyset: null
+
+# Exactly one worker; the alternatives are listed commented out below.
+worker:
+  # Serve work to ASICs over Stratum
+  type: stratum
+  # Listen port
+  port: {stratum_port}
+  # Listen address
+  host: {stratum_host}
+  # Max connections
+  max_connections: {max_connections}
+  # null for the block difficulty, fixed leading zero bits (integer), or
+  # target share period in seconds (float)
+  difficulty: null
+  # solo: shares must meet the block target; pool: shares below it are
+  # accepted and the block difficulty becomes a share period (null for neither)
+  mode: null
+  # Longest share period in seconds accepted for a period difficulty
+  max_period_secs: {stratum_max_period}
+  # Easiest difficulty level (leading zero bits of the target) of a period difficulty
+  min_difficulty_level: {stratum_min_difficulty_level}
+  # Hash rate change factor after which a period difficulty is recalculated
+  # from the latest shares only (0 disables)
+  hashrate_shift_factor: {stratum_hashrate_shift_factor}
+  # Job emission rate in milliseconds
+  rate_ms: {stratum_rate}
+  # Always set clean_jobs, even for time-only job updates
+  always_clean_jobs: false
+  # Number of recent jobs (including the current one) accepted for submits
+  job_history_size: {job_history_size}
+  # Disconnect sessions that submit no shares for this many seconds (0 disables)
+  session_idle_timeout_secs: {session_idle_timeout_secs}
+  # Bytes of the nonce assigned as extranonce1 (1-7); extranonce2 gets the rest
+  extranonce1_size: {extranonce1_size}
+  # Append every accepted share to this file as a JSON line (disabled if null)
+  audit_file: null
+  # Rotate the audit file once it exceeds this many megabytes (0 disables)
+  audit_max_size_mb: {audit_max_size_mb}
+
+# worker:
+#   # Mine on the CPU
+#   type: cpu
+#   # Number of threads (0 = all cores)
+#   threads: 0
+#   # Batch size for nonce checking
+#   batch_size: {batch_size}
+#   # OS scheduling priority of the mining threads (normal, low, idle)
+#   thread_priority: normal
+#   # Hash each nonce from a midstate over the fixed part of the work header instead of using the SIMD miners
+#   midstate: false
+#   # Nonces searched per work before fetching fresh work (0 = unlimited)
+#   max_nonces_per_work: 0
+#   # Memory limit of the batch buffers in megabytes; the batch size is reduced to fit (0 = unlimited)
+#   max_memory_mb: 0
+
+# worker:
+#   # Mine on a GPU
+#   type: gpu
+#   # Device index to use (unset = auto-select best device)
+#   device_index: null
+#   # Workgroup size (number of threads per workgroup)
+#   workgroup_size: {workgroup_size}
+#   # Number of workgroups to dispatch
+#   workgroup_count: {workgroup_count}
+#   # Maximum nonces to process in one batch
+#   batch_size: {gpu_batch_size}
+#   # Enable GPU performance monitoring
+#   enable_monitoring: {enable_monitoring}
+
+# worker:
+#   # Delegate mining to an external program
+#   type: external
+#   # Command to execute
+#   command: /usr/local/bin/miner
+#   # Command arguments
+#   args: []
+#   # Environment variables as [name, value] pairs
+#   env: []
+#   # Timeout in seconds
+#   timeout_secs: {external_timeout}
+#   # Maximum number of processes running at once
+#   max_concurrent: {external_max_concurrent}
+
+# worker:
+#   # Simulate mining at a fixed hash rate
+#   type: simulation
+#   # Target hash rate (hashes per second)
+#   hash_rate: 1000000.0
+
+# worker:
+#   # Solve every block after a fixed delay
+#   type: constant-delay
+#   # Block time in seconds
+#   block_time_secs: 30
+
+# worker:
+#   # Solve blocks when requested over HTTP
+#   type: on-demand
+#   # Listen port
+#   port: {on_demand_port}
+#   # Listen address
+#   host: {on_demand_host}
+#   # Seconds during which a request repeating an earlier Idempotency-Key
+#   # is rejected
+#   replay_window_secs: {on_demand_replay_window}
+#   # Minimum milliseconds between requests (0 for no limit)
+#   min_interval_ms: 0
+
+logging:
+  # Log level (error, warn, info, debug, trace)
+  level: {log_level}
+  # Log format (plain or json)
+  format: {log_format}
+  # Log to file
+  file: null
+  # Minimum seconds between hashrate log lines; every sample is still
+  # recorded for monitoring
+  hashrate_log_interval_secs: {hashrate_log_interval_secs}
+"#,
+        use_tls = default_true(),
+        timeout_secs = default_timeout(),
+        update_interval_secs = default_update_interval(),
+        hashrate_warmup_secs = default_hashrate_warmup(),
+        fallback_poll_interval_secs = default_fallback_poll_interval(),
+        max_concurrent_work_fetches = default_max_concurrent_work_fetches(),
+        confirm_delay_secs = default_confirm_delay(),
+        stratum_port = default_stratum_port(),
+        stratum_host = default_stratum_host(),
+        max_connections = default_max_connections(),
+        stratum_max_period = default_stratum_max_period(),
+        stratum_min_difficulty_level = default_stratum_min_difficulty_level(),
+        stratum_hashrate_shift_factor = default_stratum_hashrate_shift_factor(),
+        stratum_rate = default_stratum_rate(),
+        job_history_size = default_stratum_job_history(),
+        session_idle_timeout_secs = default_stratum_session_idle_timeout(),
+        extranonce1_size = default_stratum_extranonce1_size(),
+        audit_max_size_mb = default_stratum_audit_max_size(),
+        batch_size = default_batch_size(),
+        workgroup_size = default_workgroup_size(),
+        workgroup_count = default_workgroup_count(),
+        gpu_batch_size = default_gpu_batch_size(),
+        enable_monitoring = default_enable_monitoring(),
+        external_timeout = default_external_timeout(),
+        external_max_concurrent = default_external_max_concurrent(),
+        on_demand_port = default_on_demand_port(),
+        on_demand_host = default_on_demand_host(),
+        on_demand_replay_window = default_on_demand_replay_window(),
+        log_level = default_log_level(),
+        log_format = default_log_format(),
+        hashrate_log_interval_secs = default_hashrate_log_interval(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_format_parsing() {
+        assert_eq!(
+            "json-schema".parse::<SchemaFormat>().unwrap(),
+            SchemaFormat::JsonSchema
+        );
+        assert_eq!(
+            "Example".parse::<SchemaFormat>().unwrap(),
+            SchemaFormat::Example
+        );
+        assert!("xsd".parse::<SchemaFormat>().is_err());
+    }
+
+    #[test]
+    fn test_example_config_parses() {
+        let config = Config::from_contents(&example_config(), "example.yaml").unwrap();
+        assert_eq!(config.node.url, "localhost:1848");
+        assert_eq!(config.node.timeout_secs, default_timeout());
+        assert!(matches!(
+            config.worker,
+            WorkerConfig::Stratum {
+                port: 3333,
+                job_history_size: 4,
+                ..
+            }
+        ));
+        assert_eq!(config.logging.level, "info");
+    }
+
+    #[test]
+    fn test_commented_workers_parse() {
+        // Every commented-out worker alternative is a valid worker section
+        let example = example_config();
+        let blocks: Vec<&str> = example.split("# worker:\n").skip(1).collect();
+        assert_eq!(blocks.len(), 6);
+        for block in blocks {
+            let yaml: String = block
+                .lines()
+                .take_while(|line| line.starts_with("#   "))
+                .map(|line| format!("{}\n", &line[4..]))
+                .collect();
+            serde_yaml::from_str::<WorkerConfig>(&yaml)
+                .unwrap_or_else(|e| panic!("invalid worker example:\n{}\n{}", yaml, e));
+        }
+    }
+
+    /// Assert that every key of `value` is a property of `schema`, recursing
+    /// into nested objects and into the worker variant named by `type`
+    fn assert_keys_in_schema(value: &Value, schema: &Value, path: &str) {
+        let schema = match schema.get("oneOf") {
+            Some(Value::Array(variants)) if value.get("type").is_some() => variants
+                .iter()
+                .find(|variant| variant["properties"]["type"]["const"] == value["type"])
+                .unwrap_or_else(|| panic!("{}: no schema for type {}", path, value["type"])),
+            _ => schema,
+        };
+        let (Value::Object(fields), Some(properties)) = (value, schema.get("properties")) else {
+            return;
+        };
+        for (key, field) in fields {
+            let property = properties
+                .get(key)
+                .unwrap_or_else(|| panic!("{}.{} is missing from the schema", path, key));
+            assert_keys_in_schema(field, property, &format!("{}.{}", path, key));
+        }
+    }
+
+    #[test]
+    fn test_json_schema_covers_config_fields() {
+        let schema = config_schema();
+        // Fields skipped while empty are set, so they are serialized too
+        let mut config = Config::default();
+        config.node.exclude_chains = vec![1];
+        config.node.fallback_urls = vec!["backup.example".to_string()];
+        config.node.relay_urls = vec!["relay.example".to_string()];
+        config.node.network = Some("mainnet01".to_string());
+        config.node.dns_refresh_secs = Some(60);
+        config.node.work_fetch_timeout_secs = Some(5);
+        config.node.submit_timeout_secs = Some(5);
+        config.node.http_headers = vec!["X-Miner: test".to_string()];
+        let nested = serde_json::to_value(config).unwrap();
+        assert_keys_in_schema(&nested, &schema["oneOf"][0], "config");
+
+        // Every worker section of the example, not just the default worker
+        let example = example_config();
+        for block in example.split("# worker:\n").skip(1) {
+            let yaml: String = block
+                .lines()
+                .take_while(|line| line.starts_with("#   "))
+                .map(|line| format!("{}\n", &line[4..]))
+                .collect();
+            let worker: WorkerConfig = serde_yaml::from_str(&yaml).unwrap();
+            let worker = serde_json::to_value(worker).unwrap();
+            assert_keys_in_schema(
+                &worker,
+                &schema["oneOf"][0]["properties"]["worker"],
+                "worker",
+            );
+        }
+
+        let flat: FlatConfig = serde_json::from_value(json!({ "publicKey": "abc" })).unwrap();
+        let flat = serde_json::to_value(flat).unwrap();
+        assert_keys_in_schema(&flat, &schema["oneOf"][1], "flat");
+    }
+
+    #[test]
+    fn test_json_schema_lists_all_workers() {
+        let schema = config_schema();
+        let workers = &schema["oneOf"][0]["properties"]["worker"]["oneOf"];
+        let names: Vec<&str> = workers
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["type"]["const"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "cpu",
+                "gpu",
+                "external",
+                "stratum",
+                "simulation",
+                "constant-delay",
+                "on-demand"
+            ]
+        );
+        assert_eq!(schema["oneOf"][1]["required"][0], "publicKey");
+
+        let rendered = SchemaFormat::JsonSchema.render().unwrap();
+        assert!(serde_json::from_str::<Value>(&rendered).is_ok());
+    }
+}
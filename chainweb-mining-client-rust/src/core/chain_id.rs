@@ -18,6 +18,16 @@ impl ChainId {
     pub const fn value(self) -> u16 {
         self.0
     }
+
+    /// Chain ids of a network with `num_chains` chains, in order
+    pub fn all(num_chains: u16) -> impl DoubleEndedIterator<Item = ChainId> + ExactSizeIterator {
+        (0..num_chains).map(Self)
+    }
+
+    /// Whether the chain exists on a network with `num_chains` chains
+    pub const fn is_valid(self, num_chains: u16) -> bool {
+        self.0 < num_chains
+    }
 }
 
 impl fmt::Display for ChainId {
@@ -66,6 +76,18 @@ mod tests {
         assert_eq!(value, 7);
     }
 
+    #[test]
+    fn test_all_chains() {
+        assert_eq!(ChainId::all(0).count(), 0);
+        let chains: Vec<ChainId> = ChainId::all(10).collect();
+        assert_eq!(chains.len(), 10);
+        assert_eq!(chains.first(), Some(&ChainId::new(0)));
+        assert_eq!(chains.last(), Some(&ChainId::new(9)));
+        assert!(chains.iter().all(|chain| chain.is_valid(10)));
+        assert!(!ChainId::new(10).is_valid(10));
+        assert!(ChainId::new(19).is_valid(20));
+    }
+
     #[test]
     fn test_chain_id_serde() {
         let chain_id = ChainId::new(3);
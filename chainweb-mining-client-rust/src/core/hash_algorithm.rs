@@ -0,0 +1,85 @@
+//! Proof-of-work hash algorithm abstraction
+//!
+//! Chainweb uses Blake2s-256, which remains the default everywhere. The trait
+//! exists so that the CPU worker can run with a different algorithm, e.g. a
+//! trivial one in tests that makes solutions easy to find.
+
+use crate::core::constants::{NONCE_OFFSET, WORK_SIZE};
+use crate::core::{Nonce, Target};
+use blake2::{Blake2s256, Digest};
+use std::fmt;
+
+/// Hash function used for proof of work
+pub trait HashAlgorithm: Send + Sync + fmt::Debug {
+    /// Algorithm name, used in logs
+    fn name(&self) -> &str;
+
+    /// Hash `data` to a 256-bit digest
+    fn hash(&self, data: &[u8]) -> [u8; 32];
+
+    /// Whether the hash of `data` meets `target`
+    fn meets_target(&self, data: &[u8], target: &Target) -> bool {
+        target.meets_target(&self.hash(data))
+    }
+}
+
+/// Blake2s-256, the Chainweb proof-of-work hash
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake2s;
+
+impl HashAlgorithm for Blake2s {
+    fn name(&self) -> &str {
+        "blake2s-256"
+    }
+
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        let mut hasher = Blake2s256::new();
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+}
+
+/// Write `nonce` into a copy of `work_bytes` and return the hash if it meets `target`
+pub fn inject_nonce_and_check(
+    algorithm: &dyn HashAlgorithm,
+    work_bytes: &[u8; WORK_SIZE],
+    nonce: Nonce,
+    target: &Target,
+) -> Option<[u8; 32]> {
+    let mut work = *work_bytes;
+    work[NONCE_OFFSET..].copy_from_slice(&nonce.to_le_bytes());
+
+    let hash = algorithm.hash(&work);
+    target.meets_target(&hash).then_some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Work;
+
+    #[test]
+    fn test_blake2s_matches_work_hash() {
+        let mut work = Work::from_bytes([3u8; WORK_SIZE]);
+        work.set_nonce(Nonce::new(42));
+        assert_eq!(Blake2s.hash(work.as_bytes()), work.hash());
+        assert_eq!(Blake2s.name(), "blake2s-256");
+    }
+
+    #[test]
+    fn test_inject_nonce_and_check() {
+        let work_bytes = [0u8; WORK_SIZE];
+        let easy = Target::from_bytes([0xFF; 32]);
+        let impossible = Target::from_bytes([0x00; 32]);
+
+        let hash = inject_nonce_and_check(&Blake2s, &work_bytes, Nonce::new(7), &easy).unwrap();
+        let mut work = Work::from_bytes(work_bytes);
+        work.set_nonce(Nonce::new(7));
+        assert_eq!(hash, work.hash());
+
+        assert!(
+            inject_nonce_and_check(&Blake2s, &work_bytes, Nonce::new(7), &impossible).is_none()
+        );
+        assert!(Blake2s.meets_target(work.as_bytes(), &easy));
+    }
+}
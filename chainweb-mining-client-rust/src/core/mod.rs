@@ -5,6 +5,7 @@
 
 mod chain_id;
 mod difficulty;
+mod hash_algorithm;
 mod hash_simd;
 mod nonce;
 mod preemption;
@@ -15,16 +16,19 @@ mod work;
 
 pub use chain_id::ChainId;
 pub use difficulty::{adjust_difficulty, Difficulty, HashRate, Period};
+pub use hash_algorithm::{Blake2s, HashAlgorithm, inject_nonce_and_check};
 pub use hash_simd::{AdaptiveHasher, OptimizedHasher, VectorizedMiner};
-pub use nonce::Nonce;
+pub use nonce::{Nonce, NonceStripe};
 pub use preemption::{
     PreemptionAction, PreemptionConfig, PreemptionDecision, PreemptionStats, PreemptionStrategy,
     WorkPreemptor,
 };
-pub use simd_hasher::{SimdHasher, SimdMiner, SimdFeatures, detect_simd_features};
+pub use simd_hasher::{
+    Midstate, SimdFeatures, SimdHasher, SimdMiner, check_simd_agreement, detect_simd_features,
+};
 pub use target::Target;
 pub use target_arithmetic::{Level, TargetArithmetic, TargetWords};
-pub use work::Work;
+pub use work::{Work, WorkChanges};
 
 /// Constants for the mining protocol
 pub mod constants {
@@ -37,6 +41,12 @@ pub mod constants {
     /// Offset of the nonce in the work header
     pub const NONCE_OFFSET: usize = WORK_SIZE - NONCE_SIZE;
 
+    /// Offset of the block height in the work header
+    pub const HEIGHT_OFFSET: usize = 258;
+
+    /// Offset of the chainweb version in the work header
+    pub const VERSION_OFFSET: usize = 266;
+
     /// Size of a hash in bytes (Blake2s-256)
     pub const HASH_SIZE: usize = 32;
 
@@ -44,10 +54,27 @@ pub mod constants {
     pub const TARGET_SIZE: usize = 32;
 }
 
+/// Strip surrounding whitespace and an optional `0x`/`0X` prefix from hex input
+pub fn trim_hex(input: &str) -> &str {
+    let input = input.trim();
+    input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::constants::*;
 
+    #[test]
+    fn test_trim_hex() {
+        assert_eq!(super::trim_hex("abcd"), "abcd");
+        assert_eq!(super::trim_hex("0xabcd"), "abcd");
+        assert_eq!(super::trim_hex("  0XABCD\n"), "ABCD");
+        assert_eq!(super::trim_hex("\t0x"), "");
+    }
+
     #[test]
     fn test_constants() {
         assert_eq!(WORK_SIZE, 286);
@@ -40,6 +40,17 @@ impl Nonce {
     pub fn to_le_bytes(self) -> [u8; 8] {
         self.0.to_le_bytes()
     }
+
+    /// Hex of the little-endian bytes, as stored in the work header and
+    /// submitted over stratum
+    pub fn to_hex_le(self) -> String {
+        hex::encode(self.to_le_bytes())
+    }
+
+    /// Hex of the big-endian bytes, i.e. the zero-padded hex value
+    pub fn to_hex_be(self) -> String {
+        hex::encode(self.0.to_be_bytes())
+    }
 }
 
 impl fmt::Display for Nonce {
@@ -48,6 +59,12 @@ impl fmt::Display for Nonce {
     }
 }
 
+impl fmt::LowerHex for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
 impl From<u64> for Nonce {
     fn from(value: u64) -> Self {
         Self(value)
@@ -60,6 +77,57 @@ impl From<Nonce> for u64 {
     }
 }
 
+/// One thread's share of the nonce space searched by `count` threads
+///
+/// The nonces from `base` are cut into batches of `width`, and thread `index`
+/// searches every `count`-th batch starting at batch `index`. With a width of
+/// 1, thread `i` searches `base + i`, `base + i + count`, ... The stripes of
+/// different threads never overlap. Rather than wrapping around into the
+/// nonces of the other threads, a stripe ends at the top of the nonce space;
+/// the last batch is cut short there.
+#[derive(Debug, Clone)]
+pub struct NonceStripe {
+    next: Option<u64>,
+    width: u64,
+    stride: Option<u64>,
+}
+
+impl NonceStripe {
+    /// Stripe of thread `index` out of `count`, in batches of `width` nonces
+    pub fn new(base: u64, index: usize, count: usize, width: u64) -> Self {
+        assert!(
+            index < count,
+            "thread index {} out of {} threads",
+            index,
+            count
+        );
+        assert!(width > 0, "nonce batches must not be empty");
+        Self {
+            next: (index as u64)
+                .checked_mul(width)
+                .and_then(|offset| base.checked_add(offset)),
+            width,
+            stride: (count as u64).checked_mul(width),
+        }
+    }
+}
+
+impl Iterator for NonceStripe {
+    /// First nonce and length of a batch
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next?;
+        let len = if u64::MAX - start < self.width {
+            u64::MAX - start + 1
+        } else {
+            self.width
+        };
+        self.next = self.stride.and_then(|stride| start.checked_add(stride));
+        Some((start, len))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +164,52 @@ mod tests {
         assert_eq!(nonce.to_string(), "42");
     }
 
+    #[test]
+    fn test_nonce_hex() {
+        let nonce = Nonce::new(0x0123456789ABCDEF);
+        assert_eq!(nonce.to_hex_le(), "efcdab8967452301");
+        assert_eq!(nonce.to_hex_be(), "0123456789abcdef");
+        assert_eq!(hex::decode(nonce.to_hex_le()).unwrap(), nonce.to_le_bytes());
+
+        let nonce = Nonce::new(42);
+        assert_eq!(nonce.to_hex_be(), "000000000000002a");
+        assert_eq!(format!("{:x}", nonce), "2a");
+        assert_eq!(format!("{:#018x}", nonce), "0x000000000000002a");
+    }
+
+    #[test]
+    fn test_stripes_are_disjoint() {
+        let threads = 3;
+        let mut searched: Vec<u64> = (0..threads)
+            .flat_map(|index| NonceStripe::new(1_000, index, threads, 4).take(5))
+            .flat_map(|(start, len)| start..start + len)
+            .collect();
+        searched.sort_unstable();
+        assert_eq!(searched, (1_000..1_060).collect::<Vec<_>>());
+
+        // Single nonce batches interleave the threads
+        let stripe: Vec<_> = NonceStripe::new(10, 1, 4, 1).take(3).collect();
+        assert_eq!(stripe, vec![(11, 1), (15, 1), (19, 1)]);
+    }
+
+    #[test]
+    fn test_stripes_end_at_nonce_space_boundary() {
+        let base = u64::MAX - 10;
+        let stripes: Vec<Vec<(u64, u64)>> = (0..3)
+            .map(|index| NonceStripe::new(base, index, 3, 4).collect())
+            .collect();
+        assert_eq!(stripes[0], vec![(u64::MAX - 10, 4)]);
+        assert_eq!(stripes[1], vec![(u64::MAX - 6, 4)]);
+        assert_eq!(stripes[2], vec![(u64::MAX - 2, 3)]);
+
+        // A thread starting past the end gets no nonces at all
+        assert_eq!(NonceStripe::new(u64::MAX, 1, 2, 1).next(), None);
+        assert_eq!(
+            NonceStripe::new(0, 1, 2, u64::MAX).collect::<Vec<_>>(),
+            vec![(u64::MAX, 1)]
+        );
+    }
+
     #[test]
     fn test_nonce_conversions() {
         let nonce: Nonce = 999u64.into();
@@ -5,7 +5,7 @@
 
 use crate::core::{Target, Work};
 use crate::error::Result;
-use crate::workers::{MiningResult, Worker};
+use crate::workers::{CancellationReason, MiningResult, Worker};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -182,20 +182,38 @@ impl WorkPreemptor {
 
         match action {
             PreemptionAction::Immediate => {
-                self.immediate_preemption(worker, new_work, new_target, result_tx)
-                    .await?;
+                self.immediate_preemption(
+                    worker,
+                    new_work,
+                    new_target,
+                    result_tx,
+                    CancellationReason::Preemption,
+                )
+                .await?;
             }
             PreemptionAction::AfterBatch => {
                 // For batch completion, we could implement a more sophisticated approach
                 // For now, fall back to immediate preemption
                 warn!("Batch completion preemption not fully implemented, using immediate");
-                self.immediate_preemption(worker, new_work, new_target, result_tx)
-                    .await?;
+                self.immediate_preemption(
+                    worker,
+                    new_work,
+                    new_target,
+                    result_tx,
+                    CancellationReason::NewWork,
+                )
+                .await?;
             }
             PreemptionAction::Delayed(delay) => {
                 tokio::time::sleep(delay).await;
-                self.immediate_preemption(worker, new_work, new_target, result_tx)
-                    .await?;
+                self.immediate_preemption(
+                    worker,
+                    new_work,
+                    new_target,
+                    result_tx,
+                    CancellationReason::NewWork,
+                )
+                .await?;
             }
         }
 
@@ -217,16 +235,30 @@ impl WorkPreemptor {
         new_work: Work,
         new_target: Target,
         result_tx: mpsc::Sender<MiningResult>,
+        reason: CancellationReason,
     ) -> Result<()> {
         let _stop_start = Instant::now();
 
+        // Unchanged work continues where the search left off
+        let position = worker
+            .nonce_position()
+            .filter(|position| self.is_work_identical(&position.work, &new_work));
+
         // Stop current mining
-        worker.stop().await?;
+        worker.cancel(reason).await?;
 
         let restart_start = Instant::now();
 
         // Start mining with new work
-        worker.mine(new_work, new_target, result_tx).await?;
+        match position {
+            Some(position) => {
+                debug!("Work unchanged, resuming at nonce {}", position.nonce);
+                worker
+                    .resume(new_work, new_target, result_tx, position.nonce)
+                    .await?;
+            }
+            None => worker.mine(new_work, new_target, result_tx).await?,
+        }
 
         // Update timing statistics
         let mut stats = self.stats.lock();
@@ -310,6 +342,7 @@ pub enum PreemptionSkipReason {
 mod tests {
     use super::*;
     use crate::core::constants::WORK_SIZE;
+    use crate::workers::cpu::{CpuWorker, CpuWorkerConfig};
 
     #[test]
     fn test_preemption_config_default() {
@@ -434,6 +467,168 @@ mod tests {
         );
     }
 
+    /// Worker stub recording why it was cancelled
+    #[derive(Default)]
+    struct RecordingWorker {
+        cancellations: parking_lot::Mutex<Vec<CancellationReason>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Worker for RecordingWorker {
+        async fn mine(
+            &self,
+            _work: Work,
+            _target: Target,
+            _result_tx: mpsc::Sender<MiningResult>,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn cancel(&self, reason: CancellationReason) -> Result<()> {
+            self.cancellations.lock().push(reason);
+            Ok(())
+        }
+
+        fn worker_type(&self) -> &str {
+            "Recording"
+        }
+
+        async fn hashrate(&self) -> u64 {
+            0
+        }
+
+        fn capabilities(&self) -> crate::workers::WorkerCapabilities {
+            crate::workers::WorkerCapabilities::compute()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preemption_cancels_with_reason() {
+        let preemptor = WorkPreemptor::with_defaults();
+        let worker = Arc::new(RecordingWorker::default());
+        let (tx, _rx) = mpsc::channel(1);
+
+        for action in [
+            PreemptionAction::Immediate,
+            PreemptionAction::AfterBatch,
+            PreemptionAction::Delayed(Duration::ZERO),
+        ] {
+            preemptor
+                .execute_preemption(
+                    action,
+                    worker.clone(),
+                    Work::from_bytes([2u8; WORK_SIZE]),
+                    Target::from_bytes([0xFF; 32]),
+                    tx.clone(),
+                    || async { Err(crate::error::Error::worker("unused")) },
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            *worker.cancellations.lock(),
+            [
+                CancellationReason::Preemption,
+                CancellationReason::NewWork,
+                CancellationReason::NewWork,
+            ]
+        );
+    }
+
+    /// CPU worker that never finds a solution, and its nonce position once
+    /// it has searched a while
+    async fn searching_worker(work: &Work) -> (Arc<CpuWorker>, mpsc::Receiver<MiningResult>, u64) {
+        let worker = Arc::new(CpuWorker::new(CpuWorkerConfig {
+            threads: 2,
+            batch_size: 1000,
+            ..Default::default()
+        }));
+        let (tx, rx) = mpsc::channel(1);
+        worker
+            .mine(work.clone(), Target::from_bytes([0; 32]), tx)
+            .await
+            .unwrap();
+        let position = wait_for_position(&worker, 0).await;
+        (worker, rx, position)
+    }
+
+    /// Nonce position of `worker` once it got past `nonce`
+    async fn wait_for_position(worker: &CpuWorker, nonce: u64) -> u64 {
+        for _ in 0..500 {
+            if let Some(position) = worker
+                .nonce_position()
+                .filter(|position| position.nonce > nonce)
+            {
+                return position.nonce;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("worker made no progress past nonce {}", nonce);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_preemption_keeps_nonce_position() {
+        let work = Work::from_bytes([3u8; WORK_SIZE]);
+        let (worker, _rx, before) = searching_worker(&work).await;
+
+        let preemptor = WorkPreemptor::with_defaults();
+        assert_eq!(
+            preemptor.should_preempt(&work, &work),
+            PreemptionDecision::Skip(PreemptionSkipReason::IdenticalWork)
+        );
+        // The search goes on rather than starting over
+        let after = wait_for_position(&worker, before).await;
+        assert!(after > before);
+        assert_eq!(worker.nonce_position().unwrap().work, work);
+        worker.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_preemption_with_unchanged_work_resumes() {
+        let work = Work::from_bytes([4u8; WORK_SIZE]);
+        let (worker, _rx, before) = searching_worker(&work).await;
+        let (tx, _rx2) = mpsc::channel(1);
+
+        let preemptor = WorkPreemptor::new(PreemptionConfig {
+            validate_work_change: false,
+            ..Default::default()
+        });
+        preemptor
+            .execute_preemption(
+                PreemptionAction::Immediate,
+                worker.clone(),
+                work.clone(),
+                Target::from_bytes([0; 32]),
+                tx,
+                || async { Err(crate::error::Error::worker("unused")) },
+            )
+            .await
+            .unwrap();
+        assert!(worker.nonce_position().unwrap().nonce >= before);
+
+        // Different work starts over
+        let other = Work::from_bytes([5u8; WORK_SIZE]);
+        let (tx, _rx3) = mpsc::channel(1);
+        preemptor
+            .execute_preemption(
+                PreemptionAction::Immediate,
+                worker.clone(),
+                other.clone(),
+                Target::from_bytes([0; 32]),
+                tx,
+                || async { Err(crate::error::Error::worker("unused")) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(worker.nonce_position().unwrap().work, other);
+        worker.stop().await.unwrap();
+    }
+
     #[test]
     fn test_stats_reset() {
         let preemptor = WorkPreemptor::with_defaults();
@@ -3,8 +3,12 @@
 //! This module provides high-performance Blake2s-256 implementations
 //! using SIMD instructions when available.
 
+use crate::core::constants::NONCE_OFFSET;
+use crate::core::{Blake2s, HashAlgorithm, Nonce};
+use crate::error::{Error, Result};
 use blake2s_simd::{Params, State};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::debug;
 
 /// SIMD-optimized hasher for mining operations
@@ -87,7 +91,7 @@ impl SimdMiner {
         for (i, work) in self.work_buffer[..count].iter_mut().enumerate() {
             *work = *base_work;
             let nonce = start_nonce + i as u64;
-            work[crate::core::constants::NONCE_OFFSET..].copy_from_slice(&nonce.to_le_bytes());
+            work[NONCE_OFFSET..].copy_from_slice(&nonce.to_le_bytes());
         }
     }
     
@@ -110,13 +114,13 @@ impl SimdMiner {
         target: &crate::core::Target,
         start_nonce: u64,
         count: usize,
-    ) -> Option<(crate::core::Nonce, [u8; 32])> {
+    ) -> Option<(Nonce, [u8; 32])> {
         self.prepare_batch(base_work, start_nonce, count);
         let hashes = self.hash_batch(count);
         
         for (i, hash) in hashes.iter().enumerate() {
             if target.meets_target(hash) {
-                let nonce = crate::core::Nonce::new(start_nonce + i as u64);
+                let nonce = Nonce::new(start_nonce + i as u64);
                 return Some((nonce, *hash));
             }
         }
@@ -125,6 +129,96 @@ impl SimdMiner {
     }
 }
 
+/// Blake2s-256 state over the part of a work header before the nonce
+///
+/// Only the nonce changes between the hashes of one work header, so the four
+/// blocks before it are compressed once and each nonce costs a single
+/// compression of the final block.
+#[derive(Clone)]
+pub struct Midstate {
+    prefix: State,
+}
+
+impl Midstate {
+    /// Compress the fixed prefix of `work`
+    pub fn new(work: &[u8; 286]) -> Self {
+        let mut prefix = SimdHasher::new().create_state();
+        prefix.update(&work[..NONCE_OFFSET]);
+        Self { prefix }
+    }
+
+    /// Hash of the work header with `nonce` injected
+    #[inline]
+    pub fn hash(&self, nonce: u64) -> [u8; 32] {
+        let mut state = self.prefix.clone();
+        state.update(&nonce.to_le_bytes());
+        *state.finalize().as_array()
+    }
+
+    /// First of `count` nonces from `start_nonce` whose hash meets `target`
+    ///
+    /// Gives up early once `is_mining` is cleared.
+    pub fn mine_batch(
+        &self,
+        target: &crate::core::Target,
+        start_nonce: u64,
+        count: u64,
+        is_mining: &AtomicBool,
+    ) -> Option<(Nonce, [u8; 32])> {
+        for offset in 0..count {
+            // Check for cancellation every 1024 nonces
+            if offset % 1024 == 0 && !is_mining.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let nonce = start_nonce.wrapping_add(offset);
+            let hash = self.hash(nonce);
+            if target.meets_target(&hash) {
+                return Some((Nonce::new(nonce), hash));
+            }
+        }
+        None
+    }
+}
+
+/// Check that the SIMD hasher agrees with the scalar Blake2s-256 implementation
+///
+/// `count` consecutive nonces of `base_work`, starting at `start_nonce`, are
+/// hashed through the SIMD batch and single paths; the first hash that
+/// differs from the scalar one is reported.
+pub fn check_simd_agreement(base_work: &[u8; 286], start_nonce: u64, count: usize) -> Result<()> {
+    let mut miner = SimdMiner::new(count);
+    miner.prepare_batch(base_work, start_nonce, count);
+    let batch = miner.hash_batch(count).to_vec();
+
+    for (i, batch_hash) in batch.iter().enumerate() {
+        let nonce = Nonce::new(start_nonce + i as u64);
+        let mut work = *base_work;
+        work[NONCE_OFFSET..].copy_from_slice(&nonce.to_le_bytes());
+        let scalar = Blake2s.hash(&work);
+
+        let hashes = [
+            ("batch", *batch_hash),
+            ("single", miner.hasher.hash_single(&work)),
+        ];
+        for (path, hash) in hashes {
+            if hash != scalar {
+                return Err(Error::worker_hash_computation_error(
+                    "blake2s-256 (SIMD)",
+                    format!(
+                        "{} hash {} differs from scalar {} at nonce {}",
+                        path,
+                        hex::encode(hash),
+                        hex::encode(scalar),
+                        nonce
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Feature detection for SIMD capabilities
 pub fn detect_simd_features() -> SimdFeatures {
     let mut features = SimdFeatures::default();
@@ -248,4 +342,22 @@ mod tests {
         let result = miner.mine_batch(work.as_bytes(), &target, 0, 64);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_simd_agreement() {
+        let work = Work::default();
+        check_simd_agreement(work.as_bytes(), 0, 64).unwrap();
+        check_simd_agreement(&[0xA5; 286], u64::MAX - 8, 8).unwrap();
+    }
+
+    #[test]
+    fn test_midstate_mining_stops_when_cancelled() {
+        let midstate = Midstate::new(Work::default().as_bytes());
+        let target = Target::from_bytes([0xFF; 32]);
+        assert_eq!(
+            midstate.mine_batch(&target, 7, 16, &AtomicBool::new(true)),
+            Some((Nonce::new(7), midstate.hash(7)))
+        );
+        assert_eq!(midstate.mine_batch(&target, 7, 16, &AtomicBool::new(false)), None);
+    }
 }
\ No newline at end of file
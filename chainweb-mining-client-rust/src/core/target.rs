@@ -1,9 +1,9 @@
 //! Target type for mining difficulty
 
-use crate::core::Work;
+use crate::core::{Blake2s, HashAlgorithm, Work, trim_hex};
 use crate::error::{Error, Result};
-use blake2::{Blake2s256, Digest};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fmt;
 
 /// Represents a 256-bit mining target (difficulty threshold)
@@ -40,8 +40,8 @@ impl Target {
 
     /// Create a Target from a hex string
     pub fn from_hex(hex: &str) -> Result<Self> {
-        let bytes =
-            hex::decode(hex).map_err(|e| Error::invalid_target(format!("Invalid hex: {}", e)))?;
+        let bytes = hex::decode(trim_hex(hex))
+            .map_err(|e| Error::invalid_target(format!("Invalid hex: {}", e)))?;
 
         if bytes.len() != 32 {
             return Err(Error::invalid_target(format!(
@@ -55,6 +55,24 @@ impl Target {
         Ok(Self(array))
     }
 
+    /// Whether every byte of the target is zero
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+
+    /// Reject targets no hash can meet
+    ///
+    /// An all-zero target is unsatisfiable and almost always means the work
+    /// was parsed incorrectly, so mining against it would run forever.
+    pub fn validate(&self) -> Result<()> {
+        if self.is_zero() {
+            return Err(Error::protocol_target_validation_failed(
+                "all-zero target can never be met",
+            ));
+        }
+        Ok(())
+    }
+
     /// Get the target as bytes
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
@@ -65,19 +83,30 @@ impl Target {
         hex::encode(self.0)
     }
 
+    /// Convert to a hex string with a `0x` prefix
+    pub fn to_hex_prefixed(&self) -> String {
+        format!("0x{}", self.to_hex())
+    }
+
     /// Check if a hash meets this target (is below it)
     pub fn meets_target(&self, hash: &[u8; 32]) -> bool {
-        // Compare as little-endian integers (matching Haskell powHashToTargetWords)
-        // Compare from least significant byte to most significant byte
-        for (hash_byte, target_byte) in hash.iter().rev().zip(self.0.iter().rev()) {
-            match hash_byte.cmp(target_byte) {
-                std::cmp::Ordering::Less => return true,
-                std::cmp::Ordering::Greater => return false,
-                std::cmp::Ordering::Equal => continue,
-            }
-        }
         // Equal hashes technically don't meet the target
-        false
+        cmp_le(hash, &self.0) == Ordering::Less
+    }
+
+    /// Whether this target is harder to meet than `other`
+    ///
+    /// A harder target is a smaller 256-bit value: fewer hashes fall below
+    /// it, so it has more leading zeros and a higher difficulty. Equal
+    /// targets are neither harder nor easier than each other.
+    pub fn is_harder_than(&self, other: &Target) -> bool {
+        cmp_le(&self.0, &other.0) == Ordering::Less
+    }
+
+    /// Whether this target is easier to meet than `other`, i.e. a larger
+    /// value with a lower difficulty; see [`is_harder_than`](Self::is_harder_than)
+    pub fn is_easier_than(&self, other: &Target) -> bool {
+        other.is_harder_than(self)
     }
 
     /// Convert difficulty to target
@@ -147,22 +176,22 @@ impl Target {
 
         // Target = 2^(256 - level) - 1
         let mut bytes = [0xFFu8; 32];
-        
+
         let full_bytes = level / 8;
         let remaining_bits = level % 8;
-        
+
         // Clear the leading bytes (big-endian in the actual value)
         // But since we store in little-endian, we clear from the end
         for i in 0..full_bytes.min(32) {
             bytes[31 - i as usize] = 0;
         }
-        
+
         // Handle partial byte
         if full_bytes < 32 && remaining_bits > 0 {
             let byte_index = 31 - full_bytes as usize;
             bytes[byte_index] = 0xFF >> remaining_bits;
         }
-        
+
         Self(bytes)
     }
 
@@ -171,7 +200,7 @@ impl Target {
     pub fn get_target_level(&self) -> Option<u8> {
         // Count leading zero bits (from most significant byte in little-endian)
         let mut leading_zeros = 0u8;
-        
+
         // Start from the most significant byte (last in little-endian)
         for &byte in self.0.iter().rev() {
             if byte == 0 {
@@ -182,12 +211,12 @@ impl Target {
                 break;
             }
         }
-        
+
         // Special case: all zeros (max 255 for u8)
         if leading_zeros == 255 {
             return Some(255);
         }
-        
+
         Some(leading_zeros)
     }
 
@@ -201,6 +230,12 @@ impl Target {
     }
 }
 
+/// Compare two 256-bit little-endian values (matching Haskell
+/// powHashToTargetWords), starting from the most significant byte
+fn cmp_le(a: &[u8; 32], b: &[u8; 32]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
 /// Check if a work meets the given target using Blake2s-256 hash
 ///
 /// This function replicates the Haskell checkTarget logic:
@@ -209,13 +244,7 @@ impl Target {
 /// 3. Compare hash ≤ target
 #[allow(dead_code)]
 pub fn check_target(target: &Target, work: &Work) -> Result<bool> {
-    // Hash the work with Blake2s-256
-    let mut hasher = Blake2s256::new();
-    hasher.update(work.as_bytes());
-    let hash_bytes = hasher.finalize();
-
-    // Use the same logic as meets_target but with proper hash computation
-    Ok(target.meets_target(&hash_bytes.into()))
+    Ok(Blake2s.meets_target(work.as_bytes(), target))
 }
 
 impl fmt::Display for Target {
@@ -261,6 +290,23 @@ mod tests {
         assert_eq!(target.to_hex(), hex);
     }
 
+    #[test]
+    fn test_target_hex_prefix_and_whitespace() {
+        let target = Target::mk_target_level(12);
+        let prefixed = target.to_hex_prefixed();
+        assert_eq!(prefixed, format!("0x{}", target.to_hex()));
+        assert_eq!(Target::from_hex(&prefixed).unwrap(), target);
+        assert_eq!(
+            Target::from_hex(&format!(" {} ", target.to_hex())).unwrap(),
+            target
+        );
+        assert_eq!(
+            Target::from_hex(&format!("\n{}\t", prefixed)).unwrap(),
+            target
+        );
+        assert!(Target::from_hex("0x 00").is_err());
+    }
+
     #[test]
     fn test_target_meets_target() {
         // Target with higher value in most significant byte (little-endian format)
@@ -291,6 +337,28 @@ mod tests {
         assert!(!target.meets_target(&target_bytes));
     }
 
+    #[test]
+    fn test_more_leading_zeros_is_harder() {
+        let easy = Target::mk_target_level(10);
+        let hard = Target::mk_target_level(20);
+        assert!(hard.is_harder_than(&easy));
+        assert!(easy.is_easier_than(&hard));
+        assert!(!easy.is_harder_than(&hard));
+        assert!(!hard.is_easier_than(&easy));
+
+        // Equal targets are neither
+        assert!(!easy.is_harder_than(&easy));
+        assert!(!easy.is_easier_than(&easy));
+
+        // The most significant byte is the last one, so it decides even
+        // when every lower byte says otherwise
+        let mut low_bytes_small = [0x00; 32];
+        low_bytes_small[31] = 0x02;
+        let mut low_bytes_large = [0xFF; 32];
+        low_bytes_large[31] = 0x01;
+        assert!(Target(low_bytes_large).is_harder_than(&Target(low_bytes_small)));
+    }
+
     #[test]
     fn test_target_serde() {
         let hex = "00000000ffff0000000000000000000000000000000000000000000000000000";
@@ -310,6 +378,24 @@ mod tests {
         assert!(Target::from_hex(&"00".repeat(33)).is_err()); // Too long
     }
 
+    #[test]
+    fn test_validate_rejects_zero_target() {
+        let zero = Target::from_bytes([0u8; 32]);
+        assert!(zero.is_zero());
+        assert!(matches!(
+            zero.validate(),
+            Err(Error::Protocol(
+                crate::error::ProtocolError::TargetValidationFailed { .. }
+            ))
+        ));
+
+        let mut bytes = [0u8; 32];
+        bytes[31] = 0x0F;
+        let target = Target::from_bytes(bytes);
+        assert!(!target.is_zero());
+        target.validate().unwrap();
+    }
+
     #[test]
     fn test_target_from_bytes_le() {
         // Test with a simple pattern
@@ -3,6 +3,7 @@
 //! This module provides high-precision arithmetic operations for mining targets,
 //! including TargetWords for 256-bit operations and Level for difficulty calculations.
 
+use crate::core::Target;
 use crate::error::{Error, Result};
 use num_bigint::{BigUint, ToBigUint};
 use num_traits::{One, ToPrimitive, Zero};
@@ -309,6 +310,15 @@ impl Level {
     }
 }
 
+impl From<&Target> for TargetWords {
+    /// Convert a [`Target`], which is stored little-endian
+    fn from(target: &Target) -> Self {
+        let mut bytes = *target.as_bytes();
+        bytes.reverse();
+        Self::from_bytes(bytes)
+    }
+}
+
 impl fmt::Display for TargetWords {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_hex())
@@ -415,6 +425,13 @@ mod tests {
         assert_eq!(target.to_bytes(), bytes);
     }
 
+    #[test]
+    fn test_target_words_from_target() {
+        // Level 20: the 20 most significant bits of the little-endian target are zero
+        let words = TargetWords::from(&Target::mk_target_level(20));
+        assert_eq!(Level::from_target(&words).value(), 20);
+    }
+
     #[test]
     fn test_target_words_arithmetic() {
         let one = TargetWords::from_words([1, 0, 0, 0]);
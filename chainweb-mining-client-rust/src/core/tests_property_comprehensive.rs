@@ -5,7 +5,10 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{ChainId, Level, Nonce, Target, TargetArithmetic, TargetWords, Work};
+    use crate::core::{
+        Blake2s, ChainId, HashAlgorithm, Level, Midstate, Nonce, Target, TargetArithmetic,
+        TargetWords, Work, check_simd_agreement,
+    };
     use num_bigint::{BigUint, ToBigUint};
     use num_traits::{One, Zero};
     use proptest::prelude::*;
@@ -520,6 +523,26 @@ mod tests {
         }
     }
 
+    // SIMD and scalar Blake2s must agree on every input
+    proptest! {
+        #[test]
+        fn simd_hash_matches_scalar(
+            work_bytes in arb_work_bytes(),
+            start_nonce in 0u64..u64::MAX - 64,
+            count in 1usize..64
+        ) {
+            prop_assert!(check_simd_agreement(&work_bytes, start_nonce, count).is_ok());
+        }
+
+        #[test]
+        fn midstate_hash_matches_full_hash(work_bytes in arb_work_bytes(), nonce in any::<u64>()) {
+            let midstate = Midstate::new(&work_bytes);
+            let mut work = Work::from_bytes(work_bytes);
+            work.set_nonce(Nonce::new(nonce));
+            prop_assert_eq!(midstate.hash(nonce), Blake2s.hash(work.as_bytes()));
+        }
+    }
+
     // Edge case property tests
     proptest! {
         #[test]
@@ -1,12 +1,35 @@
 //! Work type representing a mining job
 
-use crate::core::constants::{NONCE_OFFSET, NONCE_SIZE, WORK_SIZE};
-use crate::core::{ChainId, Nonce, Target};
+use crate::core::constants::{HEIGHT_OFFSET, NONCE_OFFSET, NONCE_SIZE, VERSION_OFFSET, WORK_SIZE};
+use crate::core::{Blake2s, ChainId, HashAlgorithm, Nonce, Target, trim_hex};
 use crate::error::{Error, Result};
-use blake2::{Blake2s256, Digest};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Header regions that differ between two work headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WorkChanges {
+    /// The creation time field differs
+    pub time: bool,
+    /// The nonce differs
+    pub nonce: bool,
+    /// Any other header byte (parent, payload, target, ...) differs
+    pub header: bool,
+}
+
+impl WorkChanges {
+    /// Whether the headers are byte-for-byte identical
+    pub fn is_empty(&self) -> bool {
+        !self.time && !self.nonce && !self.header
+    }
+
+    /// Whether only the time and/or nonce changed, i.e. the work still
+    /// builds on the same parent and payload
+    pub fn is_time_only(&self) -> bool {
+        !self.header
+    }
+}
+
 /// Represents a 286-byte mining work header
 #[derive(Clone, PartialEq, Eq)]
 pub struct Work {
@@ -60,11 +83,39 @@ impl Work {
         self.bytes[NONCE_OFFSET..].copy_from_slice(&nonce.to_le_bytes());
     }
 
+    /// Copy of the work with the nonce set, leaving `self` untouched
+    pub fn with_nonce(&self, nonce: Nonce) -> Self {
+        let mut work = self.clone();
+        work.set_nonce(nonce);
+        work
+    }
+
+    /// Height of the block the work is for
+    pub fn height(&self) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.bytes[HEIGHT_OFFSET..HEIGHT_OFFSET + 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Version field of the work header
+    pub fn version(&self) -> u32 {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&self.bytes[VERSION_OFFSET..VERSION_OFFSET + 4]);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Copy of the work with the version bits selected by `mask` replaced
+    /// by those of `bits` (stratum version rolling)
+    pub fn with_rolled_version(&self, mask: u32, bits: u32) -> Self {
+        let version = (self.version() & !mask) | (bits & mask);
+        let mut work = self.clone();
+        work.bytes[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&version.to_le_bytes());
+        work
+    }
+
     /// Compute the Blake2s-256 hash of the work
     pub fn hash(&self) -> [u8; 32] {
-        let mut hasher = Blake2s256::new();
-        hasher.update(self.bytes);
-        hasher.finalize().into()
+        Blake2s.hash(&self.bytes)
     }
 
     /// Check if the work meets the given target
@@ -97,14 +148,34 @@ impl Work {
         self.update_timestamp(new_time);
     }
 
+    /// Compare this work with another and report which header regions changed
+    pub fn changed_fields(&self, other: &Work) -> WorkChanges {
+        const TIMESTAMP_OFFSET: usize = 8;
+        const TIMESTAMP_END: usize = TIMESTAMP_OFFSET + 8;
+
+        let (a, b) = (&self.bytes, &other.bytes);
+        WorkChanges {
+            time: a[TIMESTAMP_OFFSET..TIMESTAMP_END] != b[TIMESTAMP_OFFSET..TIMESTAMP_END],
+            nonce: a[NONCE_OFFSET..] != b[NONCE_OFFSET..],
+            header: a[..TIMESTAMP_OFFSET] != b[..TIMESTAMP_OFFSET]
+                || a[TIMESTAMP_END..NONCE_OFFSET] != b[TIMESTAMP_END..NONCE_OFFSET],
+        }
+    }
+
     /// Create a hex representation of the work
     pub fn to_hex(&self) -> String {
         hex::encode(self.bytes)
     }
 
-    /// Create Work from a hex string
+    /// Convert to a hex string with a `0x` prefix
+    pub fn to_hex_prefixed(&self) -> String {
+        format!("0x{}", self.to_hex())
+    }
+
+    /// Create Work from a hex string, with or without `0x` prefix and
+    /// surrounding whitespace
     pub fn from_hex(hex: &str) -> Result<Self> {
-        let bytes = hex::decode(hex).map_err(|e| {
+        let bytes = hex::decode(trim_hex(hex)).map_err(|e| {
             Error::validation_invalid_target(hex, format!("Invalid hex encoding: {}", e))
         })?;
         Self::from_slice(&bytes)
@@ -216,6 +287,21 @@ mod tests {
         assert!(Work::from_slice(&invalid).is_err());
     }
 
+    #[test]
+    fn test_wrong_size_headers_rejected() {
+        use crate::error::ValidationError;
+
+        for size in [0, WORK_SIZE - 1, WORK_SIZE + 1, 2 * WORK_SIZE] {
+            let err = Work::from_slice(&vec![0u8; size]).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Validation(ValidationError::InvalidWorkHeader { expected_size: WORK_SIZE, actual_size })
+                    if actual_size == size
+            ));
+            assert!(Work::from_hex(&"00".repeat(size)).is_err());
+        }
+    }
+
     #[test]
     fn test_work_nonce() {
         let mut work = Work::from_bytes([0u8; WORK_SIZE]);
@@ -228,6 +314,28 @@ mod tests {
         assert_eq!(work.nonce(), nonce);
     }
 
+    #[test]
+    fn test_work_height() {
+        let mut bytes = [0u8; WORK_SIZE];
+        bytes[HEIGHT_OFFSET..HEIGHT_OFFSET + 8].copy_from_slice(&4_500_123u64.to_le_bytes());
+        let work = Work::from_bytes(bytes);
+        assert_eq!(work.height(), 4_500_123);
+        // The nonce is outside the height field
+        assert_eq!(work.with_nonce(Nonce::new(u64::MAX)).height(), 4_500_123);
+    }
+
+    #[test]
+    fn test_work_with_nonce() {
+        let work = Work::from_bytes([0x22u8; WORK_SIZE]);
+        let original_nonce = work.nonce();
+
+        let solved = work.with_nonce(Nonce::new(99));
+        assert_eq!(solved.nonce(), Nonce::new(99));
+        assert_eq!(work.nonce(), original_nonce);
+        assert!(work.changed_fields(&solved).nonce);
+        assert!(!work.changed_fields(&solved).header);
+    }
+
     #[test]
     fn test_work_hash() {
         let work = Work::from_bytes([0x42u8; WORK_SIZE]);
@@ -252,6 +360,43 @@ mod tests {
         assert_eq!(work, work2);
     }
 
+    #[test]
+    fn test_work_hex_prefix_and_whitespace() {
+        let mut bytes = [0u8; WORK_SIZE];
+        bytes[0] = 0xAB;
+        let work = Work::from_bytes(bytes);
+
+        let prefixed = work.to_hex_prefixed();
+        assert!(prefixed.starts_with("0xab"));
+        assert_eq!(Work::from_hex(&prefixed).unwrap(), work);
+        assert_eq!(Work::from_hex(&work.to_hex()).unwrap(), work);
+        assert_eq!(Work::from_hex(&format!("  {}\n", prefixed)).unwrap(), work);
+        assert_eq!(
+            Work::from_hex(&format!("\t0X{}  ", work.to_hex().to_uppercase())).unwrap(),
+            work
+        );
+        assert!(Work::from_hex("0x").is_err());
+    }
+
+    #[test]
+    fn test_work_changed_fields() {
+        let work = Work::from_bytes([0x11u8; WORK_SIZE]);
+        assert!(work.changed_fields(&work).is_empty());
+
+        let mut later = work.clone();
+        later.increment_time_micros(30_000_000);
+        later.set_nonce(Nonce::new(7));
+        let changes = work.changed_fields(&later);
+        assert!(changes.time && changes.nonce && !changes.header);
+        assert!(changes.is_time_only());
+
+        let mut new_parent = work.clone();
+        new_parent.as_bytes_mut()[40] ^= 0xFF;
+        let changes = work.changed_fields(&new_parent);
+        assert!(changes.header && !changes.time);
+        assert!(!changes.is_time_only());
+    }
+
     #[test]
     fn test_work_builder() {
         let work = WorkBuilder::new()
@@ -3,8 +3,8 @@
 //! This module provides a comprehensive error handling system using `thiserror`
 //! for automatic error trait implementations with granular error categorization.
 
-use thiserror::Error;
 use std::time::Duration;
+use thiserror::Error;
 
 /// Configuration error subtypes
 #[derive(Error, Debug)]
@@ -12,19 +12,33 @@ use std::time::Duration;
 pub enum ConfigError {
     #[error("Missing required field: {field}")]
     MissingField { field: String },
-    
+
     #[error("Invalid value for {field}: {value} (expected: {expected})")]
-    InvalidValue { field: String, value: String, expected: String },
-    
+    InvalidValue {
+        field: String,
+        value: String,
+        expected: String,
+    },
+
     #[error("File not found: {path}")]
     FileNotFound { path: String },
-    
+
     #[error("Parse error in {file}: {message}")]
     ParseError { file: String, message: String },
-    
+
+    #[error("Failed to fetch config from {url} after {attempts} attempt(s): {message}")]
+    FetchFailed {
+        url: String,
+        attempts: usize,
+        message: String,
+    },
+
+    #[error("HTTP error {status} when fetching config from {url}")]
+    FetchStatus { url: String, status: u16 },
+
     #[error("Validation failed: {message}")]
     ValidationError { message: String },
-    
+
     #[error("Environment variable error: {var} - {message}")]
     EnvironmentError { var: String, message: String },
 }
@@ -34,28 +48,38 @@ pub enum ConfigError {
 #[allow(missing_docs)]
 pub enum NetworkError {
     #[error("Connection failed to {url}: {source}")]
-    ConnectionFailed { url: String, source: Box<dyn std::error::Error + Send + Sync> },
-    
+    ConnectionFailed {
+        url: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[error("Request timeout after {timeout:?} to {url}")]
     Timeout { url: String, timeout: Duration },
-    
+
     #[error("HTTP error {status} from {url}: {message}")]
-    HttpError { url: String, status: u16, message: String },
-    
+    HttpError {
+        url: String,
+        status: u16,
+        message: String,
+    },
+
     #[error("DNS resolution failed for {host}: {message}")]
     DnsError { host: String, message: String },
-    
+
     #[error("TLS/SSL error connecting to {url}: {message}")]
     TlsError { url: String, message: String },
-    
+
     #[error("Invalid URL: {url}")]
     InvalidUrl { url: String },
-    
+
     #[error("Network unreachable: {message}")]
     NetworkUnreachable { message: String },
-    
+
     #[error("Connection reset by peer: {url}")]
     ConnectionReset { url: String },
+
+    #[error("Lost all {nodes} configured node(s): {source}")]
+    NodeLost { nodes: usize, source: Box<Error> },
 }
 
 /// Protocol error subtypes for Chainweb and Stratum
@@ -64,30 +88,33 @@ pub enum NetworkError {
 pub enum ProtocolError {
     #[error("Invalid message format: {message}")]
     InvalidFormat { message: String },
-    
+
     #[error("Protocol version mismatch: expected {expected}, got {actual}")]
     VersionMismatch { expected: String, actual: String },
-    
+
     #[error("Authentication failed: {reason}")]
     AuthenticationFailed { reason: String },
-    
+
     #[error("Subscription failed for {topic}: {reason}")]
     SubscriptionFailed { topic: String, reason: String },
-    
+
     #[error("Response parse error: {field} - {message}")]
     ResponseParseError { field: String, message: String },
-    
+
     #[error("Invalid chain ID: expected {expected}, got {actual}")]
     InvalidChainId { expected: u32, actual: u32 },
-    
+
     #[error("Work validation failed: {reason}")]
     WorkValidationFailed { reason: String },
-    
+
     #[error("Target validation failed: {reason}")]
     TargetValidationFailed { reason: String },
-    
+
     #[error("Mining endpoint not available: {endpoint}")]
     EndpointUnavailable { endpoint: String },
+
+    #[error("No work available yet from {endpoint}")]
+    NoWorkAvailable { endpoint: String },
 }
 
 /// Worker error subtypes with detailed context
@@ -96,33 +123,36 @@ pub enum ProtocolError {
 pub enum WorkerError {
     #[error("Worker initialization failed: {worker_type} - {reason}")]
     InitializationFailed { worker_type: String, reason: String },
-    
+
     #[error("Worker startup failed: {worker_type} - {reason}")]
     StartupFailed { worker_type: String, reason: String },
-    
+
     #[error("Worker shutdown failed: {worker_type} - {reason}")]
     ShutdownFailed { worker_type: String, reason: String },
-    
+
     #[error("Mining operation failed: {reason}")]
     MiningFailed { reason: String },
-    
+
     #[error("Hash computation error: {algorithm} - {reason}")]
     HashComputationError { algorithm: String, reason: String },
-    
+
     #[error("Thread pool error: {reason}")]
     ThreadPoolError { reason: String },
-    
+
     #[error("Resource exhaustion: {resource} - {details}")]
     ResourceExhaustion { resource: String, details: String },
-    
+
     #[error("Worker communication error: {reason}")]
     CommunicationError { reason: String },
-    
+
     #[error("External worker process error: {command} - {reason}")]
     ExternalProcessError { command: String, reason: String },
-    
+
     #[error("Work preemption failed: {reason}")]
     PreemptionFailed { reason: String },
+
+    #[error("Worker restart attempts exhausted: {worker_type} - gave up after {attempts} restarts")]
+    RestartExhausted { worker_type: String, attempts: u32 },
 }
 
 /// Stratum-specific error subtypes
@@ -131,27 +161,46 @@ pub enum WorkerError {
 pub enum StratumError {
     #[error("Client connection failed: {client_id} - {reason}")]
     ClientConnectionFailed { client_id: String, reason: String },
-    
+
     #[error("Invalid client message: {client_id} - {message}")]
     InvalidClientMessage { client_id: String, message: String },
-    
+
     #[error("Subscription error: {client_id} - {method} - {reason}")]
-    SubscriptionError { client_id: String, method: String, reason: String },
-    
+    SubscriptionError {
+        client_id: String,
+        method: String,
+        reason: String,
+    },
+
     #[error("Job dispatch failed: {job_id} - {reason}")]
     JobDispatchFailed { job_id: String, reason: String },
-    
+
     #[error("Difficulty adjustment failed: {current} -> {target} - {reason}")]
-    DifficultyAdjustmentFailed { current: f64, target: f64, reason: String },
-    
+    DifficultyAdjustmentFailed {
+        current: f64,
+        target: f64,
+        reason: String,
+    },
+
     #[error("Share validation failed: {client_id} - {share_id} - {reason}")]
-    ShareValidationFailed { client_id: String, share_id: String, reason: String },
-    
+    ShareValidationFailed {
+        client_id: String,
+        share_id: String,
+        reason: String,
+    },
+
     #[error("Server binding failed: {address} - {reason}")]
     ServerBindingFailed { address: String, reason: String },
-    
+
     #[error("Client limit exceeded: {current}/{max}")]
     ClientLimitExceeded { current: usize, max: usize },
+
+    #[error("Invalid {field} {value:?}: {reason}")]
+    InvalidField {
+        field: &'static str,
+        value: String,
+        reason: String,
+    },
 }
 
 /// Data validation error subtypes
@@ -159,25 +208,44 @@ pub enum StratumError {
 #[allow(missing_docs)]
 pub enum ValidationError {
     #[error("Invalid work header: expected {expected_size} bytes, got {actual_size}")]
-    InvalidWorkHeader { expected_size: usize, actual_size: usize },
-    
+    InvalidWorkHeader {
+        expected_size: usize,
+        actual_size: usize,
+    },
+
     #[error("Invalid target format: {value} - {reason}")]
     InvalidTarget { value: String, reason: String },
-    
+
     #[error("Invalid nonce: {nonce} - {reason}")]
     InvalidNonce { nonce: u64, reason: String },
-    
+
     #[error("Invalid hash: {hash} - {reason}")]
     InvalidHash { hash: String, reason: String },
-    
+
     #[error("Checksum mismatch: expected {expected}, got {actual}")]
     ChecksumMismatch { expected: String, actual: String },
-    
+
     #[error("Size validation failed: {field} - expected {expected}, got {actual}")]
-    SizeValidation { field: String, expected: usize, actual: usize },
-    
+    SizeValidation {
+        field: String,
+        expected: usize,
+        actual: usize,
+    },
+
     #[error("Range validation failed: {field} - value {value} outside range [{min}, {max}]")]
-    RangeValidation { field: String, value: i64, min: i64, max: i64 },
+    RangeValidation {
+        field: String,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+
+    #[error("System clock skew of {offset_ms}ms against {reference} exceeds maximum of {max_ms}ms")]
+    ClockSkew {
+        reference: String,
+        offset_ms: i64,
+        max_ms: u64,
+    },
 }
 
 /// Communication error subtypes
@@ -186,19 +254,25 @@ pub enum ValidationError {
 pub enum CommunicationError {
     #[error("Channel send failed: {channel} - {reason}")]
     ChannelSendFailed { channel: String, reason: String },
-    
+
     #[error("Channel receive failed: {channel} - {reason}")]
     ChannelReceiveFailed { channel: String, reason: String },
-    
+
     #[error("Channel closed unexpectedly: {channel}")]
     ChannelClosed { channel: String },
-    
+
     #[error("Message serialization failed: {message_type} - {reason}")]
-    SerializationFailed { message_type: String, reason: String },
-    
+    SerializationFailed {
+        message_type: String,
+        reason: String,
+    },
+
     #[error("Message deserialization failed: {message_type} - {reason}")]
-    DeserializationFailed { message_type: String, reason: String },
-    
+    DeserializationFailed {
+        message_type: String,
+        reason: String,
+    },
+
     #[error("Broadcast failed: {recipients} recipients - {reason}")]
     BroadcastFailed { recipients: usize, reason: String },
 }
@@ -253,7 +327,10 @@ pub enum Error {
 
     /// Timeout errors with context
     #[error("Operation timed out after {timeout:?}: {operation}")]
-    Timeout { operation: String, timeout: Duration },
+    Timeout {
+        operation: String,
+        timeout: Duration,
+    },
 
     /// External process errors
     #[error("External process error: {command} - {reason}")]
@@ -269,26 +346,32 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 impl Error {
     // === Configuration Error Constructors ===
-    
+
     /// Create a missing field configuration error
     pub fn config_missing_field(field: impl Into<String>) -> Self {
-        Self::Config(ConfigError::MissingField { field: field.into() })
+        Self::Config(ConfigError::MissingField {
+            field: field.into(),
+        })
     }
-    
+
     /// Create an invalid value configuration error
-    pub fn config_invalid_value(field: impl Into<String>, value: impl Into<String>, expected: impl Into<String>) -> Self {
+    pub fn config_invalid_value(
+        field: impl Into<String>,
+        value: impl Into<String>,
+        expected: impl Into<String>,
+    ) -> Self {
         Self::Config(ConfigError::InvalidValue {
             field: field.into(),
             value: value.into(),
             expected: expected.into(),
         })
     }
-    
+
     /// Create a file not found configuration error
     pub fn config_file_not_found(path: impl Into<String>) -> Self {
         Self::Config(ConfigError::FileNotFound { path: path.into() })
     }
-    
+
     /// Create a parse error configuration error
     pub fn config_parse_error(file: impl Into<String>, message: impl Into<String>) -> Self {
         Self::Config(ConfigError::ParseError {
@@ -296,120 +379,225 @@ impl Error {
             message: message.into(),
         })
     }
-    
+
+    /// Create an error for a config URL that couldn't be reached
+    pub fn config_fetch_failed(
+        url: impl Into<String>,
+        attempts: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::Config(ConfigError::FetchFailed {
+            url: url.into(),
+            attempts,
+            message: message.into(),
+        })
+    }
+
+    /// Create an error for a config URL answering with a non-success status
+    pub fn config_fetch_status(url: impl Into<String>, status: u16) -> Self {
+        Self::Config(ConfigError::FetchStatus {
+            url: url.into(),
+            status,
+        })
+    }
+
     /// Create a generic configuration error
     pub fn config(msg: impl Into<String>) -> Self {
-        Self::Config(ConfigError::ValidationError { message: msg.into() })
+        Self::Config(ConfigError::ValidationError {
+            message: msg.into(),
+        })
     }
-    
+
     // === Network Error Constructors ===
-    
+
     /// Create a connection failed network error
-    pub fn network_connection_failed(url: impl Into<String>, source: Box<dyn std::error::Error + Send + Sync>) -> Self {
-        Self::Network(NetworkError::ConnectionFailed { url: url.into(), source })
+    pub fn network_connection_failed(
+        url: impl Into<String>,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    ) -> Self {
+        Self::Network(NetworkError::ConnectionFailed {
+            url: url.into(),
+            source,
+        })
     }
-    
+
     /// Create a timeout network error
     pub fn network_timeout(url: impl Into<String>, timeout: Duration) -> Self {
-        Self::Network(NetworkError::Timeout { url: url.into(), timeout })
+        Self::Network(NetworkError::Timeout {
+            url: url.into(),
+            timeout,
+        })
     }
-    
+
     /// Create an HTTP error with detailed context
-    pub fn network_http_error(url: impl Into<String>, status: u16, message: impl Into<String>) -> Self {
+    pub fn network_http_error(
+        url: impl Into<String>,
+        status: u16,
+        message: impl Into<String>,
+    ) -> Self {
         Self::Network(NetworkError::HttpError {
             url: url.into(),
             status,
             message: message.into(),
         })
     }
-    
+
+    /// Create an error for giving up after every configured node kept failing
+    pub fn network_node_lost(nodes: usize, source: Error) -> Self {
+        Self::Network(NetworkError::NodeLost {
+            nodes,
+            source: Box::new(source),
+        })
+    }
+
     /// Create a generic network error
     pub fn network(msg: impl Into<String>) -> Self {
-        Self::Network(NetworkError::NetworkUnreachable { message: msg.into() })
+        Self::Network(NetworkError::NetworkUnreachable {
+            message: msg.into(),
+        })
     }
-    
+
     // === Protocol Error Constructors ===
-    
+
     /// Create an invalid format protocol error
     pub fn protocol_invalid_format(message: impl Into<String>) -> Self {
-        Self::Protocol(ProtocolError::InvalidFormat { message: message.into() })
+        Self::Protocol(ProtocolError::InvalidFormat {
+            message: message.into(),
+        })
     }
-    
+
     /// Create a version mismatch protocol error
-    pub fn protocol_version_mismatch(expected: impl Into<String>, actual: impl Into<String>) -> Self {
+    pub fn protocol_version_mismatch(
+        expected: impl Into<String>,
+        actual: impl Into<String>,
+    ) -> Self {
         Self::Protocol(ProtocolError::VersionMismatch {
             expected: expected.into(),
             actual: actual.into(),
         })
     }
-    
+
     /// Create an invalid chain ID protocol error
     pub fn protocol_invalid_chain_id(expected: u32, actual: u32) -> Self {
         Self::Protocol(ProtocolError::InvalidChainId { expected, actual })
     }
-    
+
     /// Create a work validation protocol error
     pub fn protocol_work_validation_failed(reason: impl Into<String>) -> Self {
-        Self::Protocol(ProtocolError::WorkValidationFailed { reason: reason.into() })
+        Self::Protocol(ProtocolError::WorkValidationFailed {
+            reason: reason.into(),
+        })
     }
-    
+
     /// Create an endpoint unavailable protocol error
     pub fn protocol_endpoint_unavailable(endpoint: impl Into<String>) -> Self {
-        Self::Protocol(ProtocolError::EndpointUnavailable { endpoint: endpoint.into() })
+        Self::Protocol(ProtocolError::EndpointUnavailable {
+            endpoint: endpoint.into(),
+        })
     }
-    
+
+    /// Create a target validation protocol error
+    pub fn protocol_target_validation_failed(reason: impl Into<String>) -> Self {
+        Self::Protocol(ProtocolError::TargetValidationFailed {
+            reason: reason.into(),
+        })
+    }
+
+    /// Create a "no work available yet" protocol error (e.g. the node is syncing)
+    pub fn protocol_no_work(endpoint: impl Into<String>) -> Self {
+        Self::Protocol(ProtocolError::NoWorkAvailable {
+            endpoint: endpoint.into(),
+        })
+    }
+
     /// Create a generic protocol error
     pub fn protocol(msg: impl Into<String>) -> Self {
-        Self::Protocol(ProtocolError::InvalidFormat { message: msg.into() })
+        Self::Protocol(ProtocolError::InvalidFormat {
+            message: msg.into(),
+        })
     }
-    
+
     // === Worker Error Constructors ===
-    
+
     /// Create a worker initialization error
-    pub fn worker_initialization_failed(worker_type: impl Into<String>, reason: impl Into<String>) -> Self {
+    pub fn worker_initialization_failed(
+        worker_type: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
         Self::Worker(WorkerError::InitializationFailed {
             worker_type: worker_type.into(),
             reason: reason.into(),
         })
     }
-    
+
+    /// Create a worker startup error
+    pub fn worker_startup_failed(
+        worker_type: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self::Worker(WorkerError::StartupFailed {
+            worker_type: worker_type.into(),
+            reason: reason.into(),
+        })
+    }
+
     /// Create a mining operation failed error
     pub fn worker_mining_failed(reason: impl Into<String>) -> Self {
-        Self::Worker(WorkerError::MiningFailed { reason: reason.into() })
+        Self::Worker(WorkerError::MiningFailed {
+            reason: reason.into(),
+        })
     }
-    
+
     /// Create a hash computation error
-    pub fn worker_hash_computation_error(algorithm: impl Into<String>, reason: impl Into<String>) -> Self {
+    pub fn worker_hash_computation_error(
+        algorithm: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
         Self::Worker(WorkerError::HashComputationError {
             algorithm: algorithm.into(),
             reason: reason.into(),
         })
     }
-    
+
+    /// Create a worker restart exhausted error
+    pub fn worker_restart_exhausted(worker_type: impl Into<String>, attempts: u32) -> Self {
+        Self::Worker(WorkerError::RestartExhausted {
+            worker_type: worker_type.into(),
+            attempts,
+        })
+    }
+
     /// Create a generic worker error
     pub fn worker(msg: impl Into<String>) -> Self {
         Self::Worker(WorkerError::MiningFailed { reason: msg.into() })
     }
-    
+
     // === Stratum Error Constructors ===
-    
+
     /// Create a client connection failed stratum error
-    pub fn stratum_client_connection_failed(client_id: impl Into<String>, reason: impl Into<String>) -> Self {
+    pub fn stratum_client_connection_failed(
+        client_id: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
         Self::Stratum(StratumError::ClientConnectionFailed {
             client_id: client_id.into(),
             reason: reason.into(),
         })
     }
-    
+
     /// Create a share validation failed stratum error
-    pub fn stratum_share_validation_failed(client_id: impl Into<String>, share_id: impl Into<String>, reason: impl Into<String>) -> Self {
+    pub fn stratum_share_validation_failed(
+        client_id: impl Into<String>,
+        share_id: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
         Self::Stratum(StratumError::ShareValidationFailed {
             client_id: client_id.into(),
             share_id: share_id.into(),
             reason: reason.into(),
         })
     }
-    
+
     /// Create a generic stratum error
     pub fn stratum(msg: impl Into<String>) -> Self {
         Self::Stratum(StratumError::InvalidClientMessage {
@@ -417,14 +605,17 @@ impl Error {
             message: msg.into(),
         })
     }
-    
+
     // === Validation Error Constructors ===
-    
+
     /// Create an invalid work header validation error
     pub fn validation_invalid_work_header(expected_size: usize, actual_size: usize) -> Self {
-        Self::Validation(ValidationError::InvalidWorkHeader { expected_size, actual_size })
+        Self::Validation(ValidationError::InvalidWorkHeader {
+            expected_size,
+            actual_size,
+        })
     }
-    
+
     /// Create an invalid target validation error
     pub fn validation_invalid_target(value: impl Into<String>, reason: impl Into<String>) -> Self {
         Self::Validation(ValidationError::InvalidTarget {
@@ -432,7 +623,7 @@ impl Error {
             reason: reason.into(),
         })
     }
-    
+
     /// Create a size validation error
     pub fn validation_size_error(field: impl Into<String>, expected: usize, actual: usize) -> Self {
         Self::Validation(ValidationError::SizeValidation {
@@ -441,27 +632,46 @@ impl Error {
             actual,
         })
     }
-    
+
+    /// Create a clock skew validation error
+    pub fn validation_clock_skew(
+        reference: impl Into<String>,
+        offset_ms: i64,
+        max_ms: u64,
+    ) -> Self {
+        Self::Validation(ValidationError::ClockSkew {
+            reference: reference.into(),
+            offset_ms,
+            max_ms,
+        })
+    }
+
     // === Communication Error Constructors ===
-    
+
     /// Create a channel send failed communication error
-    pub fn communication_channel_send_failed(channel: impl Into<String>, reason: impl Into<String>) -> Self {
+    pub fn communication_channel_send_failed(
+        channel: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
         Self::Communication(CommunicationError::ChannelSendFailed {
             channel: channel.into(),
             reason: reason.into(),
         })
     }
-    
+
     /// Create a channel receive failed communication error
-    pub fn communication_channel_receive_failed(channel: impl Into<String>, reason: impl Into<String>) -> Self {
+    pub fn communication_channel_receive_failed(
+        channel: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
         Self::Communication(CommunicationError::ChannelReceiveFailed {
             channel: channel.into(),
             reason: reason.into(),
         })
     }
-    
+
     // === Legacy Compatibility Methods ===
-    
+
     /// Create an invalid work error (legacy compatibility)
     pub fn invalid_work(msg: impl Into<String>) -> Self {
         Self::validation_invalid_target(msg.into(), "legacy invalid work error")
@@ -510,9 +720,9 @@ impl Error {
     pub fn json(err: serde_json::Error) -> Self {
         Self::Json(err)
     }
-    
+
     // === Error Analysis Methods ===
-    
+
     /// Check if this is a recoverable error
     pub fn is_recoverable(&self) -> bool {
         matches!(
@@ -525,7 +735,7 @@ impl Error {
                 | Error::Protocol(ProtocolError::SubscriptionFailed { .. })
         )
     }
-    
+
     /// Check if this error should trigger a retry
     pub fn should_retry(&self) -> bool {
         matches!(
@@ -536,7 +746,17 @@ impl Error {
                 | Error::Protocol(ProtocolError::EndpointUnavailable { .. })
         )
     }
-    
+
+    /// Check if the node answered but has no work to hand out yet
+    pub fn is_no_work(&self) -> bool {
+        matches!(self, Error::Protocol(ProtocolError::NoWorkAvailable { .. }))
+    }
+
+    /// Check if every configured node was given up on
+    pub fn is_node_lost(&self) -> bool {
+        matches!(self, Error::Network(NetworkError::NodeLost { .. }))
+    }
+
     /// Get the error category for metrics and logging
     pub fn category(&self) -> &'static str {
         match self {
@@ -567,7 +787,10 @@ mod tests {
         assert!(err.to_string().contains("Validation failed: missing field"));
 
         let err = Error::worker("worker failed");
-        assert!(err.to_string().contains("Mining operation failed: worker failed"));
+        assert!(
+            err.to_string()
+                .contains("Mining operation failed: worker failed")
+        );
     }
 
     #[test]
@@ -576,13 +799,22 @@ mod tests {
         assert!(err.to_string().contains("Missing required field: timeout"));
 
         let err = Error::network_timeout("http://example.com", Duration::from_secs(30));
-        assert!(err.to_string().contains("Request timeout after 30s to http://example.com"));
+        assert!(
+            err.to_string()
+                .contains("Request timeout after 30s to http://example.com")
+        );
 
         let err = Error::protocol_invalid_chain_id(0, 5);
-        assert!(err.to_string().contains("Invalid chain ID: expected 0, got 5"));
+        assert!(
+            err.to_string()
+                .contains("Invalid chain ID: expected 0, got 5")
+        );
 
         let err = Error::validation_invalid_work_header(286, 300);
-        assert!(err.to_string().contains("Invalid work header: expected 286 bytes, got 300"));
+        assert!(
+            err.to_string()
+                .contains("Invalid work header: expected 286 bytes, got 300")
+        );
     }
 
     #[test]
@@ -619,11 +851,17 @@ mod tests {
     #[test]
     fn test_specific_error_constructors() {
         let err = Error::worker_initialization_failed("CPU", "thread pool creation failed");
-        assert!(err.to_string().contains("Worker initialization failed: CPU"));
+        assert!(
+            err.to_string()
+                .contains("Worker initialization failed: CPU")
+        );
         assert!(err.to_string().contains("thread pool creation failed"));
 
         let err = Error::stratum_share_validation_failed("client123", "share456", "invalid nonce");
-        assert!(err.to_string().contains("Share validation failed: client123"));
+        assert!(
+            err.to_string()
+                .contains("Share validation failed: client123")
+        );
         assert!(err.to_string().contains("share456"));
         assert!(err.to_string().contains("invalid nonce"));
     }
@@ -644,7 +882,10 @@ mod tests {
     fn test_network_error_hierarchy() {
         let connection_err = NetworkError::ConnectionFailed {
             url: "http://example.com".to_string(),
-            source: Box::new(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused")),
+            source: Box::new(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                "refused",
+            )),
         };
         let err = Error::Network(connection_err);
         assert!(err.is_recoverable());
@@ -682,7 +923,10 @@ mod tests {
             max: 1000,
         };
         let err = Error::Validation(validation_err);
-        assert!(err.to_string().contains("Range validation failed: difficulty"));
+        assert!(
+            err.to_string()
+                .contains("Range validation failed: difficulty")
+        );
         assert!(err.to_string().contains("value -1 outside range [0, 1000]"));
         assert_eq!(err.category(), "validation");
     }
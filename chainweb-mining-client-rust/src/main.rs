@@ -2,26 +2,55 @@
 //!
 //! High-performance mining client for the Kadena Chainweb network.
 
-
 use chainweb_mining_client::{
-    config::{Args, Config, WorkerConfig},
-    core::{ChainId, PreemptionConfig, PreemptionDecision, PreemptionStrategy, WorkPreemptor},
-    error::Result,
-    protocol::chainweb::{ChainwebClient, ChainwebClientConfig},
-    utils::{self, monitoring::global_monitoring},
+    config::{Args, Config, ConfigFormat, SchemaFormat, WorkerConfig},
+    core::{
+        ChainId, PreemptionConfig, PreemptionDecision, PreemptionStrategy, Target, WorkPreemptor,
+    },
+    error::{Error, Result},
+    protocol::{
+        account_rotation::AccountRotator,
+        chainweb::{ChainwebClient, ChainwebClientConfig, Submission},
+        confirmation,
+        fetch_limit::WorkFetchLimiter,
+        http_pool::global_http_pool,
+        retry,
+        solution_sink::SolutionSink,
+        sse::UpdateEvent,
+    },
+    utils::{
+        self,
+        clock::{self, NtpTimeSource},
+        deadman::{self, DeadMansSwitch},
+        events::{self, MiningEvent, SocketEventSink, emit_event},
+        keys::{self, KeyFormat, KeyPair},
+        monitoring::{AlertSeverity, RejectReason, StatusFormat, global_monitoring},
+        notifier::{DEFAULT_WEBHOOK_TIMEOUT, WebhookNotifier},
+        selftest,
+        thermal::{self, SysfsSensor, ThermalGuard, ThermalLimits},
+        watchdog::{self, Watchdog},
+    },
     workers::{
-        Worker,
+        CancellationReason, CurrentWork, SupervisedWorker, SupervisorConfig, Worker,
         cpu::{CpuWorker, CpuWorkerConfig},
         external::{ExternalWorker, ExternalWorkerConfig},
+        preflight,
+        stratum::ShareAuditLog,
+        supervisor::WorkerFactory,
     },
 };
 use clap::Parser;
 use futures::StreamExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+/// Source of work update notifications: the node's update stream, or
+/// polling once the stream is lost
+type UpdateStream<'a> = std::pin::Pin<Box<dyn futures::Stream<Item = Result<UpdateEvent>> + 'a>>;
 
 const INFO_MESSAGE: &str = r#"
 Chainweb Mining Client
@@ -108,53 +137,158 @@ async fn main() -> Result<()> {
 
     // Handle monitoring status
     if args.monitoring_status {
-        print_monitoring_status();
+        return print_monitoring_status(args.format.as_deref());
+    }
+
+    if args.selftest {
+        let report = selftest::run_selftest().await;
+        print!("{}", report);
+        if !report.passed() {
+            return Err(Error::other("Self-test failed"));
+        }
         return Ok(());
     }
 
     // Handle key generation
-    if args.generate_key {
-        generate_key_pair();
+    if args.generate_key_requested() {
+        return generate_key_pair(args.format.as_deref(), args.key_file.as_deref());
+    }
+
+    // Handle config schema
+    if let Some(format) = args.config_schema.as_deref() {
+        print!("{}", SchemaFormat::from_str(format)?.render()?);
         return Ok(());
     }
 
     // Handle print config
     let print_config_flag = args.print_config;
-    let print_config_format = args.print_config_as.clone();
+    let print_config_selection = args.print_config_as.clone();
+    let print_config_format = args
+        .print_config_format
+        .as_deref()
+        .map(ConfigFormat::from_str)
+        .transpose()?;
+
+    // Clock check settings (opt-in, avoids a hard NTP dependency)
+    let clock_check = args.check_clock.then(|| {
+        (
+            args.ntp_server
+                .clone()
+                .unwrap_or_else(|| clock::DEFAULT_NTP_SERVER.to_string()),
+            args.max_clock_skew_secs
+                .map(Duration::from_secs)
+                .unwrap_or(clock::DEFAULT_MAX_CLOCK_SKEW),
+        )
+    });
+
+    // Watchdog settings: hash and work update stall timeouts, abort on a stall
+    let watchdog_settings = (args.watchdog || args.watchdog_abort).then(|| {
+        let timeout = |secs: Option<u64>| {
+            secs.map(|secs| Duration::from_secs(secs.max(1)))
+                .unwrap_or(watchdog::DEFAULT_WATCHDOG_TIMEOUT)
+        };
+        (
+            timeout(args.watchdog_hash_timeout_secs),
+            timeout(args.watchdog_work_timeout_secs),
+            args.watchdog_abort,
+        )
+    });
+
+    // Dead man's switch settings: dry spell threshold, exit when it trips
+    let dead_mans_switch = (args.dead_mans_switch || args.dead_mans_switch_halt)
+        .then(|| {
+            let multiple = args
+                .dead_mans_switch_multiple
+                .unwrap_or(deadman::DEFAULT_DRY_SPELL_MULTIPLE);
+            if multiple.is_finite() && multiple >= 1.0 {
+                Ok((multiple, args.dead_mans_switch_halt))
+            } else {
+                Err(Error::config_invalid_value(
+                    "dead_mans_switch_multiple",
+                    multiple.to_string(),
+                    "at least 1",
+                ))
+            }
+        })
+        .transpose()?;
+
+    // GPU temperature limits: pause above the maximum, resume below the resume temperature
+    let thermal_limits = args
+        .gpu_max_temp_c
+        .map(|max_temp_c| ThermalLimits::new(max_temp_c, args.gpu_resume_temp_c))
+        .transpose()?;
+    if thermal_limits.is_none() && args.gpu_resume_temp_c.is_some() {
+        warn!("--gpu-resume-temp-c has no effect without --gpu-max-temp-c");
+    }
+
+    let startup_jitter = args.startup_jitter_ms.map(Duration::from_millis);
+    let ping = args.ping;
+    let verify_solutions = !args.no_verify_solution;
+
+    let solution_sink_path = args.solution_sink.clone();
+    let submit_solutions = !args.no_submit;
+    let event_socket = args.event_socket.clone();
+    if let Some(max_submit_latency_ms) = args.max_submit_latency_ms {
+        let monitoring = global_monitoring();
+        monitoring.update_config(utils::AlertConfig {
+            max_submit_latency_ms,
+            ..monitoring.alert_config()
+        });
+    }
+    let alert_min_severity = args
+        .alert_min_severity
+        .as_deref()
+        .map(AlertSeverity::from_str)
+        .transpose()?
+        .unwrap_or(AlertSeverity::Warning);
+    if let Some(url) = &args.alert_webhook {
+        let notifier = WebhookNotifier::new(url.as_str(), DEFAULT_WEBHOOK_TIMEOUT)?;
+        global_monitoring().add_alert_notifier(Arc::new(notifier), alert_min_severity);
+    }
+    if !submit_solutions && solution_sink_path.is_none() {
+        return Err(Error::config(
+            "--no-submit requires --solution-sink, otherwise solutions are lost",
+        ));
+    }
+    // Solutions for an easier target than the node's are invalid blocks
+    let test_target_level = args.test_target_level;
+    if test_target_level.is_some() && submit_solutions {
+        return Err(Error::config(
+            "--test-target-level requires --no-submit, solutions for the test target aren't valid blocks",
+        ));
+    }
 
     // Load configuration
     let config = Config::from_args(args)?;
 
-    if print_config_flag || print_config_format.is_some() {
-        let format = print_config_format.as_deref().unwrap_or("full");
-        print_config(&config, format)?;
+    if print_config_flag || print_config_selection.is_some() || print_config_format.is_some() {
+        let selection = print_config_selection.as_deref().unwrap_or("full");
+        print_config(&config, selection, print_config_format.unwrap_or_default())?;
         return Ok(());
     }
 
     // Initialize logging
     utils::init_logging(&config.logging.level, &config.logging.format);
+    utils::install_panic_hook();
 
     // Initialize monitoring system
     let _monitoring = global_monitoring();
     info!("📊 Monitoring system initialized");
 
+    if let Some((ntp_server, max_skew)) = clock_check {
+        check_system_clock(&ntp_server, max_skew).await?;
+    }
+
     info!(
         "Starting Chainweb Mining Client v{}",
         env!("CARGO_PKG_VERSION")
     );
-    let chain_str = config
-        .node
-        .chain_id
-        .map(|id| id.to_string())
-        .unwrap_or_else(|| "all chains".to_string());
-    info!(
-        "Mining on {} for account {}",
-        chain_str, config.mining.account
-    );
+    info!("{}", config.startup_summary());
 
     // Create Chainweb client
     let chainweb_config = ChainwebClientConfig {
         node_url: config.node.url.clone(),
+        fallback_node_urls: config.node.fallback_urls.clone(),
         chain_id: config
             .node
             .chain_id
@@ -163,11 +297,79 @@ async fn main() -> Result<()> {
         account: config.mining.account.clone(),
         public_key: config.mining.public_key.clone(),
         timeout: Duration::from_secs(config.node.timeout_secs),
+        work_fetch_timeout: config.node.work_fetch_timeout_secs.map(Duration::from_secs),
+        submit_timeout: config.node.submit_timeout_secs.map(Duration::from_secs),
         use_tls: config.node.use_tls,
         insecure: config.node.insecure,
     };
 
-    let mut client = ChainwebClient::new(chainweb_config)?;
+    // Node DNS caching applies to every pooled client, so set it up before
+    // the mining client takes one from the pool
+    if let Some(secs) = config.node.dns_refresh_secs {
+        let pool = global_http_pool();
+        pool.set_dns_refresh(Some(Duration::from_secs(secs)));
+        pool.spawn_dns_refresh();
+        info!("Refreshing node DNS every {}s", secs);
+    }
+    if !config.node.http_headers.is_empty() {
+        global_http_pool().set_headers(config.node.header_map()?);
+    }
+
+    let mut client = ChainwebClient::new(chainweb_config)?
+        .with_submit_encoding(config.node.submit_encoding)
+        .with_node_loss_policy(config.node.on_node_loss)
+        .with_work_fetch_limiter(Arc::new(WorkFetchLimiter::new(
+            config.mining.max_concurrent_work_fetches,
+        )));
+    if let Some(network) = &config.node.network {
+        client = client.with_network(network.as_str());
+    }
+    if !config.node.relay_urls.is_empty() {
+        client = client.with_relay_nodes(&config.node.relay_urls)?;
+    }
+    if let Some(rotation) = config.mining.account_rotation {
+        let accounts = config.mining.accounts();
+        info!(
+            "Rotating between {} mining accounts ({})",
+            accounts.len(),
+            rotation
+        );
+        client = client.with_account_rotation(AccountRotator::new(accounts, Some(rotation)));
+    }
+    let chain_accounts = config.mining.chain_accounts()?;
+    if !chain_accounts.is_empty() {
+        info!(
+            "Requesting work of {} chains for their own account",
+            chain_accounts.len()
+        );
+        client = client.with_chain_accounts(chain_accounts.clone());
+    }
+    if let Some(level) = test_target_level {
+        warn!(
+            "Mining against test target level {} instead of the node's target",
+            level
+        );
+        client = client.with_target_override(Target::mk_target_level(level));
+    }
+    if let Some(keyset) = &config.mining.keyset {
+        info!(
+            "Requesting work for keyset {} of {} keys",
+            keyset.pred,
+            keyset.keys.len()
+        );
+        client = client.with_miner_keyset(keyset.clone());
+    }
+
+    if ping {
+        return print_ping_results(&client).await;
+    }
+
+    // Stagger startup so a fleet of miners doesn't hit the node at once
+    if let Some(max_jitter) = startup_jitter {
+        let delay = retry::startup_jitter(max_jitter);
+        info!("Delaying startup by {} ms", delay.as_millis());
+        tokio::time::sleep(delay).await;
+    }
 
     // Get node info
     let node_info = client.get_node_info().await?;
@@ -176,110 +378,67 @@ async fn main() -> Result<()> {
         node_info.node_version, node_info.node_api_version
     );
 
+    if let Some(chain_id) = config.node.chain_id {
+        node_info.validate_chain_id(ChainId::new(chain_id))?;
+    }
+    node_info.validate_chain_accounts(chain_accounts.keys().copied())?;
+    let active_chains = node_info.active_chains(&config.node.exclude_chains)?;
+    if !active_chains.is_empty() {
+        info!(
+            "Active chains: {}",
+            active_chains
+                .iter()
+                .map(ChainId::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if config.node.chain_id.is_none()
+        && let Some(&first) = active_chains.first()
+    {
+        client.set_chain_id(first);
+    }
+    info!("Mining on chain {}", client.chain_id());
+
     // Set the node version for future API calls
     client.set_node_version(node_info.node_version.clone());
-    
+
     // Create Arc for shared ownership
     let client_arc = Arc::new(client);
 
-    // Create worker based on configuration
-    let worker: Arc<dyn Worker> = match &config.worker {
-        WorkerConfig::Cpu {
-            threads,
-            batch_size,
-        } => {
-            let cpu_config = CpuWorkerConfig {
-                threads: *threads,
-                batch_size: *batch_size,
-                update_interval: Duration::from_secs(1),
-            };
-            Arc::new(CpuWorker::new(cpu_config))
-        }
-        WorkerConfig::Gpu {
-            device_index,
-            workgroup_size,
-            workgroup_count,
-            batch_size,
-            enable_monitoring,
-        } => {
-            let gpu_config = chainweb_mining_client::workers::gpu::GpuConfig {
-                device_index: *device_index,
-                workgroup_size: *workgroup_size,
-                workgroup_count: *workgroup_count,
-                batch_size: *batch_size,
-                enable_monitoring: *enable_monitoring,
-            };
-            let gpu_worker = tokio::runtime::Handle::current()
-                .block_on(chainweb_mining_client::workers::gpu::GpuWorker::new(gpu_config))
-                .expect("Failed to create GPU worker");
-            Arc::new(gpu_worker)
-        }
-        WorkerConfig::External {
-            command,
-            args,
-            env,
-            timeout_secs,
-        } => {
-            let external_config = ExternalWorkerConfig {
-                command: PathBuf::from(command),
-                args: args.clone(),
-                env: env.clone(),
-                timeout_secs: *timeout_secs,
-            };
-            Arc::new(ExternalWorker::new(external_config))
-        }
+    // Fail before connecting miners if a server worker can't listen
+    match &config.worker {
         WorkerConfig::Stratum {
             port,
             host,
             max_connections,
-            difficulty,
-            rate_ms,
-        } => {
-            let stratum_config = chainweb_mining_client::workers::stratum::StratumServerConfig {
-                port: *port,
-                host: host.clone(),
-                max_connections: *max_connections,
-                difficulty: difficulty.clone(),
-                rate_ms: *rate_ms,
-                authorize_callback: None, // No custom authorization by default
-            };
-            Arc::new(chainweb_mining_client::workers::stratum::StratumServer::new(stratum_config))
-        }
-        WorkerConfig::Simulation { hash_rate } => {
-            let simulation_config =
-                chainweb_mining_client::workers::simulation::SimulationWorkerConfig {
-                    hash_rate: *hash_rate,
-                };
-            Arc::new(
-                chainweb_mining_client::workers::simulation::SimulationWorker::new(
-                    simulation_config,
-                ),
-            )
+            ..
+        } => preflight::check_server_worker("Stratum", host, *port, *max_connections)?,
+        WorkerConfig::OnDemand { port, host, .. } => {
+            preflight::check_server_worker("On-demand", host, *port, 0)?
         }
-        WorkerConfig::ConstantDelay { block_time_secs } => {
-            let constant_delay_config =
-                chainweb_mining_client::workers::constant_delay::ConstantDelayWorkerConfig {
-                    block_time_secs: *block_time_secs,
-                };
-            Arc::new(
-                chainweb_mining_client::workers::constant_delay::ConstantDelayWorker::new(
-                    constant_delay_config,
-                ),
-            )
-        }
-        WorkerConfig::OnDemand { port, host } => {
-            let on_demand_config =
-                chainweb_mining_client::workers::on_demand::OnDemandWorkerConfig {
-                    port: *port,
-                    host: host.clone(),
-                };
-            Arc::new(
-                chainweb_mining_client::workers::on_demand::OnDemandWorker::new(on_demand_config),
-            )
-        }
-    };
+        _ => {}
+    }
+
+    // Create worker based on configuration, supervised so that repeated
+    // mining failures tear it down and recreate it from the same config
+    let worker_config = config.worker.clone();
+    let hashrate_warmup = Duration::from_secs(config.mining.hashrate_warmup_secs);
+    let factory: WorkerFactory = Arc::new(move || {
+        let worker_config = worker_config.clone();
+        Box::pin(async move { create_worker(&worker_config, hashrate_warmup).await })
+    });
+    let worker = SupervisedWorker::new(factory, SupervisorConfig::default()).await?;
 
-    info!("Using {} worker", worker.worker_type());
+    if config.worker_defaulted {
+        info!(
+            "Using {} worker by default; select a worker with --worker (or require one with --strict-config)",
+            worker.worker_type()
+        );
+    } else {
+        info!("Using {} worker", worker.worker_type());
+    }
+    let capabilities = worker.capabilities();
 
     // Create work preemptor with default configuration
     let preemption_config = PreemptionConfig {
@@ -290,50 +449,204 @@ async fn main() -> Result<()> {
     };
     let preemptor = WorkPreemptor::new(preemption_config);
 
+    // Optional sink receiving every solution for external consumers
+    let solution_chain = client_arc.chain_id();
+    let solution_sink = match &solution_sink_path {
+        Some(path) => {
+            info!("Writing solutions to {}", path.display());
+            Some(SolutionSink::open(path).await?)
+        }
+        None => None,
+    };
+    if !submit_solutions {
+        warn!("Solution submission to the node is disabled (--no-submit)");
+    }
+    let confirm_delay = config
+        .mining
+        .confirm_blocks
+        .then(|| Duration::from_secs(config.mining.confirm_delay_secs));
+
+    // Optional socket streaming mining events to external consumers
+    if let Some(address) = &event_socket {
+        events::set_event_sink(Arc::new(SocketEventSink::bind(address).await?));
+    }
+
     // Create channel for mining results
     let (result_tx, mut result_rx) = mpsc::channel(10);
 
     // Subscribe to work updates
-    let mut update_stream = client_arc.subscribe_updates().await?;
-    
+    let mut update_stream: UpdateStream<'_> = Box::pin(client_arc.subscribe_updates().await?);
+    let mut stream_node = client_arc.active_node();
+
     // Stream reconnection state
     let mut stream_retry_count = 0u32;
     let mut stream_retry_delay = Duration::from_millis(100);
     const MAX_STREAM_RETRIES: u32 = 10;
     const MAX_STREAM_DELAY: Duration = Duration::from_secs(30);
+    const MAX_STREAM_PROBE_DELAY: Duration = Duration::from_secs(600);
+    let fallback_poll_interval =
+        Duration::from_secs(config.mining.fallback_poll_interval_secs.max(1));
 
-    // Get initial work
-    let (mut current_work, mut current_target) = client_arc.get_work().await?;
-    info!("Received initial work");
+    // While polling, the update stream is retried with a growing delay
+    let mut polling = false;
+    let mut stream_probe_delay = MAX_STREAM_DELAY;
+    let stream_probe = tokio::time::sleep(stream_probe_delay);
+    tokio::pin!(stream_probe);
 
-    // Start mining
-    worker
-        .mine(current_work.clone(), current_target, result_tx.clone())
+    // Get initial work, waiting while the node has none (e.g. still syncing)
+    let mut no_work_backoff = retry::NoWorkBackoff::default();
+    let (work, target) = client_arc
+        .get_work_with_backoff(&mut no_work_backoff, &mut update_stream)
         .await?;
+    let mut current = CurrentWork::new(work, target);
+    info!("Received initial work");
+    global_monitoring().record_work_update();
+
+    // Detect a stuck worker or main loop from progress that stopped
+    if let Some((hash_timeout, work_timeout, abort)) = watchdog_settings {
+        // The hash count stands still while the thermal guard pauses mining
+        let watchdog = Watchdog::mining(capabilities, hash_timeout, work_timeout, {
+            let worker = worker.clone();
+            move || worker.is_paused()
+        });
+        let watched: Vec<_> = watchdog
+            .indicators()
+            .map(|(name, timeout)| format!("{} {}s", name, timeout.as_secs()))
+            .collect();
+        watchdog.spawn(abort);
+        info!(
+            "Watchdog enabled ({}{})",
+            watched.join(", "),
+            if abort { ", aborting on a stall" } else { "" }
+        );
+    }
 
-    // Main mining loop
-    loop {
+    // Detect a worker that hashes but can never find a solution
+    if let Some((multiple, halt)) = dead_mans_switch {
+        if capabilities.counts_hashes() {
+            DeadMansSwitch::new(multiple).spawn(halt);
+            info!(
+                "Dead man's switch enabled ({} expected blocks{})",
+                multiple,
+                if halt { ", exiting when it trips" } else { "" }
+            );
+        } else {
+            warn!(
+                "Dead man's switch inactive, the {} worker has no hash count to expect blocks from",
+                worker.worker_type()
+            );
+        }
+    }
+
+    // Pause the GPU while it overheats
+    if let Some(limits) = thermal_limits {
+        let drm_dir = Path::new(thermal::SYSFS_DRM_DIR);
+        if !capabilities.supports_pause {
+            warn!(
+                "GPU temperature limits ignored, the {} worker can't be paused",
+                worker.worker_type()
+            );
+        } else if let Some(sensor) = SysfsSensor::discover(drm_dir) {
+            info!(
+                "Thermal protection enabled (pause above {:.1}°C, resume below {:.1}°C, sensors {:?})",
+                limits.max_temp_c,
+                limits.resume_temp_c,
+                sensor.inputs()
+            );
+            ThermalGuard::new(limits).spawn(sensor, worker.clone());
+        } else {
+            warn!(
+                "GPU temperature limits ignored, no GPU temperature sensor found in {}",
+                drm_dir.display()
+            );
+        }
+    }
+
+    // Start mining; failures are retried by the supervisor until it gives up
+    if let Err(e) = worker
+        .start(current.work.clone(), current.target, result_tx.clone())
+        .await
+    {
+        error!("Failed to start mining: {}", e);
+    }
+
+    // Every hashrate sample is recorded, but logged at most this often
+    let mut hashrate_log = utils::LogThrottle::new(Duration::from_secs(
+        config.logging.hashrate_log_interval_secs,
+    ));
+
+    // Main mining loop, ending with the outcome of the run
+    let outcome = loop {
         tokio::select! {
             // Handle mining results
             Some(result) = result_rx.recv() => {
-                info!("Found solution! Nonce: {}", result.nonce);
+                info!("Found solution! Nonce: {} (le {})", result.nonce, result.nonce.to_hex_le());
+                global_monitoring().record_solution();
+                emit_event(MiningEvent::SolutionFound {
+                    worker: worker.worker_type().to_string(),
+                    nonce: result.nonce.value(),
+                    hash: hex::encode(result.hash),
+                });
 
-                // Submit solution
-                match client_arc.submit_solution(&result.work).await {
-                    Ok(()) => {
-                        info!("Solution accepted!");
+                if verify_solutions && !current.accepts(&result) {
+                    error!(
+                        "{} worker reported a solution that doesn't meet the target, not submitting it (nonce {})",
+                        worker.worker_type(),
+                        result.nonce
+                    );
+                    global_monitoring().record_rejection(RejectReason::InvalidSolution);
+                } else {
+                    if let Some(sink) = &solution_sink
+                        && let Err(e) = sink.write(solution_chain, &result).await
+                    {
+                        error!("Failed to write solution to {}: {}", sink.path().display(), e);
                     }
-                    Err(e) => {
-                        error!("Failed to submit solution: {}", e);
+
+                    // Submit solution
+                    if submit_solutions {
+                        let outcome = client_arc.submit_solution(&result.work).await;
+                        global_monitoring().record_submission(&outcome);
+                        if let Ok(Submission::Duplicate) = outcome {
+                            // Sent, reported and checked the first time already
+                            debug!("Solution was submitted before, not reporting it again");
+                        } else {
+                            match &outcome {
+                                Ok(_) => {
+                                    info!("Solution accepted!");
+                                    if let Some(delay) = confirm_delay {
+                                        confirmation::spawn_confirmation(
+                                            client_arc.clone(),
+                                            solution_chain,
+                                            result.work.clone(),
+                                            delay,
+                                        );
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Failed to submit solution: {}", e);
+                                }
+                            }
+                            emit_event(MiningEvent::SubmitOutcome {
+                                hash: hex::encode(result.hash),
+                                accepted: outcome.is_ok(),
+                                error: outcome.err().map(|e| e.to_string()),
+                            });
+                        }
                     }
                 }
 
                 // Get new work and continue mining
-                match client_arc.get_work().await {
+                match client_arc.get_work_with_backoff(&mut no_work_backoff, &mut update_stream).await {
                     Ok((work, target)) => {
-                        current_work = work;
-                        current_target = target;
-                        worker.mine(current_work.clone(), current_target, result_tx.clone()).await?;
+                        current.replace(work, target);
+                        if let Err(e) = worker.mine(current.work.clone(), current.target, result_tx.clone()).await {
+                            error!("Failed to restart mining: {}", e);
+                        }
+                    }
+                    Err(e) if e.is_node_lost() => {
+                        error!("{}, shutting down", e);
+                        worker.cancel(CancellationReason::Shutdown).await?;
+                        break Err(e);
                     }
                     Err(e) => {
                         error!("Failed to get new work: {}", e);
@@ -344,21 +657,37 @@ async fn main() -> Result<()> {
             // Handle work updates
             Some(update_result) = update_stream.next() => {
                 match update_result {
+                    Ok(event) if !event.changes_work() => {
+                        debug!("Ignoring {:?} update event", event);
+                    }
                     Ok(_) => {
                         info!("Received work update");
+                        global_monitoring().record_work_update();
 
                         // Get new work first
                         match client_arc.get_work().await {
+                            Ok((new_work, new_target)) if !capabilities.supports_preemption => {
+                                // Servers can't be stopped mid-work; hand them the new job directly
+                                if let Err(e) = worker.mine(new_work.clone(), new_target, result_tx.clone()).await {
+                                    error!("Failed to update work: {}", e);
+                                } else {
+                                    current.replace(new_work, new_target);
+                                }
+                            }
                             Ok((new_work, new_target)) => {
                                 // Use preemptor to decide if and how to preempt
-                                let decision = preemptor.should_preempt(&new_work, &current_work);
+                                let decision = preemptor.should_preempt(&new_work, &current.work);
 
                                 match decision {
                                     PreemptionDecision::Preempt(action) => {
                                         info!("Preempting current work with action: {:?}", action);
+                                        emit_event(MiningEvent::PreemptionDecision {
+                                            preempted: true,
+                                            detail: format!("{:?}", action),
+                                        });
 
                                         // Execute preemption using the sophisticated logic
-                                        let worker_clone = worker.clone();
+                                        let worker_clone: Arc<dyn Worker> = worker.clone();
                                         let result_tx_clone = result_tx.clone();
                                         let client_clone = Arc::clone(&client_arc);
 
@@ -376,71 +705,179 @@ async fn main() -> Result<()> {
                                             error!("Failed to execute preemption: {}", e);
                                         } else {
                                             // Update current work if preemption succeeded
-                                            current_work = new_work;
+                                            current.replace(new_work, new_target);
                                         }
                                     }
                                     PreemptionDecision::Skip(reason) => {
                                         info!("Skipping work preemption: {:?}", reason);
+                                        emit_event(MiningEvent::PreemptionDecision {
+                                            preempted: false,
+                                            detail: format!("{:?}", reason),
+                                        });
                                         // Continue with current work
                                     }
                                 }
                             }
+                            Err(e) if e.is_no_work() => {
+                                debug!("Work update received but the node has no work yet");
+                            }
+                            Err(e) if client_arc.node_lost() => {
+                                let e = Error::network_node_lost(client_arc.node_count(), e);
+                                error!("{}, shutting down", e);
+                                worker.cancel(CancellationReason::Shutdown).await?;
+                                break Err(e);
+                            }
                             Err(e) => {
                                 error!("Failed to get updated work: {}", e);
                             }
                         }
                     }
+                    Err(e) if client_arc.node_lost() => {
+                        let e = Error::network_node_lost(client_arc.node_count(), e);
+                        error!("{}, shutting down", e);
+                        worker.cancel(CancellationReason::Shutdown).await?;
+                        break Err(e);
+                    }
                     Err(e) => {
                         warn!("Update stream error: {}", e);
-                        
+
                         // Attempt to reconnect with exponential backoff
                         if stream_retry_count < MAX_STREAM_RETRIES {
                             stream_retry_count += 1;
                             info!("Attempting to reconnect stream (attempt {}/{})", stream_retry_count, MAX_STREAM_RETRIES);
-                            
+
                             // Wait before reconnecting
                             tokio::time::sleep(stream_retry_delay).await;
-                            
+
                             // Try to reconnect
                             match client_arc.subscribe_updates().await {
                                 Ok(new_stream) => {
-                                    update_stream = new_stream;
+                                    update_stream = Box::pin(new_stream);
+                                    stream_node = client_arc.active_node();
                                     info!("Successfully reconnected to update stream");
-                                    
+
                                     // Reset retry state on successful reconnection
                                     stream_retry_count = 0;
                                     stream_retry_delay = Duration::from_millis(100);
                                 }
                                 Err(reconnect_error) => {
                                     error!("Failed to reconnect to updates: {}", reconnect_error);
-                                    
+
                                     // Increase delay for next attempt (exponential backoff)
                                     stream_retry_delay = std::cmp::min(
                                         Duration::from_millis((stream_retry_delay.as_millis() as f64 * 2.0) as u64),
                                         MAX_STREAM_DELAY
                                     );
+
+                                    // The old stream is gone; come back here for the next attempt
+                                    update_stream = Box::pin(futures::stream::once(async move { Err(reconnect_error) }));
                                 }
                             }
                         } else {
-                            error!("Max stream reconnection attempts exceeded, giving up on automatic updates");
-                            // Continue mining with current work but without updates
+                            warn!(
+                                "Update stream lost after {} reconnection attempts, polling for new work every {}s",
+                                MAX_STREAM_RETRIES,
+                                fallback_poll_interval.as_secs()
+                            );
+                            update_stream = Box::pin(client_arc.clone().poll_updates(fallback_poll_interval, &current.work));
+                            polling = true;
+                            stream_probe_delay = MAX_STREAM_DELAY;
+                            stream_probe.as_mut().reset(tokio::time::Instant::now() + stream_probe_delay);
+                        }
+                    }
+                }
+            }
+
+            // Try to get the update stream back while polling
+            _ = &mut stream_probe, if polling => {
+                match client_arc.subscribe_updates().await {
+                    Ok(new_stream) => {
+                        update_stream = Box::pin(new_stream);
+                        stream_node = client_arc.active_node();
+                        polling = false;
+                        stream_retry_count = 0;
+                        stream_retry_delay = Duration::from_millis(100);
+                        info!("Update stream restored, stopped polling for new work");
+                    }
+                    Err(e) => {
+                        stream_probe_delay = std::cmp::min(stream_probe_delay * 2, MAX_STREAM_PROBE_DELAY);
+                        debug!("Update stream still unavailable, retrying in {:?}: {}", stream_probe_delay, e);
+                        stream_probe.as_mut().reset(tokio::time::Instant::now() + stream_probe_delay);
+                    }
+                }
+            }
+
+            // The worker searched its nonce budget without a solution
+            _ = worker.work_exhausted() => {
+                match client_arc.get_work_with_backoff(&mut no_work_backoff, &mut update_stream).await {
+                    Ok((work, target)) => {
+                        current.replace(work, target);
+                        if let Err(e) = worker.mine(current.work.clone(), current.target, result_tx.clone()).await {
+                            error!("Failed to restart mining with fresh work: {}", e);
                         }
                     }
+                    Err(e) if e.is_node_lost() => {
+                        error!("{}, shutting down", e);
+                        worker.cancel(CancellationReason::Shutdown).await?;
+                        break Err(e);
+                    }
+                    Err(e) => {
+                        error!("Failed to get fresh work: {}", e);
+                    }
                 }
             }
 
+            // A background restart used up the worker's restart budget
+            _ = worker.restarts_exhausted() => {}
+
             // Handle shutdown signal
             _ = tokio::signal::ctrl_c() => {
                 info!("Shutting down...");
-                worker.stop().await?;
-                break;
+                worker.cancel(CancellationReason::Shutdown).await?;
+                break Ok(());
+            }
+        }
+
+        if worker.is_exhausted() {
+            error!(
+                "{} worker could not be recovered, shutting down",
+                worker.worker_type()
+            );
+            break Err(Error::worker_restart_exhausted(
+                worker.worker_type(),
+                worker.restart_count(),
+            ));
+        }
+
+        // Follow node failover: the update stream must come from the active node
+        let active_node = client_arc.active_node();
+        if active_node != stream_node {
+            info!(
+                "Active node changed from {} to {}, re-subscribing to updates",
+                stream_node, active_node
+            );
+            match client_arc.subscribe_updates().await {
+                Ok(new_stream) => {
+                    update_stream = Box::pin(new_stream);
+                    stream_node = client_arc.active_node();
+                    stream_retry_count = 0;
+                    polling = false;
+                }
+                Err(e) => {
+                    error!("Failed to subscribe to updates on {}: {}", active_node, e);
+                }
             }
         }
 
         // Print hashrate periodically
-        let hashrate = worker.hashrate().await;
-        if hashrate > 0 {
-            info!("Current hashrate: {}", utils::format_hashrate(hashrate));
+        if capabilities.reports_hashrate {
+            let hashrate = worker.hashrate().await;
+            if hashrate > 0 {
+                global_monitoring().record_hash_rate(hashrate as f64);
+                if hashrate_log.ready() {
+                    info!("Current hashrate: {}", utils::format_hashrate(hashrate));
+                }
+            }
         }
 
         // Print preemption statistics periodically
@@ -454,76 +891,252 @@ async fn main() -> Result<()> {
                 stats.avg_restart_time_ms
             );
         }
+    };
+
+    // Summarize the run, on errors as well as on a regular shutdown
+    let report = global_monitoring().efficiency_report();
+    if config.logging.format == "json" {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report);
     }
 
     info!("Mining client stopped");
-    Ok(())
+    outcome
 }
 
-/// Generate a new Ed25519 key pair for mining
-fn generate_key_pair() {
-    use ed25519_dalek::{SigningKey, VerifyingKey};
-
-    let mut secret_bytes = [0u8; 32];
-    getrandom::fill(&mut secret_bytes).expect("Failed to generate random bytes");
-    let signing_key = SigningKey::from_bytes(&secret_bytes);
-    let verifying_key: VerifyingKey = (&signing_key).into();
-
-    let private_key = hex::encode(signing_key.to_bytes());
-    let public_key = hex::encode(verifying_key.to_bytes());
-
-    println!("public:  {}", public_key);
-    println!("private: {}", private_key);
+/// Validate the system clock against an NTP server
+///
+/// Excessive skew is fatal since the node would reject the produced headers;
+/// an unreachable NTP server only produces a warning.
+async fn check_system_clock(ntp_server: &str, max_skew: Duration) -> Result<()> {
+    let source = NtpTimeSource::new(ntp_server);
+    match clock::check_clock_skew(&source, max_skew).await {
+        Ok(offset) => {
+            info!("System clock offset against {}: {:.3}s", ntp_server, offset);
+            Ok(())
+        }
+        Err(e @ Error::Validation(_)) => {
+            error!("{}", e);
+            Err(e)
+        }
+        Err(e) => {
+            warn!(
+                "Clock check against {} failed, continuing: {}",
+                ntp_server, e
+            );
+            Ok(())
+        }
+    }
 }
 
-/// Print configuration in the specified format
-fn print_config(config: &Config, format: &str) -> Result<()> {
-    match format {
-        "full" => {
-            // Print as YAML for compatibility with Haskell version
-            let yaml = serde_yaml::to_string(config).map_err(|e| {
-                chainweb_mining_client::error::Error::config(format!(
-                    "Failed to serialize config: {}",
-                    e
-                ))
-            })?;
-            println!("{}", yaml);
+/// Create a worker from its configuration
+async fn create_worker(
+    worker_config: &WorkerConfig,
+    hashrate_warmup: Duration,
+) -> Result<Arc<dyn Worker>> {
+    let worker: Arc<dyn Worker> = match worker_config {
+        WorkerConfig::Cpu {
+            threads,
+            batch_size,
+            thread_priority,
+            midstate,
+            max_nonces_per_work,
+            max_memory_mb,
+        } => {
+            let cpu_config = CpuWorkerConfig {
+                threads: *threads,
+                batch_size: *batch_size,
+                thread_priority: *thread_priority,
+                midstate: *midstate,
+                max_nonces_per_work: *max_nonces_per_work,
+                max_memory_mb: *max_memory_mb,
+                update_interval: Duration::from_secs(1),
+                warmup: hashrate_warmup,
+            };
+            Arc::new(CpuWorker::new(cpu_config))
         }
-        "minimal" => {
-            // Print only non-default values
-            // For now, just print the full config
-            let yaml = serde_yaml::to_string(config).map_err(|e| {
-                chainweb_mining_client::error::Error::config(format!(
-                    "Failed to serialize config: {}",
-                    e
-                ))
-            })?;
-            println!("{}", yaml);
+        WorkerConfig::Gpu {
+            device_index,
+            workgroup_size,
+            workgroup_count,
+            batch_size,
+            enable_monitoring,
+        } => {
+            let gpu_config = chainweb_mining_client::workers::gpu::GpuConfig {
+                device_index: *device_index,
+                workgroup_size: *workgroup_size,
+                workgroup_count: *workgroup_count,
+                batch_size: *batch_size,
+                enable_monitoring: *enable_monitoring,
+                warmup: hashrate_warmup,
+            };
+            Arc::new(chainweb_mining_client::workers::gpu::GpuWorker::new(gpu_config).await?)
         }
-        "diff" => {
-            // Print only values that differ from defaults
-            // For now, just print the full config
-            let yaml = serde_yaml::to_string(config).map_err(|e| {
-                chainweb_mining_client::error::Error::config(format!(
-                    "Failed to serialize config: {}",
-                    e
-                ))
-            })?;
-            println!("{}", yaml);
+        WorkerConfig::External {
+            command,
+            args,
+            env,
+            timeout_secs,
+            max_concurrent,
+        } => {
+            let external_config = ExternalWorkerConfig {
+                command: PathBuf::from(command),
+                args: args.clone(),
+                env: env.clone(),
+                timeout_secs: *timeout_secs,
+                max_concurrent: *max_concurrent,
+            };
+            Arc::new(ExternalWorker::new(external_config))
+        }
+        WorkerConfig::Stratum {
+            port,
+            host,
+            max_connections,
+            difficulty,
+            max_period_secs: _,
+            min_difficulty_level,
+            hashrate_shift_factor,
+            mode,
+            rate_ms,
+            always_clean_jobs,
+            job_history_size,
+            session_idle_timeout_secs,
+            extranonce1_size,
+            audit_file,
+            audit_max_size_mb,
+        } => {
+            let audit_log = audit_file
+                .as_ref()
+                .map(|path| {
+                    ShareAuditLog::open(path, audit_max_size_mb.saturating_mul(1024 * 1024))
+                })
+                .transpose()?
+                .map(Arc::new);
+            let stratum_config = chainweb_mining_client::workers::stratum::StratumServerConfig {
+                port: *port,
+                host: host.clone(),
+                max_connections: *max_connections,
+                difficulty: difficulty.clone(),
+                mode: *mode,
+                min_difficulty_level: *min_difficulty_level,
+                hashrate_shift_factor: *hashrate_shift_factor,
+                rate_ms: *rate_ms,
+                always_clean_jobs: *always_clean_jobs,
+                job_history_size: *job_history_size,
+                session_idle_timeout_secs: *session_idle_timeout_secs,
+                extranonce1_size: *extranonce1_size,
+                fixed_extranonce1: None,
+                authorize_callback: None, // No custom authorization by default
+                audit_log,
+            };
+            Arc::new(chainweb_mining_client::workers::stratum::StratumServer::new(stratum_config))
         }
+        WorkerConfig::Simulation { hash_rate } => {
+            let simulation_config =
+                chainweb_mining_client::workers::simulation::SimulationWorkerConfig {
+                    hash_rate: *hash_rate,
+                };
+            Arc::new(
+                chainweb_mining_client::workers::simulation::SimulationWorker::new(
+                    simulation_config,
+                ),
+            )
+        }
+        WorkerConfig::ConstantDelay { block_time_secs } => {
+            let constant_delay_config =
+                chainweb_mining_client::workers::constant_delay::ConstantDelayWorkerConfig {
+                    block_time_secs: *block_time_secs,
+                };
+            Arc::new(
+                chainweb_mining_client::workers::constant_delay::ConstantDelayWorker::new(
+                    constant_delay_config,
+                ),
+            )
+        }
+        WorkerConfig::OnDemand {
+            port,
+            host,
+            replay_window_secs,
+            min_interval_ms,
+        } => {
+            let on_demand_config =
+                chainweb_mining_client::workers::on_demand::OnDemandWorkerConfig {
+                    port: *port,
+                    host: host.clone(),
+                    replay_window: Duration::from_secs(*replay_window_secs),
+                    min_interval: Duration::from_millis(*min_interval_ms),
+                };
+            Arc::new(
+                chainweb_mining_client::workers::on_demand::OnDemandWorker::new(on_demand_config),
+            )
+        }
+    };
+
+    Ok(worker)
+}
+
+/// Generate a new Ed25519 key pair for mining
+fn generate_key_pair(format: Option<&str>, key_file: Option<&Path>) -> Result<()> {
+    let format = format
+        .map(KeyFormat::from_str)
+        .transpose()?
+        .unwrap_or_default();
+    let output = KeyPair::generate()?.render(format)?;
+
+    match key_file {
+        Some(path) => {
+            keys::write_private_file(path, &output)?;
+            println!("Key pair written to {}", path.display());
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// Print the `selection` (full, minimal or diff) of the configuration in `format`
+fn print_config(config: &Config, selection: &str, format: ConfigFormat) -> Result<()> {
+    match selection {
+        // Minimal and diff output print the full config for now
+        "full" | "minimal" | "diff" => print!("{}", config.render(format)?),
         _ => {
-            return Err(chainweb_mining_client::error::Error::config(format!(
+            return Err(Error::config(format!(
                 "Invalid print-config-as format: {}. Must be one of: full, minimal, diff",
-                format
+                selection
             )));
         }
     }
     Ok(())
 }
 
-/// Print current monitoring status
-fn print_monitoring_status() {
-    let monitoring = global_monitoring();
-    let report = monitoring.generate_status_report();
-    println!("{}", report);
+/// Print the latency to each configured node, fastest first
+async fn print_ping_results(client: &ChainwebClient) -> Result<()> {
+    let mut results = client.ping_all().await;
+    results.sort_by_key(|(_, latency)| latency.as_ref().map_or(Duration::MAX, |d| *d));
+
+    let mut reachable = false;
+    for (node, latency) in &results {
+        match latency {
+            Ok(latency) => {
+                reachable = true;
+                println!("{:<40} {:>8.1} ms", node, latency.as_secs_f64() * 1000.0);
+            }
+            Err(e) => println!("{:<40} unreachable ({})", node, e),
+        }
+    }
+
+    if !reachable {
+        return Err(Error::network("No configured node is reachable"));
+    }
+    Ok(())
+}
+
+/// Print current monitoring status in `format` (text or json)
+fn print_monitoring_status(format: Option<&str>) -> Result<()> {
+    let format = format
+        .map(StatusFormat::from_str)
+        .transpose()?
+        .unwrap_or_default();
+    println!("{}", global_monitoring().render_status_report(format)?);
+    Ok(())
 }
@@ -0,0 +1,155 @@
+//! Rotation of the account work is requested for
+//!
+//! Operators can list several accounts and rotate through them after a
+//! number of accepted solutions or after a fixed interval. Work requested
+//! from the node always carries the currently active account; the rotation
+//! wraps around to the first account after the last one.
+
+use crate::config::{AccountRotation, MiningAccount};
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+#[derive(Debug)]
+struct RotationState {
+    index: usize,
+    solutions: u64,
+    since: Instant,
+}
+
+/// Tracks the active mining account
+#[derive(Debug)]
+pub struct AccountRotator {
+    accounts: Vec<MiningAccount>,
+    policy: Option<AccountRotation>,
+    state: Mutex<RotationState>,
+}
+
+impl AccountRotator {
+    /// Rotate through `accounts` (at least one) according to `policy`
+    pub fn new(accounts: Vec<MiningAccount>, policy: Option<AccountRotation>) -> Self {
+        assert!(
+            !accounts.is_empty(),
+            "account rotation needs at least one account"
+        );
+        Self {
+            accounts,
+            policy,
+            state: Mutex::new(RotationState {
+                index: 0,
+                solutions: 0,
+                since: Instant::now(),
+            }),
+        }
+    }
+
+    /// Always use `account`
+    pub fn single(account: MiningAccount) -> Self {
+        Self::new(vec![account], None)
+    }
+
+    /// The account to request work for
+    pub fn current(&self) -> MiningAccount {
+        self.current_at(Instant::now())
+    }
+
+    fn current_at(&self, now: Instant) -> MiningAccount {
+        let mut state = self.state.lock();
+        if let Some(AccountRotation::Interval(secs)) = self.policy
+            && now.duration_since(state.since) >= Duration::from_secs(secs)
+        {
+            self.advance(&mut state, now, format_args!("after {}s", secs));
+        }
+        self.accounts[state.index].clone()
+    }
+
+    /// Count a solution accepted by the node
+    pub fn record_solution(&self) {
+        let mut state = self.state.lock();
+        state.solutions += 1;
+        if let Some(AccountRotation::Blocks(blocks)) = self.policy
+            && state.solutions >= blocks
+        {
+            self.advance(
+                &mut state,
+                Instant::now(),
+                format_args!("after {} blocks", blocks),
+            );
+        }
+    }
+
+    fn advance(&self, state: &mut RotationState, now: Instant, reason: std::fmt::Arguments<'_>) {
+        state.solutions = 0;
+        state.since = now;
+        if self.accounts.len() > 1 {
+            state.index = (state.index + 1) % self.accounts.len();
+            info!(
+                "Rotating mining account to {} ({})",
+                self.accounts[state.index].account, reason
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accounts() -> Vec<MiningAccount> {
+        ["a", "b", "c"]
+            .iter()
+            .map(|name| MiningAccount {
+                account: name.to_string(),
+                public_key: format!("{}-key", name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rotates_after_blocks() {
+        let rotator = AccountRotator::new(accounts(), Some(AccountRotation::Blocks(2)));
+        assert_eq!(rotator.current().account, "a");
+        rotator.record_solution();
+        assert_eq!(rotator.current().account, "a");
+        rotator.record_solution();
+        assert_eq!(rotator.current().account, "b");
+
+        for _ in 0..4 {
+            rotator.record_solution();
+        }
+        // Wraps around after the last account
+        assert_eq!(rotator.current().account, "a");
+    }
+
+    #[test]
+    fn test_rotates_after_interval() {
+        let rotator = AccountRotator::new(accounts(), Some(AccountRotation::Interval(60)));
+        let start = Instant::now();
+        rotator.record_solution();
+        assert_eq!(
+            rotator.current_at(start + Duration::from_secs(59)).account,
+            "a"
+        );
+        assert_eq!(
+            rotator.current_at(start + Duration::from_secs(61)).account,
+            "b"
+        );
+        assert_eq!(
+            rotator.current_at(start + Duration::from_secs(62)).account,
+            "b"
+        );
+        assert_eq!(
+            rotator.current_at(start + Duration::from_secs(125)).account,
+            "c"
+        );
+    }
+
+    #[test]
+    fn test_single_account_never_rotates() {
+        let rotator = AccountRotator::single(accounts().remove(0));
+        for _ in 0..3 {
+            rotator.record_solution();
+        }
+        assert_eq!(rotator.current().account, "a");
+    }
+}
@@ -1,23 +1,55 @@
 //! Chainweb node communication protocol
 
-use crate::core::{ChainId, Target, Work};
+use crate::config::{MinerKeyset, MiningAccount, NodeLossPolicy, NodeUrl, SubmitEncoding};
+use crate::core::{ChainId, Target, Work, constants::WORK_SIZE};
 use crate::error::{Error, Result};
-use crate::protocol::http_pool::{get_insecure_client, get_mining_client};
-use crate::protocol::retry::retry_http;
+use crate::protocol::account_rotation::AccountRotator;
+use crate::protocol::circuit_breaker::{DEFAULT_FAILURE_THRESHOLD, DEFAULT_RESET_TIMEOUT};
+use crate::protocol::dedup::SubmissionDedup;
+use crate::protocol::failover::NodeSet;
+use crate::protocol::fetch_limit::WorkFetchLimiter;
+use crate::protocol::http_pool::{ClientType, global_http_pool};
+use crate::protocol::retry::{NoWorkBackoff, retry_http, should_retry};
+use crate::protocol::sse::{SseDecoder, UpdateEvent};
+use crate::utils::events::{MiningEvent, emit_event};
+use crate::utils::monitoring::{RejectReason, global_monitoring};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use bytes::Bytes;
-use eventsource_stream::Eventsource;
 use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{debug, error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Chainweb versions used in the mining API path of public and development networks
+pub const KNOWN_NETWORKS: &[&str] = &[
+    "mainnet01",
+    "testnet04",
+    "testnet05",
+    "development",
+    "recap-development",
+];
+
+/// First wait before asking for work again once no node is reachable
+const NODE_LOSS_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest default timeout of a work request; a slow node is better asked
+/// again than waited for
+pub const DEFAULT_WORK_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Chainweb client configuration
 #[derive(Debug, Clone)]
 pub struct ChainwebClientConfig {
     /// Node URL (e.g., "https://api.chainweb.com")
     pub node_url: String,
+    /// Fallback nodes in order of preference, used when the primary keeps failing.
+    /// Entries without an `http://`/`https://` scheme use the `use_tls` setting.
+    pub fallback_node_urls: Vec<String>,
     /// Chain ID to mine on
     pub chain_id: ChainId,
     /// Miner account name
@@ -26,18 +58,57 @@ pub struct ChainwebClientConfig {
     pub public_key: String,
     /// Request timeout
     pub timeout: Duration,
+    /// Timeout of work requests (the shorter of `timeout` and
+    /// [`DEFAULT_WORK_FETCH_TIMEOUT`] if not set)
+    pub work_fetch_timeout: Option<Duration>,
+    /// Timeout of solution submissions (`timeout` if not set)
+    pub submit_timeout: Option<Duration>,
     /// Whether to use TLS
     pub use_tls: bool,
     /// Allow insecure TLS connections (self-signed certificates)
     pub insecure: bool,
 }
 
+impl ChainwebClientConfig {
+    /// Timeout applied to each work request
+    pub fn work_fetch_timeout(&self) -> Duration {
+        self.work_fetch_timeout
+            .unwrap_or_else(|| self.timeout.min(DEFAULT_WORK_FETCH_TIMEOUT))
+    }
+
+    /// Timeout applied to each solution submission
+    pub fn submit_timeout(&self) -> Duration {
+        self.submit_timeout.unwrap_or(self.timeout)
+    }
+}
+
 /// Chainweb client for interacting with nodes
 #[derive(Clone)]
 pub struct ChainwebClient {
     config: ChainwebClientConfig,
-    client: Arc<Client>,
+    client_type: ClientType,
     node_version: Option<String>,
+    network: Option<String>,
+    submissions: Arc<SubmissionDedup>,
+    nodes: Arc<NodeSet>,
+    relays: Arc<Vec<String>>,
+    submit_encoding: SubmitEncoding,
+    binary_unsupported: Arc<AtomicBool>,
+    work_fetches: Arc<WorkFetchLimiter>,
+    accounts: Arc<AccountRotator>,
+    chain_accounts: Arc<BTreeMap<u16, MiningAccount>>,
+    keyset: Option<Arc<MinerKeyset>>,
+    target_override: Option<Target>,
+    node_loss: NodeLossPolicy,
+}
+
+/// How a solution handed to [`ChainwebClient::submit_solution`] was dealt with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+    /// A node accepted the solution
+    Accepted,
+    /// The solution was submitted before and was not sent again
+    Duplicate,
 }
 
 /// Work request payload
@@ -49,10 +120,13 @@ struct WorkRequest {
     public_keys: Vec<String>,
 }
 
+/// Chain graph as `(chain, adjacent chains)` pairs
+pub type ChainGraph = Vec<(u16, Vec<u16>)>;
+
 /// Node info response
 #[derive(Debug, Deserialize)]
 pub struct NodeInfo {
-    /// Node version string
+    /// Chainweb version of the network, e.g. `mainnet01`
     #[serde(rename = "nodeVersion")]
     pub node_version: String,
     /// Node API version string
@@ -64,59 +138,440 @@ pub struct NodeInfo {
     /// Total number of chains
     #[serde(rename = "nodeNumberOfChains", default)]
     pub node_number_of_chains: u16,
+    /// Genesis block height of each chain, as `(chain, height)`
+    #[serde(rename = "nodeGenesisHeights", default)]
+    pub node_genesis_heights: Vec<(String, u64)>,
+    /// Chain graphs over time, as `(height, [(chain, adjacent chains)])`
+    /// with each graph in effect from its block height on
+    #[serde(rename = "nodeGraphHistory", alias = "nodeHistoricalChains", default)]
+    pub node_graph_history: Vec<(u64, ChainGraph)>,
+    /// Encodings the node accepts for solved work (`json`, `binary`), if it
+    /// advertises them
+    #[serde(rename = "nodeSubmitEncodings", default)]
+    pub node_submit_encodings: Option<Vec<String>>,
+}
+
+impl NodeInfo {
+    /// Number of chains of the network, 0 if the node didn't say
+    pub fn number_of_chains(&self) -> u16 {
+        if self.node_number_of_chains > 0 {
+            self.node_number_of_chains
+        } else {
+            self.node_chains.len() as u16
+        }
+    }
+
+    /// Whether the node accepts solved work in `encoding`, `None` if it
+    /// doesn't advertise its submit encodings
+    pub fn accepts_submit_encoding(&self, encoding: SubmitEncoding) -> Option<bool> {
+        let encodings = self.node_submit_encodings.as_ref()?;
+        Some(
+            encodings
+                .iter()
+                .any(|e| e.parse::<SubmitEncoding>().ok() == Some(encoding)),
+        )
+    }
+
+    /// Check that `chain_id` exists on the node's network
+    pub fn validate_chain_id(&self, chain_id: ChainId) -> Result<()> {
+        self.check_chain("chain_id", chain_id.value())
+    }
+
+    /// Check that every chain with its own account exists on the node's network
+    pub fn validate_chain_accounts(&self, chains: impl IntoIterator<Item = u16>) -> Result<()> {
+        chains
+            .into_iter()
+            .try_for_each(|chain| self.check_chain("chain_accounts", chain))
+    }
+
+    /// Chains of the network without `excluded`, in order
+    ///
+    /// Every excluded chain must exist on the network. The result is empty if
+    /// the node didn't report its chains.
+    pub fn active_chains(&self, excluded: &[u16]) -> Result<Vec<ChainId>> {
+        for &chain in excluded {
+            self.check_chain("exclude_chains", chain)?;
+        }
+        let active: Vec<ChainId> = ChainId::all(self.number_of_chains())
+            .filter(|chain| !excluded.contains(&chain.value()))
+            .collect();
+        if active.is_empty() && self.number_of_chains() > 0 {
+            return Err(Error::config_invalid_value(
+                "exclude_chains",
+                format!("{:?}", excluded),
+                "at least one chain left to mine",
+            ));
+        }
+        Ok(active)
+    }
+
+    fn check_chain(&self, field: &str, chain: u16) -> Result<()> {
+        let chains = self.number_of_chains();
+        if chains > 0 && !ChainId::new(chain).is_valid(chains) {
+            return Err(Error::config_invalid_value(
+                field,
+                chain.to_string(),
+                format!(
+                    "chain between 0 and {} on {}",
+                    chains - 1,
+                    self.node_version
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Head of a chain in a cut
+#[derive(Debug, Clone, Deserialize)]
+pub struct CutHead {
+    /// Block height of the head
+    pub height: u64,
+    /// Block hash of the head, base64url encoded
+    pub hash: String,
+}
+
+/// Cut response: the latest block of every chain as seen by the node
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cut {
+    /// Head of each chain, keyed by chain id
+    pub hashes: HashMap<String, CutHead>,
+}
+
+impl Cut {
+    /// Head of `chain`, if the cut includes it
+    pub fn head(&self, chain: ChainId) -> Option<&CutHead> {
+        self.hashes.get(&chain.to_string())
+    }
+}
+
+/// Page of block headers, base64url encoded
+#[derive(Debug, Deserialize)]
+struct HeaderPage {
+    items: Vec<String>,
 }
 
 impl ChainwebClient {
     /// Create a new Chainweb client using the HTTP connection pool
     pub fn new(config: ChainwebClientConfig) -> Result<Self> {
-        // Use the appropriate client from the HTTP pool
-        let client = if config.insecure {
-            get_insecure_client()?
+        // Use the appropriate client from the HTTP pool, created up front
+        // so a client that can't be built fails here
+        let client_type = if config.insecure {
+            ClientType::Insecure
         } else {
-            get_mining_client()?
+            ClientType::Mining
         };
+        global_http_pool().get_client(client_type.clone())?;
 
         info!(
             "Created Chainweb client using HTTP connection pool (insecure: {})",
             config.insecure
         );
 
+        let nodes = Self::node_set(&config, DEFAULT_FAILURE_THRESHOLD, DEFAULT_RESET_TIMEOUT)?;
+        let accounts = AccountRotator::single(MiningAccount {
+            account: config.account.clone(),
+            public_key: config.public_key.clone(),
+        });
+
         Ok(Self {
             config,
-            client,
+            client_type,
             node_version: None,
+            network: None,
+            submissions: Arc::new(SubmissionDedup::default()),
+            nodes: Arc::new(nodes),
+            relays: Arc::new(Vec::new()),
+            submit_encoding: SubmitEncoding::default(),
+            binary_unsupported: Arc::new(AtomicBool::new(false)),
+            work_fetches: Arc::new(WorkFetchLimiter::default()),
+            accounts: Arc::new(accounts),
+            chain_accounts: Arc::new(BTreeMap::new()),
+            keyset: None,
+            target_override: None,
+            node_loss: NodeLossPolicy::default(),
         })
     }
 
+    fn node_set(
+        config: &ChainwebClientConfig,
+        failure_threshold: u32,
+        reset_timeout: Duration,
+    ) -> Result<NodeSet> {
+        let primary = NodeUrl::parse(&config.node_url)?;
+        let mut nodes = vec![(primary.authority_for(config.use_tls), config.use_tls)];
+        for fallback in &config.fallback_node_urls {
+            let url = NodeUrl::parse(fallback)?;
+            let use_tls = url.scheme_tls().unwrap_or(config.use_tls);
+            nodes.push((url.authority_for(use_tls), use_tls));
+        }
+        Ok(NodeSet::new(nodes, failure_threshold, reset_timeout))
+    }
+
+    /// Set how many consecutive failures trigger a failover and how long a
+    /// failing node is avoided before it is tried again
+    pub fn with_failover_policy(
+        mut self,
+        failure_threshold: u32,
+        reset_timeout: Duration,
+    ) -> Result<Self> {
+        self.nodes = Arc::new(Self::node_set(
+            &self.config,
+            failure_threshold,
+            reset_timeout,
+        )?);
+        Ok(self)
+    }
+
+    /// Also submit every solution to `relays`, concurrently with the active node
+    ///
+    /// Entries without an `http://`/`https://` scheme use the `use_tls` setting.
+    pub fn with_relay_nodes(mut self, relays: &[String]) -> Result<Self> {
+        let relays = relays
+            .iter()
+            .map(|relay| {
+                let url = NodeUrl::parse(relay)?;
+                let use_tls = url.scheme_tls().unwrap_or(self.config.use_tls);
+                let scheme = if use_tls { "https" } else { "http" };
+                Ok(format!("{}://{}", scheme, url.authority_for(use_tls)))
+            })
+            .collect::<Result<_>>()?;
+        self.relays = Arc::new(relays);
+        Ok(self)
+    }
+
+    /// Set the window in which a repeated submission of the same solved work is skipped
+    pub fn with_submission_dedup_window(mut self, window: Duration) -> Self {
+        self.submissions = Arc::new(SubmissionDedup::new(window));
+        self
+    }
+
+    /// Share a work fetch limiter, e.g. with the clients of other chains
+    pub fn with_work_fetch_limiter(mut self, limiter: Arc<WorkFetchLimiter>) -> Self {
+        self.work_fetches = limiter;
+        self
+    }
+
+    /// Request work for the accounts of `rotator` instead of the configured account
+    pub fn with_account_rotation(mut self, rotator: AccountRotator) -> Self {
+        self.accounts = Arc::new(rotator);
+        self
+    }
+
+    /// Request work of the chains in `chain_accounts` for their account
+    /// instead of the configured (or rotating) account
+    pub fn with_chain_accounts(mut self, chain_accounts: BTreeMap<u16, MiningAccount>) -> Self {
+        self.chain_accounts = Arc::new(chain_accounts);
+        self
+    }
+
+    /// Request work for `keyset` instead of the single public key keyset
+    /// of the account
+    pub fn with_miner_keyset(mut self, keyset: MinerKeyset) -> Self {
+        self.keyset = Some(Arc::new(keyset));
+        self
+    }
+
+    /// Hand out work with `target` instead of the node's target
+    ///
+    /// Solutions found against a target easier than the node's aren't valid
+    /// blocks, so this is only meant for testing without submitting them.
+    pub fn with_target_override(mut self, target: Target) -> Self {
+        self.target_override = Some(target);
+        self
+    }
+
+    /// Set what [`get_work_with_backoff`](Self::get_work_with_backoff) does
+    /// once the circuit of every node is open
+    pub fn with_node_loss_policy(mut self, policy: NodeLossPolicy) -> Self {
+        self.node_loss = policy;
+        self
+    }
+
+    /// Set the encoding used for solution submissions
+    ///
+    /// Binary submissions fall back to JSON if the node info doesn't list
+    /// binary among the node's submit encodings, or once the node answers
+    /// with `415 Unsupported Media Type`.
+    pub fn with_submit_encoding(mut self, encoding: SubmitEncoding) -> Self {
+        self.submit_encoding = encoding;
+        self
+    }
+
+    /// Encoding the next submission will use
+    pub fn submit_encoding(&self) -> SubmitEncoding {
+        match self.submit_encoding {
+            SubmitEncoding::Binary if !self.binary_unsupported.load(Ordering::Relaxed) => {
+                SubmitEncoding::Binary
+            }
+            _ => SubmitEncoding::Json,
+        }
+    }
+
+    /// Force the chainweb version used in mining URLs instead of the one
+    /// reported by the node
+    ///
+    /// Unknown versions are accepted (e.g. custom devnets) but logged.
+    pub fn with_network(mut self, network: impl Into<String>) -> Self {
+        let network = network.into();
+        if !KNOWN_NETWORKS.contains(&network.as_str()) {
+            warn!(
+                "Unknown chainweb network '{}' (known: {})",
+                network,
+                KNOWN_NETWORKS.join(", ")
+            );
+        }
+        self.network = Some(network);
+        self
+    }
+
+    /// Set the chain work is mined on, e.g. once the active chains are known
+    pub fn set_chain_id(&mut self, chain_id: ChainId) {
+        self.config.chain_id = chain_id;
+    }
+
+    /// Chain work is mined on
+    pub fn chain_id(&self) -> ChainId {
+        self.config.chain_id
+    }
+
     /// Set the node version (should be called after get_node_info)
     pub fn set_node_version(&mut self, version: String) {
         self.node_version = Some(version);
     }
 
-    /// Get the node version, defaulting to "mainnet01" if not set
+    /// Get the version used in mining URLs: the network override, then the
+    /// node version, defaulting to "mainnet01" if neither is set
     fn node_version(&self) -> &str {
-        self.node_version.as_deref().unwrap_or("mainnet01")
+        self.network
+            .as_deref()
+            .or(self.node_version.as_deref())
+            .unwrap_or("mainnet01")
+    }
+
+    /// URL of a mining API endpoint (`work`, `solved`, `updates`) on a node
+    fn mining_url(&self, base_url: &str, endpoint: &str) -> String {
+        format!(
+            "{}/chainweb/0.0/{}/mining/{}",
+            base_url,
+            self.node_version(),
+            endpoint
+        )
+    }
+
+    /// URL of a chainweb API endpoint of this network on a node
+    fn api_url(&self, base_url: &str, endpoint: &str) -> String {
+        format!(
+            "{}/chainweb/0.0/{}/{}",
+            base_url,
+            self.node_version(),
+            endpoint
+        )
     }
 
-    /// Get the base URL for the node
+    /// Get the base URL for the active node
+    #[cfg(test)]
     fn base_url(&self) -> String {
-        let scheme = if self.config.use_tls { "https" } else { "http" };
-        format!("{}://{}", scheme, self.config.node_url)
+        self.nodes.active_node().base_url()
+    }
+
+    /// Address (`host[:port]`) of the node requests currently go to
+    pub fn active_node(&self) -> String {
+        self.nodes.active_node().authority().to_string()
+    }
+
+    /// Number of configured nodes, including fallbacks
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the client should give up: the node loss policy is exit and
+    /// the circuit of every node is open
+    pub fn node_lost(&self) -> bool {
+        self.node_loss == NodeLossPolicy::Exit && self.nodes.all_failing()
+    }
+
+    /// Run a single request attempt against the selected node, feeding the
+    /// outcome into that node's circuit breaker
+    async fn on_active_node<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let index = self.nodes.select();
+        let result = operation(self.nodes.node(index).base_url()).await;
+        match &result {
+            Ok(_) => self.nodes.record_success(index),
+            Err(e) if should_retry(e) => self.nodes.record_failure(index),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Measure the round-trip latency to the active node
+    pub async fn ping(&self) -> Result<Duration> {
+        self.ping_url(&self.nodes.active_node().base_url()).await
+    }
+
+    /// Measure the round-trip latency to every configured node, in order of
+    /// preference
+    pub async fn ping_all(&self) -> Vec<(String, Result<Duration>)> {
+        let mut results = Vec::with_capacity(self.nodes.len());
+        for index in 0..self.nodes.len() {
+            let node = self.nodes.node(index);
+            let latency = self.ping_url(&node.base_url()).await;
+            results.push((node.authority().to_string(), latency));
+        }
+        results
+    }
+
+    /// HTTP client from the pool, fetched per request so a client rebuilt
+    /// after a DNS change is picked up
+    fn http(&self) -> Result<Arc<Client>> {
+        global_http_pool().get_client(self.client_type.clone())
+    }
+
+    /// Time a single `/info` request, without retries
+    async fn ping_url(&self, base_url: &str) -> Result<Duration> {
+        let url = format!("{}/info", base_url);
+        let started = Instant::now();
+
+        let response = self
+            .http()?
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| Error::network_connection_failed(&url, Box::new(e)))?;
+        if !response.status().is_success() {
+            return Err(Error::network_http_error(
+                &url,
+                response.status().as_u16(),
+                format!("Ping failed: {}", response.status()),
+            ));
+        }
+
+        let latency = started.elapsed();
+        global_monitoring().record_response_time(latency.as_secs_f64() * 1000.0);
+        debug!("Ping {}: {:?}", url, latency);
+        Ok(latency)
     }
 
     /// Get node information with retry logic
     pub async fn get_node_info(&self) -> Result<NodeInfo> {
-        retry_http(|| self.get_node_info_once()).await
+        retry_http(|| {
+            self.on_active_node(|base_url| async move { self.get_node_info_once(&base_url).await })
+        })
+        .await
     }
 
     /// Get node information (single attempt)
-    async fn get_node_info_once(&self) -> Result<NodeInfo> {
-        let url = format!("{}/info", self.base_url());
+    async fn get_node_info_once(&self, base_url: &str) -> Result<NodeInfo> {
+        let url = format!("{}/info", base_url);
 
         debug!("Getting node info from: {}", url);
 
         let response = self
-            .client
+            .http()?
             .get(&url)
             .send()
             .await
@@ -126,74 +581,273 @@ impl ChainwebClient {
             return Err(Error::network_http_error(
                 &url,
                 response.status().as_u16(),
-                format!("Node info request failed: {}", response.status())
+                format!("Node info request failed: {}", response.status()),
             ));
         }
 
-        let info = response
-            .json::<NodeInfo>()
-            .await
-            .map_err(|e| Error::protocol_invalid_format(format!("Failed to parse node info JSON: {}", e)))?;
+        let info = response.json::<NodeInfo>().await.map_err(|e| {
+            Error::protocol_invalid_format(format!("Failed to parse node info JSON: {}", e))
+        })?;
 
         info!("Connected to Chainweb node version: {}", info.node_version);
 
+        if self.submit_encoding == SubmitEncoding::Binary
+            && info.accepts_submit_encoding(SubmitEncoding::Binary) == Some(false)
+            && !self.binary_unsupported.swap(true, Ordering::Relaxed)
+        {
+            info!("Node does not accept binary submissions, submitting solutions as JSON");
+        }
+
         Ok(info)
     }
 
+    /// Get the node's current cut with retry logic
+    pub async fn get_cut(&self) -> Result<Cut> {
+        retry_http(|| {
+            self.on_active_node(|base_url| async move { self.get_cut_once(&base_url).await })
+        })
+        .await
+    }
+
+    /// Get the node's current cut (single attempt)
+    async fn get_cut_once(&self, base_url: &str) -> Result<Cut> {
+        let url = self.api_url(base_url, "cut");
+
+        let response = self
+            .http()?
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| Error::network_connection_failed(&url, Box::new(e)))?;
+        if !response.status().is_success() {
+            return Err(Error::network_http_error(
+                &url,
+                response.status().as_u16(),
+                format!("Cut request failed: {}", response.status()),
+            ));
+        }
+
+        response
+            .json::<Cut>()
+            .await
+            .map_err(|e| Error::protocol_invalid_format(format!("Failed to parse cut JSON: {}", e)))
+    }
+
+    /// Work header of the block at `height` on the branch of `chain` ending
+    /// in the block `upper`, `None` if that branch has no block at the height
+    pub async fn branch_header_at(
+        &self,
+        chain: ChainId,
+        upper: &str,
+        height: u64,
+    ) -> Result<Option<Work>> {
+        retry_http(|| {
+            self.on_active_node(|base_url| async move {
+                self.branch_header_at_once(&base_url, chain, upper, height)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Work header of the block at `height` on a branch (single attempt)
+    async fn branch_header_at_once(
+        &self,
+        base_url: &str,
+        chain: ChainId,
+        upper: &str,
+        height: u64,
+    ) -> Result<Option<Work>> {
+        let url = self.api_url(base_url, &format!("chain/{}/header/branch", chain));
+
+        let response = self
+            .http()?
+            .post(&url)
+            .query(&[("minheight", height), ("maxheight", height)])
+            .json(&serde_json::json!({ "lower": [], "upper": [upper] }))
+            .send()
+            .await
+            .map_err(|e| Error::network_connection_failed(&url, Box::new(e)))?;
+        if !response.status().is_success() {
+            return Err(Error::network_http_error(
+                &url,
+                response.status().as_u16(),
+                format!("Header branch request failed: {}", response.status()),
+            ));
+        }
+
+        let page = response.json::<HeaderPage>().await.map_err(|e| {
+            Error::protocol_invalid_format(format!("Failed to parse header page JSON: {}", e))
+        })?;
+        let Some(item) = page.items.first() else {
+            return Ok(None);
+        };
+
+        // Full headers end in the block hash, which isn't part of the work
+        let header = URL_SAFE_NO_PAD.decode(item).map_err(|e| {
+            Error::protocol_invalid_format(format!("Invalid base64 block header: {}", e))
+        })?;
+        if header.len() < WORK_SIZE {
+            return Err(Error::validation_invalid_work_header(
+                WORK_SIZE,
+                header.len(),
+            ));
+        }
+        Work::from_slice(&header[..WORK_SIZE]).map(Some)
+    }
+
     /// Get work from the node with retry logic
+    ///
+    /// Fetches go through the work fetch limiter, so a burst of calls
+    /// doesn't turn into a burst of requests.
     pub async fn get_work(&self) -> Result<(Work, Target)> {
-        retry_http(|| self.get_work_once()).await
+        self.work_fetches
+            .fetch(self.config.chain_id, || {
+                retry_http(|| {
+                    self.on_active_node(
+                        |base_url| async move { self.get_work_once(&base_url).await },
+                    )
+                })
+            })
+            .await
     }
 
-    /// Get work from the node (single attempt)
-    async fn get_work_once(&self) -> Result<(Work, Target)> {
-        let url = format!(
-            "{}/chainweb/0.0/{}/mining/work",
-            self.base_url(),
-            self.node_version()
-        );
+    /// Get work, waiting with `backoff` for as long as the node has none
+    ///
+    /// A wait is cut short as soon as `updates` yields, since a work update
+    /// means new work is likely available. Once the circuit of every node is
+    /// open, the node loss policy decides between giving up with a
+    /// [`is_node_lost`](Error::is_node_lost) error and retrying with a capped
+    /// backoff until a node answers again.
+    pub async fn get_work_with_backoff<S>(
+        &self,
+        backoff: &mut NoWorkBackoff,
+        updates: &mut S,
+    ) -> Result<(Work, Target)>
+    where
+        S: futures::Stream + Unpin,
+    {
+        let mut node_loss_delay = NODE_LOSS_BASE_DELAY;
+        loop {
+            match self.get_work().await {
+                Ok(work) => {
+                    if backoff.waits() > 0 {
+                        info!("Work is available again");
+                    }
+                    if node_loss_delay > NODE_LOSS_BASE_DELAY {
+                        info!("Node {} is reachable again", self.active_node());
+                    }
+                    backoff.reset();
+                    return Ok(work);
+                }
+                Err(e) if e.is_no_work() => {
+                    let delay = backoff.next_delay();
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        Some(_) = updates.next() => {
+                            debug!("Work update received, asking for work again");
+                        }
+                    }
+                }
+                Err(e) if self.node_lost() => {
+                    return Err(Error::network_node_lost(self.node_count(), e));
+                }
+                Err(e) if self.nodes.all_failing() => {
+                    let delay = node_loss_delay;
+                    if delay == NODE_LOSS_BASE_DELAY {
+                        warn!(
+                            "No configured node is reachable ({}), retrying until one answers",
+                            e
+                        );
+                    } else {
+                        debug!("Still no node reachable, retrying in {:?}", delay);
+                    }
+                    node_loss_delay = std::cmp::min(delay * 2, DEFAULT_RESET_TIMEOUT);
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 
-        let request = WorkRequest {
-            account: self.config.account.clone(),
-            predicate: "keys-all".to_string(),
-            public_keys: vec![self.config.public_key.clone()],
+    /// Payload of a work request for the account of the chain, or else the
+    /// current account
+    fn work_request(&self) -> WorkRequest {
+        let account = match self.chain_accounts.get(&self.config.chain_id.value()) {
+            Some(account) => account.clone(),
+            None => self.accounts.current(),
         };
+        match &self.keyset {
+            Some(keyset) => WorkRequest {
+                account: account.account,
+                predicate: keyset.pred.clone(),
+                public_keys: keyset.keys.clone(),
+            },
+            None => WorkRequest {
+                account: account.account,
+                predicate: "keys-all".to_string(),
+                public_keys: vec![account.public_key],
+            },
+        }
+    }
+
+    /// Get work from the node (single attempt)
+    async fn get_work_once(&self, base_url: &str) -> Result<(Work, Target)> {
+        let url = self.mining_url(base_url, "work");
+
+        let request = self.work_request();
 
         debug!("Requesting work from: {}", url);
 
         let response = self
-            .client
+            .http()?
             .get(&url)
+            .timeout(self.config.work_fetch_timeout())
             .json(&request)
             .send()
             .await
             .map_err(|e| Error::network_connection_failed(&url, Box::new(e)))?;
 
-        if !response.status().is_success() {
-            let status = response.status();
+        // A node that is still syncing has nothing to hand out yet
+        let status = response.status();
+        if status == reqwest::StatusCode::NO_CONTENT {
+            return Err(Error::protocol_no_work(&url));
+        }
+
+        if !status.is_success() {
             if status == 404 {
-                return Err(Error::protocol_endpoint_unavailable(
-                    format!("{}/mining/work", self.base_url())
-                ));
+                return Err(Error::protocol_endpoint_unavailable(format!(
+                    "{}/mining/work",
+                    base_url
+                )));
+            }
+            let body = response.text().await.unwrap_or_default();
+            if status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+                && body.to_lowercase().contains("no work")
+            {
+                return Err(Error::protocol_no_work(&url));
             }
             return Err(Error::network_http_error(
                 &url,
                 status.as_u16(),
-                format!("Work request failed: {}", status)
+                format!("Work request failed: {}", status),
             ));
         }
 
         // The response is raw binary data: 4 bytes ChainId + 32 bytes Target + 286 bytes Work
-        let response_bytes = response
-            .bytes()
-            .await
-            .map_err(|e| Error::protocol_invalid_format(format!("Failed to read work response: {}", e)))?;
+        let response_bytes = response.bytes().await.map_err(|e| {
+            Error::protocol_invalid_format(format!("Failed to read work response: {}", e))
+        })?;
+
+        if response_bytes.is_empty() {
+            return Err(Error::protocol_no_work(&url));
+        }
 
         if response_bytes.len() != 322 {
             return Err(Error::validation_size_error(
                 "work_response",
                 322,
-                response_bytes.len()
+                response_bytes.len(),
             ));
         }
 
@@ -206,12 +860,18 @@ impl ChainwebClient {
         if chain_id != self.config.chain_id.value() as u32 {
             return Err(Error::protocol_invalid_chain_id(
                 self.config.chain_id.value() as u32,
-                chain_id
+                chain_id,
             ));
         }
 
         // Next 32 bytes: Target (little-endian, 256-bit)
         let target = Target::from_bytes_le(&response_bytes[4..36])?;
+        target.validate()?;
+        global_monitoring().record_chain_target(chain_id as u16, &target);
+        emit_event(MiningEvent::WorkReceived {
+            chain_id: chain_id as u16,
+            target: target.to_hex(),
+        });
 
         // Remaining 286 bytes: Work header
         let work = Work::from_slice(&response_bytes[36..])?;
@@ -223,41 +883,100 @@ impl ChainwebClient {
             work.nonce()
         );
 
+        if let Some(test_target) = self.target_override {
+            debug!(
+                "Replacing target {} with test target {}",
+                target, test_target
+            );
+            return Ok((work, test_target));
+        }
         Ok((work, target))
     }
 
     /// Submit a solution to the node with retry logic
-    pub async fn submit_solution(&self, work: &Work) -> Result<()> {
-        retry_http(|| self.submit_solution_once(work)).await
+    ///
+    /// Solutions already submitted within the dedup window (keyed by block
+    /// hash) are skipped, so coinciding solutions from different sources are
+    /// only sent once. With relay nodes the solution is submitted to each
+    /// of them at the same time, once, and counts as accepted if any node
+    /// accepts it.
+    pub async fn submit_solution(&self, work: &Work) -> Result<Submission> {
+        let hash = work.hash();
+        if !self.submissions.check_and_record(hash) {
+            warn!(
+                "Skipping duplicate submission of solved work {}",
+                hex::encode(hash)
+            );
+            global_monitoring().record_rejection(RejectReason::Duplicate);
+            return Ok(Submission::Duplicate);
+        }
+
+        let started = Instant::now();
+        let primary = retry_http(|| {
+            self.on_active_node(|base_url| async move {
+                self.submit_solution_once(&base_url, work).await
+            })
+        });
+        let result = if self.relays.is_empty() {
+            primary.await
+        } else {
+            let relays =
+                futures::future::join_all(self.relays.iter().map(|relay| async move {
+                    (relay, self.submit_solution_once(relay, work).await)
+                }));
+            let (primary, relays) = tokio::join!(primary, relays);
+
+            match &primary {
+                Ok(()) => info!("Solution accepted by {}", self.active_node()),
+                Err(e) => warn!("Solution not accepted by {}: {}", self.active_node(), e),
+            }
+            let mut accepted = primary.is_ok();
+            for (relay, outcome) in &relays {
+                match outcome {
+                    Ok(()) => info!("Solution accepted by relay {}", relay),
+                    Err(e) => warn!("Solution not accepted by relay {}: {}", relay, e),
+                }
+                accepted |= outcome.is_ok();
+            }
+            if accepted { Ok(()) } else { primary }
+        };
+        global_monitoring().record_submit_latency(started.elapsed().as_secs_f64() * 1000.0);
+        match &result {
+            Ok(()) => self.accounts.record_solution(),
+            Err(_) => self.submissions.forget(&hash),
+        }
+        result.map(|()| Submission::Accepted)
     }
 
     /// Submit a solution to the node (single attempt)
-    async fn submit_solution_once(&self, work: &Work) -> Result<()> {
-        let url = format!(
-            "{}/chainweb/0.0/{}/mining/solved",
-            self.base_url(),
-            self.node_version()
-        );
+    async fn submit_solution_once(&self, base_url: &str, work: &Work) -> Result<()> {
+        let url = self.mining_url(base_url, "solved");
 
         debug!("Submitting solution to: {}", url);
 
-        // Submit raw work bytes directly using Bytes to avoid allocation
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/octet-stream")
-            .body(Bytes::copy_from_slice(work.as_bytes()))
-            .send()
-            .await
-            .map_err(|e| Error::network_connection_failed(&url, Box::new(e)))?;
+        let mut encoding = self.submit_encoding();
+        let mut response = self.send_solution(&url, work, encoding).await?;
+
+        if encoding == SubmitEncoding::Binary
+            && response.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE
+        {
+            warn!("Node does not accept binary submissions, falling back to JSON");
+            self.binary_unsupported.store(true, Ordering::Relaxed);
+            encoding = SubmitEncoding::Json;
+            response = self.send_solution(&url, work, encoding).await?;
+        }
+        debug!("Submitted solution using {:?} encoding", encoding);
 
         if !response.status().is_success() {
             let status = response.status();
+            if status.is_client_error() {
+                global_monitoring().record_rejection(RejectReason::NodeInvalid);
+            }
             let body = response.text().await.unwrap_or_default();
             return Err(Error::network_http_error(
                 &url,
                 status.as_u16(),
-                format!("Solution submission failed: {} - {}", status, body)
+                format!("Solution submission failed: {} - {}", status, body),
             ));
         }
 
@@ -266,13 +985,79 @@ impl ChainwebClient {
         Ok(())
     }
 
-    /// Subscribe to work updates via Server-Sent Events
-    pub async fn subscribe_updates(&self) -> Result<impl futures::Stream<Item = Result<()>>> {
-        let url = format!(
-            "{}/chainweb/0.0/{}/mining/updates",
-            self.base_url(),
-            self.node_version()
-        );
+    /// Send solved work in the given encoding
+    async fn send_solution(
+        &self,
+        url: &str,
+        work: &Work,
+        encoding: SubmitEncoding,
+    ) -> Result<reqwest::Response> {
+        let request = match encoding {
+            // Submit raw work bytes directly using Bytes to avoid allocation
+            SubmitEncoding::Binary => self
+                .http()?
+                .post(url)
+                .header("Content-Type", "application/octet-stream")
+                .body(Bytes::copy_from_slice(work.as_bytes())),
+            SubmitEncoding::Json => self
+                .http()?
+                .post(url)
+                .json(&URL_SAFE_NO_PAD.encode(work.as_bytes())),
+        };
+
+        request
+            .timeout(self.config.submit_timeout())
+            .send()
+            .await
+            .map_err(|e| Error::network_connection_failed(url, Box::new(e)))
+    }
+
+    /// Subscribe to work updates via Server-Sent Events on the active node
+    pub async fn subscribe_updates(
+        &self,
+    ) -> Result<impl futures::Stream<Item = Result<UpdateEvent>>> {
+        self.on_active_node(|base_url| async move { self.subscribe_updates_once(&base_url).await })
+            .await
+    }
+
+    /// Poll for new work every `interval`, yielding whenever the node hands
+    /// out work on a different parent or payload than the last seen work,
+    /// starting from `current`
+    ///
+    /// This is the fallback when the update stream can't be re-established.
+    /// Failed polls are logged and retried at the next interval.
+    pub fn poll_updates(
+        self: Arc<Self>,
+        interval: Duration,
+        current: &Work,
+    ) -> impl futures::Stream<Item = Result<UpdateEvent>> + use<> {
+        let ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        futures::stream::unfold(
+            (self, ticker, current.clone()),
+            |(client, mut ticker, mut last)| async move {
+                loop {
+                    ticker.tick().await;
+                    match client.get_work().await {
+                        Ok((work, _)) if last.changed_fields(&work).header => {
+                            debug!("Polling found new work");
+                            last = work;
+                            return Some((Ok(UpdateEvent::NewWork), (client, ticker, last)));
+                        }
+                        Ok(_) => {}
+                        Err(e) if e.is_no_work() => {}
+                        Err(e) => warn!("Polling for new work failed: {}", e),
+                    }
+                }
+            },
+        )
+    }
+
+    /// Subscribe to work updates on the given node (single attempt)
+    async fn subscribe_updates_once(
+        &self,
+        base_url: &str,
+    ) -> Result<impl futures::Stream<Item = Result<UpdateEvent>> + use<>> {
+        let url = self.mining_url(base_url, "updates");
 
         debug!("Subscribing to updates at: {}", url);
 
@@ -281,7 +1066,7 @@ impl ChainwebClient {
         let body = Bytes::copy_from_slice(&chain_id_bytes);
 
         let response = self
-            .client
+            .http()?
             .get(&url)
             .header("Content-Type", "application/octet-stream")
             .body(body)
@@ -292,48 +1077,66 @@ impl ChainwebClient {
         if !response.status().is_success() {
             let status = response.status();
             if status == 404 {
-                return Err(Error::protocol_endpoint_unavailable(
-                    format!("{}/mining/updates", self.base_url())
-                ));
+                return Err(Error::protocol_endpoint_unavailable(format!(
+                    "{}/mining/updates",
+                    base_url
+                )));
             }
             return Err(Error::network_http_error(
                 &url,
                 status.as_u16(),
-                format!("Subscribe request failed: {}", status)
+                format!("Subscribe request failed: {}", status),
             ));
         }
 
-        let stream = response
-            .bytes_stream()
-            .eventsource()
-            .map(|result| match result {
-                Ok(event) => {
-                    debug!("Received update event: {:?}", event);
-                    Ok(())
-                }
+        // Malformed events are skipped; only connection errors end up as
+        // stream errors and make the caller reconnect
+        let mut decoder = SseDecoder::new();
+        let stream = response.bytes_stream().flat_map(move |chunk| {
+            let items: Vec<Result<UpdateEvent>> = match chunk {
+                Ok(bytes) => decoder
+                    .push(&bytes)
+                    .into_iter()
+                    .filter_map(|event| match event {
+                        Ok(event) => {
+                            debug!("Received update event: {:?}", event);
+                            Some(Ok(UpdateEvent::from(&event)))
+                        }
+                        Err(reason) => {
+                            warn!("Skipping malformed update event: {}", reason);
+                            None
+                        }
+                    })
+                    .collect(),
                 Err(e) => {
                     error!("SSE error: {}", e);
-                    Err(Error::protocol_invalid_format(format!("SSE stream error: {}", e)))
+                    vec![Err(Error::network_connection_failed(&url, Box::new(e)))]
                 }
-            });
+            };
+            futures::stream::iter(items)
+        });
 
         Ok(stream)
     }
-
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::AccountRotation;
+    use crate::utils::monitoring::MonitoringSystem;
 
     #[test]
     fn test_chainweb_client_creation() {
         let config = ChainwebClientConfig {
             node_url: "api.chainweb.com".to_string(),
+            fallback_node_urls: Vec::new(),
             chain_id: ChainId::new(0),
             account: "miner".to_string(),
             public_key: "abc123".to_string(),
             timeout: Duration::from_secs(30),
+            work_fetch_timeout: None,
+            submit_timeout: None,
             use_tls: true,
             insecure: false,
         };
@@ -342,14 +1145,70 @@ mod tests {
         assert_eq!(client.base_url(), "https://api.chainweb.com");
     }
 
+    #[test]
+    fn test_node_url_port_is_the_port_connected_to() {
+        for input in [
+            "http://node",
+            "https://node",
+            "node",
+            "node:1848",
+            "http://node:8080",
+        ] {
+            let url = NodeUrl::parse(input).unwrap();
+            for explicit_tls in [None, Some(false), Some(true)] {
+                let use_tls = url.use_tls(explicit_tls);
+                let client = ChainwebClient::new(ChainwebClientConfig {
+                    node_url: url.authority(),
+                    fallback_node_urls: vec![input.to_string()],
+                    chain_id: ChainId::new(0),
+                    account: "miner".to_string(),
+                    public_key: "abc123".to_string(),
+                    timeout: Duration::from_secs(30),
+                    work_fetch_timeout: None,
+                    submit_timeout: None,
+                    use_tls,
+                    insecure: false,
+                })
+                .unwrap();
+
+                let port = |index| {
+                    let base_url = client.nodes.node(index).base_url();
+                    reqwest::Url::parse(&base_url)
+                        .unwrap()
+                        .port_or_known_default()
+                };
+                assert_eq!(
+                    port(0),
+                    Some(url.port_for(use_tls)),
+                    "{} {:?}",
+                    input,
+                    explicit_tls
+                );
+                // A fallback keeps the TLS setting of its scheme
+                let fallback_tls = url.scheme_tls().unwrap_or(use_tls);
+                assert_eq!(
+                    port(1),
+                    Some(url.port_for(fallback_tls)),
+                    "{} {:?}",
+                    input,
+                    explicit_tls
+                );
+            }
+            assert_eq!(url.port(), url.port_for(url.use_tls(None)));
+        }
+    }
+
     #[test]
     fn test_base_url() {
         let config = ChainwebClientConfig {
             node_url: "localhost:1848".to_string(),
+            fallback_node_urls: Vec::new(),
             chain_id: ChainId::new(0),
             account: "test".to_string(),
             public_key: "test".to_string(),
             timeout: Duration::from_secs(30),
+            work_fetch_timeout: None,
+            submit_timeout: None,
             use_tls: false,
             insecure: false,
         };
@@ -386,16 +1245,120 @@ mod tests {
         assert_eq!(info.node_api_version, "0.0");
         assert_eq!(info.node_chains.len(), 10);
         assert_eq!(info.node_number_of_chains, 10);
+        assert!(info.node_graph_history.is_empty());
+    }
+
+    #[test]
+    fn test_node_info_chain_graph() {
+        let json = r#"{
+            "nodeVersion": "testnet04",
+            "nodeApiVersion": "0.0",
+            "nodePackageVersion": "2.19",
+            "nodeChains": ["0", "1", "2"],
+            "nodeNumberOfChains": 3,
+            "nodeGenesisHeights": [["0", 0], ["1", 0], ["2", 332604]],
+            "nodeHistoricalChains": [
+                [0, [[0, [1]], [1, [0]]]],
+                [332604, [[0, [1, 2]], [1, [0, 2]], [2, [0, 1]]]]
+            ]
+        }"#;
+
+        let info: NodeInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.number_of_chains(), 3);
+        assert_eq!(info.node_genesis_heights[2], ("2".to_string(), 332604));
+        assert_eq!(info.node_graph_history.len(), 2);
+        let (height, graph) = &info.node_graph_history[1];
+        assert_eq!(*height, 332604);
+        assert_eq!(graph[2], (2, vec![0, 1]));
+
+        assert!(info.validate_chain_id(ChainId::new(2)).is_ok());
+        let err = info.validate_chain_id(ChainId::new(3)).unwrap_err();
+        assert!(err.to_string().contains("between 0 and 2 on testnet04"));
+    }
+
+    fn node_info_with_chains(chains: u16) -> NodeInfo {
+        serde_json::from_value(serde_json::json!({
+            "nodeVersion": "testnet",
+            "nodeApiVersion": "0.0",
+            "nodeNumberOfChains": chains,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_chain_ids_of_10_chain_network() {
+        let info = node_info_with_chains(10);
+        assert!(info.validate_chain_id(ChainId::new(9)).is_ok());
+        let err = info.validate_chain_id(ChainId::new(10)).unwrap_err();
+        assert!(err.to_string().contains("between 0 and 9 on testnet"));
+        assert!(info.validate_chain_id(ChainId::new(19)).is_err());
+        assert!(info.active_chains(&[12]).is_err());
+        assert_eq!(
+            info.active_chains(&[]).unwrap(),
+            ChainId::all(10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_chain_ids_of_20_chain_network() {
+        let info = node_info_with_chains(20);
+        assert!(info.validate_chain_id(ChainId::new(10)).is_ok());
+        assert!(info.validate_chain_id(ChainId::new(19)).is_ok());
+        let err = info.validate_chain_id(ChainId::new(20)).unwrap_err();
+        assert!(err.to_string().contains("between 0 and 19 on testnet"));
+        assert_eq!(info.active_chains(&[12]).unwrap().len(), 19);
+    }
+
+    #[tokio::test]
+    async fn test_excluded_chains_are_not_mined() {
+        let info: NodeInfo = serde_json::from_str(
+            r#"{"nodeVersion": "mainnet01", "nodeApiVersion": "0.0", "nodeNumberOfChains": 20}"#,
+        )
+        .unwrap();
+        let active = info.active_chains(&[0, 3, 7, 12]).unwrap();
+        assert_eq!(active.len(), 16);
+        assert!(
+            active
+                .iter()
+                .all(|chain| ![0, 3, 7, 12].contains(&chain.value()))
+        );
+        assert!(info.active_chains(&[20]).is_err());
+        assert!(info.active_chains(&(0..20).collect::<Vec<_>>()).is_err());
+
+        // With chain 0 excluded the client mines chain 1 and refuses work for chain 0
+        let mut server = mockito::Server::new_async().await;
+        let work_path = "/chainweb/0.0/mainnet01/mining/work";
+        let mut client = local_client(&server);
+        client.set_chain_id(active[0]);
+        assert_eq!(client.chain_id(), ChainId::new(1));
+
+        let excluded = server
+            .mock("GET", work_path)
+            .with_body(work_response(0))
+            .create_async()
+            .await;
+        assert!(client.get_work().await.is_err());
+        excluded.remove_async().await;
+
+        server
+            .mock("GET", work_path)
+            .with_body(work_response(1))
+            .create_async()
+            .await;
+        assert!(client.get_work().await.is_ok());
     }
 
     #[test]
     fn test_node_version_handling() {
         let config = ChainwebClientConfig {
             node_url: "api.chainweb.com".to_string(),
+            fallback_node_urls: Vec::new(),
             chain_id: ChainId::new(0),
             account: "miner".to_string(),
             public_key: "abc123".to_string(),
             timeout: Duration::from_secs(30),
+            work_fetch_timeout: None,
+            submit_timeout: None,
             use_tls: true,
             insecure: false,
         };
@@ -409,4 +1372,781 @@ mod tests {
         client.set_node_version("testnet04".to_string());
         assert_eq!(client.node_version(), "testnet04");
     }
+
+    #[test]
+    fn test_work_url_uses_network_override() {
+        let config = ChainwebClientConfig {
+            node_url: "api.chainweb.com".to_string(),
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(30),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: true,
+            insecure: false,
+        };
+
+        // Detected version
+        let mut client = ChainwebClient::new(config).unwrap();
+        client.set_node_version("testnet04".to_string());
+        assert_eq!(
+            client.mining_url(&client.base_url(), "work"),
+            "https://api.chainweb.com/chainweb/0.0/testnet04/mining/work"
+        );
+
+        // Override wins over the detected version, unknown values are allowed
+        let client = client.with_network("my-devnet");
+        assert_eq!(
+            client.mining_url(&client.base_url(), "work"),
+            "https://api.chainweb.com/chainweb/0.0/my-devnet/mining/work"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_submission_is_sent_once() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chainweb/0.0/mainnet01/mining/solved")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ChainwebClientConfig {
+            node_url: server.host_with_port(),
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(5),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: false,
+            insecure: false,
+        };
+        let client = ChainwebClient::new(config).unwrap();
+
+        let work = Work::from_bytes([0x42; 286]);
+        let monitor = MonitoringSystem::new();
+        for expected in [Submission::Accepted, Submission::Duplicate] {
+            let outcome = client.submit_solution(&work).await;
+            monitor.record_submission(&outcome);
+            assert_eq!(outcome.unwrap(), expected);
+        }
+
+        mock.assert_async().await;
+        // Only the submission that was sent counts as an accepted block
+        assert_eq!(monitor.efficiency_report().blocks_accepted, 1);
+        assert_eq!(monitor.efficiency_report().blocks_rejected, 0);
+    }
+
+    fn work_response(chain_id: u32) -> Vec<u8> {
+        let mut body = chain_id.to_le_bytes().to_vec();
+        body.extend_from_slice(&[0xFF; 32]);
+        body.extend_from_slice(&[0x11; 286]);
+        body
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_second_node() {
+        let mut primary = mockito::Server::new_async().await;
+        let mut backup = mockito::Server::new_async().await;
+        let work_path = "/chainweb/0.0/mainnet01/mining/work";
+
+        let failing = primary
+            .mock("GET", work_path)
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let healthy = backup
+            .mock("GET", work_path)
+            .with_status(200)
+            .with_body(work_response(0))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ChainwebClientConfig {
+            node_url: primary.host_with_port(),
+            fallback_node_urls: vec![format!("http://{}", backup.host_with_port())],
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(5),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: false,
+            insecure: false,
+        };
+        let client = ChainwebClient::new(config)
+            .unwrap()
+            .with_failover_policy(2, Duration::from_secs(1))
+            .unwrap();
+
+        let (work, _target) = client.get_work().await.unwrap();
+        assert_eq!(work.as_bytes(), &[0x11; 286]);
+        assert_eq!(client.active_node(), backup.host_with_port());
+        failing.assert_async().await;
+        healthy.assert_async().await;
+
+        // Once the primary recovers, requests switch back to it
+        failing.remove_async().await;
+        let recovered = primary
+            .mock("GET", work_path)
+            .with_status(200)
+            .with_body(work_response(0))
+            .expect(1)
+            .create_async()
+            .await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+
+        client.get_work().await.unwrap();
+        assert_eq!(client.active_node(), primary.host_with_port());
+        recovered.assert_async().await;
+    }
+
+    fn local_client(server: &mockito::Server) -> ChainwebClient {
+        ChainwebClient::new(ChainwebClientConfig {
+            node_url: server.host_with_port(),
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(5),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: false,
+            insecure: false,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_request_timeout_defaults() {
+        let config = ChainwebClientConfig {
+            node_url: "localhost:1848".to_string(),
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(30),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: false,
+            insecure: false,
+        };
+        assert_eq!(config.work_fetch_timeout(), DEFAULT_WORK_FETCH_TIMEOUT);
+        assert_eq!(config.submit_timeout(), Duration::from_secs(30));
+
+        let fast = ChainwebClientConfig {
+            timeout: Duration::from_secs(2),
+            ..config.clone()
+        };
+        assert_eq!(fast.work_fetch_timeout(), Duration::from_secs(2));
+
+        let explicit = ChainwebClientConfig {
+            work_fetch_timeout: Some(Duration::from_secs(45)),
+            submit_timeout: Some(Duration::from_secs(90)),
+            ..config
+        };
+        assert_eq!(explicit.work_fetch_timeout(), Duration::from_secs(45));
+        assert_eq!(explicit.submit_timeout(), Duration::from_secs(90));
+    }
+
+    #[tokio::test]
+    async fn test_requests_use_their_own_timeouts() {
+        // A node that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let client = ChainwebClient::new(ChainwebClientConfig {
+            node_url: addr.to_string(),
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(30),
+            work_fetch_timeout: Some(Duration::from_millis(200)),
+            submit_timeout: Some(Duration::from_millis(800)),
+            use_tls: false,
+            insecure: false,
+        })
+        .unwrap();
+        let base_url = client.base_url();
+
+        let started = Instant::now();
+        assert!(client.get_work_once(&base_url).await.is_err());
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(200) && elapsed < Duration::from_millis(800),
+            "{:?}",
+            elapsed
+        );
+
+        let started = Instant::now();
+        assert!(
+            client
+                .submit_solution_once(&base_url, &Work::from_bytes([0x42; 286]))
+                .await
+                .is_err()
+        );
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(800) && elapsed < Duration::from_secs(10),
+            "{:?}",
+            elapsed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_work_requested_for_next_account_after_rotation() {
+        let mut server = mockito::Server::new_async().await;
+        let solved = server
+            .mock("POST", "/chainweb/0.0/mainnet01/mining/solved")
+            .with_status(204)
+            .expect(2)
+            .create_async()
+            .await;
+        let work = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/work")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "account": "second",
+                "public-keys": ["second-key"],
+            })))
+            .with_status(200)
+            .with_body(work_response(0))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let accounts = ["first", "second"]
+            .iter()
+            .map(|name| MiningAccount {
+                account: name.to_string(),
+                public_key: format!("{}-key", name),
+            })
+            .collect();
+        let client = local_client(&server).with_account_rotation(AccountRotator::new(
+            accounts,
+            Some(AccountRotation::Blocks(2)),
+        ));
+
+        client
+            .submit_solution(&Work::from_bytes([1; 286]))
+            .await
+            .unwrap();
+        client
+            .submit_solution(&Work::from_bytes([2; 286]))
+            .await
+            .unwrap();
+        client.get_work().await.unwrap();
+
+        solved.assert_async().await;
+        work.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_work_requested_for_chain_account() {
+        let mut server = mockito::Server::new_async().await;
+        let mapped = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/work")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "account": "chain5",
+                "public-keys": ["chain5-key"],
+            })))
+            .with_status(200)
+            .with_body(work_response(5))
+            .expect(1)
+            .create_async()
+            .await;
+        let unmapped = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/work")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "account": "miner",
+                "public-keys": ["abc123"],
+            })))
+            .with_status(200)
+            .with_body(work_response(1))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let chain_accounts = BTreeMap::from([(
+            5,
+            MiningAccount {
+                account: "chain5".to_string(),
+                public_key: "chain5-key".to_string(),
+            },
+        )]);
+        let mut client = local_client(&server).with_chain_accounts(chain_accounts);
+
+        client.set_chain_id(ChainId::new(5));
+        client.get_work().await.unwrap();
+        client.set_chain_id(ChainId::new(1));
+        client.get_work().await.unwrap();
+
+        mapped.assert_async().await;
+        unmapped.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_work_requested_for_miner_keyset() {
+        let mut server = mockito::Server::new_async().await;
+        let work = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/work")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "account": "miner",
+                "predicate": "keys-2",
+                "public-keys": ["key1", "key2", "key3"],
+            })))
+            .with_status(200)
+            .with_body(work_response(0))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let keyset = MinerKeyset {
+            keys: vec!["key1".to_string(), "key2".to_string(), "key3".to_string()],
+            pred: "keys-2".to_string(),
+        };
+        let client = local_client(&server).with_miner_keyset(keyset);
+        client.get_work().await.unwrap();
+
+        work.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_target_override_finds_solutions_quickly() {
+        use crate::workers::Worker;
+        use crate::workers::cpu::{CpuWorker, CpuWorkerConfig};
+
+        let mut server = mockito::Server::new_async().await;
+        let node_target = Target::mk_target_level(200);
+        let mut body = 0u32.to_le_bytes().to_vec();
+        body.extend_from_slice(node_target.as_bytes());
+        body.extend_from_slice(&[0x11; 286]);
+        let _work = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/work")
+            .with_status(200)
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let easy_target = Target::mk_target_level(8);
+        let client = local_client(&server).with_target_override(easy_target);
+        let (work, target) = client.get_work().await.unwrap();
+        assert_eq!(target, easy_target);
+        assert_eq!(work, Work::from_bytes([0x11; 286]));
+
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 1,
+            batch_size: 1000,
+            ..Default::default()
+        });
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        worker.mine(work, target, tx).await.unwrap();
+        let solution = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(easy_target.meets_target(&solution.hash));
+        assert!(!node_target.meets_target(&solution.hash));
+    }
+
+    #[tokio::test]
+    async fn test_update_stream_skips_malformed_event() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/updates")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(
+                b"event: BlockHeader\ndata: \xFF\xFE\n\nevent: BlockHeader\ndata: {}\n\n"
+                    .as_slice(),
+            )
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let updates: Vec<_> = client.subscribe_updates().await.unwrap().collect().await;
+        assert_eq!(updates.len(), 1);
+        assert!(updates[0].is_ok());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_stream_yields_typed_events() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/updates")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body("event: ping\ndata: \n\nevent: New Cut\ndata: {}\n\n")
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let updates: Vec<UpdateEvent> = client
+            .subscribe_updates()
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(updates, vec![UpdateEvent::Ping, UpdateEvent::NewCut]);
+        assert!(!updates[0].changes_work());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_polling_picks_up_work_without_update_stream() {
+        let mut server = mockito::Server::new_async().await;
+        let updates = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/updates")
+            .with_status(500)
+            .create_async()
+            .await;
+        let work_path = "/chainweb/0.0/mainnet01/mining/work";
+        let unchanged = server
+            .mock("GET", work_path)
+            .with_status(200)
+            .with_body(work_response(0))
+            .expect(1)
+            .create_async()
+            .await;
+        let mut new_work = work_response(0);
+        new_work[36..].fill(0x22);
+        let changed = server
+            .mock("GET", work_path)
+            .with_status(200)
+            .with_body(new_work)
+            .create_async()
+            .await;
+
+        let client = Arc::new(local_client(&server));
+        assert!(client.subscribe_updates().await.is_err());
+
+        let current = Work::from_bytes([0x11; 286]);
+        let mut polled = Box::pin(
+            client
+                .clone()
+                .poll_updates(Duration::from_millis(20), &current),
+        );
+        tokio::time::timeout(Duration::from_secs(5), polled.next())
+            .await
+            .expect("polling never reported new work")
+            .unwrap()
+            .unwrap();
+
+        updates.assert_async().await;
+        unchanged.assert_async().await;
+        changed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_ping_measures_latency() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/info")
+            .with_status(200)
+            .with_body("{}")
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let latency = client.ping().await.unwrap();
+        assert!(latency > Duration::ZERO);
+        assert!(latency < Duration::from_secs(5));
+
+        let results = client.ping_all().await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, server.host_with_port());
+        assert!(results[0].1.is_ok());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rejects_work_with_zero_target() {
+        let mut server = mockito::Server::new_async().await;
+        let mut body = work_response(0);
+        body[4..36].fill(0);
+        let mock = server
+            .mock("GET", "/chainweb/0.0/mainnet01/mining/work")
+            .with_status(200)
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let err = client.get_work().await.unwrap_err();
+        assert!(err.to_string().contains("Target validation failed"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_backs_off_while_no_work_available() {
+        let mut server = mockito::Server::new_async().await;
+        let work_path = "/chainweb/0.0/mainnet01/mining/work";
+        let no_work = server
+            .mock("GET", work_path)
+            .with_status(204)
+            .expect(2)
+            .create_async()
+            .await;
+        let work = server
+            .mock("GET", work_path)
+            .with_status(200)
+            .with_body(work_response(0))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+
+        let mut backoff = NoWorkBackoff::new(Duration::from_millis(50), Duration::from_millis(100));
+        let mut updates = futures::stream::pending::<()>();
+        let started = Instant::now();
+        let (work_bytes, _target) = client
+            .get_work_with_backoff(&mut backoff, &mut updates)
+            .await
+            .unwrap();
+
+        // Waited 50ms after the first no-work answer, then 100ms after the second
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(work_bytes.as_bytes(), &[0x11; 286]);
+        assert_eq!(backoff.waits(), 0);
+        no_work.assert_async().await;
+        work.assert_async().await;
+    }
+
+    /// Client for a node that refuses every connection
+    fn unreachable_client(policy: NodeLossPolicy) -> ChainwebClient {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let node_url = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        ChainwebClient::new(ChainwebClientConfig {
+            node_url,
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(5),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: false,
+            insecure: false,
+        })
+        .unwrap()
+        .with_failover_policy(1, Duration::from_secs(3600))
+        .unwrap()
+        .with_node_loss_policy(policy)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_exits_once_every_node_is_lost() {
+        let client = unreachable_client(NodeLossPolicy::Exit);
+        let mut updates = futures::stream::pending::<()>();
+        let error = client
+            .get_work_with_backoff(&mut NoWorkBackoff::default(), &mut updates)
+            .await
+            .unwrap_err();
+        assert!(error.is_node_lost(), "unexpected error: {}", error);
+        assert!(client.node_lost());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keeps_retrying_when_every_node_is_lost() {
+        let client = unreachable_client(NodeLossPolicy::Retry);
+        let mut updates = futures::stream::pending::<()>();
+        let result = tokio::time::timeout(
+            Duration::from_secs(600),
+            client.get_work_with_backoff(&mut NoWorkBackoff::default(), &mut updates),
+        )
+        .await;
+        // Still retrying long after the circuit opened
+        assert!(result.is_err());
+        assert!(!client.node_lost());
+    }
+
+    #[tokio::test]
+    async fn test_solution_accepted_by_relay_is_a_success() {
+        let mut primary = mockito::Server::new_async().await;
+        let mut relay = mockito::Server::new_async().await;
+        let rejected = primary
+            .mock("POST", "/chainweb/0.0/mainnet01/mining/solved")
+            .with_status(400)
+            .with_body("stale block")
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = relay
+            .mock("POST", "/chainweb/0.0/mainnet01/mining/solved")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = local_client(&primary)
+            .with_relay_nodes(&[format!("http://{}", relay.host_with_port())])
+            .unwrap();
+        client
+            .submit_solution(&Work::from_bytes([0x17; 286]))
+            .await
+            .unwrap();
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+
+        // Rejected everywhere, the primary's error is reported
+        let client = local_client(&primary)
+            .with_relay_nodes(&[primary.host_with_port()])
+            .unwrap();
+        let err = client
+            .submit_solution(&Work::from_bytes([0x18; 286]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("400"));
+    }
+
+    #[tokio::test]
+    async fn test_binary_submission_sends_raw_header() {
+        let mut server = mockito::Server::new_async().await;
+        let work = Work::from_bytes([0x42; 286]);
+        let mock = server
+            .mock("POST", "/chainweb/0.0/mainnet01/mining/solved")
+            .match_header("content-type", "application/octet-stream")
+            .match_header("content-length", "286")
+            .match_body(work.as_bytes().to_vec())
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = local_client(&server).with_submit_encoding(SubmitEncoding::Binary);
+        client.submit_solution(&work).await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_binary_submission_falls_back_to_json() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/chainweb/0.0/mainnet01/mining/solved";
+        let binary = server
+            .mock("POST", path)
+            .match_header("content-type", "application/octet-stream")
+            .with_status(415)
+            .expect(1)
+            .create_async()
+            .await;
+        let json = server
+            .mock("POST", path)
+            .match_header("content-type", "application/json")
+            // JSON string holding the base64url encoded 286 byte header
+            .match_body(mockito::Matcher::Regex(
+                r#"^"[A-Za-z0-9_-]{382}"$"#.to_string(),
+            ))
+            .with_status(204)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        assert_eq!(client.submit_encoding(), SubmitEncoding::Binary);
+
+        client
+            .submit_solution(&Work::from_bytes([0x42; 286]))
+            .await
+            .unwrap();
+        assert_eq!(client.submit_encoding(), SubmitEncoding::Json);
+
+        // Later submissions go straight to JSON
+        let mut other = Work::from_bytes([0x42; 286]);
+        other.set_nonce(crate::core::Nonce::new(7));
+        client.submit_solution(&other).await.unwrap();
+
+        binary.assert_async().await;
+        json.assert_async().await;
+    }
+
+    #[test]
+    fn test_node_info_submit_encodings() {
+        let mut info = node_info_with_chains(20);
+        assert_eq!(info.accepts_submit_encoding(SubmitEncoding::Binary), None);
+
+        info.node_submit_encodings = Some(vec!["json".to_string(), "cbor".to_string()]);
+        assert_eq!(
+            info.accepts_submit_encoding(SubmitEncoding::Binary),
+            Some(false)
+        );
+        assert_eq!(
+            info.accepts_submit_encoding(SubmitEncoding::Json),
+            Some(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_encoding_detected_from_node_info() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/chainweb/0.0/mainnet01/mining/solved";
+        let info = server
+            .mock("GET", "/info")
+            .with_status(200)
+            .with_body(
+                r#"{"nodeVersion": "mainnet01", "nodeApiVersion": "0.0", "nodeSubmitEncodings": ["json"]}"#,
+            )
+            .create_async()
+            .await;
+        let binary = server
+            .mock("POST", path)
+            .match_header("content-type", "application/octet-stream")
+            .expect(0)
+            .create_async()
+            .await;
+        let json = server
+            .mock("POST", path)
+            .match_header("content-type", "application/json")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        client.get_node_info().await.unwrap();
+        assert_eq!(client.submit_encoding(), SubmitEncoding::Json);
+
+        // The first submission goes straight to JSON, without a rejected binary attempt
+        client
+            .submit_solution(&Work::from_bytes([0x42; 286]))
+            .await
+            .unwrap();
+        info.assert_async().await;
+        binary.assert_async().await;
+        json.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_binary_kept_for_node_info_without_submit_encodings() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/info")
+            .with_status(200)
+            .with_body(r#"{"nodeVersion": "mainnet01", "nodeApiVersion": "0.0"}"#)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        client.get_node_info().await.unwrap();
+        assert_eq!(client.submit_encoding(), SubmitEncoding::Binary);
+    }
 }
@@ -0,0 +1,141 @@
+//! Circuit breaker for node connections
+//!
+//! Counts consecutive failures against a node. Once the threshold is reached
+//! the circuit opens and the node is avoided; after the reset timeout a single
+//! trial request is let through (half-open) to probe whether it recovered.
+
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default number of consecutive failures that opens the circuit
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Default time an open circuit waits before allowing a trial request
+pub const DEFAULT_RESET_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// State of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Too many failures, requests are rejected
+    Open,
+    /// Reset timeout elapsed, a trial request decides whether to close again
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Consecutive-failure circuit breaker
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker opening after `failure_threshold` consecutive failures
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    /// Current state of the circuit
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock();
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() >= self.reset_timeout => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Whether a request may be sent (closed or half-open)
+    pub fn allows_request(&self) -> bool {
+        self.state() != CircuitState::Open
+    }
+
+    /// Record a successful request, closing the circuit
+    pub fn record_success(&self) {
+        let mut state = self.state.lock();
+        state.consecutive_failures = 0;
+        state.opened_at = None;
+    }
+
+    /// Record a failed request, returning `true` if this opened the circuit
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state.lock();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+
+        let half_open = state
+            .opened_at
+            .is_some_and(|opened_at| opened_at.elapsed() >= self.reset_timeout);
+        let was_closed = state.opened_at.is_none();
+
+        if half_open || (was_closed && state.consecutive_failures >= self.failure_threshold) {
+            state.opened_at = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_THRESHOLD, DEFAULT_RESET_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allows_request());
+
+        // Further failures while open don't re-open it
+        assert!(!breaker.record_failure());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_success();
+        assert!(!breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.allows_request());
+
+        // A failed trial opens the circuit again
+        assert!(breaker.record_failure());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,233 @@
+//! Confirmation that accepted blocks end up on the winning chain
+//!
+//! A node accepting a solution only means the block was valid when it
+//! arrived; a competing block at the same height can still win and orphan
+//! it. Some time after the submission the node's cut is fetched, and once
+//! the chain has grown past the block's height the header at that height on
+//! the branch leading to the cut is compared with the submitted one.
+
+use crate::core::{ChainId, Work};
+use crate::error::Result;
+use crate::protocol::ChainwebClient;
+use crate::utils::monitoring::global_monitoring;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Default wait between a submission and its confirmation poll
+pub const DEFAULT_CONFIRM_DELAY: Duration = Duration::from_secs(60);
+
+/// Polls before giving up on a cut that doesn't reach the block's height
+const CONFIRM_ATTEMPTS: u32 = 5;
+
+/// Fate of an accepted block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmation {
+    /// The block is on the winning chain
+    Confirmed,
+    /// Another block at the same height is on the winning chain
+    Orphaned,
+    /// The chain hadn't reached the block's height by the last poll
+    Unconfirmed,
+}
+
+/// Check whether the accepted block `work` on `chain` is on the winning
+/// chain, polling the cut every `delay` until it includes the block's height
+pub async fn confirm_block(
+    client: &ChainwebClient,
+    chain: ChainId,
+    work: &Work,
+    delay: Duration,
+) -> Result<Confirmation> {
+    let height = work.height();
+    for _ in 0..CONFIRM_ATTEMPTS {
+        tokio::time::sleep(delay).await;
+
+        let cut = client.get_cut().await?;
+        let Some(head) = cut.head(chain) else {
+            debug!("Cut doesn't include chain {} yet", chain);
+            continue;
+        };
+        if head.height < height {
+            debug!(
+                "Chain {} is at height {}, waiting for block at height {}",
+                chain, head.height, height
+            );
+            continue;
+        }
+
+        let winner = client.branch_header_at(chain, &head.hash, height).await?;
+        return Ok(if winner.as_ref() == Some(work) {
+            Confirmation::Confirmed
+        } else {
+            Confirmation::Orphaned
+        });
+    }
+    Ok(Confirmation::Unconfirmed)
+}
+
+/// Confirm the accepted block `work` in the background, recording the
+/// outcome in the monitoring system
+pub fn spawn_confirmation(
+    client: Arc<ChainwebClient>,
+    chain: ChainId,
+    work: Work,
+    delay: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let height = work.height();
+        match confirm_block(&client, chain, &work, delay).await {
+            Ok(confirmation) => {
+                match confirmation {
+                    Confirmation::Confirmed => {
+                        info!("Block at height {} on chain {} is confirmed", height, chain)
+                    }
+                    Confirmation::Orphaned => {
+                        warn!("Block at height {} on chain {} was orphaned", height, chain)
+                    }
+                    Confirmation::Unconfirmed => warn!(
+                        "Chain {} didn't reach height {} in time to confirm the block",
+                        chain, height
+                    ),
+                }
+                global_monitoring().record_block_confirmation(confirmation);
+            }
+            Err(e) => warn!(
+                "Failed to confirm block at height {} on chain {}: {}",
+                height, chain, e
+            ),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::constants::{HEIGHT_OFFSET, WORK_SIZE};
+    use crate::protocol::chainweb::ChainwebClientConfig;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use mockito::Matcher;
+
+    const BRANCH_PATH: &str = "/chainweb/0.0/mainnet01/chain/0/header/branch";
+
+    fn work_at_height(height: u64, fill: u8) -> Work {
+        let mut bytes = [fill; WORK_SIZE];
+        bytes[HEIGHT_OFFSET..HEIGHT_OFFSET + 8].copy_from_slice(&height.to_le_bytes());
+        Work::from_bytes(bytes)
+    }
+
+    /// Header page with the full header of `work`, block hash included
+    fn header_page(work: &Work) -> String {
+        let mut header = work.as_bytes().to_vec();
+        header.extend_from_slice(&[0xAB; 32]);
+        serde_json::json!({
+            "items": [URL_SAFE_NO_PAD.encode(header)],
+            "limit": 1,
+            "next": null,
+        })
+        .to_string()
+    }
+
+    async fn mock_cut(server: &mut mockito::Server, height: u64, polls: usize) -> mockito::Mock {
+        server
+            .mock("GET", "/chainweb/0.0/mainnet01/cut")
+            .expect(polls)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({
+                    "hashes": { "0": { "height": height, "hash": "head-hash" } },
+                    "height": height * 20,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await
+    }
+
+    fn local_client(server: &mockito::Server) -> ChainwebClient {
+        ChainwebClient::new(ChainwebClientConfig {
+            node_url: server.host_with_port(),
+            fallback_node_urls: Vec::new(),
+            chain_id: ChainId::new(0),
+            account: "miner".to_string(),
+            public_key: "abc123".to_string(),
+            timeout: Duration::from_secs(5),
+            work_fetch_timeout: None,
+            submit_timeout: None,
+            use_tls: false,
+            insecure: false,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_block_on_winning_branch_is_confirmed() {
+        let mut server = mockito::Server::new_async().await;
+        let ours = work_at_height(1000, 0x42);
+        let _cut = mock_cut(&mut server, 1003, 1).await;
+        let branch = server
+            .mock("POST", BRANCH_PATH)
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("minheight".into(), "1000".into()),
+                Matcher::UrlEncoded("maxheight".into(), "1000".into()),
+            ]))
+            .match_body(Matcher::PartialJson(
+                serde_json::json!({ "upper": ["head-hash"] }),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(header_page(&ours))
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let confirmation = confirm_block(&client, ChainId::new(0), &ours, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(confirmation, Confirmation::Confirmed);
+        branch.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_block_missing_from_winning_branch_is_orphaned() {
+        let mut server = mockito::Server::new_async().await;
+        let ours = work_at_height(1000, 0x42);
+        let theirs = work_at_height(1000, 0x77);
+        let _cut = mock_cut(&mut server, 1003, 1).await;
+        let _branch = server
+            .mock("POST", BRANCH_PATH)
+            .match_query(Matcher::Any)
+            .with_header("content-type", "application/json")
+            .with_body(header_page(&theirs))
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let confirmation = confirm_block(&client, ChainId::new(0), &ours, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(confirmation, Confirmation::Orphaned);
+    }
+
+    #[tokio::test]
+    async fn test_cut_below_block_height_is_unconfirmed() {
+        let mut server = mockito::Server::new_async().await;
+        let cut = mock_cut(&mut server, 999, CONFIRM_ATTEMPTS as usize).await;
+        let branch = server
+            .mock("POST", BRANCH_PATH)
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = local_client(&server);
+        let ours = work_at_height(1000, 0x42);
+        let confirmation = confirm_block(&client, ChainId::new(0), &ours, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(confirmation, Confirmation::Unconfirmed);
+        cut.assert_async().await;
+        branch.assert_async().await;
+    }
+}
@@ -0,0 +1,78 @@
+//! Deduplication of solution submissions
+//!
+//! Several sources can produce the same solved header (e.g. a stratum block
+//! share and an internal worker). The guard remembers recently submitted
+//! block hashes so a duplicate within the window is not sent again.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Default window in which a repeated submission is considered a duplicate
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(60);
+
+/// Remembers recently submitted block hashes
+#[derive(Debug)]
+pub struct SubmissionDedup {
+    window: Duration,
+    recent: Mutex<HashMap<[u8; 32], Instant>>,
+}
+
+impl SubmissionDedup {
+    /// Create a guard with the given dedup window
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a hash, returning `false` if it was already seen within the window
+    pub fn check_and_record(&self, hash: [u8; 32]) -> bool {
+        let now = Instant::now();
+        let mut recent = self.recent.lock();
+        recent.retain(|_, seen| now.duration_since(*seen) < self.window);
+
+        if recent.contains_key(&hash) {
+            return false;
+        }
+        recent.insert(hash, now);
+        true
+    }
+
+    /// Forget a hash, e.g. after a failed submission so it may be retried
+    pub fn forget(&self, hash: &[u8; 32]) {
+        self.recent.lock().remove(hash);
+    }
+}
+
+impl Default for SubmissionDedup {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEDUP_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_within_window() {
+        let dedup = SubmissionDedup::default();
+        assert!(dedup.check_and_record([1; 32]));
+        assert!(!dedup.check_and_record([1; 32]));
+        assert!(dedup.check_and_record([2; 32]));
+    }
+
+    #[test]
+    fn test_expired_and_forgotten_entries() {
+        let dedup = SubmissionDedup::new(Duration::ZERO);
+        assert!(dedup.check_and_record([1; 32]));
+        assert!(dedup.check_and_record([1; 32]));
+
+        let dedup = SubmissionDedup::default();
+        assert!(dedup.check_and_record([3; 32]));
+        dedup.forget(&[3; 32]);
+        assert!(dedup.check_and_record([3; 32]));
+    }
+}
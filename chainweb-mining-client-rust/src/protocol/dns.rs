@@ -0,0 +1,190 @@
+//! Cached DNS resolution with periodic refresh
+//!
+//! Pooled HTTP connections stay on the address a host resolved to when they
+//! were opened, so a miner behind a load-balanced endpoint can keep talking to
+//! a retired address indefinitely. Each pooled client resolves through its own
+//! [`DnsCache`]; once the TTL has passed the cached hosts are resolved again
+//! and a client whose hosts changed address is rebuilt by the pool.
+
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, info};
+
+/// Source of host name resolutions
+#[async_trait]
+pub trait HostResolver: Send + Sync + fmt::Debug {
+    /// Addresses of `host`; a port of 0 is replaced by the URL's port
+    async fn lookup(&self, host: &str) -> io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolver of the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+#[async_trait]
+impl HostResolver for SystemResolver {
+    async fn lookup(&self, host: &str) -> io::Result<Vec<SocketAddr>> {
+        Ok(tokio::net::lookup_host((host, 0)).await?.collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedHost {
+    addrs: Vec<SocketAddr>,
+    resolved_at: Instant,
+}
+
+/// Resolutions of the hosts of one HTTP client, kept for `ttl`
+#[derive(Debug, Clone)]
+pub struct DnsCache {
+    resolver: Arc<dyn HostResolver>,
+    ttl: Duration,
+    hosts: Arc<RwLock<HashMap<String, CachedHost>>>,
+    changed: Arc<AtomicBool>,
+}
+
+impl DnsCache {
+    /// Cache resolutions of `resolver` for `ttl`
+    pub fn new(resolver: Arc<dyn HostResolver>, ttl: Duration) -> Self {
+        Self {
+            resolver,
+            ttl,
+            hosts: Arc::new(RwLock::new(HashMap::new())),
+            changed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Addresses of `host`, resolved again once the cached ones are older than the TTL
+    pub async fn lookup(&self, host: &str) -> io::Result<Vec<SocketAddr>> {
+        if let Some(cached) = self.hosts.read().get(host)
+            && cached.resolved_at.elapsed() < self.ttl
+        {
+            return Ok(cached.addrs.clone());
+        }
+        self.resolve(host).await
+    }
+
+    /// Resolve every cached host whose TTL has passed
+    ///
+    /// Returns whether any of them now resolves to different addresses.
+    pub async fn refresh(&self) -> io::Result<bool> {
+        let expired: Vec<String> = self
+            .hosts
+            .read()
+            .iter()
+            .filter(|(_, cached)| cached.resolved_at.elapsed() >= self.ttl)
+            .map(|(host, _)| host.clone())
+            .collect();
+        for host in expired {
+            self.resolve(&host).await?;
+        }
+        Ok(self.changed.swap(false, Ordering::Relaxed))
+    }
+
+    async fn resolve(&self, host: &str) -> io::Result<Vec<SocketAddr>> {
+        let mut addrs = self.resolver.lookup(host).await?;
+        addrs.sort();
+        addrs.dedup();
+
+        let previous = self.hosts.write().insert(
+            host.to_string(),
+            CachedHost {
+                addrs: addrs.clone(),
+                resolved_at: Instant::now(),
+            },
+        );
+        match previous {
+            Some(previous) if previous.addrs != addrs => {
+                info!(
+                    "DNS for {} changed from {:?} to {:?}",
+                    host, previous.addrs, addrs
+                );
+                self.changed.store(true, Ordering::Relaxed);
+            }
+            Some(_) => {}
+            None => debug!("Resolved {} to {:?}", host, addrs),
+        }
+        Ok(addrs)
+    }
+}
+
+impl Resolve for DnsCache {
+    fn resolve(&self, name: Name) -> Resolving {
+        let cache = self.clone();
+        Box::pin(async move {
+            let addrs = cache.lookup(name.as_str()).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Resolves every host to the current address, counting lookups
+    #[derive(Debug, Default)]
+    struct StaticResolver {
+        addr: RwLock<Option<SocketAddr>>,
+        lookups: AtomicUsize,
+    }
+
+    impl StaticResolver {
+        fn new(addr: SocketAddr) -> Arc<Self> {
+            Arc::new(Self {
+                addr: RwLock::new(Some(addr)),
+                lookups: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl HostResolver for StaticResolver {
+        async fn lookup(&self, host: &str) -> io::Result<Vec<SocketAddr>> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+            let addr = *self.addr.read();
+            addr.map(|addr| vec![addr])
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, host.to_string()))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_changed_record_is_picked_up_after_ttl() {
+        let old: SocketAddr = "10.0.0.1:0".parse().unwrap();
+        let new: SocketAddr = "10.0.0.2:0".parse().unwrap();
+        let resolver = StaticResolver::new(old);
+        let cache = DnsCache::new(resolver.clone(), Duration::from_secs(60));
+
+        assert_eq!(cache.lookup("node.test").await.unwrap(), vec![old]);
+        *resolver.addr.write() = Some(new);
+
+        // Served from the cache until the TTL has passed
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(cache.lookup("node.test").await.unwrap(), vec![old]);
+        assert!(!cache.refresh().await.unwrap());
+        assert_eq!(resolver.lookups.load(Ordering::Relaxed), 1);
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert!(cache.refresh().await.unwrap());
+        assert_eq!(cache.lookup("node.test").await.unwrap(), vec![new]);
+        // The change is reported once
+        assert!(!cache.refresh().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookup_is_an_error() {
+        let resolver = Arc::new(StaticResolver::default());
+        let cache = DnsCache::new(resolver, Duration::from_secs(60));
+        assert!(cache.lookup("node.test").await.is_err());
+    }
+}
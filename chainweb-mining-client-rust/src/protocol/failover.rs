@@ -0,0 +1,216 @@
+//! Failover between several chainweb nodes
+//!
+//! Nodes are kept in order of preference, the first one being the primary.
+//! Each node has its own [`CircuitBreaker`]; when the breaker of the active
+//! node opens, requests move to the next node that is not failing. Once the
+//! reset timeout of a preferred node elapses, requests go back to it, and a
+//! failed trial simply fails over again.
+
+use crate::protocol::circuit_breaker::CircuitBreaker;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// A node the client can talk to
+#[derive(Debug)]
+pub struct NodeEndpoint {
+    authority: String,
+    use_tls: bool,
+    breaker: CircuitBreaker,
+}
+
+impl NodeEndpoint {
+    /// Node address (`host[:port]`)
+    pub fn authority(&self) -> &str {
+        &self.authority
+    }
+
+    /// Base URL including the scheme
+    pub fn base_url(&self) -> String {
+        let scheme = if self.use_tls { "https" } else { "http" };
+        format!("{}://{}", scheme, self.authority)
+    }
+}
+
+/// Ordered set of nodes with a single active node
+#[derive(Debug)]
+pub struct NodeSet {
+    nodes: Vec<NodeEndpoint>,
+    active: AtomicUsize,
+}
+
+impl NodeSet {
+    /// Create a node set from `(authority, use_tls)` pairs in order of preference
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` is empty.
+    pub fn new(
+        nodes: Vec<(String, bool)>,
+        failure_threshold: u32,
+        reset_timeout: Duration,
+    ) -> Self {
+        assert!(!nodes.is_empty(), "node set requires at least one node");
+        Self {
+            nodes: nodes
+                .into_iter()
+                .map(|(authority, use_tls)| NodeEndpoint {
+                    authority,
+                    use_tls,
+                    breaker: CircuitBreaker::new(failure_threshold, reset_timeout),
+                })
+                .collect(),
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of configured nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the set is empty (never true for a constructed set)
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Node at `index`
+    pub fn node(&self, index: usize) -> &NodeEndpoint {
+        &self.nodes[index]
+    }
+
+    /// Index of the currently active node
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Currently active node
+    pub fn active_node(&self) -> &NodeEndpoint {
+        self.node(self.active())
+    }
+
+    /// Select the node for the next request, switching back to a preferred
+    /// node once its circuit allows a trial request
+    pub fn select(&self) -> usize {
+        let active = self.active();
+        if let Some(preferred) = (0..active).find(|&i| self.nodes[i].breaker.allows_request()) {
+            if self.switch(active, preferred) {
+                info!(
+                    "Switching back to node {} from {}",
+                    self.nodes[preferred].authority, self.nodes[active].authority
+                );
+            }
+            return self.active();
+        }
+        active
+    }
+
+    /// Whether the circuit of every node is open, i.e. no node is reachable
+    pub fn all_failing(&self) -> bool {
+        self.nodes.iter().all(|node| !node.breaker.allows_request())
+    }
+
+    /// Record a successful request against the node at `index`
+    pub fn record_success(&self, index: usize) {
+        self.nodes[index].breaker.record_success();
+    }
+
+    /// Record a failed request, failing over if the active node's circuit opened
+    pub fn record_failure(&self, index: usize) {
+        if !self.nodes[index].breaker.record_failure() || index != self.active() {
+            return;
+        }
+
+        let next = (1..self.nodes.len())
+            .map(|offset| (index + offset) % self.nodes.len())
+            .find(|&i| self.nodes[i].breaker.allows_request());
+
+        match next {
+            Some(next) if self.switch(index, next) => {
+                warn!(
+                    "Node {} is failing, failing over to {}",
+                    self.nodes[index].authority, self.nodes[next].authority
+                );
+            }
+            Some(_) => {}
+            None if self.nodes.len() > 1 => {
+                warn!(
+                    "Node {} is failing and no healthy fallback node is available",
+                    self.nodes[index].authority
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn switch(&self, from: usize, to: usize) -> bool {
+        self.active
+            .compare_exchange(from, to, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_set(reset_timeout: Duration) -> NodeSet {
+        NodeSet::new(
+            vec![
+                ("primary:1848".to_string(), false),
+                ("backup.example".to_string(), true),
+            ],
+            2,
+            reset_timeout,
+        )
+    }
+
+    #[test]
+    fn test_fails_over_after_threshold() {
+        let nodes = node_set(Duration::from_secs(60));
+        assert_eq!(nodes.active_node().base_url(), "http://primary:1848");
+
+        nodes.record_failure(0);
+        assert_eq!(nodes.select(), 0);
+        nodes.record_failure(0);
+        assert_eq!(nodes.select(), 1);
+        assert_eq!(nodes.active_node().base_url(), "https://backup.example");
+    }
+
+    #[test]
+    fn test_switches_back_when_primary_recovers() {
+        let nodes = node_set(Duration::ZERO);
+        nodes.record_failure(0);
+        nodes.record_failure(0);
+        assert_eq!(nodes.active(), 1);
+
+        // Reset timeout elapsed: the primary gets a trial request
+        assert_eq!(nodes.select(), 0);
+        nodes.record_success(0);
+        assert_eq!(nodes.select(), 0);
+    }
+
+    #[test]
+    fn test_stays_on_last_node_when_all_fail() {
+        let nodes = NodeSet::new(vec![("only".to_string(), true)], 1, Duration::from_secs(60));
+        nodes.record_failure(0);
+        assert_eq!(nodes.select(), 0);
+        assert_eq!(nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_all_failing_once_every_circuit_opens() {
+        let nodes = node_set(Duration::from_secs(60));
+        for _ in 0..2 {
+            nodes.record_failure(0);
+        }
+        assert!(!nodes.all_failing());
+        for _ in 0..2 {
+            nodes.record_failure(1);
+        }
+        assert!(nodes.all_failing());
+
+        nodes.record_success(1);
+        assert!(!nodes.all_failing());
+    }
+}
@@ -0,0 +1,207 @@
+//! Bounded, coalescing work fetches
+//!
+//! A burst of work updates across many chains would otherwise turn into as
+//! many simultaneous `get_work` requests. The limiter caps how many fetches
+//! run at once and lets callers for a chain that already has a fetch waiting
+//! for a slot join that fetch instead of queueing another one. A fetch that
+//! has started is not joined, since callers arriving later may be asking for
+//! newer work.
+
+use crate::core::{ChainId, Target, Work};
+use crate::error::Result;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::{Semaphore, watch};
+
+/// Default number of work fetches that may run at once
+pub const DEFAULT_MAX_CONCURRENT_WORK_FETCHES: usize = 4;
+
+/// Result handed to callers that joined a queued fetch, `None` if it failed
+type SharedResult = Option<Option<(Work, Target)>>;
+
+/// Limits concurrent work fetches, shared across chains
+#[derive(Debug)]
+pub struct WorkFetchLimiter {
+    permits: Semaphore,
+    limit: usize,
+    queued: Mutex<HashMap<ChainId, watch::Receiver<SharedResult>>>,
+}
+
+/// Removes a chain's queued entry once its fetch starts or is abandoned
+struct QueuedGuard<'a> {
+    limiter: &'a WorkFetchLimiter,
+    chain: ChainId,
+    receiver: watch::Receiver<SharedResult>,
+}
+
+impl QueuedGuard<'_> {
+    fn dequeue(&self) {
+        let mut queued = self.limiter.queued.lock();
+        if queued
+            .get(&self.chain)
+            .is_some_and(|entry| entry.same_channel(&self.receiver))
+        {
+            queued.remove(&self.chain);
+        }
+    }
+}
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.dequeue();
+    }
+}
+
+impl WorkFetchLimiter {
+    /// Create a limiter allowing `limit` fetches at once (at least one)
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            permits: Semaphore::new(limit),
+            limit,
+            queued: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Maximum number of concurrent fetches
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Run `fetch` for `chain` once a slot is free
+    ///
+    /// If a fetch for the same chain is already waiting for a slot, its work
+    /// is returned instead. Should that fetch fail, the caller fetches on its
+    /// own, so every caller sees its own error.
+    pub async fn fetch<F, Fut>(&self, chain: ChainId, fetch: F) -> Result<(Work, Target)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(Work, Target)>>,
+    {
+        let queued = self.queued.lock().get(&chain).cloned();
+        if let Some(mut receiver) = queued {
+            if let Ok(result) = receiver.wait_for(Option::is_some).await
+                && let Some(Some(work)) = result.clone()
+            {
+                return Ok(work);
+            }
+            return self.run(fetch).await;
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        self.queued.lock().insert(chain, receiver.clone());
+        let guard = QueuedGuard {
+            limiter: self,
+            chain,
+            receiver,
+        };
+
+        let _permit = self.permits.acquire().await;
+        guard.dequeue();
+        let result = fetch().await;
+        sender.send_replace(Some(result.as_ref().ok().cloned()));
+        result
+    }
+
+    async fn run<F, Fut>(&self, fetch: F) -> Result<(Work, Target)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(Work, Target)>>,
+    {
+        let _permit = self.permits.acquire().await;
+        fetch().await
+    }
+}
+
+impl Default for WorkFetchLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_WORK_FETCHES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn sample_work() -> (Work, Target) {
+        (Work::from_bytes([1u8; 286]), Target::from_bytes([0xFF; 32]))
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_stay_within_limit() {
+        let limiter = Arc::new(WorkFetchLimiter::new(3));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = Vec::new();
+        for i in 0..40u16 {
+            let limiter = limiter.clone();
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            let started = started.clone();
+            tasks.push(tokio::spawn(async move {
+                limiter
+                    .fetch(ChainId::new(i % 20), || async move {
+                        started.fetch_add(1, Ordering::SeqCst);
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        Ok(sample_work())
+                    })
+                    .await
+            }));
+        }
+        for task in tasks {
+            assert!(task.await.unwrap().is_ok());
+        }
+
+        // The limit was reached but never exceeded
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        // Callers for chains with a queued fetch joined it
+        assert!(started.load(Ordering::SeqCst) < 40);
+    }
+
+    #[tokio::test]
+    async fn test_queued_fetches_coalesce_per_chain() {
+        let limiter = WorkFetchLimiter::new(1);
+        let calls = AtomicUsize::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(sample_work())
+        };
+
+        // Chain 1 occupies the only slot, so both chain 0 fetches queue up
+        let (a, b, c) = tokio::join!(
+            limiter.fetch(ChainId::new(1), fetch),
+            limiter.fetch(ChainId::new(0), fetch),
+            limiter.fetch(ChainId::new(0), fetch),
+        );
+        assert!(a.is_ok());
+        assert_eq!(b.unwrap().0, c.unwrap().0);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(limiter.queued.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_is_retried_by_joined_callers() {
+        let limiter = WorkFetchLimiter::new(1);
+        let (a, b, c) = tokio::join!(
+            limiter.fetch(ChainId::new(1), || async { Ok(sample_work()) }),
+            limiter.fetch(ChainId::new(0), || async {
+                Err(Error::config("node unavailable"))
+            }),
+            limiter.fetch(ChainId::new(0), || async { Ok(sample_work()) }),
+        );
+        assert!(a.is_ok());
+        assert!(b.is_err());
+        assert!(c.is_ok());
+    }
+}
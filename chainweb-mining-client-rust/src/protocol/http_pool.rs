@@ -5,14 +5,18 @@
 //! different use cases (mining, configuration loading, etc.).
 
 use crate::error::{Error, Result};
+use crate::protocol::dns::{DnsCache, HostResolver, SystemResolver};
 use parking_lot::RwLock;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Client, ClientBuilder};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// HTTP client pool configuration
 #[derive(Debug, Clone)]
@@ -39,6 +43,13 @@ pub struct HttpPoolConfig {
     pub warmup_connections: usize,
     /// Enable detailed connection metrics
     pub enable_metrics: bool,
+    /// Cache DNS resolutions for this long and re-resolve them periodically,
+    /// rebuilding clients whose hosts changed address (system resolver
+    /// without caching if unset)
+    pub dns_refresh: Option<Duration>,
+    /// Extra headers sent with every node request, i.e. by every client
+    /// except [`ClientType::Config`]
+    pub headers: HeaderMap,
 }
 
 impl Default for HttpPoolConfig {
@@ -55,12 +66,14 @@ impl Default for HttpPoolConfig {
             enable_warmup: true,
             warmup_connections: 2,
             enable_metrics: false,
+            dns_refresh: None,
+            headers: HeaderMap::new(),
         }
     }
 }
 
 /// Client type for specialized configurations
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub enum ClientType {
     /// Mining operations (high-performance, frequent requests)
     Mining,
@@ -135,15 +148,28 @@ impl ConnectionMetrics {
     }
 }
 
+/// A cached client and the DNS cache it resolves through
+#[derive(Debug)]
+struct PooledClient {
+    client: Arc<Client>,
+    dns: Option<DnsCache>,
+}
+
 /// HTTP client pool for managing multiple specialized clients
 #[derive(Debug)]
 pub struct HttpClientPool {
     /// Pool configuration
     config: HttpPoolConfig,
     /// Cached clients by type
-    clients: RwLock<HashMap<ClientType, Arc<Client>>>,
+    clients: RwLock<HashMap<ClientType, PooledClient>>,
     /// Connection metrics
     metrics: ConnectionMetrics,
+    /// Resolver behind the DNS caches
+    resolver: Arc<dyn HostResolver>,
+    /// DNS cache TTL of newly built clients, see [`HttpPoolConfig::dns_refresh`]
+    dns_refresh: RwLock<Option<Duration>>,
+    /// Extra headers of newly built clients, see [`HttpPoolConfig::headers`]
+    headers: RwLock<HeaderMap>,
 }
 
 impl HttpClientPool {
@@ -154,10 +180,19 @@ impl HttpClientPool {
 
     /// Create a new HTTP client pool with custom configuration
     pub fn with_config(config: HttpPoolConfig) -> Self {
+        Self::with_resolver(config, Arc::new(SystemResolver))
+    }
+
+    /// Create a new HTTP client pool resolving hosts through `resolver`
+    /// when DNS caching is enabled
+    pub fn with_resolver(config: HttpPoolConfig, resolver: Arc<dyn HostResolver>) -> Self {
         Self {
+            dns_refresh: RwLock::new(config.dns_refresh),
+            headers: RwLock::new(config.headers.clone()),
             config,
             clients: RwLock::new(HashMap::new()),
             metrics: ConnectionMetrics::new(),
+            resolver,
         }
     }
 
@@ -166,11 +201,11 @@ impl HttpClientPool {
         // Check if client already exists
         {
             let clients = self.clients.read();
-            if let Some(client) = clients.get(&client_type) {
+            if let Some(pooled) = clients.get(&client_type) {
                 if self.config.enable_metrics {
                     self.metrics.record_cache_hit();
                 }
-                return Ok(Arc::clone(client));
+                return Ok(Arc::clone(&pooled.client));
             }
         }
 
@@ -179,7 +214,10 @@ impl HttpClientPool {
         }
 
         // Create new client
-        let client = self.create_client(&client_type)?;
+        let dns = self
+            .dns_refresh()
+            .map(|ttl| DnsCache::new(Arc::clone(&self.resolver), ttl));
+        let client = self.create_client(&client_type, dns.as_ref())?;
         let client_arc = Arc::new(client);
 
         // Store in cache
@@ -187,9 +225,15 @@ impl HttpClientPool {
             let mut clients = self.clients.write();
             // Double-check in case another thread created it
             if let Some(existing) = clients.get(&client_type) {
-                return Ok(Arc::clone(existing));
+                return Ok(Arc::clone(&existing.client));
             }
-            clients.insert(client_type.clone(), Arc::clone(&client_arc));
+            clients.insert(
+                client_type.clone(),
+                PooledClient {
+                    client: Arc::clone(&client_arc),
+                    dns,
+                },
+            );
         }
 
         if self.config.enable_metrics {
@@ -215,7 +259,7 @@ impl HttpClientPool {
     }
 
     /// Create a specialized client for the given type
-    fn create_client(&self, client_type: &ClientType) -> Result<Client> {
+    fn create_client(&self, client_type: &ClientType, dns: Option<&DnsCache>) -> Result<Client> {
         let mut builder = ClientBuilder::new()
             .user_agent(&self.config.user_agent)
             .connect_timeout(self.config.connect_timeout)
@@ -226,6 +270,10 @@ impl HttpClientPool {
             .pool_max_idle_per_host(self.config.max_idle_per_host)
             .pool_idle_timeout(self.config.keep_alive_timeout);
 
+        if let Some(dns) = dns {
+            builder = builder.dns_resolver(Arc::new(dns.clone()));
+        }
+
         // Note: reqwest enables gzip compression by default in most builds
         // The 'gzip' feature is controlled at the crate level via Cargo.toml features
 
@@ -258,6 +306,11 @@ impl HttpClientPool {
             }
         }
 
+        // Config URLs may point anywhere, the extra headers are for nodes
+        if *client_type != ClientType::Config {
+            builder = builder.default_headers(self.headers.read().clone());
+        }
+
         let client = builder
             .build()
             .map_err(|e| Error::network(format!("Failed to create HTTP client: {}", e)))?;
@@ -321,8 +374,82 @@ impl HttpClientPool {
         info!("HTTP client pool cache cleared");
     }
 
+    /// Re-resolve the hosts of every cached client whose DNS TTL has passed
+    ///
+    /// Clients with a host that now resolves to a different address are
+    /// dropped from the cache, so the next [`get_client`](Self::get_client)
+    /// builds a fresh one instead of reusing connections to the old address.
+    /// Returns the number of clients dropped.
+    pub async fn refresh_dns(&self) -> usize {
+        let caches: Vec<(ClientType, DnsCache)> = self
+            .clients
+            .read()
+            .iter()
+            .filter_map(|(client_type, pooled)| Some((client_type.clone(), pooled.dns.clone()?)))
+            .collect();
+
+        let mut rebuilt = 0;
+        for (client_type, dns) in caches {
+            match dns.refresh().await {
+                Ok(true) => {
+                    info!(
+                        "DNS changed, rebuilding HTTP client for type: {:?}",
+                        client_type
+                    );
+                    self.clients.write().remove(&client_type);
+                    rebuilt += 1;
+                }
+                Ok(false) => {}
+                Err(e) => warn!("DNS refresh for {:?} client failed: {}", client_type, e),
+            }
+        }
+        rebuilt
+    }
+
+    /// DNS cache TTL, `None` if clients use the system resolver directly
+    pub fn dns_refresh(&self) -> Option<Duration> {
+        *self.dns_refresh.read()
+    }
+
+    /// Change the DNS cache TTL
+    ///
+    /// Cached clients are dropped so the setting applies to every client.
+    pub fn set_dns_refresh(&self, refresh: Option<Duration>) {
+        *self.dns_refresh.write() = refresh;
+        self.clear_cache();
+    }
+
+    /// Extra headers sent with node requests
+    pub fn headers(&self) -> HeaderMap {
+        self.headers.read().clone()
+    }
+
+    /// Change the extra headers sent with node requests
+    ///
+    /// Cached clients are dropped so the headers apply to every client.
+    pub fn set_headers(&self, headers: HeaderMap) {
+        *self.headers.write() = headers;
+        self.clear_cache();
+    }
+
+    /// Refresh DNS every [`dns_refresh`](Self::dns_refresh) in the
+    /// background, `None` if DNS caching is disabled
+    pub fn spawn_dns_refresh(&'static self) -> Option<JoinHandle<()>> {
+        let interval = self.dns_refresh()?;
+        Some(tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticker.tick().await;
+                self.refresh_dns().await;
+            }
+        }))
+    }
+
     /// Update pool configuration (clears cache to apply new settings)
     pub fn update_config(&mut self, config: HttpPoolConfig) {
+        *self.dns_refresh.write() = config.dns_refresh;
+        *self.headers.write() = config.headers.clone();
         self.config = config;
         self.clear_cache();
         info!("HTTP client pool configuration updated");
@@ -336,7 +463,7 @@ impl Default for HttpClientPool {
 }
 
 /// Statistics about the HTTP client pool
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct HttpPoolStats {
     /// Number of active client instances
     pub active_clients: usize,
@@ -350,6 +477,15 @@ pub struct HttpPoolStats {
     pub uptime_seconds: u64,
 }
 
+/// Parse a `NAME: VALUE` header, as given to `--http-header`
+pub fn parse_http_header(header: &str) -> Result<(HeaderName, HeaderValue)> {
+    let invalid = || Error::config_invalid_value("http_header", header, "NAME: VALUE");
+    let (name, value) = header.split_once(':').ok_or_else(invalid)?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid())?;
+    let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+    Ok((name, value))
+}
+
 /// Global HTTP client pool instance
 static HTTP_POOL: std::sync::OnceLock<HttpClientPool> = std::sync::OnceLock::new();
 
@@ -388,6 +524,56 @@ mod tests {
         assert!(config.gzip);
     }
 
+    #[test]
+    fn test_parse_http_header() {
+        let (name, value) = parse_http_header("X-Miner-Rig:  rig-7 ").unwrap();
+        assert_eq!(name, "x-miner-rig");
+        assert_eq!(value, "rig-7");
+        assert!(parse_http_header("X-Miner-Rig").is_err());
+        assert!(parse_http_header("Bad Name: value").is_err());
+        assert!(parse_http_header("X-Foo: line\nbreak").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_node_requests_identify_client() {
+        let mut server = mockito::Server::new_async().await;
+        let user_agent = format!("chainweb-mining-client/{}", env!("CARGO_PKG_VERSION"));
+        let node = server
+            .mock("GET", "/info")
+            .match_header("user-agent", user_agent.as_str())
+            .match_header("x-foo", "bar")
+            .create_async()
+            .await;
+        let config_source = server
+            .mock("GET", "/config")
+            .match_header("user-agent", user_agent.as_str())
+            .match_header("x-foo", mockito::Matcher::Missing)
+            .create_async()
+            .await;
+
+        let pool = HttpClientPool::new();
+        pool.set_headers(HeaderMap::from_iter([
+            parse_http_header("X-Foo: bar").unwrap()
+        ]));
+        let mining = pool.get_client(ClientType::Mining).unwrap();
+        let response = mining
+            .get(format!("{}/info", server.url()))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        let config = pool.get_client(ClientType::Config).unwrap();
+        let response = config
+            .get(format!("{}/config", server.url()))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        node.assert_async().await;
+        config_source.assert_async().await;
+    }
+
     #[test]
     fn test_client_type_equality() {
         assert_eq!(ClientType::Mining, ClientType::Mining);
@@ -490,4 +676,59 @@ mod tests {
         // Only one client should be cached
         assert_eq!(pool.get_stats().active_clients, 1);
     }
+
+    /// Resolves every host to a single, switchable address
+    #[derive(Debug)]
+    struct SwitchableResolver(RwLock<std::net::SocketAddr>);
+
+    #[async_trait::async_trait]
+    impl HostResolver for SwitchableResolver {
+        async fn lookup(&self, _host: &str) -> std::io::Result<Vec<std::net::SocketAddr>> {
+            Ok(vec![*self.0.read()])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_is_rebuilt_when_dns_changes() {
+        let mut old_node = mockito::Server::new_async().await;
+        let mut new_node = mockito::Server::new_async().await;
+        old_node
+            .mock("GET", "/info")
+            .with_body("old")
+            .create_async()
+            .await;
+        new_node
+            .mock("GET", "/info")
+            .with_body("new")
+            .create_async()
+            .await;
+
+        let resolver = Arc::new(SwitchableResolver(RwLock::new(old_node.socket_address())));
+        let config = HttpPoolConfig {
+            dns_refresh: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+        let pool = HttpClientPool::with_resolver(config, resolver.clone());
+        let get_info = |client: Arc<Client>| async move {
+            let response = client.get("http://node.test/info").send().await.unwrap();
+            response.text().await.unwrap()
+        };
+
+        let client = pool.get_client(ClientType::General).unwrap();
+        assert_eq!(get_info(client.clone()).await, "old");
+
+        // The record changes, but the cached resolution holds until the TTL
+        *resolver.0.write() = new_node.socket_address();
+        assert_eq!(pool.refresh_dns().await, 0);
+        assert!(Arc::ptr_eq(
+            &client,
+            &pool.get_client(ClientType::General).unwrap()
+        ));
+
+        sleep(Duration::from_millis(600)).await;
+        assert_eq!(pool.refresh_dns().await, 1);
+        let rebuilt = pool.get_client(ClientType::General).unwrap();
+        assert!(!Arc::ptr_eq(&client, &rebuilt));
+        assert_eq!(get_info(rebuilt).await, "new");
+    }
 }
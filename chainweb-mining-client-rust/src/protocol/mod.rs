@@ -1,9 +1,26 @@
 //! Protocol implementations for communication with Chainweb nodes
 
+pub mod account_rotation;
 pub mod chainweb;
+pub mod circuit_breaker;
+pub mod confirmation;
+pub mod dedup;
+pub mod dns;
+pub mod failover;
+pub mod fetch_limit;
 pub mod http_pool;
 pub mod retry;
+pub mod solution_sink;
+pub mod sse;
 
+pub use account_rotation::AccountRotator;
 pub use chainweb::ChainwebClient;
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+pub use confirmation::Confirmation;
+pub use dedup::SubmissionDedup;
+pub use dns::{DnsCache, HostResolver, SystemResolver};
+pub use failover::NodeSet;
+pub use fetch_limit::WorkFetchLimiter;
 pub use http_pool::{ClientType, HttpClientPool, HttpPoolConfig, global_http_pool};
 pub use retry::{RetryPolicy, retry_http};
+pub use solution_sink::{SolutionRecord, SolutionSink};
@@ -3,7 +3,7 @@
 use crate::error::{Error, Result};
 use std::future::Future;
 use std::time::Duration;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 /// Maximum delay between retries (5 seconds, matching Haskell implementation)
 const MAX_DELAY: Duration = Duration::from_secs(5);
@@ -91,6 +91,11 @@ impl RetryPolicy {
 /// Determine if an error should trigger a retry
 /// Based on Haskell implementation's httpRetries function and enhanced with granular error analysis
 pub fn should_retry(error: &Error) -> bool {
+    // Giving up on every node is final, whatever the underlying failure was
+    if error.is_node_lost() {
+        return false;
+    }
+
     // Use the built-in error analysis method
     if error.should_retry() {
         return true;
@@ -176,12 +181,103 @@ where
     RetryPolicy::default().execute(operation).await
 }
 
+/// Random delay in `[0, max]` used to stagger the startup of many instances
+pub fn startup_jitter(max: Duration) -> Duration {
+    let max_ms = max.as_millis().min(u64::MAX as u128) as u64;
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::random_range(0..=max_ms))
+}
+
+/// First delay while the node has no work available
+const NO_WORK_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Capped backoff used while the node has no work to hand out (e.g. while
+/// it is syncing). This is not an error, so it is kept apart from the retry
+/// policy: the wait is unbounded and only the first occurrence is logged at
+/// info level.
+#[derive(Debug, Clone)]
+pub struct NoWorkBackoff {
+    base_delay: Duration,
+    max_delay: Duration,
+    next: Duration,
+    waits: u32,
+}
+
+impl Default for NoWorkBackoff {
+    fn default() -> Self {
+        Self::new(NO_WORK_BASE_DELAY, MAX_DELAY)
+    }
+}
+
+impl NoWorkBackoff {
+    /// Create a backoff starting at `base_delay` and doubling up to `max_delay`
+    pub fn new(base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            base_delay,
+            max_delay,
+            next: base_delay,
+            waits: 0,
+        }
+    }
+
+    /// Delay before asking for work again, logging the wait
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        if self.waits == 0 {
+            info!("Node has no work available yet, waiting for work");
+        } else {
+            debug!("Still no work available, retrying in {:?}", delay);
+        }
+        self.waits = self.waits.saturating_add(1);
+        self.next = std::cmp::min(delay * 2, self.max_delay);
+        delay
+    }
+
+    /// Number of waits since the last reset
+    pub fn waits(&self) -> u32 {
+        self.waits
+    }
+
+    /// Work is available again: start over from the base delay
+    pub fn reset(&mut self) {
+        self.next = self.base_delay;
+        self.waits = 0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
+    #[test]
+    fn test_no_work_backoff_is_capped() {
+        let mut backoff =
+            NoWorkBackoff::new(Duration::from_millis(100), Duration::from_millis(300));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(200));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(300));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(300));
+        assert_eq!(backoff.waits(), 4);
+
+        backoff.reset();
+        assert_eq!(backoff.waits(), 0);
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_startup_jitter_respects_bound() {
+        assert_eq!(startup_jitter(Duration::ZERO), Duration::ZERO);
+
+        let max = Duration::from_millis(50);
+        for _ in 0..1000 {
+            assert!(startup_jitter(max) <= max);
+        }
+    }
+
     #[tokio::test]
     async fn test_retry_success_on_first_attempt() {
         let policy = RetryPolicy::new(3, Duration::from_millis(10), Duration::from_millis(100));
@@ -0,0 +1,128 @@
+//! Solution sink for external submission pipelines
+//!
+//! Each solution found is appended to a file as one JSON line and flushed
+//! immediately, so the path may also be a named pipe read by the operator's
+//! own submission infrastructure. The sink is used in addition to the normal
+//! node submission, or instead of it with `--no-submit`.
+
+use crate::core::ChainId;
+use crate::error::Result;
+use crate::workers::MiningResult;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// One solution as written to the sink
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolutionRecord {
+    /// Chain the work was requested for
+    pub chain_id: u16,
+    /// Winning nonce
+    pub nonce: u64,
+    /// Proof-of-work hash (hex)
+    pub hash: String,
+    /// Solved 286 byte header including the nonce (hex)
+    pub work: String,
+    /// Unix time in seconds when the solution was recorded
+    pub timestamp: u64,
+}
+
+impl SolutionRecord {
+    /// Build the record for a mining result
+    pub fn new(chain_id: ChainId, result: &MiningResult) -> Self {
+        Self {
+            chain_id: chain_id.0,
+            nonce: result.nonce.value(),
+            hash: hex::encode(result.hash),
+            work: result.work.to_hex(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Appends solutions to a file or FIFO as JSON lines
+#[derive(Debug)]
+pub struct SolutionSink {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl SolutionSink {
+    /// Open `path` for appending, creating it if it doesn't exist
+    ///
+    /// Opening a FIFO waits until a reader has opened the other end.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Path the sink writes to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write one solution and flush it
+    pub async fn write(&self, chain_id: ChainId, result: &MiningResult) -> Result<()> {
+        let mut line = serde_json::to_vec(&SolutionRecord::new(chain_id, result))?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Nonce, Work};
+
+    fn result(nonce: u64) -> MiningResult {
+        let mut work = Work::from_bytes([0x11; 286]);
+        work.set_nonce(Nonce::new(nonce));
+        MiningResult {
+            hash: work.hash(),
+            work,
+            nonce: Nonce::new(nonce),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writes_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solutions.jsonl");
+
+        let sink = SolutionSink::open(&path).await.unwrap();
+        sink.write(ChainId::new(3), &result(7)).await.unwrap();
+        sink.write(ChainId::new(3), &result(8)).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<SolutionRecord> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+
+        let expected = result(7);
+        assert_eq!(records[0].chain_id, 3);
+        assert_eq!(records[0].nonce, 7);
+        assert_eq!(records[0].hash, hex::encode(expected.hash));
+        assert_eq!(records[0].work, expected.work.to_hex());
+        assert_eq!(records[1].nonce, 8);
+    }
+}
@@ -0,0 +1,256 @@
+//! Tolerant decoder for the work update event stream
+//!
+//! The update stream uses server-sent events: `field: value` lines, with a
+//! blank line terminating each event. Chunks from the connection are buffered
+//! until complete lines arrive, so events split across reads (including
+//! multi-line `data` fields) are reassembled. An event that can't be decoded,
+//! e.g. because it contains invalid UTF-8 or grows beyond [`MAX_EVENT_SIZE`],
+//! is reported as malformed on its own; the events after it are unaffected.
+//! Decoded events are classified as [`UpdateEvent`]s by their event type.
+
+/// Largest accepted event, in bytes
+pub const MAX_EVENT_SIZE: usize = 64 * 1024;
+
+/// A decoded server-sent event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event type, `message` if none was given
+    pub event: String,
+    /// Event data, multiple `data` lines joined with newlines
+    pub data: String,
+    /// Event id, if any
+    pub id: Option<String>,
+}
+
+/// Kind of a work update event, from the event type of an [`SseEvent`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateEvent {
+    /// The node's cut advanced
+    NewCut,
+    /// The node has new work
+    NewWork,
+    /// Keep-alive without new work
+    Ping,
+    /// Any other event type, taken as a possible work change
+    Other(String),
+}
+
+impl UpdateEvent {
+    /// Whether the event may come with new work, so work should be fetched again
+    pub fn changes_work(&self) -> bool {
+        !matches!(self, UpdateEvent::Ping)
+    }
+}
+
+impl From<&SseEvent> for UpdateEvent {
+    /// Event types are matched ignoring case and separators, so `New Cut`,
+    /// `new-cut` and `newCut` are all [`UpdateEvent::NewCut`]
+    fn from(event: &SseEvent) -> Self {
+        let kind: String = event
+            .event
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match kind.as_str() {
+            "newcut" | "cut" => UpdateEvent::NewCut,
+            "newwork" | "work" => UpdateEvent::NewWork,
+            "ping" | "keepalive" | "heartbeat" => UpdateEvent::Ping,
+            _ => UpdateEvent::Other(event.event.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PendingEvent {
+    event: String,
+    data: String,
+    id: Option<String>,
+    size: usize,
+    malformed: Option<String>,
+}
+
+/// Incremental server-sent event decoder
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    pending: PendingEvent,
+    started: bool,
+}
+
+impl SseDecoder {
+    /// Create a decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the stream, returning the events it completed; a
+    /// malformed event is returned as `Err` with the reason
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Result<SseEvent, String>> {
+        self.buffer.extend_from_slice(chunk);
+        if !self.started && self.buffer.len() >= 3 {
+            self.started = true;
+            if self.buffer.starts_with(b"\xEF\xBB\xBF") {
+                self.buffer.drain(..3);
+            }
+        }
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=end).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+        }
+
+        // A line that never ends can't be buffered forever
+        if self.buffer.len() > MAX_EVENT_SIZE {
+            self.buffer.clear();
+            self.mark_malformed(format!("line longer than {} bytes", MAX_EVENT_SIZE));
+        }
+        events
+    }
+
+    fn mark_malformed(&mut self, reason: String) {
+        self.pending.malformed.get_or_insert(reason);
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<Result<SseEvent, String>> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if self.pending.malformed.is_some() {
+            return None;
+        }
+
+        self.pending.size += line.len();
+        if self.pending.size > MAX_EVENT_SIZE {
+            self.mark_malformed(format!("event larger than {} bytes", MAX_EVENT_SIZE));
+            return None;
+        }
+
+        let line = match std::str::from_utf8(line) {
+            Ok(line) => line,
+            Err(e) => {
+                self.mark_malformed(format!("invalid UTF-8: {}", e));
+                return None;
+            }
+        };
+
+        // Lines starting with a colon are comments (keep-alives)
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.pending.event = value.to_string(),
+            "data" => {
+                self.pending.data.push_str(value);
+                self.pending.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.pending.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Result<SseEvent, String>> {
+        let pending = std::mem::take(&mut self.pending);
+        if let Some(reason) = pending.malformed {
+            return Some(Err(reason));
+        }
+        // Events without data are not dispatched
+        if pending.data.is_empty() {
+            return None;
+        }
+
+        let mut data = pending.data;
+        data.pop();
+        let event = if pending.event.is_empty() {
+            "message".to_string()
+        } else {
+            pending.event
+        };
+        Some(Ok(SseEvent {
+            event,
+            data,
+            id: pending.id,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reassembles_split_events() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"event: BlockHeader\r\nda").is_empty());
+        assert!(decoder.push(b"ta: first\ndata: second\n").is_empty());
+
+        let events = decoder.push(b"\n: keep-alive\n\ndata:x\n\n");
+        assert_eq!(
+            events,
+            vec![
+                Ok(SseEvent {
+                    event: "BlockHeader".to_string(),
+                    data: "first\nsecond".to_string(),
+                    id: None,
+                }),
+                Ok(SseEvent {
+                    event: "message".to_string(),
+                    data: "x".to_string(),
+                    id: None,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skips_malformed_event_only() {
+        let mut decoder = SseDecoder::new();
+        let events =
+            decoder.push(b"event: BlockHeader\ndata: \xFF\xFE\n\nevent: BlockHeader\ndata: ok\n\n");
+        assert_eq!(events.len(), 2);
+        assert!(events[0].as_ref().unwrap_err().contains("UTF-8"));
+        assert_eq!(events[1].as_ref().unwrap().data, "ok");
+    }
+
+    #[test]
+    fn test_classifies_update_events() {
+        let mut decoder = SseDecoder::new();
+        let events: Vec<UpdateEvent> = decoder
+            .push(b"event: New Cut\ndata: {}\n\nevent: ping\ndata: \n\nevent: new-work\ndata: 1\n\ndata: x\n\n")
+            .iter()
+            .map(|event| UpdateEvent::from(event.as_ref().unwrap()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                UpdateEvent::NewCut,
+                UpdateEvent::Ping,
+                UpdateEvent::NewWork,
+                UpdateEvent::Other("message".to_string()),
+            ]
+        );
+        let refetches: Vec<bool> = events.iter().map(UpdateEvent::changes_work).collect();
+        assert_eq!(refetches, vec![true, false, true, true]);
+    }
+
+    #[test]
+    fn test_oversized_event_is_malformed() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(&vec![b'a'; MAX_EVENT_SIZE + 1]).is_empty());
+        let events = decoder.push(b"\n\ndata: ok\n\n");
+        assert!(events[0].is_err());
+        assert_eq!(events[1].as_ref().unwrap().data, "ok");
+    }
+}
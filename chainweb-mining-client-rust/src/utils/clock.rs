@@ -0,0 +1,237 @@
+//! System clock validation against an NTP server
+//!
+//! Work headers carry the creation time taken from the system clock, and the
+//! node rejects headers that are too far in the future. The check compares
+//! the local clock against an (S)NTP server once at startup.
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+
+/// Default NTP server used for the clock check
+pub const DEFAULT_NTP_SERVER: &str = "pool.ntp.org";
+
+/// Default maximum tolerated clock skew
+pub const DEFAULT_MAX_CLOCK_SKEW: Duration = Duration::from_secs(10);
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970)
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+
+/// Size of an NTP packet without extensions
+const NTP_PACKET_SIZE: usize = 48;
+
+/// Source of reference time to compare the system clock against
+#[async_trait]
+pub trait TimeSource: Send + Sync {
+    /// Offset of the reference clock relative to the system clock in seconds
+    /// (positive when the system clock is behind)
+    async fn offset(&self) -> Result<f64>;
+
+    /// Name of the reference, used in logs and errors
+    fn name(&self) -> &str;
+}
+
+/// SNTP client querying a single server
+#[derive(Debug, Clone)]
+pub struct NtpTimeSource {
+    server: String,
+    timeout: Duration,
+}
+
+impl NtpTimeSource {
+    /// Create a source for `host` or `host:port` (port defaults to 123)
+    pub fn new(server: impl Into<String>) -> Self {
+        Self {
+            server: server.into(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Set the query timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn address(&self) -> String {
+        if self.server.contains(':') {
+            self.server.clone()
+        } else {
+            format!("{}:123", self.server)
+        }
+    }
+}
+
+#[async_trait]
+impl TimeSource for NtpTimeSource {
+    async fn offset(&self) -> Result<f64> {
+        let address = self.address();
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(&address).await.map_err(|e| {
+            Error::network(format!("Failed to resolve NTP server {}: {}", address, e))
+        })?;
+
+        // LI = 0, version = 4, mode = 3 (client)
+        let mut request = [0u8; NTP_PACKET_SIZE];
+        request[0] = 0x23;
+
+        let sent = SystemTime::now();
+        socket.send(&request).await?;
+
+        let mut response = [0u8; NTP_PACKET_SIZE];
+        let len = tokio::time::timeout(self.timeout, socket.recv(&mut response))
+            .await
+            .map_err(|_| Error::network_timeout(&address, self.timeout))??;
+        let received = SystemTime::now();
+
+        if len < NTP_PACKET_SIZE {
+            return Err(Error::protocol_invalid_format(format!(
+                "NTP response from {} too short: {} bytes",
+                address, len
+            )));
+        }
+
+        parse_offset(&response, sent, received)
+    }
+
+    fn name(&self) -> &str {
+        &self.server
+    }
+}
+
+/// Compute the clock offset from an NTP response using the standard
+/// `((t1 - t0) + (t2 - t3)) / 2` formula
+fn parse_offset(
+    response: &[u8; NTP_PACKET_SIZE],
+    sent: SystemTime,
+    received: SystemTime,
+) -> Result<f64> {
+    let server_receive = ntp_timestamp(&response[32..40]);
+    let server_transmit = ntp_timestamp(&response[40..48]);
+    if server_transmit == 0.0 {
+        return Err(Error::protocol_invalid_format(
+            "NTP response without transmit timestamp",
+        ));
+    }
+
+    let t0 = unix_secs(sent);
+    let t3 = unix_secs(received);
+    Ok(((server_receive - t0) + (server_transmit - t3)) / 2.0)
+}
+
+/// Convert a 64 bit NTP timestamp to Unix seconds (0.0 for an unset timestamp)
+fn ntp_timestamp(bytes: &[u8]) -> f64 {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as f64;
+    if seconds == 0 {
+        return 0.0;
+    }
+    (seconds as f64 - NTP_UNIX_OFFSET_SECS as f64) + fraction / (u32::MAX as f64 + 1.0)
+}
+
+fn unix_secs(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Check the system clock against `source`, failing if the skew exceeds `max_skew`
+///
+/// Returns the measured offset in seconds on success.
+pub async fn check_clock_skew(source: &dyn TimeSource, max_skew: Duration) -> Result<f64> {
+    let offset = source.offset().await?;
+    if offset.abs() > max_skew.as_secs_f64() {
+        return Err(Error::validation_clock_skew(
+            source.name(),
+            (offset * 1000.0).round() as i64,
+            max_skew.as_millis() as u64,
+        ));
+    }
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Time source with a fixed offset
+    struct MockTimeSource(f64);
+
+    #[async_trait]
+    impl TimeSource for MockTimeSource {
+        async fn offset(&self) -> Result<f64> {
+            Ok(self.0)
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    fn ntp_response(time: SystemTime) -> [u8; NTP_PACKET_SIZE] {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap();
+        let seconds = (since_epoch.as_secs() + NTP_UNIX_OFFSET_SECS) as u32;
+        let fraction = ((since_epoch.subsec_nanos() as u64) << 32) / 1_000_000_000;
+
+        let mut packet = [0u8; NTP_PACKET_SIZE];
+        packet[0] = 0x24; // LI = 0, version = 4, mode = 4 (server)
+        for offset in [32, 40] {
+            packet[offset..offset + 4].copy_from_slice(&seconds.to_be_bytes());
+            packet[offset + 4..offset + 8].copy_from_slice(&(fraction as u32).to_be_bytes());
+        }
+        packet
+    }
+
+    #[tokio::test]
+    async fn test_skew_within_threshold() {
+        let offset = check_clock_skew(&MockTimeSource(-2.5), DEFAULT_MAX_CLOCK_SKEW)
+            .await
+            .unwrap();
+        assert_eq!(offset, -2.5);
+    }
+
+    #[tokio::test]
+    async fn test_excessive_skew_is_flagged() {
+        let err = check_clock_skew(&MockTimeSource(42.0), DEFAULT_MAX_CLOCK_SKEW)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Validation(crate::error::ValidationError::ClockSkew {
+                offset_ms: 42_000,
+                max_ms: 10_000,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        let now = SystemTime::now();
+        let response = ntp_response(now + Duration::from_secs(30));
+        let offset = parse_offset(&response, now, now).unwrap();
+        assert!((offset - 30.0).abs() < 0.001);
+
+        assert!(parse_offset(&[0u8; NTP_PACKET_SIZE], now, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ntp_query_against_fake_server() {
+        // Fake NTP server whose clock runs a minute ahead
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut buf = [0u8; NTP_PACKET_SIZE];
+            let (_, peer) = server.recv_from(&mut buf).await.unwrap();
+            let response = ntp_response(SystemTime::now() + Duration::from_secs(60));
+            server.send_to(&response, peer).await.unwrap();
+        });
+
+        let source = NtpTimeSource::new(address).with_timeout(Duration::from_secs(2));
+        let err = check_clock_skew(&source, DEFAULT_MAX_CLOCK_SKEW)
+            .await
+            .unwrap_err();
+        assert_eq!(err.category(), "validation");
+    }
+}
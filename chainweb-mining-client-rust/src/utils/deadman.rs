@@ -0,0 +1,144 @@
+//! Dead man's switch for a miner that never finds a solution
+//!
+//! A worker hashing with the wrong algorithm or against the wrong target keeps
+//! reporting a healthy hash rate without ever solving a block. Solutions
+//! arrive as a Poisson process: after hashing enough to expect `n` blocks at
+//! the difficulties mined, a working miner has found none with a chance of
+//! `e^-n`. The switch trips once a dry spell reaches a multiple of the
+//! expected time per block, i.e. `n` exceeds the multiple. The default makes
+//! it about a one in a billion event for a working miner, so a trip points
+//! at a bug rather than bad luck.
+
+use crate::utils::monitoring::{MonitoringSystem, global_monitoring};
+use std::fmt;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+/// Default number of expected blocks without a solution before the switch trips
+pub const DEFAULT_DRY_SPELL_MULTIPLE: f64 = 20.0;
+
+/// How often the dry spell is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Time since the latest solution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrySpell {
+    /// Blocks expected from the hashes computed meanwhile
+    pub expected_blocks: f64,
+    /// Time since the latest solution, or since startup
+    pub elapsed: Duration,
+}
+
+impl DrySpell {
+    /// Chance of a working miner finding no solution in this spell
+    pub fn chance(&self) -> f64 {
+        (-self.expected_blocks).exp()
+    }
+}
+
+impl fmt::Display for DrySpell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no solution for {}s although {:.1} were expected (chance {:.1e})",
+            self.elapsed.as_secs(),
+            self.expected_blocks,
+            self.chance()
+        )
+    }
+}
+
+/// Trips on dry spells far beyond the expected time per block
+#[derive(Debug)]
+pub struct DeadMansSwitch {
+    multiple: f64,
+    tripped: bool,
+}
+
+impl DeadMansSwitch {
+    /// Switch tripping once a dry spell spans `multiple` expected blocks
+    pub fn new(multiple: f64) -> Self {
+        Self {
+            multiple,
+            tripped: false,
+        }
+    }
+
+    /// The current dry spell of `monitoring` if it trips the switch
+    ///
+    /// Each dry spell is reported once; the switch re-arms with the next
+    /// solution.
+    pub fn check(&mut self, monitoring: &MonitoringSystem) -> Option<DrySpell> {
+        let spell = monitoring.dry_spell();
+        if spell.expected_blocks < self.multiple {
+            self.tripped = false;
+            return None;
+        }
+        if self.tripped {
+            return None;
+        }
+        self.tripped = true;
+        Some(spell)
+    }
+
+    /// Raise a critical alert on every dry spell that trips the switch,
+    /// exiting on the first one if `halt` is set
+    pub fn spawn(mut self, halt: bool) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let Some(spell) = self.check(global_monitoring()) else {
+                    continue;
+                };
+                global_monitoring().record_dry_spell(&spell);
+                if halt {
+                    error!("Dead man's switch stopping the miner: {}", spell);
+                    std::process::exit(1);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Target, TargetArithmetic, TargetWords};
+    use crate::utils::monitoring::AlertSeverity;
+
+    #[test]
+    fn test_implausible_dry_spell_trips() {
+        let monitor = MonitoringSystem::new();
+        let target = Target::mk_target_level(20);
+        monitor.record_chain_target(0, &target);
+        let hashes_per_block =
+            TargetArithmetic::expected_block_time(&TargetWords::from(&target), 1.0);
+        let mut switch = DeadMansSwitch::new(DEFAULT_DRY_SPELL_MULTIPLE);
+
+        // A few blocks' worth of bad luck is no reason to worry
+        monitor.record_hashes(5.0 * hashes_per_block);
+        assert_eq!(switch.check(&monitor), None);
+
+        monitor.record_hashes(20.0 * hashes_per_block);
+        let spell = switch.check(&monitor).expect("dry spell");
+        assert!((spell.expected_blocks - 25.0).abs() < 1e-6);
+        assert!(spell.chance() < 1e-10);
+        // The same spell is only reported once
+        assert_eq!(switch.check(&monitor), None);
+
+        monitor.record_dry_spell(&spell);
+        let alert = monitor.get_recent_alerts(1).pop().unwrap();
+        assert_eq!(alert.category, "dry_spell");
+        assert_eq!(alert.severity, AlertSeverity::Critical);
+        assert_eq!(alert.context["expected_blocks"], "25.0");
+
+        // A solution starts a new spell
+        monitor.record_solution();
+        monitor.record_hashes(5.0 * hashes_per_block);
+        assert_eq!(switch.check(&monitor), None);
+        monitor.record_hashes(20.0 * hashes_per_block);
+        assert!(switch.check(&monitor).is_some());
+    }
+}
@@ -0,0 +1,167 @@
+//! Mining efficiency summary printed on shutdown
+//!
+//! Luck compares the blocks actually found with the number expected from
+//! the hashes computed and the difficulty of the work they were computed
+//! for: 100% means exactly as many blocks as expected, more is lucky.
+
+use crate::utils::format_hashrate;
+use serde::Serialize;
+use std::fmt;
+
+/// Totals of a mining run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EfficiencyReport {
+    /// Runtime in seconds
+    pub runtime_secs: u64,
+    /// Hashes computed, integrated from the reported hash rate
+    pub total_hashes: f64,
+    /// Average hash rate over the runtime (hashes per second)
+    pub avg_hash_rate: f64,
+    /// Solutions found by the worker
+    pub blocks_found: u64,
+    /// Solutions accepted by the node
+    pub blocks_accepted: u64,
+    /// Solutions the node rejected or that couldn't be submitted
+    pub blocks_rejected: u64,
+    /// Accepted blocks confirmed on the winning chain
+    pub blocks_confirmed: u64,
+    /// Accepted blocks that lost to another block at the same height
+    pub blocks_orphaned: u64,
+    /// Solutions dropped because their job was outdated
+    pub blocks_stale: u64,
+    /// Stratum shares submitted
+    pub shares_submitted: u64,
+    /// Stratum shares accepted
+    pub shares_accepted: u64,
+    /// Blocks expected from the hashes computed at the work's difficulty
+    pub expected_blocks: f64,
+}
+
+impl EfficiencyReport {
+    /// Blocks found relative to the expected number, `None` until blocks
+    /// are expected at all
+    pub fn luck(&self) -> Option<f64> {
+        (self.expected_blocks > 0.0).then(|| self.blocks_found as f64 / self.expected_blocks)
+    }
+
+    /// The report as a JSON object, including the luck
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["luck"] = self.luck().into();
+        value
+    }
+}
+
+impl fmt::Display for EfficiencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (hours, minutes, seconds) = (
+            self.runtime_secs / 3600,
+            self.runtime_secs / 60 % 60,
+            self.runtime_secs % 60,
+        );
+        writeln!(f, "=== Mining Efficiency ===")?;
+        writeln!(f, "Runtime: {}h {}m {}s", hours, minutes, seconds)?;
+        writeln!(f, "Total Hashes: {:.3e}", self.total_hashes)?;
+        writeln!(
+            f,
+            "Average Hash Rate: {}",
+            format_hashrate(self.avg_hash_rate as u64)
+        )?;
+        writeln!(
+            f,
+            "Blocks: {} found, {} accepted, {} rejected, {} stale",
+            self.blocks_found, self.blocks_accepted, self.blocks_rejected, self.blocks_stale
+        )?;
+        if self.blocks_confirmed + self.blocks_orphaned > 0 {
+            writeln!(
+                f,
+                "Confirmations: {} confirmed, {} orphaned",
+                self.blocks_confirmed, self.blocks_orphaned
+            )?;
+        }
+        if self.shares_submitted > 0 {
+            writeln!(
+                f,
+                "Shares: {} submitted, {} accepted",
+                self.shares_submitted, self.shares_accepted
+            )?;
+        }
+        match self.luck() {
+            Some(luck) => writeln!(
+                f,
+                "Luck: {:.1}% ({} found, {:.2} expected)",
+                luck * 100.0,
+                self.blocks_found,
+                self.expected_blocks
+            ),
+            None => writeln!(f, "Luck: n/a (no hashes at a known difficulty)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Target, Work};
+    use crate::utils::monitoring::MonitoringSystem;
+    use crate::workers::Worker;
+    use crate::workers::simulation::{SimulationWorker, SimulationWorkerConfig};
+    use tokio::sync::mpsc;
+    use tokio::time::Instant;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_luck_of_simulation_run() {
+        const HASH_RATE: f64 = 1_000_000.0;
+        const BLOCKS: u64 = 400;
+
+        let monitor = MonitoringSystem::new();
+        let target = Target::mk_target_level(20);
+        monitor.record_chain_target(0, &target);
+
+        let worker = SimulationWorker::new(SimulationWorkerConfig {
+            hash_rate: HASH_RATE,
+        });
+        let (tx, mut rx) = mpsc::channel(1);
+        let started = Instant::now();
+        for _ in 0..BLOCKS {
+            worker
+                .mine(Work::from_bytes([0; 286]), target, tx.clone())
+                .await
+                .unwrap();
+            rx.recv().await.unwrap();
+            monitor.record_solution();
+        }
+        monitor.record_hashes(HASH_RATE * started.elapsed().as_secs_f64());
+
+        // Block times are exponential, so 400 blocks put the luck within a
+        // few percent of 100%; the bounds leave room for six deviations
+        let report = monitor.efficiency_report();
+        let luck = report.luck().unwrap();
+        assert!((0.7..1.3).contains(&luck), "luck {} out of range", luck);
+        assert_eq!(report.blocks_found, BLOCKS);
+        assert_eq!(report.to_json()["luck"], luck);
+    }
+
+    #[test]
+    fn test_luck_needs_expected_blocks() {
+        let report = EfficiencyReport {
+            blocks_found: 3,
+            ..Default::default()
+        };
+        assert_eq!(report.luck(), None);
+        assert!(report.to_string().contains("Luck: n/a"));
+        assert!(report.to_json()["luck"].is_null());
+
+        let report = EfficiencyReport {
+            blocks_found: 3,
+            expected_blocks: 2.0,
+            runtime_secs: 3725,
+            ..Default::default()
+        };
+        assert_eq!(report.luck(), Some(1.5));
+        let text = report.to_string();
+        assert!(text.contains("Runtime: 1h 2m 5s"));
+        assert!(text.contains("Luck: 150.0% (3 found, 2.00 expected)"));
+    }
+}
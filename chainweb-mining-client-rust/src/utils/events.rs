@@ -0,0 +1,313 @@
+//! Structured mining events for external consumers
+//!
+//! Events are emitted at the interesting points of the mining loop (new work,
+//! preemption decisions, solutions and their submission, stratum shares and
+//! monitoring alerts). Emission goes through a process wide [`EventSink`]
+//! that is only installed with `--event-socket`; without one, emitting an
+//! event is a no-op.
+//!
+//! [`SocketEventSink`] serves the events as JSON lines to every client
+//! connected to a TCP (`host:port`) or Unix (`unix:PATH`) socket. Slow
+//! clients that fall behind lose events rather than stalling the miner.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Number of events buffered per client before old ones are dropped
+const EVENT_BUFFER: usize = 1024;
+
+/// A mining event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum MiningEvent {
+    /// New work was received from the node
+    WorkReceived {
+        /// Chain of the work
+        chain_id: u16,
+        /// Target (hex)
+        target: String,
+    },
+    /// The preemptor decided how to handle a work update
+    PreemptionDecision {
+        /// Whether the current work was preempted
+        preempted: bool,
+        /// Preemption action or skip reason
+        detail: String,
+    },
+    /// A worker found a solution
+    SolutionFound {
+        /// Worker that found it
+        worker: String,
+        /// Winning nonce
+        nonce: u64,
+        /// Proof-of-work hash (hex)
+        hash: String,
+    },
+    /// Outcome of submitting a solution to the node
+    SubmitOutcome {
+        /// Proof-of-work hash (hex)
+        hash: String,
+        /// Whether the node accepted the solution
+        accepted: bool,
+        /// Error message if it was not accepted
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A stratum client submitted a share
+    ShareSubmitted {
+        /// Worker name given in the submit
+        worker: String,
+        /// Whether the share was accepted
+        accepted: bool,
+        /// Rejection reason
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// The monitoring system raised an alert
+    Alert {
+        /// Alert severity
+        severity: String,
+        /// Alert category
+        category: String,
+        /// Alert message
+        message: String,
+    },
+}
+
+/// An event together with the time it was emitted, as written to sinks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// Unix time in milliseconds
+    pub timestamp_ms: u64,
+    /// The event
+    #[serde(flatten)]
+    pub event: MiningEvent,
+}
+
+impl EventRecord {
+    /// Stamp `event` with the current time
+    pub fn now(event: MiningEvent) -> Self {
+        Self {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            event,
+        }
+    }
+}
+
+/// Destination for mining events
+pub trait EventSink: Send + Sync {
+    /// Deliver an event; must not block
+    fn emit(&self, record: EventRecord);
+}
+
+static EVENT_SINK: OnceLock<Arc<dyn EventSink>> = OnceLock::new();
+
+/// Install the process wide event sink; returns `false` if one is already set
+pub fn set_event_sink(sink: Arc<dyn EventSink>) -> bool {
+    EVENT_SINK.set(sink).is_ok()
+}
+
+/// Emit an event to the installed sink, if any
+pub fn emit_event(event: MiningEvent) {
+    if let Some(sink) = EVENT_SINK.get() {
+        sink.emit(EventRecord::now(event));
+    }
+}
+
+/// Serves events as JSON lines to clients of a TCP or Unix socket
+#[derive(Debug, Clone)]
+pub struct SocketEventSink {
+    tx: broadcast::Sender<Arc<str>>,
+    address: String,
+}
+
+impl SocketEventSink {
+    /// Listen on `address`: `host:port` for TCP or `unix:PATH` for a Unix
+    /// socket. A stale socket file at PATH is replaced.
+    pub async fn bind(address: &str) -> Result<Self> {
+        let (tx, _) = broadcast::channel(EVENT_BUFFER);
+
+        let address = match address.strip_prefix("unix:") {
+            Some(path) => Self::bind_unix(path, tx.clone())?,
+            None => {
+                let listener = TcpListener::bind(address).await.map_err(|e| {
+                    Error::config(format!("Failed to bind event socket {}: {}", address, e))
+                })?;
+                let local = listener.local_addr()?.to_string();
+                let accept_tx = tx.clone();
+                tokio::spawn(async move {
+                    loop {
+                        match listener.accept().await {
+                            Ok((stream, peer)) => {
+                                debug!("Event client connected from {}", peer);
+                                tokio::spawn(serve_client(stream, accept_tx.subscribe()));
+                            }
+                            Err(e) => warn!("Failed to accept event client: {}", e),
+                        }
+                    }
+                });
+                local
+            }
+        };
+
+        info!("Serving mining events on {}", address);
+        Ok(Self { tx, address })
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(path: &str, tx: broadcast::Sender<Arc<str>>) -> Result<String> {
+        let _ = std::fs::remove_file(path);
+        let listener = tokio::net::UnixListener::bind(path).map_err(|e| {
+            Error::config(format!("Failed to bind event socket unix:{}: {}", path, e))
+        })?;
+        let address = format!("unix:{}", path);
+        let accept_address = address.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        debug!("Event client connected on {}", accept_address);
+                        tokio::spawn(serve_client(stream, tx.subscribe()));
+                    }
+                    Err(e) => warn!("Failed to accept event client: {}", e),
+                }
+            }
+        });
+        Ok(address)
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(path: &str, _tx: broadcast::Sender<Arc<str>>) -> Result<String> {
+        Err(Error::config_invalid_value(
+            "event_socket",
+            format!("unix:{}", path),
+            "host:port (Unix sockets are not supported on this platform)",
+        ))
+    }
+
+    /// Address the sink listens on, with the actual port for TCP
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl EventSink for SocketEventSink {
+    fn emit(&self, record: EventRecord) {
+        match serde_json::to_string(&record) {
+            // Sending only fails when no client is connected
+            Ok(line) => {
+                let _ = self.tx.send(Arc::from(line + "\n"));
+            }
+            Err(e) => warn!("Failed to serialize event: {}", e),
+        }
+    }
+}
+
+/// Forward events to one client until it disconnects
+async fn serve_client<W: AsyncWrite + Unpin>(mut stream: W, mut rx: broadcast::Receiver<Arc<str>>) {
+    loop {
+        match rx.recv().await {
+            Ok(line) => {
+                if stream.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Event client fell behind, dropped {} events", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+    debug!("Event client disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    async fn wait_for_clients(sink: &SocketEventSink, count: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while sink.client_count() < count {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("client never subscribed");
+    }
+
+    #[tokio::test]
+    async fn test_tcp_client_receives_work_received() {
+        let sink = SocketEventSink::bind("127.0.0.1:0").await.unwrap();
+        let stream = tokio::net::TcpStream::connect(sink.address())
+            .await
+            .unwrap();
+        wait_for_clients(&sink, 1).await;
+
+        let event = MiningEvent::WorkReceived {
+            chain_id: 3,
+            target: "ff".repeat(32),
+        };
+        sink.emit(EventRecord::now(event.clone()));
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await.unwrap();
+        assert!(line.contains(r#""event":"work_received""#));
+        let record: EventRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(record.event, event);
+        assert!(record.timestamp_ms > 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.sock");
+        let sink = SocketEventSink::bind(&format!("unix:{}", path.display()))
+            .await
+            .unwrap();
+
+        let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        wait_for_clients(&sink, 1).await;
+        sink.emit(EventRecord::now(MiningEvent::SubmitOutcome {
+            hash: "00".repeat(32),
+            accepted: false,
+            error: Some("rejected".to_string()),
+        }));
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await.unwrap();
+        let record: EventRecord = serde_json::from_str(&line).unwrap();
+        assert!(matches!(
+            record.event,
+            MiningEvent::SubmitOutcome {
+                accepted: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_emit_without_sink_is_noop() {
+        emit_event(MiningEvent::Alert {
+            severity: "Warning".to_string(),
+            category: "test".to_string(),
+            message: "nobody listens".to_string(),
+        });
+    }
+}
@@ -0,0 +1,219 @@
+//! Mining key pair generation and output formatting
+
+use crate::error::{Error, Result};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Output format for a generated key pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyFormat {
+    /// `public:`/`private:` lines (the historical output)
+    #[default]
+    Plain,
+    /// JSON object
+    Json,
+    /// YAML document
+    Yaml,
+    /// Shell `export` lines
+    Env,
+}
+
+impl FromStr for KeyFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "plain" => Ok(KeyFormat::Plain),
+            "json" => Ok(KeyFormat::Json),
+            "yaml" => Ok(KeyFormat::Yaml),
+            "env" => Ok(KeyFormat::Env),
+            _ => Err(Error::config_invalid_value(
+                "format",
+                s,
+                "plain, json, yaml, or env",
+            )),
+        }
+    }
+}
+
+/// An Ed25519 mining key pair with its derived `k:` account
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyPair {
+    /// Hex encoded public key
+    pub public_key: String,
+    /// Hex encoded private key
+    pub private_key: String,
+    /// Principal account for the public key (`k:<public-key>`)
+    pub account: String,
+}
+
+impl KeyPair {
+    /// Generate a new random key pair
+    pub fn generate() -> Result<Self> {
+        Self::generate_with(getrandom::fill)
+    }
+
+    /// Generate a key pair from the random bytes of `fill_random`
+    fn generate_with(
+        fill_random: impl FnOnce(&mut [u8]) -> std::result::Result<(), getrandom::Error>,
+    ) -> Result<Self> {
+        let mut secret_bytes = [0u8; 32];
+        fill_random(&mut secret_bytes)
+            .map_err(|e| Error::other(format!("Failed to generate random bytes: {}", e)))?;
+        Ok(Self::from_secret(&secret_bytes))
+    }
+
+    /// Derive the key pair from a 32 byte secret
+    pub fn from_secret(secret: &[u8; 32]) -> Self {
+        let signing_key = SigningKey::from_bytes(secret);
+        let verifying_key: VerifyingKey = (&signing_key).into();
+        let public_key = hex::encode(verifying_key.to_bytes());
+
+        Self {
+            account: format!("k:{}", public_key),
+            private_key: hex::encode(signing_key.to_bytes()),
+            public_key,
+        }
+    }
+
+    /// Render the key pair in the given format
+    pub fn render(&self, format: KeyFormat) -> Result<String> {
+        match format {
+            KeyFormat::Plain => Ok(format!(
+                "public:  {}\nprivate: {}\n",
+                self.public_key, self.private_key
+            )),
+            KeyFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+            KeyFormat::Yaml => Ok(serde_yaml::to_string(self)?),
+            KeyFormat::Env => Ok(format!(
+                "export CHAINWEB_MINING_PUBLIC_KEY={}\nexport CHAINWEB_MINING_PRIVATE_KEY={}\nexport CHAINWEB_MINING_ACCOUNT={}\n",
+                self.public_key, self.private_key, self.account
+            )),
+        }
+    }
+}
+
+/// Write secret material to a new file readable only by the owner (0600 on Unix)
+pub fn write_private_file(path: &Path, contents: &str) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    // The mode only applies on creation; tighten pre-existing files as well
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_keys() -> KeyPair {
+        KeyPair::from_secret(&[7u8; 32])
+    }
+
+    #[test]
+    fn test_key_format_parsing() {
+        assert_eq!("json".parse::<KeyFormat>().unwrap(), KeyFormat::Json);
+        assert_eq!("YAML".parse::<KeyFormat>().unwrap(), KeyFormat::Yaml);
+        assert_eq!("env".parse::<KeyFormat>().unwrap(), KeyFormat::Env);
+        assert_eq!("plain".parse::<KeyFormat>().unwrap(), KeyFormat::Plain);
+        assert!("xml".parse::<KeyFormat>().is_err());
+    }
+
+    #[test]
+    fn test_account_derivation() {
+        let keys = test_keys();
+        assert_eq!(keys.public_key.len(), 64);
+        assert_eq!(keys.private_key, hex::encode([7u8; 32]));
+        assert_eq!(keys.account, format!("k:{}", keys.public_key));
+    }
+
+    #[test]
+    fn test_rng_failure_is_an_error() {
+        let err = KeyPair::generate_with(|_| Err(getrandom::Error::UNSUPPORTED)).unwrap_err();
+        assert!(err.to_string().contains("Failed to generate random bytes"));
+        assert_ne!(
+            KeyPair::generate().unwrap().private_key,
+            test_keys().private_key
+        );
+    }
+
+    #[test]
+    fn test_plain_format() {
+        let keys = test_keys();
+        let out = keys.render(KeyFormat::Plain).unwrap();
+        assert_eq!(
+            out,
+            format!(
+                "public:  {}\nprivate: {}\n",
+                keys.public_key, keys.private_key
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_format() {
+        let keys = test_keys();
+        let value: serde_json::Value =
+            serde_json::from_str(&keys.render(KeyFormat::Json).unwrap()).unwrap();
+        assert_eq!(value["public_key"], keys.public_key);
+        assert_eq!(value["private_key"], keys.private_key);
+        assert_eq!(value["account"], keys.account);
+    }
+
+    #[test]
+    fn test_yaml_format() {
+        let keys = test_keys();
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(&keys.render(KeyFormat::Yaml).unwrap()).unwrap();
+        assert_eq!(value["account"].as_str(), Some(keys.account.as_str()));
+        assert_eq!(value["public_key"].as_str(), Some(keys.public_key.as_str()));
+    }
+
+    #[test]
+    fn test_env_format() {
+        let keys = test_keys();
+        let out = keys.render(KeyFormat::Env).unwrap();
+        assert!(out.contains(&format!(
+            "export CHAINWEB_MINING_PUBLIC_KEY={}\n",
+            keys.public_key
+        )));
+        assert!(out.contains(&format!(
+            "export CHAINWEB_MINING_ACCOUNT={}\n",
+            keys.account
+        )));
+        assert_eq!(out.lines().count(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private_file(&path, "secret").unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "secret");
+    }
+}
@@ -4,6 +4,7 @@
 //! for better debugging and monitoring of mining operations.
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tracing::{Level, Span, field, span};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -237,7 +238,7 @@ macro_rules! log_worker_state {
 
 /// Performance logging for mining operations
 pub struct MiningMetrics {
-    start_time: std::time::Instant,
+    start_time: Instant,
     hashes: u64,
     solutions: u64,
     rejects: u64,
@@ -248,7 +249,7 @@ impl MiningMetrics {
     /// Create new mining metrics tracker
     pub fn new(context: LogContext) -> Self {
         Self {
-            start_time: std::time::Instant::now(),
+            start_time: Instant::now(),
             hashes: 0,
             solutions: 0,
             rejects: 0,
@@ -299,6 +300,39 @@ impl MiningMetrics {
     }
 }
 
+/// Limits how often a recurring log line is written
+#[derive(Debug, Clone)]
+pub struct LogThrottle {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl LogThrottle {
+    /// Allow one line per `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: None,
+        }
+    }
+
+    /// Whether the line should be written now; if so, the next one waits
+    /// for another interval
+    pub fn ready(&mut self) -> bool {
+        self.ready_at(Instant::now())
+    }
+
+    fn ready_at(&mut self, now: Instant) -> bool {
+        match self.last {
+            Some(last) if now.duration_since(last) < self.interval => false,
+            _ => {
+                self.last = Some(now);
+                true
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,4 +364,22 @@ mod tests {
         assert_eq!(metrics.solutions, 1);
         assert_eq!(metrics.rejects, 1);
     }
+
+    #[test]
+    fn test_log_throttle_allows_one_line_per_interval() {
+        let mut throttle = LogThrottle::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        // A loop iteration every 5ms for 35 seconds
+        let logged: Vec<Duration> = (0..7000)
+            .map(|i| start + Duration::from_millis(5 * i))
+            .filter(|&now| throttle.ready_at(now))
+            .map(|now| now - start)
+            .collect();
+        assert_eq!(logged, [0, 10, 20, 30].map(Duration::from_secs).to_vec());
+
+        // Without an interval every line is written
+        let mut throttle = LogThrottle::new(Duration::ZERO);
+        assert!((0..3).all(|_| throttle.ready_at(start)));
+    }
 }
@@ -1,15 +1,31 @@
 //! Utility functions and helpers
 
+pub mod clock;
+pub mod deadman;
+pub mod efficiency;
+pub mod events;
+pub mod keys;
 pub mod logging;
 pub mod memory;
 pub mod monitoring;
+pub mod notifier;
+pub mod panic;
+pub mod priority;
+pub mod selftest;
+pub mod thermal;
 pub mod units;
+pub mod watchdog;
 
-pub use logging::{LogContext, MiningMetrics, init_structured_logging};
+pub use efficiency::EfficiencyReport;
+pub use events::{EventSink, MiningEvent, SocketEventSink, emit_event, set_event_sink};
+pub use logging::{LogContext, LogThrottle, MiningMetrics, init_structured_logging};
 pub use monitoring::{
-    AlertConfig, HealthStatus, MonitoringSystem, PerformanceMetrics, global_monitoring,
+    AlertConfig, AlertSeverity, ChainDifficulty, HealthStatus, MonitoringSystem, NamedWorkerStats,
+    PerformanceMetrics, RejectReason, StatusFormat, StatusReport, global_monitoring,
     init_monitoring_with_pool,
 };
+pub use notifier::{AlertNotifier, WebhookNotifier};
+pub use panic::{install_panic_hook, spawn_monitored};
 
 use tracing_subscriber::EnvFilter;
 
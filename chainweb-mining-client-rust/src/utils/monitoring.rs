@@ -3,10 +3,21 @@
 //! This module provides comprehensive monitoring capabilities for production
 //! deployments, including metrics collection, health checks, and alerting.
 
-use crate::protocol::http_pool::HttpClientPool;
+use crate::core::{Level, Target, TargetArithmetic, TargetWords};
+use crate::error::{Error, Result};
+use crate::protocol::chainweb::Submission;
+use crate::protocol::confirmation::Confirmation;
+use crate::protocol::http_pool::{HttpClientPool, HttpPoolStats};
+use crate::utils::deadman::DrySpell;
+use crate::utils::efficiency::EfficiencyReport;
+use crate::utils::notifier::AlertNotifier;
+use crate::utils::thermal::ThermalEvent;
+use crate::utils::watchdog::Stall;
+use crate::workers::CancellationReason;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
@@ -25,6 +36,78 @@ pub enum HealthStatus {
     Down,
 }
 
+/// Reason a share or solution was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectReason {
+    /// Share does not meet the session difficulty
+    LowDifficulty,
+    /// Solution is for a job that no longer builds on the current parent
+    Stale,
+    /// Solution was already submitted
+    Duplicate,
+    /// Share references an unknown job
+    JobNotFound,
+    /// Node rejected the solution as invalid
+    NodeInvalid,
+    /// Submission could not be parsed
+    Malformed,
+    /// Worker reported a solution whose hash doesn't meet the target
+    InvalidSolution,
+}
+
+impl RejectReason {
+    /// Label used in reports and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectReason::LowDifficulty => "low-difficulty",
+            RejectReason::Stale => "stale",
+            RejectReason::Duplicate => "duplicate",
+            RejectReason::JobNotFound => "job-not-found",
+            RejectReason::NodeInvalid => "node-invalid",
+            RejectReason::Malformed => "malformed",
+            RejectReason::InvalidSolution => "invalid-solution",
+        }
+    }
+}
+
+/// Difficulty of the latest work received for a chain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChainDifficulty {
+    /// Leading zero bits of the target
+    pub level: u32,
+    /// Expected seconds to solve at the hash rate when the work arrived
+    pub expected_block_time_secs: f64,
+}
+
+/// Share statistics of a named stratum worker, summed over its sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct NamedWorkerStats {
+    /// Shares submitted
+    pub shares_submitted: u64,
+    /// Shares accepted
+    pub shares_accepted: u64,
+    /// Estimated hash rate of the connected sessions (hashes per second)
+    pub hash_rate: f64,
+    /// Connected sessions
+    pub sessions: usize,
+}
+
+impl NamedWorkerStats {
+    /// Shares rejected
+    pub fn shares_rejected(&self) -> u64 {
+        self.shares_submitted - self.shares_accepted
+    }
+
+    /// Fraction of submitted shares that were rejected (0.0 to 1.0)
+    pub fn reject_rate(&self) -> f64 {
+        if self.shares_submitted == 0 {
+            0.0
+        } else {
+            self.shares_rejected() as f64 / self.shares_submitted as f64
+        }
+    }
+}
+
 /// Performance metrics for monitoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceMetrics {
@@ -48,6 +131,15 @@ pub struct PerformanceMetrics {
     pub cpu_utilization: f64,
     /// Uptime in seconds
     pub uptime_seconds: u64,
+    /// Hash rate of each CPU mining thread, indexed by thread
+    #[serde(default)]
+    pub thread_hash_rates: Vec<f64>,
+    /// Median solution submission latency (milliseconds)
+    #[serde(default)]
+    pub submit_latency_p50_ms: f64,
+    /// 95th percentile solution submission latency (milliseconds)
+    #[serde(default)]
+    pub submit_latency_p95_ms: f64,
 }
 
 impl Default for PerformanceMetrics {
@@ -63,6 +155,9 @@ impl Default for PerformanceMetrics {
             memory_usage_bytes: 0,
             cpu_utilization: 0.0,
             uptime_seconds: 0,
+            thread_hash_rates: Vec::new(),
+            submit_latency_p50_ms: 0.0,
+            submit_latency_p95_ms: 0.0,
         }
     }
 }
@@ -74,6 +169,8 @@ pub struct AlertConfig {
     pub min_hash_rate: f64,
     /// Maximum response time before alerting (milliseconds)
     pub max_response_time_ms: f64,
+    /// Maximum solution submission latency before alerting (milliseconds)
+    pub max_submit_latency_ms: f64,
     /// Minimum acceptance rate before alerting (0.0 to 1.0)
     pub min_acceptance_rate: f64,
     /// Maximum memory usage before alerting (bytes)
@@ -89,14 +186,17 @@ impl Default for AlertConfig {
         let mut enabled_alerts = HashMap::new();
         enabled_alerts.insert("hash_rate".to_string(), true);
         enabled_alerts.insert("response_time".to_string(), true);
+        enabled_alerts.insert("submit_latency".to_string(), true);
         enabled_alerts.insert("acceptance_rate".to_string(), true);
         enabled_alerts.insert("memory_usage".to_string(), true);
         enabled_alerts.insert("cpu_usage".to_string(), true);
         enabled_alerts.insert("connection_issues".to_string(), true);
+        enabled_alerts.insert("worker_restart".to_string(), true);
 
         Self {
             min_hash_rate: 1000.0,                      // 1 KH/s minimum
             max_response_time_ms: 10000.0,              // 10 seconds max
+            max_submit_latency_ms: 2000.0,              // 2 seconds max
             min_acceptance_rate: 0.9,                   // 90% minimum acceptance
             max_memory_usage_bytes: 1024 * 1024 * 1024, // 1 GB max
             max_cpu_utilization: 95.0,                  // 95% max CPU
@@ -105,8 +205,8 @@ impl Default for AlertConfig {
     }
 }
 
-/// Alert severity levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Alert severity levels, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AlertSeverity {
     /// Informational alerts for general status updates
     Info,
@@ -118,6 +218,65 @@ pub enum AlertSeverity {
     Emergency,
 }
 
+impl FromStr for AlertSeverity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(AlertSeverity::Info),
+            "warning" => Ok(AlertSeverity::Warning),
+            "critical" => Ok(AlertSeverity::Critical),
+            "emergency" => Ok(AlertSeverity::Emergency),
+            _ => Err(Error::config_invalid_value(
+                "alert_min_severity",
+                s,
+                "info, warning, critical or emergency",
+            )),
+        }
+    }
+}
+
+/// Output format of the status report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusFormat {
+    /// Human readable report
+    #[default]
+    Text,
+    /// [`StatusReport`] as JSON, for tooling
+    Json,
+}
+
+impl FromStr for StatusFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(StatusFormat::Text),
+            "json" => Ok(StatusFormat::Json),
+            _ => Err(Error::config_invalid_value("format", s, "text or json")),
+        }
+    }
+}
+
+/// Snapshot of the monitoring state, the structured form of the status report
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    /// Overall health
+    pub health: HealthStatus,
+    /// Performance metrics
+    pub metrics: PerformanceMetrics,
+    /// Share statistics of named stratum workers
+    pub workers: BTreeMap<String, NamedWorkerStats>,
+    /// Rejected shares and solutions by reason
+    pub rejections: BTreeMap<String, u64>,
+    /// Cancelled mining operations by reason
+    pub cancellations: BTreeMap<String, u64>,
+    /// Most recent alerts
+    pub recent_alerts: Vec<Alert>,
+    /// HTTP client pool statistics, if a pool is attached
+    pub http_pool: Option<HttpPoolStats>,
+}
+
 /// Alert message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
@@ -198,6 +357,18 @@ impl TimeSeries {
             .fold(f64::INFINITY, f64::min)
     }
 
+    /// Value below which `quantile` (0.0 to 1.0) of the samples fall,
+    /// using the nearest-rank method; 0.0 without samples
+    pub fn percentile(&self, quantile: f64) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        let mut sorted: Vec<f64> = self.values.iter().map(|(_, v)| *v).collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = (quantile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.saturating_sub(1)]
+    }
+
     /// Calculate the linear trend of the time series
     /// Returns the slope of the best-fit line through the data points
     pub fn trend(&self) -> f64 {
@@ -226,6 +397,20 @@ impl TimeSeries {
     }
 }
 
+/// Hashes computed so far, integrated from hash rate samples
+#[derive(Debug, Default)]
+struct HashAccounting {
+    /// Time and value of the latest hash rate sample
+    last_sample: Option<(Instant, f64)>,
+    total_hashes: f64,
+    /// Blocks expected from `total_hashes` at the difficulty each was computed for
+    expected_blocks: f64,
+    /// Difficulty of the latest work received
+    difficulty: Option<f64>,
+    /// `expected_blocks` and time at the latest solution
+    last_solution: Option<(Instant, f64)>,
+}
+
 /// Comprehensive monitoring system
 pub struct MonitoringSystem {
     /// System configuration
@@ -234,20 +419,37 @@ pub struct MonitoringSystem {
     metrics: RwLock<PerformanceMetrics>,
     /// Recent alerts
     recent_alerts: RwLock<VecDeque<Alert>>,
+    /// External alert destinations with their minimum severity
+    notifiers: RwLock<Vec<(AlertSeverity, Arc<dyn AlertNotifier>)>>,
     /// Time series data
     hash_rate_series: RwLock<TimeSeries>,
     response_time_series: RwLock<TimeSeries>,
+    submit_latency_series: RwLock<TimeSeries>,
     memory_usage_series: RwLock<TimeSeries>,
     /// Counters
     solutions_counter: AtomicU64,
     shares_counter: AtomicU64,
     accepted_shares_counter: AtomicU64,
+    accepted_blocks_counter: AtomicU64,
+    rejected_blocks_counter: AtomicU64,
+    confirmed_blocks_counter: AtomicU64,
+    orphaned_blocks_counter: AtomicU64,
+    work_updates_counter: AtomicU64,
+    /// Hashes and expected blocks for the efficiency report
+    hash_accounting: RwLock<HashAccounting>,
+    /// Rejections by reason
+    rejection_reasons: RwLock<HashMap<String, u64>>,
+    cancellation_reasons: RwLock<HashMap<String, u64>>,
+    /// Latest difficulty per chain
+    chain_difficulty: RwLock<BTreeMap<u16, ChainDifficulty>>,
+    /// Stratum worker statistics by worker name
+    named_workers: RwLock<BTreeMap<String, NamedWorkerStats>>,
     /// Status tracking
     system_start_time: Instant,
     monitoring_enabled: AtomicBool,
     last_health_check: RwLock<Instant>,
     /// HTTP pool reference for monitoring
-    http_pool: Option<Arc<HttpClientPool>>,
+    http_pool: RwLock<Option<Arc<HttpClientPool>>>,
 }
 
 impl MonitoringSystem {
@@ -257,32 +459,64 @@ impl MonitoringSystem {
             config: RwLock::new(AlertConfig::default()),
             metrics: RwLock::new(PerformanceMetrics::default()),
             recent_alerts: RwLock::new(VecDeque::new()),
+            notifiers: RwLock::new(Vec::new()),
             hash_rate_series: RwLock::new(TimeSeries::new(Duration::from_secs(3600), 3600)),
             response_time_series: RwLock::new(TimeSeries::new(Duration::from_secs(3600), 3600)),
+            submit_latency_series: RwLock::new(TimeSeries::new(Duration::from_secs(3600), 1000)),
             memory_usage_series: RwLock::new(TimeSeries::new(Duration::from_secs(3600), 3600)),
             solutions_counter: AtomicU64::new(0),
             shares_counter: AtomicU64::new(0),
             accepted_shares_counter: AtomicU64::new(0),
+            accepted_blocks_counter: AtomicU64::new(0),
+            rejected_blocks_counter: AtomicU64::new(0),
+            confirmed_blocks_counter: AtomicU64::new(0),
+            orphaned_blocks_counter: AtomicU64::new(0),
+            work_updates_counter: AtomicU64::new(0),
+            hash_accounting: RwLock::new(HashAccounting::default()),
+            rejection_reasons: RwLock::new(HashMap::new()),
+            cancellation_reasons: RwLock::new(HashMap::new()),
+            chain_difficulty: RwLock::new(BTreeMap::new()),
+            named_workers: RwLock::new(BTreeMap::new()),
             system_start_time: Instant::now(),
             monitoring_enabled: AtomicBool::new(true),
             last_health_check: RwLock::new(Instant::now()),
-            http_pool: None,
+            http_pool: RwLock::new(None),
         }
     }
 
     /// Create monitoring system with HTTP pool reference
     pub fn with_http_pool(http_pool: Arc<HttpClientPool>) -> Self {
-        let mut monitor = Self::new();
-        monitor.http_pool = Some(http_pool);
+        let monitor = Self::new();
+        monitor.attach_http_pool(http_pool);
         monitor
     }
 
+    /// Attach the HTTP pool whose stats are included in health checks and
+    /// reports, replacing any previously attached pool
+    pub fn attach_http_pool(&self, http_pool: Arc<HttpClientPool>) {
+        *self.http_pool.write() = Some(http_pool);
+    }
+
+    /// Forward alerts of at least `min_severity` to `notifier`
+    pub fn add_alert_notifier(
+        &self,
+        notifier: Arc<dyn AlertNotifier>,
+        min_severity: AlertSeverity,
+    ) {
+        self.notifiers.write().push((min_severity, notifier));
+    }
+
     /// Update configuration
     pub fn update_config(&self, config: AlertConfig) {
         *self.config.write() = config;
         info!("Monitoring configuration updated");
     }
 
+    /// Current alert configuration
+    pub fn alert_config(&self) -> AlertConfig {
+        self.config.read().clone()
+    }
+
     /// Record hash rate measurement
     pub fn record_hash_rate(&self, hash_rate: f64) {
         if !self.monitoring_enabled.load(Ordering::Relaxed) {
@@ -291,6 +525,17 @@ impl MonitoringSystem {
 
         self.hash_rate_series.write().add_sample(hash_rate);
 
+        // The previous rate was in effect until this sample
+        let now = Instant::now();
+        let previous = self
+            .hash_accounting
+            .write()
+            .last_sample
+            .replace((now, hash_rate));
+        if let Some((at, rate)) = previous {
+            self.record_hashes(rate * now.duration_since(at).as_secs_f64());
+        }
+
         let mut metrics = self.metrics.write();
         metrics.hash_rate = hash_rate;
         metrics.avg_hash_rate = self.hash_rate_series.read().average();
@@ -316,6 +561,56 @@ impl MonitoringSystem {
         }
     }
 
+    /// Record the hash rate of each CPU mining thread
+    pub fn record_thread_hash_rates(&self, rates: &[f64]) {
+        if !self.monitoring_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut metrics = self.metrics.write();
+        metrics.thread_hash_rates.clear();
+        metrics.thread_hash_rates.extend_from_slice(rates);
+    }
+
+    /// Record how long submitting a solution to the node took, including retries
+    pub fn record_submit_latency(&self, latency_ms: f64) {
+        if !self.monitoring_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let (p50, p95) = {
+            let mut series = self.submit_latency_series.write();
+            series.add_sample(latency_ms);
+            (series.percentile(0.5), series.percentile(0.95))
+        };
+        {
+            let mut metrics = self.metrics.write();
+            metrics.submit_latency_p50_ms = p50;
+            metrics.submit_latency_p95_ms = p95;
+        }
+
+        let config = self.config.read();
+        if *config.enabled_alerts.get("submit_latency").unwrap_or(&true)
+            && latency_ms > config.max_submit_latency_ms
+        {
+            self.create_alert(
+                AlertSeverity::Warning,
+                "submit_latency",
+                &format!(
+                    "Solution submission took {:.2}ms, exceeding {:.2}ms; slow submits risk orphaned blocks",
+                    latency_ms, config.max_submit_latency_ms
+                ),
+                vec![
+                    ("current_latency".to_string(), latency_ms.to_string()),
+                    (
+                        "maximum_latency".to_string(),
+                        config.max_submit_latency_ms.to_string(),
+                    ),
+                ],
+            );
+        }
+    }
+
     /// Record response time measurement
     pub fn record_response_time(&self, response_time_ms: f64) {
         if !self.monitoring_enabled.load(Ordering::Relaxed) {
@@ -354,6 +649,10 @@ impl MonitoringSystem {
 
     /// Record solution found
     pub fn record_solution(&self) {
+        {
+            let mut accounting = self.hash_accounting.write();
+            accounting.last_solution = Some((Instant::now(), accounting.expected_blocks));
+        }
         self.solutions_counter.fetch_add(1, Ordering::Relaxed);
         let mut metrics = self.metrics.write();
         metrics.solutions_found = self.solutions_counter.load(Ordering::Relaxed);
@@ -361,6 +660,64 @@ impl MonitoringSystem {
         info!("Solution found - total: {}", metrics.solutions_found);
     }
 
+    /// Count hashes computed for the latest work
+    pub fn record_hashes(&self, hashes: f64) {
+        let mut accounting = self.hash_accounting.write();
+        accounting.total_hashes += hashes;
+        if let Some(difficulty) = accounting.difficulty {
+            accounting.expected_blocks += hashes / difficulty;
+        }
+    }
+
+    /// Hashes computed so far
+    pub fn total_hashes(&self) -> f64 {
+        self.hash_accounting.read().total_hashes
+    }
+
+    /// Blocks expected since the latest solution, or since startup if there
+    /// was none, and the time that passed meanwhile
+    pub fn dry_spell(&self) -> DrySpell {
+        let accounting = self.hash_accounting.read();
+        let (since, blocks_before) = accounting
+            .last_solution
+            .unwrap_or((self.system_start_time, 0.0));
+        DrySpell {
+            expected_blocks: accounting.expected_blocks - blocks_before,
+            elapsed: since.elapsed(),
+        }
+    }
+
+    /// Record new work received from the node
+    pub fn record_work_update(&self) {
+        self.work_updates_counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Work updates received so far
+    pub fn work_updates(&self) -> u64 {
+        self.work_updates_counter.load(Ordering::Relaxed)
+    }
+
+    /// Record the node's answer to a submitted solution; a duplicate that
+    /// wasn't sent again counts neither way
+    pub fn record_submission(&self, outcome: &Result<Submission>) {
+        let counter = match outcome {
+            Ok(Submission::Accepted) => &self.accepted_blocks_counter,
+            Ok(Submission::Duplicate) => return,
+            Err(_) => &self.rejected_blocks_counter,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record whether an accepted block ended up on the winning chain
+    pub fn record_block_confirmation(&self, confirmation: Confirmation) {
+        let counter = match confirmation {
+            Confirmation::Confirmed => &self.confirmed_blocks_counter,
+            Confirmation::Orphaned => &self.orphaned_blocks_counter,
+            Confirmation::Unconfirmed => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Record share submission
     pub fn record_share_submitted(&self, accepted: bool) {
         self.shares_counter.fetch_add(1, Ordering::Relaxed);
@@ -412,6 +769,66 @@ impl MonitoringSystem {
         }
     }
 
+    /// Record why a share or solution was rejected
+    pub fn record_rejection(&self, reason: RejectReason) {
+        *self
+            .rejection_reasons
+            .write()
+            .entry(reason.as_str().to_string())
+            .or_insert(0) += 1;
+        debug!("Rejection recorded: {}", reason.as_str());
+    }
+
+    /// Rejection counts by reason
+    pub fn get_rejection_reasons(&self) -> HashMap<String, u64> {
+        self.rejection_reasons.read().clone()
+    }
+
+    /// Record why a worker's mining was cancelled
+    pub fn record_cancellation(&self, reason: CancellationReason) {
+        *self
+            .cancellation_reasons
+            .write()
+            .entry(reason.as_str().to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Cancellation counts by reason
+    pub fn get_cancellation_reasons(&self) -> HashMap<String, u64> {
+        self.cancellation_reasons.read().clone()
+    }
+
+    /// Record the target of work received for `chain`
+    ///
+    /// The expected block time uses the hash rate at the time of the call.
+    pub fn record_chain_target(&self, chain: u16, target: &Target) {
+        let words = TargetWords::from(target);
+        let hash_rate = self.metrics.read().hash_rate;
+        let difficulty = ChainDifficulty {
+            level: Level::from_target(&words).value(),
+            expected_block_time_secs: TargetArithmetic::expected_block_time(&words, hash_rate),
+        };
+        self.chain_difficulty.write().insert(chain, difficulty);
+        // Seconds per block at 1 H/s, i.e. the expected hashes per block
+        self.hash_accounting.write().difficulty =
+            Some(TargetArithmetic::expected_block_time(&words, 1.0));
+    }
+
+    /// Latest difficulty per chain, ordered by chain id
+    pub fn get_chain_difficulty(&self) -> BTreeMap<u16, ChainDifficulty> {
+        self.chain_difficulty.read().clone()
+    }
+
+    /// Record the latest statistics of the stratum worker `name`
+    pub fn record_named_worker(&self, name: &str, stats: NamedWorkerStats) {
+        self.named_workers.write().insert(name.to_string(), stats);
+    }
+
+    /// Stratum worker statistics, ordered by worker name
+    pub fn get_named_workers(&self) -> BTreeMap<String, NamedWorkerStats> {
+        self.named_workers.read().clone()
+    }
+
     /// Update memory usage
     pub fn record_memory_usage(&self, memory_bytes: u64) {
         if !self.monitoring_enabled.load(Ordering::Relaxed) {
@@ -480,6 +897,105 @@ impl MonitoringSystem {
         }
     }
 
+    /// Record that a supervised worker could not be recovered by restarting it
+    pub fn record_worker_restart_exhausted(&self, worker_type: &str, attempts: u32) {
+        let config = self.config.read();
+        if *config.enabled_alerts.get("worker_restart").unwrap_or(&true) {
+            self.create_alert(
+                AlertSeverity::Critical,
+                "worker_restart",
+                &format!(
+                    "{} worker failed to recover after {} restart attempts",
+                    worker_type, attempts
+                ),
+                vec![
+                    ("worker_type".to_string(), worker_type.to_string()),
+                    ("restart_attempts".to_string(), attempts.to_string()),
+                ],
+            );
+        }
+    }
+
+    /// Record a panic, either caught by the panic hook on `thread` or
+    /// surfaced as the join error of the task named `thread`
+    pub fn record_panic(&self, thread: &str, message: &str) {
+        let config = self.config.read();
+        if *config.enabled_alerts.get("panic").unwrap_or(&true) {
+            self.create_alert(
+                AlertSeverity::Critical,
+                "panic",
+                &format!("{} panicked: {}", thread, message),
+                vec![
+                    ("thread".to_string(), thread.to_string()),
+                    ("message".to_string(), message.to_string()),
+                ],
+            );
+        }
+    }
+
+    /// Record that the dead man's switch tripped over a dry spell
+    pub fn record_dry_spell(&self, spell: &DrySpell) {
+        let config = self.config.read();
+        if *config.enabled_alerts.get("dry_spell").unwrap_or(&true) {
+            self.create_alert(
+                AlertSeverity::Critical,
+                "dry_spell",
+                &format!("Mining may be broken: {}", spell),
+                vec![
+                    (
+                        "expected_blocks".to_string(),
+                        format!("{:.1}", spell.expected_blocks),
+                    ),
+                    (
+                        "elapsed_secs".to_string(),
+                        spell.elapsed.as_secs().to_string(),
+                    ),
+                ],
+            );
+        }
+    }
+
+    /// Record that the GPU overheated or cooled down again
+    pub fn record_thermal_event(&self, event: &ThermalEvent) {
+        let config = self.config.read();
+        if *config.enabled_alerts.get("thermal").unwrap_or(&true) {
+            let (severity, temp_c, limit_c) = match *event {
+                ThermalEvent::Overheated { temp_c, limit_c } => {
+                    (AlertSeverity::Critical, temp_c, limit_c)
+                }
+                ThermalEvent::Cooled { temp_c, limit_c } => (AlertSeverity::Info, temp_c, limit_c),
+            };
+            self.create_alert(
+                severity,
+                "thermal",
+                &event.to_string(),
+                vec![
+                    ("temp_c".to_string(), format!("{:.1}", temp_c)),
+                    ("limit_c".to_string(), format!("{:.1}", limit_c)),
+                ],
+            );
+        }
+    }
+
+    /// Record that the watchdog found a progress indicator stuck
+    pub fn record_watchdog_stall(&self, stall: &Stall) {
+        let config = self.config.read();
+        if *config.enabled_alerts.get("watchdog").unwrap_or(&true) {
+            self.create_alert(
+                AlertSeverity::Critical,
+                "watchdog",
+                &format!("Mining appears stuck: {}", stall),
+                vec![
+                    ("indicator".to_string(), stall.indicator.to_string()),
+                    (
+                        "stalled_secs".to_string(),
+                        stall.stalled_for.as_secs().to_string(),
+                    ),
+                ],
+            );
+        }
+    }
+
     /// Perform comprehensive health check
     pub fn health_check(&self) -> HealthStatus {
         let now = Instant::now();
@@ -517,7 +1033,7 @@ impl MonitoringSystem {
         }
 
         // HTTP pool health check
-        if let Some(ref pool) = self.http_pool {
+        if let Some(pool) = self.http_pool.read().as_ref() {
             let pool_stats = pool.get_stats();
             if let Some(hit_rate) = pool_stats.cache_hit_rate {
                 if hit_rate < 0.8 {
@@ -582,7 +1098,57 @@ impl MonitoringSystem {
         }
     }
 
-    /// Generate comprehensive status report
+    /// Summary of the run so far, printed on shutdown
+    pub fn efficiency_report(&self) -> EfficiencyReport {
+        let runtime = self.system_start_time.elapsed();
+        let accounting = self.hash_accounting.read();
+        let stale = self
+            .rejection_reasons
+            .read()
+            .get(RejectReason::Stale.as_str())
+            .copied();
+        EfficiencyReport {
+            runtime_secs: runtime.as_secs(),
+            total_hashes: accounting.total_hashes,
+            avg_hash_rate: if runtime.is_zero() {
+                0.0
+            } else {
+                accounting.total_hashes / runtime.as_secs_f64()
+            },
+            blocks_found: self.solutions_counter.load(Ordering::Relaxed),
+            blocks_accepted: self.accepted_blocks_counter.load(Ordering::Relaxed),
+            blocks_rejected: self.rejected_blocks_counter.load(Ordering::Relaxed),
+            blocks_confirmed: self.confirmed_blocks_counter.load(Ordering::Relaxed),
+            blocks_orphaned: self.orphaned_blocks_counter.load(Ordering::Relaxed),
+            blocks_stale: stale.unwrap_or(0),
+            shares_submitted: self.shares_counter.load(Ordering::Relaxed),
+            shares_accepted: self.accepted_shares_counter.load(Ordering::Relaxed),
+            expected_blocks: accounting.expected_blocks,
+        }
+    }
+
+    /// Snapshot of the monitoring state for the status report
+    pub fn status_report(&self) -> StatusReport {
+        StatusReport {
+            health: self.health_check(),
+            metrics: self.get_metrics(),
+            workers: self.get_named_workers(),
+            rejections: self.get_rejection_reasons().into_iter().collect(),
+            cancellations: self.get_cancellation_reasons().into_iter().collect(),
+            recent_alerts: self.get_recent_alerts(5),
+            http_pool: self.http_pool.read().as_ref().map(|pool| pool.get_stats()),
+        }
+    }
+
+    /// Render the status report in `format`
+    pub fn render_status_report(&self, format: StatusFormat) -> Result<String> {
+        match format {
+            StatusFormat::Text => Ok(self.generate_status_report()),
+            StatusFormat::Json => Ok(serde_json::to_string_pretty(&self.status_report())?),
+        }
+    }
+
+    /// Human readable status report
     pub fn generate_status_report(&self) -> String {
         let metrics = self.get_metrics();
         let health = self.health_check();
@@ -607,6 +1173,10 @@ impl MonitoringSystem {
             "Response Time: {:.2} ms\n",
             metrics.avg_response_time_ms
         ));
+        report.push_str(&format!(
+            "Submit Latency: p50 {:.2} ms, p95 {:.2} ms\n",
+            metrics.submit_latency_p50_ms, metrics.submit_latency_p95_ms
+        ));
         report.push_str(&format!(
             "Memory Usage: {} bytes\n",
             metrics.memory_usage_bytes
@@ -616,6 +1186,46 @@ impl MonitoringSystem {
             metrics.cpu_utilization
         ));
 
+        if !metrics.thread_hash_rates.is_empty() {
+            report.push_str("\n--- CPU Threads ---\n");
+            for (thread, rate) in metrics.thread_hash_rates.iter().enumerate() {
+                report.push_str(&format!("Thread {}: {:.2} H/s\n", thread, rate));
+            }
+        }
+
+        let workers = self.get_named_workers();
+        if !workers.is_empty() {
+            report.push_str("\n--- Stratum Workers ---\n");
+            for (name, stats) in &workers {
+                report.push_str(&format!(
+                    "{}: {} shares ({:.1}% rejected), {:.2} H/s, {} session(s)\n",
+                    name,
+                    stats.shares_submitted,
+                    stats.reject_rate() * 100.0,
+                    stats.hash_rate,
+                    stats.sessions
+                ));
+            }
+        }
+
+        let mut rejections: Vec<_> = self.get_rejection_reasons().into_iter().collect();
+        if !rejections.is_empty() {
+            rejections.sort();
+            report.push_str("\n--- Rejections ---\n");
+            for (reason, count) in rejections {
+                report.push_str(&format!("{}: {}\n", reason, count));
+            }
+        }
+
+        let mut cancellations: Vec<_> = self.get_cancellation_reasons().into_iter().collect();
+        if !cancellations.is_empty() {
+            cancellations.sort();
+            report.push_str("\n--- Cancellations ---\n");
+            for (reason, count) in cancellations {
+                report.push_str(&format!("{}: {}\n", reason, count));
+            }
+        }
+
         if !recent_alerts.is_empty() {
             report.push_str(&format!(
                 "\n--- Recent Alerts ({}) ---\n",
@@ -629,7 +1239,7 @@ impl MonitoringSystem {
             }
         }
 
-        if let Some(ref pool) = self.http_pool {
+        if let Some(pool) = self.http_pool.read().as_ref() {
             let pool_stats = pool.get_stats();
             report.push_str("\n--- HTTP Pool Stats ---\n");
             report.push_str(&format!("Active Clients: {}\n", pool_stats.active_clients));
@@ -642,6 +1252,131 @@ impl MonitoringSystem {
         report
     }
 
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn prometheus_metrics(&self) -> String {
+        let metrics = self.get_metrics();
+        let mut out = String::new();
+
+        let mut gauge = |name: &str, help: &str, kind: &str, value: String| {
+            out.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n{} {}\n",
+                name, help, name, kind, name, value
+            ));
+        };
+        gauge(
+            "chainweb_mining_hash_rate",
+            "Current hash rate in hashes per second",
+            "gauge",
+            metrics.hash_rate.to_string(),
+        );
+        gauge(
+            "chainweb_mining_solutions_total",
+            "Solutions found",
+            "counter",
+            metrics.solutions_found.to_string(),
+        );
+        gauge(
+            "chainweb_mining_shares_total",
+            "Shares submitted",
+            "counter",
+            metrics.shares_submitted.to_string(),
+        );
+        gauge(
+            "chainweb_mining_acceptance_rate",
+            "Share acceptance rate (0 to 1)",
+            "gauge",
+            metrics.acceptance_rate.to_string(),
+        );
+        gauge(
+            "chainweb_mining_uptime_seconds",
+            "Uptime in seconds",
+            "gauge",
+            metrics.uptime_seconds.to_string(),
+        );
+
+        let mut rejections: Vec<_> = self.get_rejection_reasons().into_iter().collect();
+        rejections.sort();
+        out.push_str(
+            "# HELP chainweb_mining_rejections_total Rejected shares and solutions by reason\n",
+        );
+        out.push_str("# TYPE chainweb_mining_rejections_total counter\n");
+        for (reason, count) in rejections {
+            out.push_str(&format!(
+                "chainweb_mining_rejections_total{{reason=\"{}\"}} {}\n",
+                reason, count
+            ));
+        }
+
+        let mut cancellations: Vec<_> = self.get_cancellation_reasons().into_iter().collect();
+        cancellations.sort();
+        out.push_str(
+            "# HELP chainweb_mining_cancellations_total Cancelled mining operations by reason\n",
+        );
+        out.push_str("# TYPE chainweb_mining_cancellations_total counter\n");
+        for (reason, count) in cancellations {
+            out.push_str(&format!(
+                "chainweb_mining_cancellations_total{{reason=\"{}\"}} {}\n",
+                reason, count
+            ));
+        }
+
+        let chains = self.get_chain_difficulty();
+        out.push_str("# HELP chainweb_chain_difficulty_level Leading zero bits of the latest work target per chain\n");
+        out.push_str("# TYPE chainweb_chain_difficulty_level gauge\n");
+        for (chain, difficulty) in &chains {
+            out.push_str(&format!(
+                "chainweb_chain_difficulty_level{{chain=\"{}\"}} {}\n",
+                chain, difficulty.level
+            ));
+        }
+        out.push_str("# HELP chainweb_chain_expected_block_time_seconds Expected time to solve the latest work per chain at the current hash rate\n");
+        out.push_str("# TYPE chainweb_chain_expected_block_time_seconds gauge\n");
+        for (chain, difficulty) in &chains {
+            out.push_str(&format!(
+                "chainweb_chain_expected_block_time_seconds{{chain=\"{}\"}} {}\n",
+                chain,
+                prometheus_float(difficulty.expected_block_time_secs)
+            ));
+        }
+
+        let workers = self.get_named_workers();
+        let mut worker_metric =
+            |name: &str, help: &str, kind: &str, value: &dyn Fn(&NamedWorkerStats) -> String| {
+                out.push_str(&format!(
+                    "# HELP {} {}\n# TYPE {} {}\n",
+                    name, help, name, kind
+                ));
+                for (worker, stats) in &workers {
+                    out.push_str(&format!(
+                        "{}{{worker=\"{}\"}} {}\n",
+                        name,
+                        prometheus_label(worker),
+                        value(stats)
+                    ));
+                }
+            };
+        worker_metric(
+            "chainweb_stratum_worker_shares_total",
+            "Shares submitted per stratum worker name",
+            "counter",
+            &|s| s.shares_submitted.to_string(),
+        );
+        worker_metric(
+            "chainweb_stratum_worker_rejected_shares_total",
+            "Shares rejected per stratum worker name",
+            "counter",
+            &|s| s.shares_rejected().to_string(),
+        );
+        worker_metric(
+            "chainweb_stratum_worker_hash_rate",
+            "Estimated hash rate per stratum worker name",
+            "gauge",
+            &|s| prometheus_float(s.hash_rate),
+        );
+
+        out
+    }
+
     /// Internal method to create alerts
     fn create_alert(
         &self,
@@ -663,6 +1398,12 @@ impl MonitoringSystem {
             timestamp,
         };
 
+        crate::utils::events::emit_event(crate::utils::events::MiningEvent::Alert {
+            severity: format!("{:?}", severity),
+            category: category.to_string(),
+            message: message.to_string(),
+        });
+
         // Log the alert
         match severity {
             AlertSeverity::Info => info!("[ALERT] {}: {}", category, message),
@@ -671,6 +1412,12 @@ impl MonitoringSystem {
             AlertSeverity::Emergency => error!("[EMERGENCY] {}: {}", category, message),
         }
 
+        for (min_severity, notifier) in self.notifiers.read().iter() {
+            if severity >= *min_severity {
+                notifier.notify(&alert);
+            }
+        }
+
         // Store the alert
         let mut alerts = self.recent_alerts.write();
         alerts.push_back(alert);
@@ -682,6 +1429,27 @@ impl MonitoringSystem {
     }
 }
 
+/// Escape a label value for the Prometheus text format
+fn prometheus_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Format a float sample the way Prometheus expects non-finite values
+fn prometheus_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
 impl Default for MonitoringSystem {
     fn default() -> Self {
         Self::new()
@@ -697,8 +1465,10 @@ pub fn global_monitoring() -> &'static MonitoringSystem {
 }
 
 /// Initialize global monitoring with HTTP pool
+///
+/// If the global monitor was already used, the pool is attached to it.
 pub fn init_monitoring_with_pool(http_pool: Arc<HttpClientPool>) {
-    let _ = MONITORING.set(MonitoringSystem::with_http_pool(http_pool));
+    global_monitoring().attach_http_pool(http_pool);
 }
 
 #[cfg(test)]
@@ -767,6 +1537,82 @@ mod tests {
         assert_eq!(alerts[0].severity, AlertSeverity::Warning);
     }
 
+    #[test]
+    fn test_worker_restart_exhausted_alert() {
+        let monitor = MonitoringSystem::new();
+        monitor.record_worker_restart_exhausted("GPU", 5);
+
+        let alerts = monitor.get_recent_alerts(10);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].category, "worker_restart");
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert_eq!(
+            alerts[0].context.get("restart_attempts"),
+            Some(&"5".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_alerts_below_min_severity_are_not_posted() {
+        let mut server = mockito::Server::new_async().await;
+        let webhook = server
+            .mock("POST", "/alerts")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "severity": "Critical",
+                "category": "worker_restart",
+                "context": { "restart_attempts": "5" },
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+        let unexpected = server
+            .mock("POST", "/alerts")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({ "severity": "Info" }),
+            ))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let monitor = MonitoringSystem::new();
+        let notifier = crate::utils::notifier::WebhookNotifier::new(
+            format!("{}/alerts", server.url()),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        monitor.add_alert_notifier(Arc::new(notifier), AlertSeverity::Critical);
+
+        monitor.create_alert(AlertSeverity::Info, "status", "all good", vec![]);
+        monitor.record_worker_restart_exhausted("GPU", 5);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !webhook.matched_async().await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        webhook.assert_async().await;
+        unexpected.assert_async().await;
+        assert_eq!(monitor.get_recent_alerts(10).len(), 2);
+    }
+
+    #[test]
+    fn test_alert_severity_parsing() {
+        assert_eq!(
+            "critical".parse::<AlertSeverity>().unwrap(),
+            AlertSeverity::Critical
+        );
+        assert_eq!(
+            "Warning".parse::<AlertSeverity>().unwrap(),
+            AlertSeverity::Warning
+        );
+        assert!("loud".parse::<AlertSeverity>().is_err());
+        assert!(AlertSeverity::Info < AlertSeverity::Warning);
+        assert!(AlertSeverity::Critical < AlertSeverity::Emergency);
+    }
+
     #[test]
     fn test_time_series() {
         let mut series = TimeSeries::new(Duration::from_secs(60), 100);
@@ -778,6 +1624,87 @@ mod tests {
         assert_eq!(series.average(), 20.0);
         assert_eq!(series.max(), 30.0);
         assert_eq!(series.min(), 10.0);
+        assert_eq!(series.percentile(0.5), 20.0);
+        assert_eq!(series.percentile(1.0), 30.0);
+        assert_eq!(
+            TimeSeries::new(Duration::from_secs(60), 100).percentile(0.95),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_submit_latency_percentiles() {
+        let monitor = MonitoringSystem::new();
+        for latency in [40.0, 10.0, 30.0, 20.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0] {
+            monitor.record_submit_latency(latency);
+        }
+
+        let metrics = monitor.get_metrics();
+        assert_eq!(metrics.submit_latency_p50_ms, 50.0);
+        assert_eq!(metrics.submit_latency_p95_ms, 100.0);
+        assert!(monitor.get_recent_alerts(10).is_empty());
+        assert!(
+            monitor
+                .generate_status_report()
+                .contains("Submit Latency: p50 50.00 ms, p95 100.00 ms")
+        );
+
+        // Only the slow submission raises an alert
+        monitor.update_config(AlertConfig {
+            max_submit_latency_ms: 500.0,
+            ..monitor.alert_config()
+        });
+        monitor.record_submit_latency(750.0);
+        let alerts = monitor.get_recent_alerts(10);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].category, "submit_latency");
+    }
+
+    #[test]
+    fn test_attach_http_pool_after_first_use() {
+        let monitor = MonitoringSystem::new();
+        monitor.record_hash_rate(5000.0);
+        assert!(!monitor.generate_status_report().contains("HTTP Pool Stats"));
+
+        monitor.attach_http_pool(Arc::new(HttpClientPool::new()));
+        let report = monitor.generate_status_report();
+        assert!(report.contains("--- HTTP Pool Stats ---"));
+        assert!(report.contains("Active Clients: 0"));
+    }
+
+    #[test]
+    fn test_json_status_report() {
+        let monitor = MonitoringSystem::with_http_pool(Arc::new(HttpClientPool::new()));
+        monitor.record_rejection(RejectReason::Stale);
+        monitor.create_alert(AlertSeverity::Warning, "test", "json report", vec![]);
+
+        let json = monitor.render_status_report(StatusFormat::Json).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for key in [
+            "health",
+            "metrics",
+            "workers",
+            "rejections",
+            "cancellations",
+            "recent_alerts",
+            "http_pool",
+        ] {
+            assert!(report.get(key).is_some(), "missing {}", key);
+        }
+        assert!(report["health"].is_string());
+        assert!(report["metrics"]["hash_rate"].is_number());
+        assert_eq!(report["rejections"]["stale"], 1);
+        assert_eq!(report["recent_alerts"][0]["message"], "json report");
+        assert!(report["http_pool"]["active_clients"].is_number());
+
+        assert_eq!("JSON".parse::<StatusFormat>().unwrap(), StatusFormat::Json);
+        assert!("yaml".parse::<StatusFormat>().is_err());
+        assert!(
+            monitor
+                .render_status_report(StatusFormat::default())
+                .unwrap()
+                .starts_with("===")
+        );
     }
 
     #[test]
@@ -797,6 +1724,93 @@ mod tests {
         assert_eq!(metrics.hash_rate, 1000.0);
     }
 
+    #[test]
+    fn test_rejection_reasons() {
+        let monitor = MonitoringSystem::new();
+        monitor.record_rejection(RejectReason::LowDifficulty);
+        monitor.record_rejection(RejectReason::LowDifficulty);
+        monitor.record_rejection(RejectReason::JobNotFound);
+        monitor.record_rejection(RejectReason::NodeInvalid);
+        monitor.record_rejection(RejectReason::LowDifficulty);
+
+        let reasons = monitor.get_rejection_reasons();
+        assert_eq!(reasons.len(), 3);
+        assert_eq!(reasons["low-difficulty"], 3);
+        assert_eq!(reasons["job-not-found"], 1);
+        assert_eq!(reasons["node-invalid"], 1);
+        assert!(!reasons.contains_key("stale"));
+
+        let report = monitor.generate_status_report();
+        assert!(report.contains(
+            "--- Rejections ---\njob-not-found: 1\nlow-difficulty: 3\nnode-invalid: 1\n"
+        ));
+
+        let prometheus = monitor.prometheus_metrics();
+        assert!(
+            prometheus.contains("chainweb_mining_rejections_total{reason=\"low-difficulty\"} 3\n")
+        );
+        assert!(
+            prometheus.contains("chainweb_mining_rejections_total{reason=\"node-invalid\"} 1\n")
+        );
+    }
+
+    #[test]
+    fn test_chain_difficulty_gauges() {
+        let monitor = MonitoringSystem::new();
+        monitor.record_hash_rate(1_000_000.0);
+        monitor.record_chain_target(0, &Target::mk_target_level(20));
+        monitor.record_chain_target(7, &Target::mk_target_level(40));
+        // Newer work replaces the previous value for the chain
+        monitor.record_chain_target(7, &Target::mk_target_level(42));
+
+        let chains = monitor.get_chain_difficulty();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[&0].level, 20);
+        let expected_secs = 2f64.powi(42) / 1_000_000.0;
+        assert!((chains[&7].expected_block_time_secs - expected_secs).abs() / expected_secs < 1e-3);
+
+        let prometheus = monitor.prometheus_metrics();
+        assert!(prometheus.contains("chainweb_chain_difficulty_level{chain=\"0\"} 20\n"));
+        assert!(prometheus.contains("chainweb_chain_difficulty_level{chain=\"7\"} 42\n"));
+        assert!(prometheus.contains("chainweb_chain_expected_block_time_seconds{chain=\"0\"} "));
+        assert!(prometheus.contains("chainweb_chain_expected_block_time_seconds{chain=\"7\"} "));
+        assert_eq!(prometheus_float(f64::INFINITY), "+Inf");
+    }
+
+    #[test]
+    fn test_named_worker_breakdown() {
+        let monitor = MonitoringSystem::new();
+        let stats = NamedWorkerStats {
+            shares_submitted: 4,
+            shares_accepted: 3,
+            hash_rate: 2000.0,
+            sessions: 2,
+        };
+        monitor.record_named_worker("miner.rig1", stats);
+        monitor.record_named_worker("miner.\"odd\"", NamedWorkerStats::default());
+        assert_eq!(stats.reject_rate(), 0.25);
+
+        let report = monitor.generate_status_report();
+        assert!(report.contains("--- Stratum Workers ---\n"));
+        assert!(
+            report.contains("miner.rig1: 4 shares (25.0% rejected), 2000.00 H/s, 2 session(s)\n")
+        );
+
+        let prometheus = monitor.prometheus_metrics();
+        assert!(
+            prometheus.contains("chainweb_stratum_worker_shares_total{worker=\"miner.rig1\"} 4\n")
+        );
+        assert!(
+            prometheus.contains(
+                "chainweb_stratum_worker_rejected_shares_total{worker=\"miner.rig1\"} 1\n"
+            )
+        );
+        assert!(
+            prometheus.contains("chainweb_stratum_worker_hash_rate{worker=\"miner.rig1\"} 2000\n")
+        );
+        assert!(prometheus.contains("{worker=\"miner.\\\"odd\\\"\"} 0\n"));
+    }
+
     #[test]
     fn test_status_report_generation() {
         let monitor = MonitoringSystem::new();
@@ -807,5 +1821,12 @@ mod tests {
         assert!(report.contains("Hash Rate: 1500.00"));
         assert!(report.contains("Solutions Found: 1"));
         assert!(report.contains("Health Status"));
+        assert!(!report.contains("CPU Threads"));
+        assert!(!report.contains("Rejections"));
+
+        monitor.record_thread_hash_rates(&[700.0, 800.0]);
+        let report = monitor.generate_status_report();
+        assert!(report.contains("Thread 0: 700.00 H/s"));
+        assert!(report.contains("Thread 1: 800.00 H/s"));
     }
 }
@@ -0,0 +1,71 @@
+//! Delivery of monitoring alerts to external services
+//!
+//! Alerts always go to the log and the in-memory alert buffer. Notifiers
+//! registered with [`MonitoringSystem::add_alert_notifier`] additionally
+//! receive every alert at or above their minimum severity, so operators can
+//! be paged by email, Slack or any HTTP endpoint. [`WebhookNotifier`] POSTs
+//! the alert as JSON; installed with `--alert-webhook`.
+//!
+//! Notification is fire-and-forget: a slow or unreachable endpoint never
+//! holds up the code that raised the alert.
+//!
+//! [`MonitoringSystem::add_alert_notifier`]: crate::utils::MonitoringSystem::add_alert_notifier
+
+use crate::error::Result;
+use crate::utils::monitoring::Alert;
+use reqwest::Client;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Default timeout of a webhook POST
+pub const DEFAULT_WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Destination for monitoring alerts
+pub trait AlertNotifier: Send + Sync {
+    /// Deliver an alert; must not block
+    fn notify(&self, alert: &Alert);
+}
+
+/// Posts alerts (severity, category, message, context and timestamp) as
+/// JSON to a URL
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    /// Notifier posting to `url`, giving up on a request after `timeout`
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().timeout(timeout).build()?,
+            url: url.into(),
+        })
+    }
+
+    /// URL alerts are posted to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl AlertNotifier for WebhookNotifier {
+    fn notify(&self, alert: &Alert) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            debug!("No async runtime, not posting alert to {}", self.url);
+            return;
+        };
+
+        let request = self.client.post(&self.url).json(alert);
+        let url = self.url.clone();
+        runtime.spawn(async move {
+            match request.send().await {
+                Ok(response) if !response.status().is_success() => {
+                    warn!("Alert webhook {} responded with {}", url, response.status());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to post alert to {}: {}", url, e),
+            }
+        });
+    }
+}
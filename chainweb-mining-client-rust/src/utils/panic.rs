@@ -0,0 +1,131 @@
+//! Reporting of panics
+//!
+//! By default a panic only prints to stderr, bypassing the structured logs,
+//! and a panicking tokio task disappears silently unless its handle is
+//! awaited. The panic hook logs the panic through tracing, raises a critical
+//! alert and flushes the output streams before the default hook runs, and
+//! [`spawn_monitored`] and [`spawn_blocking_monitored`] turn the join error of
+//! a panicked task into an alert.
+
+use crate::utils::monitoring::global_monitoring;
+use std::any::Any;
+use std::future::Future;
+use std::io::Write;
+use std::panic::{self, PanicHookInfo};
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+/// Install the panic hook, keeping the current hook to run after it
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        report_panic(info);
+        previous(info);
+    }));
+}
+
+fn report_panic(info: &PanicHookInfo<'_>) {
+    let thread = std::thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+    let message = payload_message(info.payload());
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "unknown location".to_string());
+
+    error!(thread, %location, "Panic: {}", message);
+    global_monitoring().record_panic(thread, &message);
+
+    // The audit log and event sink write through on every record, only the
+    // output streams may still hold buffered logs
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Spawn `future` on the runtime, raising an alert if it panics
+///
+/// The returned handle completes once the task has finished and its outcome
+/// has been reported.
+pub fn spawn_monitored<F>(name: &'static str, future: F) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    report_join(name, tokio::spawn(future))
+}
+
+/// Run the blocking `f` on the runtime's blocking threads, raising an alert
+/// if it panics, like [`spawn_monitored`]
+pub fn spawn_blocking_monitored<F>(name: &'static str, f: F) -> JoinHandle<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    report_join(name, tokio::task::spawn_blocking(f))
+}
+
+/// Wait for `task`, raising an alert if it panicked
+fn report_join(name: &'static str, task: JoinHandle<()>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Err(e) = task.await {
+            if e.is_panic() {
+                let message = payload_message(e.into_panic().as_ref());
+                error!("{} task panicked: {}", name, message);
+                global_monitoring().record_panic(name, &message);
+            } else {
+                debug!("{} task was cancelled", name);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::monitoring::AlertSeverity;
+
+    /// Panic alert raised for the task `name`
+    fn panic_alert(name: &str) -> crate::utils::monitoring::Alert {
+        global_monitoring()
+            .get_recent_alerts(50)
+            .into_iter()
+            .find(|alert| {
+                alert.category == "panic"
+                    && alert
+                        .context
+                        .get("thread")
+                        .is_some_and(|thread| thread == name)
+            })
+            .expect("panic alert")
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_raises_alert() {
+        spawn_monitored("panic-test-task", async { panic!("worker exploded") })
+            .await
+            .unwrap();
+
+        let alert = panic_alert("panic-test-task");
+        assert_eq!(alert.severity, AlertSeverity::Critical);
+        assert_eq!(alert.context.get("message").unwrap(), "worker exploded");
+    }
+
+    #[tokio::test]
+    async fn test_panicking_blocking_task_raises_alert() {
+        spawn_blocking_monitored("panic-test-thread", || panic!("thread exploded"))
+            .await
+            .unwrap();
+
+        let alert = panic_alert("panic-test-thread");
+        assert_eq!(alert.severity, AlertSeverity::Critical);
+        assert_eq!(alert.context.get("message").unwrap(), "thread exploded");
+    }
+}
@@ -0,0 +1,90 @@
+//! OS scheduling priority for mining threads
+//!
+//! On Linux the nice value of the calling thread is lowered. Other Unix
+//! systems only support a process wide nice value, which is used instead.
+//! Elsewhere (including Windows, which would need unsafe FFI that this crate
+//! forbids) setting a priority fails and callers keep mining at normal
+//! priority. Raising the priority again usually requires privileges, so
+//! [`ThreadPriority::Normal`] leaves the thread untouched.
+
+use crate::config::ThreadPriority;
+use crate::error::{Error, Result};
+
+impl ThreadPriority {
+    /// Unix nice value for this priority, `None` for normal priority
+    pub fn nice_value(self) -> Option<i32> {
+        match self {
+            ThreadPriority::Normal => None,
+            ThreadPriority::Low => Some(10),
+            ThreadPriority::Idle => Some(19),
+        }
+    }
+}
+
+/// Set the scheduling priority of the calling thread
+pub fn set_current_thread_priority(priority: ThreadPriority) -> Result<()> {
+    if priority == ThreadPriority::Normal {
+        return Ok(());
+    }
+    set_priority(priority)
+}
+
+#[cfg(unix)]
+fn set_priority(priority: ThreadPriority) -> Result<()> {
+    let Some(nice) = priority.nice_value() else {
+        return Ok(());
+    };
+
+    // On Linux, a thread id targets just that thread
+    #[cfg(target_os = "linux")]
+    let who = Some(rustix::thread::gettid());
+    #[cfg(not(target_os = "linux"))]
+    let who = None;
+
+    rustix::process::setpriority_process(who, nice).map_err(|e| {
+        Error::config(format!(
+            "Failed to set thread priority {:?}: {}",
+            priority, e
+        ))
+    })
+}
+
+#[cfg(not(unix))]
+fn set_priority(priority: ThreadPriority) -> Result<()> {
+    Err(Error::config(format!(
+        "Thread priority {:?} is not supported on this platform",
+        priority
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_priority_on_host() {
+        // Run on a fresh thread so the test runner's threads keep their priority
+        std::thread::spawn(|| {
+            set_current_thread_priority(ThreadPriority::Normal).unwrap();
+            set_current_thread_priority(ThreadPriority::Low).unwrap();
+            set_current_thread_priority(ThreadPriority::Idle).unwrap();
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_priority() {
+        assert_eq!(
+            "idle".parse::<ThreadPriority>().unwrap(),
+            ThreadPriority::Idle
+        );
+        assert_eq!(
+            "Low".parse::<ThreadPriority>().unwrap(),
+            ThreadPriority::Low
+        );
+        assert!("realtime".parse::<ThreadPriority>().is_err());
+        assert_eq!(ThreadPriority::default(), ThreadPriority::Normal);
+    }
+}
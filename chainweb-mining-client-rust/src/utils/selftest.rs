@@ -0,0 +1,196 @@
+//! Internal consistency checks run by `--selftest`
+//!
+//! The checks exercise the parts of the build most likely to differ between
+//! machines (hash implementations selected by CPU features, serialization)
+//! without contacting a node, so a broken deployment is caught up front.
+
+use crate::config::Config;
+use crate::core::{Blake2s, HashAlgorithm, OptimizedHasher, Target, Work, check_simd_agreement};
+use crate::workers::Worker;
+use crate::workers::simulation::{SimulationWorker, SimulationWorkerConfig};
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Outcome of a single check
+#[derive(Debug, Clone)]
+pub struct SelfTestCheck {
+    /// Check name
+    pub name: &'static str,
+    /// `Err` with the reason if the check failed
+    pub result: Result<(), String>,
+}
+
+/// Outcome of all checks
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    /// Checks in the order they ran
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.result.is_ok())
+    }
+
+    /// Checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestCheck> {
+        self.checks.iter().filter(|check| check.result.is_err())
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.result {
+                Ok(()) => writeln!(f, "PASS  {}", check.name)?,
+                Err(reason) => writeln!(f, "FAIL  {}: {}", check.name, reason)?,
+            }
+        }
+        let failed = self.failures().count();
+        writeln!(
+            f,
+            "{} of {} checks passed",
+            self.checks.len() - failed,
+            self.checks.len()
+        )
+    }
+}
+
+/// Run all checks
+pub async fn run_selftest() -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+    let mut record = |name, result| report.checks.push(SelfTestCheck { name, result });
+
+    record("blake2s known answers", check_blake2s_known_answers());
+    record("target level round-trip", check_target_levels());
+    record("simd and scalar hashes agree", check_simd_matches_scalar());
+    record("config round-trip", check_config_round_trip());
+    record("simulation worker solve", check_simulation_solve().await);
+    report
+}
+
+fn check_blake2s_known_answers() -> Result<(), String> {
+    // From RFC 7693 and the reference implementation
+    let vectors: [(&[u8], &str); 2] = [
+        (
+            b"",
+            "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+        ),
+        (
+            b"abc",
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+        ),
+    ];
+    for (input, expected) in vectors {
+        let actual = hex::encode(Blake2s.hash(input));
+        if actual != expected {
+            return Err(format!(
+                "hash of {:?} is {}, expected {}",
+                String::from_utf8_lossy(input),
+                actual,
+                expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_target_levels() -> Result<(), String> {
+    for level in 0..255u8 {
+        let target = Target::mk_target_level(level);
+        if target.get_target_level() != Some(level) {
+            return Err(format!(
+                "level {} came back as {:?}",
+                level,
+                target.get_target_level()
+            ));
+        }
+        let parsed = Target::from_hex(&target.to_hex()).map_err(|e| e.to_string())?;
+        if parsed != target {
+            return Err(format!("hex of level {} target doesn't parse back", level));
+        }
+    }
+    Ok(())
+}
+
+fn check_simd_matches_scalar() -> Result<(), String> {
+    let base: [u8; 286] = std::array::from_fn(|i| (i * 7) as u8);
+    check_simd_agreement(&base, 1000, 16).map_err(|e| e.to_string())?;
+
+    let optimized = OptimizedHasher::new();
+    if optimized.hash_single(&base) != Blake2s.hash(&base) {
+        return Err("optimized hash differs from scalar".to_string());
+    }
+    Ok(())
+}
+
+fn check_config_round_trip() -> Result<(), String> {
+    let config = Config::default();
+    let yaml = serde_yaml::to_string(&config).map_err(|e| e.to_string())?;
+    let parsed = Config::from_contents(&yaml, "selftest.yaml").map_err(|e| e.to_string())?;
+    if serde_yaml::to_string(&parsed).map_err(|e| e.to_string())? != yaml {
+        return Err("YAML config changed after a round-trip".to_string());
+    }
+
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    let parsed = Config::from_contents(&json, "selftest.json").map_err(|e| e.to_string())?;
+    if serde_json::to_string(&parsed).map_err(|e| e.to_string())? != json {
+        return Err("JSON config changed after a round-trip".to_string());
+    }
+    Ok(())
+}
+
+async fn check_simulation_solve() -> Result<(), String> {
+    let worker = SimulationWorker::new(SimulationWorkerConfig { hash_rate: 1e12 });
+    let work = Work::from_bytes([0x5A; 286]);
+    let (tx, mut rx) = mpsc::channel(1);
+
+    worker
+        .mine(work.clone(), Target::from_bytes([0xFF; 32]), tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let result = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await;
+    let _ = worker.stop().await;
+
+    match result {
+        Ok(Some(result)) if result.work == work => Ok(()),
+        Ok(Some(_)) => Err("solution is for different work".to_string()),
+        Ok(None) => Err("worker stopped without a solution".to_string()),
+        Err(_) => Err("no solution within 5 seconds".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_selftest_passes() {
+        let report = run_selftest().await;
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.checks.len(), 5);
+    }
+
+    #[test]
+    fn test_report_lists_failures() {
+        let report = SelfTestReport {
+            checks: vec![
+                SelfTestCheck {
+                    name: "good",
+                    result: Ok(()),
+                },
+                SelfTestCheck {
+                    name: "bad",
+                    result: Err("mismatch".to_string()),
+                },
+            ],
+        };
+        assert!(!report.passed());
+        let text = report.to_string();
+        assert!(text.contains("PASS  good"));
+        assert!(text.contains("FAIL  bad: mismatch"));
+        assert!(text.contains("1 of 2 checks passed"));
+    }
+}
@@ -0,0 +1,325 @@
+//! Pausing the GPU while it overheats
+//!
+//! wgpu doesn't expose device temperatures, so they are read from the hwmon
+//! sensors that Linux GPU drivers publish in sysfs. Mining pauses once the
+//! hottest GPU exceeds the maximum temperature and resumes once it has cooled
+//! below the (lower) resume temperature; the band in between keeps a GPU
+//! hovering around the limit from flapping.
+
+use crate::error::{Error, Result};
+use crate::utils::monitoring::global_monitoring;
+use crate::workers::Worker;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// Default distance of the resume temperature below the maximum temperature
+pub const DEFAULT_THERMAL_HYSTERESIS_C: f64 = 10.0;
+
+/// Directory of the DRM devices in sysfs
+pub const SYSFS_DRM_DIR: &str = "/sys/class/drm";
+
+/// How often the temperature is read
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Temperatures in °C at which mining pauses and resumes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThermalLimits {
+    /// Pause mining above this temperature
+    pub max_temp_c: f64,
+    /// Resume mining below this temperature
+    pub resume_temp_c: f64,
+}
+
+impl ThermalLimits {
+    /// Limits pausing above `max_temp_c` and resuming below `resume_temp_c`,
+    /// by default [`DEFAULT_THERMAL_HYSTERESIS_C`] below the maximum
+    pub fn new(max_temp_c: f64, resume_temp_c: Option<f64>) -> Result<Self> {
+        if !max_temp_c.is_finite() || max_temp_c <= 0.0 {
+            return Err(Error::config_invalid_value(
+                "gpu_max_temp_c",
+                max_temp_c.to_string(),
+                "positive temperature in °C",
+            ));
+        }
+        let resume_temp_c = resume_temp_c.unwrap_or(max_temp_c - DEFAULT_THERMAL_HYSTERESIS_C);
+        if !resume_temp_c.is_finite() || resume_temp_c >= max_temp_c {
+            return Err(Error::config_invalid_value(
+                "gpu_resume_temp_c",
+                resume_temp_c.to_string(),
+                format!("temperature in °C below the maximum of {}", max_temp_c),
+            ));
+        }
+        Ok(Self {
+            max_temp_c,
+            resume_temp_c,
+        })
+    }
+}
+
+/// A change of the thermal state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThermalEvent {
+    /// The temperature rose above the maximum, mining pauses
+    Overheated {
+        /// Temperature read
+        temp_c: f64,
+        /// Maximum temperature
+        limit_c: f64,
+    },
+    /// The temperature fell below the resume temperature, mining resumes
+    Cooled {
+        /// Temperature read
+        temp_c: f64,
+        /// Resume temperature
+        limit_c: f64,
+    },
+}
+
+impl fmt::Display for ThermalEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThermalEvent::Overheated { temp_c, limit_c } => {
+                write!(
+                    f,
+                    "GPU at {:.1}°C, above {:.1}°C, pausing mining",
+                    temp_c, limit_c
+                )
+            }
+            ThermalEvent::Cooled { temp_c, limit_c } => {
+                write!(
+                    f,
+                    "GPU cooled to {:.1}°C, below {:.1}°C, resuming mining",
+                    temp_c, limit_c
+                )
+            }
+        }
+    }
+}
+
+/// Source of GPU temperatures
+pub trait TemperatureSensor: Send {
+    /// Current temperature in °C, if it can be read
+    fn read_celsius(&mut self) -> Option<f64>;
+}
+
+/// Hottest of the GPU temperatures published by the drivers' hwmon sensors
+#[derive(Debug, Clone)]
+pub struct SysfsSensor {
+    inputs: Vec<PathBuf>,
+}
+
+impl SysfsSensor {
+    /// Sensor over the `temp1_input` files of all DRM cards below `drm_dir`
+    /// (usually [`SYSFS_DRM_DIR`]), if there are any
+    pub fn discover(drm_dir: &Path) -> Option<Self> {
+        let mut inputs: Vec<PathBuf> = std::fs::read_dir(drm_dir)
+            .ok()?
+            .flatten()
+            .filter(|card| {
+                let name = card.file_name();
+                let name = name.to_string_lossy();
+                // Connectors such as card0-DP-1 share the card's sensors
+                name.starts_with("card") && !name.contains('-')
+            })
+            .filter_map(|card| std::fs::read_dir(card.path().join("device/hwmon")).ok())
+            .flat_map(|hwmons| {
+                hwmons
+                    .flatten()
+                    .map(|hwmon| hwmon.path().join("temp1_input"))
+            })
+            .filter(|input| input.is_file())
+            .collect();
+        inputs.sort();
+        (!inputs.is_empty()).then_some(Self { inputs })
+    }
+
+    /// Files the temperatures are read from
+    pub fn inputs(&self) -> &[PathBuf] {
+        &self.inputs
+    }
+}
+
+impl TemperatureSensor for SysfsSensor {
+    fn read_celsius(&mut self) -> Option<f64> {
+        // hwmon reports millidegrees Celsius
+        self.inputs
+            .iter()
+            .filter_map(|input| {
+                std::fs::read_to_string(input)
+                    .ok()?
+                    .trim()
+                    .parse::<i64>()
+                    .ok()
+            })
+            .max()
+            .map(|millidegrees| millidegrees as f64 / 1000.0)
+    }
+}
+
+/// Thermal state machine pausing mining between the limits
+#[derive(Debug)]
+pub struct ThermalGuard {
+    limits: ThermalLimits,
+    overheated: bool,
+}
+
+impl ThermalGuard {
+    /// Guard starting in the normal (not overheated) state
+    pub fn new(limits: ThermalLimits) -> Self {
+        Self {
+            limits,
+            overheated: false,
+        }
+    }
+
+    /// Whether mining should currently be paused
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    /// Feed a temperature reading, returning the state change it causes
+    pub fn observe(&mut self, temp_c: f64) -> Option<ThermalEvent> {
+        if !self.overheated && temp_c > self.limits.max_temp_c {
+            self.overheated = true;
+            Some(ThermalEvent::Overheated {
+                temp_c,
+                limit_c: self.limits.max_temp_c,
+            })
+        } else if self.overheated && temp_c < self.limits.resume_temp_c {
+            self.overheated = false;
+            Some(ThermalEvent::Cooled {
+                temp_c,
+                limit_c: self.limits.resume_temp_c,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Read `sensor` periodically, pausing and resuming `worker` on thermal
+    /// events and raising an alert for each
+    pub fn spawn(
+        mut self,
+        mut sensor: impl TemperatureSensor + 'static,
+        worker: Arc<dyn Worker>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+            let mut readable = true;
+            loop {
+                ticker.tick().await;
+                let Some(temp_c) = sensor.read_celsius() else {
+                    if std::mem::replace(&mut readable, false) {
+                        warn!("GPU temperature can't be read, thermal protection is suspended");
+                    }
+                    continue;
+                };
+                if !std::mem::replace(&mut readable, true) {
+                    info!("GPU temperature readable again ({:.1}°C)", temp_c);
+                }
+                let Some(event) = self.observe(temp_c) else {
+                    continue;
+                };
+                global_monitoring().record_thermal_event(&event);
+                if let Err(e) = worker.set_paused(self.overheated).await {
+                    let action = if self.overheated { "pause" } else { "resume" };
+                    error!(
+                        "Failed to {} the {} worker: {}",
+                        action,
+                        worker.worker_type(),
+                        e
+                    );
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::monitoring::{AlertSeverity, MonitoringSystem};
+
+    #[test]
+    fn test_thermal_guard_hysteresis() {
+        let limits = ThermalLimits::new(85.0, Some(75.0)).unwrap();
+        let mut guard = ThermalGuard::new(limits);
+        let monitor = MonitoringSystem::new();
+
+        // Warming up to the limit is fine
+        for temp in [60.0, 70.0, 80.0, 85.0] {
+            assert_eq!(guard.observe(temp), None);
+        }
+        let overheated = guard.observe(86.5).expect("overheat");
+        assert_eq!(
+            overheated,
+            ThermalEvent::Overheated {
+                temp_c: 86.5,
+                limit_c: 85.0
+            }
+        );
+        assert!(guard.is_overheated());
+
+        // Cooling into the band between the limits keeps mining paused
+        for temp in [90.0, 84.0, 80.0, 75.0] {
+            assert_eq!(guard.observe(temp), None);
+            assert!(guard.is_overheated());
+        }
+        let cooled = guard.observe(74.0).expect("cooled");
+        assert_eq!(
+            cooled,
+            ThermalEvent::Cooled {
+                temp_c: 74.0,
+                limit_c: 75.0
+            }
+        );
+        assert!(!guard.is_overheated());
+
+        // Warming into the band again doesn't pause
+        assert_eq!(guard.observe(84.0), None);
+        assert!(guard.observe(85.5).is_some());
+
+        monitor.record_thermal_event(&overheated);
+        monitor.record_thermal_event(&cooled);
+        let alerts = monitor.get_recent_alerts(2);
+        assert!(alerts.iter().all(|alert| alert.category == "thermal"));
+        assert!(
+            alerts
+                .iter()
+                .any(|alert| alert.severity == AlertSeverity::Critical)
+        );
+        assert!(
+            alerts
+                .iter()
+                .any(|alert| alert.severity == AlertSeverity::Info)
+        );
+    }
+
+    #[test]
+    fn test_thermal_limits() {
+        assert_eq!(ThermalLimits::new(85.0, None).unwrap().resume_temp_c, 75.0);
+        assert!(ThermalLimits::new(85.0, Some(85.0)).is_err());
+        assert!(ThermalLimits::new(0.0, None).is_err());
+        assert!(ThermalLimits::new(f64::NAN, None).is_err());
+    }
+
+    #[test]
+    fn test_sysfs_sensor_reads_hottest_card() {
+        let drm = tempfile::tempdir().unwrap();
+        for (card, millidegrees) in [("card0", "61000\n"), ("card1", "72500\n")] {
+            let hwmon = drm.path().join(card).join("device/hwmon/hwmon3");
+            std::fs::create_dir_all(&hwmon).unwrap();
+            std::fs::write(hwmon.join("temp1_input"), millidegrees).unwrap();
+        }
+        std::fs::create_dir_all(drm.path().join("card1-DP-1")).unwrap();
+
+        let mut sensor = SysfsSensor::discover(drm.path()).unwrap();
+        assert_eq!(sensor.inputs().len(), 2);
+        assert_eq!(sensor.read_celsius(), Some(72.5));
+        assert!(SysfsSensor::discover(&drm.path().join("missing")).is_none());
+    }
+}
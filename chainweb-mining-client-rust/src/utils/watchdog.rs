@@ -0,0 +1,260 @@
+//! Detection of a stuck mining loop
+//!
+//! A deadlocked worker or a stalled select loop leaves the process running
+//! without doing anything useful. The watchdog samples monotonic progress
+//! indicators, such as the hash count and the number of work updates, and
+//! reports a stall once one of them hasn't advanced within its timeout.
+
+use crate::utils::monitoring::global_monitoring;
+use crate::workers::WorkerCapabilities;
+use std::fmt;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::error;
+
+/// Default time an indicator may stand still before it counts as stalled
+pub const DEFAULT_WATCHDOG_TIMEOUT: Duration = Duration::from_secs(300);
+
+struct Indicator {
+    name: &'static str,
+    timeout: Duration,
+    read: Box<dyn Fn() -> u64 + Send + Sync>,
+    /// Whether the indicator is expected to stand still, e.g. while paused
+    idle: Box<dyn Fn() -> bool + Send + Sync>,
+    last: Option<u64>,
+    since: Instant,
+}
+
+/// A progress indicator that stopped advancing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stall {
+    /// Name of the indicator
+    pub indicator: &'static str,
+    /// Time since the indicator last advanced
+    pub stalled_for: Duration,
+}
+
+impl fmt::Display for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} haven't advanced for {}s",
+            self.indicator,
+            self.stalled_for.as_secs()
+        )
+    }
+}
+
+/// Watches progress indicators for stalls
+#[derive(Default)]
+pub struct Watchdog {
+    indicators: Vec<Indicator>,
+}
+
+impl Watchdog {
+    /// Watchdog without indicators
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watchdog of a mining run: the work updates, and the hash count of
+    /// workers that compute hashes themselves unless `paused` is set
+    pub fn mining(
+        capabilities: WorkerCapabilities,
+        hash_timeout: Duration,
+        work_timeout: Duration,
+        paused: impl Fn() -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let watchdog = Self::new().watch("work updates", work_timeout, || {
+            global_monitoring().work_updates()
+        });
+        if !capabilities.counts_hashes() {
+            return watchdog;
+        }
+        watchdog.watch_unless(
+            "hashes",
+            hash_timeout,
+            || global_monitoring().total_hashes() as u64,
+            paused,
+        )
+    }
+
+    /// Watch the counter returned by `read`, stalled once it hasn't
+    /// changed for `timeout`
+    pub fn watch(
+        self,
+        name: &'static str,
+        timeout: Duration,
+        read: impl Fn() -> u64 + Send + Sync + 'static,
+    ) -> Self {
+        self.watch_unless(name, timeout, read, || false)
+    }
+
+    /// Like [`Watchdog::watch`], but the timeout only runs while `idle`
+    /// returns false
+    pub fn watch_unless(
+        mut self,
+        name: &'static str,
+        timeout: Duration,
+        read: impl Fn() -> u64 + Send + Sync + 'static,
+        idle: impl Fn() -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.indicators.push(Indicator {
+            name,
+            timeout,
+            read: Box::new(read),
+            idle: Box::new(idle),
+            last: None,
+            since: Instant::now(),
+        });
+        self
+    }
+
+    /// Names and timeouts of the watched indicators
+    pub fn indicators(&self) -> impl Iterator<Item = (&'static str, Duration)> + '_ {
+        self.indicators.iter().map(|i| (i.name, i.timeout))
+    }
+
+    /// Wait until an indicator stalls
+    ///
+    /// The stalled indicator is re-armed, so waiting again reports it once
+    /// more after another timeout without progress.
+    pub async fn wait_for_stall(&mut self) -> Stall {
+        let Some(shortest) = self.indicators.iter().map(|i| i.timeout).min() else {
+            return std::future::pending().await;
+        };
+        let check_interval = (shortest / 4).max(Duration::from_millis(100));
+
+        loop {
+            let now = Instant::now();
+            for indicator in &mut self.indicators {
+                let value = (indicator.read)();
+                if indicator.last != Some(value) || (indicator.idle)() {
+                    indicator.last = Some(value);
+                    indicator.since = now;
+                } else if now.duration_since(indicator.since) >= indicator.timeout {
+                    let stalled_for = now.duration_since(indicator.since);
+                    indicator.since = now;
+                    return Stall {
+                        indicator: indicator.name,
+                        stalled_for,
+                    };
+                }
+            }
+            tokio::time::sleep(check_interval).await;
+        }
+    }
+
+    /// Raise a critical alert on every stall, aborting the process on the
+    /// first one if `abort` is set so an orchestrator can restart it
+    pub fn spawn(mut self, abort: bool) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let stall = self.wait_for_stall().await;
+                global_monitoring().record_watchdog_stall(&stall);
+                if abort {
+                    error!("Watchdog aborting the process: {}", stall);
+                    std::process::abort();
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_hash_counter_triggers() {
+        let hashes = Arc::new(AtomicU64::new(0));
+        let work_updates = Arc::new(AtomicU64::new(0));
+        let mut watchdog = Watchdog::new()
+            .watch("hashes", Duration::from_secs(60), {
+                let hashes = hashes.clone();
+                move || hashes.load(Ordering::Relaxed)
+            })
+            .watch("work updates", Duration::from_secs(60), {
+                // Work keeps flowing throughout
+                let work_updates = work_updates.clone();
+                move || work_updates.fetch_add(1, Ordering::Relaxed)
+            });
+
+        // Hashes advance for a while, then the counter stalls
+        let started = Instant::now();
+        let stall = tokio::select! {
+            stall = watchdog.wait_for_stall() => stall,
+            _ = async {
+                for _ in 0..10 {
+                    hashes.fetch_add(1000, Ordering::Relaxed);
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+                std::future::pending::<()>().await
+            } => unreachable!(),
+        };
+
+        assert_eq!(stall.indicator, "hashes");
+        assert!(stall.stalled_for >= Duration::from_secs(60));
+        assert!(started.elapsed() >= Duration::from_secs(150));
+        assert!(started.elapsed() < Duration::from_secs(200));
+    }
+
+    #[test]
+    fn test_stratum_hash_count_is_not_watched() {
+        // Capabilities of the stratum server, whose hash rate is estimated
+        let stratum = WorkerCapabilities {
+            reports_hashrate: true,
+            ..WorkerCapabilities::server()
+        };
+        let timeout = Duration::from_secs(300);
+        let watched: Vec<_> = Watchdog::mining(stratum, timeout, timeout, || false)
+            .indicators()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(watched, ["work updates"]);
+
+        let watched: Vec<_> =
+            Watchdog::mining(WorkerCapabilities::compute(), timeout, timeout, || false)
+                .indicators()
+                .map(|(name, _)| name)
+                .collect();
+        assert_eq!(watched, ["work updates", "hashes"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_paused_counter_never_triggers() {
+        let paused = Arc::new(AtomicBool::new(true));
+        let mut watchdog = Watchdog::new().watch_unless("hashes", Duration::from_secs(60), || 0, {
+            let paused = paused.clone();
+            move || paused.load(Ordering::Relaxed)
+        });
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(3600), watchdog.wait_for_stall()).await;
+        assert!(result.is_err());
+
+        // Once resumed, the standing counter stalls after the timeout
+        paused.store(false, Ordering::Relaxed);
+        let started = Instant::now();
+        let stall = watchdog.wait_for_stall().await;
+        assert_eq!(stall.indicator, "hashes");
+        assert!(started.elapsed() >= Duration::from_secs(60));
+        assert!(started.elapsed() < Duration::from_secs(90));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_advancing_counters_never_trigger() {
+        let hashes = Arc::new(AtomicU64::new(0));
+        let mut watchdog = Watchdog::new().watch("hashes", Duration::from_secs(60), {
+            let hashes = hashes.clone();
+            move || hashes.fetch_add(1, Ordering::Relaxed)
+        });
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(3600), watchdog.wait_for_stall()).await;
+        assert!(result.is_err());
+    }
+}
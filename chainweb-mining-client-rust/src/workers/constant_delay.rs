@@ -2,7 +2,7 @@
 
 use crate::core::{Target, Work};
 use crate::error::Result;
-use crate::workers::{MiningResult, Worker};
+use crate::workers::{MiningResult, Worker, WorkerCapabilities};
 use async_trait::async_trait;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -91,6 +91,14 @@ impl Worker for ConstantDelayWorker {
         "ConstantDelay"
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities {
+            // Blocks per hour, not hashes per second
+            reports_hashrate: false,
+            ..WorkerCapabilities::compute()
+        }
+    }
+
     async fn hashrate(&self) -> u64 {
         // Constant delay doesn't have a meaningful hashrate
         // Return blocks per hour as a rough metric
@@ -1,9 +1,19 @@
 //! CPU mining implementation using multiple threads
 
-use crate::core::{Nonce, Target, VectorizedMiner, Work, SimdMiner, detect_simd_features};
+use crate::config::{ThreadCount, ThreadPriority};
+use crate::core::constants::{HASH_SIZE, WORK_SIZE};
+use crate::core::{
+    HashAlgorithm, Midstate, Nonce, NonceStripe, SimdMiner, Target, VectorizedMiner, Work,
+    check_simd_agreement, detect_simd_features, inject_nonce_and_check,
+};
 use crate::error::Result;
 use crate::utils::monitoring::global_monitoring;
-use crate::workers::{MiningResult, Worker};
+use crate::utils::panic::spawn_blocking_monitored;
+use crate::utils::priority::set_current_thread_priority;
+use crate::workers::{
+    DEFAULT_HASHRATE_WARMUP, HashrateWarmup, MiningResult, NoncePosition, Worker,
+    WorkerCapabilities,
+};
 use async_trait::async_trait;
 #[cfg(test)]
 use blake2::Digest;
@@ -11,11 +21,10 @@ use parking_lot::Mutex;
 #[cfg(test)]
 use rayon::prelude::*;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
-use tokio::task;
-use tracing::{debug, info};
+use tokio::sync::{Notify, mpsc};
+use tracing::{debug, info, warn};
 
 /// CPU mining worker configuration
 #[derive(Debug, Clone)]
@@ -26,6 +35,19 @@ pub struct CpuWorkerConfig {
     pub batch_size: u64,
     /// Update interval for hashrate calculation
     pub update_interval: Duration,
+    /// Time after mining starts during which hashrate samples are suppressed
+    pub warmup: Duration,
+    /// OS scheduling priority of the mining threads
+    pub thread_priority: ThreadPriority,
+    /// Hash each nonce from a midstate over the fixed part of the work
+    /// header; bypasses the SIMD and vectorized miners, so it's opt-in
+    pub midstate: bool,
+    /// Nonces searched (over all threads) before giving up on work without
+    /// a solution, so fresh work is fetched (0 = unlimited)
+    pub max_nonces_per_work: u64,
+    /// Memory limit of the per-thread batch buffers in megabytes; the batch
+    /// size is reduced to fit (0 = unlimited)
+    pub max_memory_mb: u64,
 }
 
 impl Default for CpuWorkerConfig {
@@ -34,10 +56,82 @@ impl Default for CpuWorkerConfig {
             threads: 0, // Use all cores
             batch_size: 100_000,
             update_interval: Duration::from_secs(1),
+            warmup: DEFAULT_HASHRATE_WARMUP,
+            thread_priority: ThreadPriority::Normal,
+            midstate: false,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
         }
     }
 }
 
+/// Interval between per-thread hashrate log lines
+const THREAD_STATS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Buffer bytes a mining thread allocates per nonce of batch size: a work and
+/// a hash buffer in both the vectorized and the SIMD miner, plus a pooled
+/// nonce buffer
+const BUFFER_BYTES_PER_NONCE: u64 = 2 * (WORK_SIZE + HASH_SIZE) as u64 + 8;
+
+/// Number of mining threads for a configured count of `threads` (0 for one
+/// per core) on a machine reporting `cores` cores
+fn resolve_threads(threads: usize, cores: usize) -> usize {
+    if threads == 0 {
+        ThreadCount::Auto.resolve(cores)
+    } else {
+        threads
+    }
+}
+
+/// Batch buffer memory of `threads` threads mining `batch_size` nonces per batch
+pub fn projected_buffer_memory(threads: usize, batch_size: u64) -> u64 {
+    (threads as u64)
+        .saturating_mul(batch_size)
+        .saturating_mul(BUFFER_BYTES_PER_NONCE)
+}
+
+/// Largest batch size up to `batch_size` whose buffers for `threads` threads
+/// fit in `max_memory_mb` megabytes (0 = unlimited); at least one nonce
+fn fit_batch_size(threads: usize, batch_size: u64, max_memory_mb: u64) -> u64 {
+    if max_memory_mb == 0 {
+        return batch_size;
+    }
+    let max_bytes = max_memory_mb.saturating_mul(1024 * 1024);
+    let bytes_per_nonce = (threads.max(1) as u64).saturating_mul(BUFFER_BYTES_PER_NONCE);
+    batch_size.min(max_bytes / bytes_per_nonce).max(1)
+}
+
+/// Snapshot of the CPU worker's per-thread hash counters
+#[derive(Debug, Clone, Default)]
+pub struct CpuWorkerStats {
+    /// Hashes computed since mining started, indexed by thread
+    pub thread_hashes: Vec<u64>,
+    /// Time since mining started
+    pub elapsed: Duration,
+}
+
+impl CpuWorkerStats {
+    /// Hashes computed by all threads since mining started
+    pub fn total_hashes(&self) -> u64 {
+        self.thread_hashes.iter().sum()
+    }
+
+    /// Hash rate of each thread (hashes per second), indexed by thread
+    pub fn thread_hashrates(&self) -> Vec<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        self.thread_hashes
+            .iter()
+            .map(|&hashes| {
+                if secs > 0.0 {
+                    hashes as f64 / secs
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
 /// Reusable buffer pool for nonce batches
 #[derive(Clone)]
 struct NonceBufferPool {
@@ -61,9 +155,9 @@ impl NonceBufferPool {
 
     fn get_buffer(&self) -> Vec<u64> {
         let mut buffers = self.buffers.lock();
-        buffers.pop().unwrap_or_else(|| {
-            vec![0; self.batch_size as usize]
-        })
+        buffers
+            .pop()
+            .unwrap_or_else(|| vec![0; self.batch_size as usize])
     }
 
     fn return_buffer(&self, buffer: Vec<u64>) {
@@ -77,34 +171,81 @@ impl NonceBufferPool {
     }
 }
 
+/// Nonce search progress of the work being mined
+struct SearchProgress {
+    work: Work,
+    /// Start of the next batch of each thread, where its stripe continues;
+    /// `u64::MAX` once the stripe ended
+    next_batches: Vec<AtomicU64>,
+}
+
 /// CPU mining worker with SIMD optimizations
 pub struct CpuWorker {
     config: CpuWorkerConfig,
+    threads: usize,
     is_mining: Arc<AtomicBool>,
     hash_count: Arc<AtomicU64>,
+    thread_hashes: Arc<Vec<AtomicU64>>,
+    /// Nonces searched for the current work, across threads
+    nonces_searched: Arc<AtomicU64>,
+    /// Signalled when the nonce budget of the current work is used up
+    exhausted: Arc<Notify>,
+    /// Nonce search progress on the current work
+    progress: Mutex<Option<Arc<SearchProgress>>>,
+    mining_started: Arc<Mutex<Instant>>,
     last_hashrate_time: Arc<Mutex<Instant>>,
+    warmup: Arc<HashrateWarmup>,
     nonce_pool: NonceBufferPool,
     vectorized_miner_pool: Arc<Mutex<Vec<VectorizedMiner>>>,
     simd_miner_pool: Arc<Mutex<Vec<SimdMiner>>>,
     use_simd: bool,
+    hash_algorithm: Option<Arc<dyn HashAlgorithm>>,
 }
 
 impl CpuWorker {
     /// Create a new CPU worker
     pub fn new(config: CpuWorkerConfig) -> Self {
-        let threads = if config.threads == 0 {
-            num_cpus::get()
-        } else {
-            config.threads
-        };
+        let cores = num_cpus::get();
+        let threads = resolve_threads(config.threads, cores);
+        if config.threads == 0 && threads != cores {
+            warn!("Detected {} cores, using {} threads", cores, threads);
+        }
 
         info!("Initializing CPU worker with {} threads", threads);
-        
+
+        let batch_size = fit_batch_size(threads, config.batch_size, config.max_memory_mb);
+        if batch_size < config.batch_size {
+            warn!(
+                "Reducing CPU batch size from {} to {} to stay within {} MB of buffers",
+                config.batch_size, batch_size, config.max_memory_mb
+            );
+        }
+        let config = CpuWorkerConfig {
+            batch_size,
+            ..config
+        };
+        info!(
+            "Allocating {:.1} MB of CPU batch buffers ({} threads, batch size {})",
+            projected_buffer_memory(threads, batch_size) as f64 / (1024.0 * 1024.0),
+            threads,
+            batch_size
+        );
+
         // Detect SIMD features
         let simd_features = detect_simd_features();
-        let use_simd = simd_features.has_avx2 || simd_features.has_sse41 || simd_features.has_neon;
+        let mut use_simd =
+            simd_features.has_avx2 || simd_features.has_sse41 || simd_features.has_neon;
         info!("CPU features: {}", simd_features.description());
-        
+
+        // A SIMD path producing wrong hashes would never meet the real target
+        if use_simd {
+            let sample: [u8; 286] = std::array::from_fn(|i| (i * 31) as u8);
+            if let Err(e) = check_simd_agreement(&sample, 0, 64) {
+                warn!("Disabling SIMD Blake2s: {}", e);
+                use_simd = false;
+            }
+        }
+
         if use_simd {
             info!("Using SIMD-optimized Blake2s implementation");
         } else {
@@ -127,13 +268,41 @@ impl CpuWorker {
 
         Self {
             config: config.clone(),
+            threads,
             is_mining: Arc::new(AtomicBool::new(false)),
             hash_count: Arc::new(AtomicU64::new(0)),
+            thread_hashes: Arc::new((0..threads).map(|_| AtomicU64::new(0)).collect()),
+            nonces_searched: Arc::new(AtomicU64::new(0)),
+            exhausted: Arc::new(Notify::new()),
+            progress: Mutex::new(None),
+            mining_started: Arc::new(Mutex::new(Instant::now())),
             last_hashrate_time: Arc::new(Mutex::new(Instant::now())),
+            warmup: Arc::new(HashrateWarmup::new(config.warmup)),
             nonce_pool: NonceBufferPool::new(config.batch_size, threads),
             vectorized_miner_pool: Arc::new(Mutex::new(vectorized_miners)),
             simd_miner_pool: Arc::new(Mutex::new(simd_miners)),
             use_simd,
+            hash_algorithm: None,
+        }
+    }
+
+    /// Mine with a custom hash algorithm instead of the built-in optimized
+    /// Blake2s-256 miners
+    pub fn with_hash_algorithm(mut self, algorithm: Arc<dyn HashAlgorithm>) -> Self {
+        info!("CPU worker using {} hash algorithm", algorithm.name());
+        self.hash_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Per-thread hash counts since mining started
+    pub fn stats(&self) -> CpuWorkerStats {
+        CpuWorkerStats {
+            thread_hashes: self
+                .thread_hashes
+                .iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+            elapsed: self.mining_started.lock().elapsed(),
         }
     }
 
@@ -176,6 +345,30 @@ impl CpuWorker {
         })
     }
 
+    /// Batch mining with a pluggable hash algorithm, one nonce at a time
+    fn mine_batch_with_algorithm(
+        algorithm: &dyn HashAlgorithm,
+        work_bytes: &[u8; 286],
+        target: &Target,
+        start_nonce: u64,
+        batch_size: u64,
+        is_mining: &AtomicBool,
+    ) -> Option<(Nonce, [u8; 32])> {
+        for offset in 0..batch_size {
+            // Check for cancellation every 1024 nonces
+            if offset % 1024 == 0 && !is_mining.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let nonce = Nonce::new(start_nonce.wrapping_add(offset));
+            if let Some(hash) = inject_nonce_and_check(algorithm, work_bytes, nonce, target) {
+                return Some((nonce, hash));
+            }
+        }
+
+        None
+    }
+
     /// SIMD-optimized batch mining using new SIMD hasher
     fn mine_batch_simd_optimized(
         work_bytes: &[u8; 286],
@@ -201,12 +394,9 @@ impl CpuWorker {
             };
 
             // Use SIMD mining for this batch
-            if let Some((nonce, hash)) = simd_miner.mine_batch(
-                work_bytes,
-                target,
-                batch_start_nonce,
-                current_batch_size,
-            ) {
+            if let Some((nonce, hash)) =
+                simd_miner.mine_batch(work_bytes, target, batch_start_nonce, current_batch_size)
+            {
                 return Some((nonce, hash));
             }
         }
@@ -256,13 +446,14 @@ impl CpuWorker {
     }
 }
 
-#[async_trait]
-impl Worker for CpuWorker {
-    async fn mine(
+impl CpuWorker {
+    /// Start the mining threads on `work`, searching from `start_nonce`
+    fn start_mining(
         &self,
         work: Work,
         target: Target,
         result_tx: mpsc::Sender<MiningResult>,
+        start_nonce: u64,
     ) -> Result<()> {
         if self.is_mining.load(Ordering::Relaxed) {
             return Err(crate::error::Error::worker("Already mining"));
@@ -270,168 +461,341 @@ impl Worker for CpuWorker {
 
         self.is_mining.store(true, Ordering::Relaxed);
         self.hash_count.store(0, Ordering::Relaxed);
+        self.nonces_searched.store(0, Ordering::Relaxed);
+        for count in self.thread_hashes.iter() {
+            count.store(0, Ordering::Relaxed);
+        }
         *self.last_hashrate_time.lock() = Instant::now();
+        *self.mining_started.lock() = Instant::now();
+        self.warmup.start();
 
-        let is_mining = self.is_mining.clone();
-        let hash_count = self.hash_count.clone();
+        let threads = self.threads;
         let batch_size = self.config.batch_size;
-        let nonce_pool = self.nonce_pool.clone();
-        let vectorized_pool = self.vectorized_miner_pool.clone();
-        let simd_pool = self.simd_miner_pool.clone();
         let use_simd = self.use_simd;
+        let hash_algorithm = self.hash_algorithm.clone();
+        let thread_priority = self.config.thread_priority;
+        let max_nonces = self.config.max_nonces_per_work;
 
         // Get work as bytes once to avoid repeated cloning
         let work_bytes = *work.as_bytes();
 
-        // Spawn mining task
-        task::spawn_blocking(move || {
-            let mut current_nonce = 0u64;
-            let nonce_buffer = nonce_pool.get_buffer();
-
-            // Get appropriate miner based on SIMD support
-            let mut vectorized_miner = if !use_simd {
-                Some({
-                    let mut pool = vectorized_pool.lock();
-                    pool.pop()
-                        .unwrap_or_else(|| VectorizedMiner::new(batch_size as usize))
-                })
-            } else {
-                None
-            };
-            
-            let mut simd_miner = if use_simd {
-                Some({
-                    let mut pool = simd_pool.lock();
-                    pool.pop()
-                        .unwrap_or_else(|| SimdMiner::new(batch_size as usize))
-                })
-            } else {
-                None
-            };
-
-            // Initialize monitoring
-            let monitoring = global_monitoring();
-            let start_time = Instant::now();
-            let mut last_hash_rate_update = Instant::now();
+        // The prefix before the nonce is the same for every thread
+        let midstate =
+            (self.config.midstate && hash_algorithm.is_none()).then(|| Midstate::new(&work_bytes));
+        let optimizations = if midstate.is_some() {
+            "midstate"
+        } else if use_simd {
+            "AVX2/SIMD"
+        } else {
+            "standard"
+        };
 
-            info!("Starting CPU mining with {} optimizations", 
-                  if use_simd { "AVX2/SIMD" } else { "standard" });
+        if start_nonce == 0 {
+            info!(
+                "Starting CPU mining on {} threads with {} optimizations",
+                threads, optimizations
+            );
+        } else {
+            info!(
+                "Resuming CPU mining at nonce {} on {} threads with {} optimizations",
+                start_nonce, threads, optimizations
+            );
+        }
 
-            let mining_result = loop {
-                if !is_mining.load(Ordering::Relaxed) {
-                    break None;
+        let progress = Arc::new(SearchProgress {
+            work: work.clone(),
+            next_batches: (0..threads).map(|_| AtomicU64::new(start_nonce)).collect(),
+        });
+        *self.progress.lock() = Some(progress.clone());
+
+        // Spawn one mining task per thread; thread `i` checks every
+        // `threads`-th batch starting at batch `i`, see [`NonceStripe`]
+        let finished_stripes = Arc::new(AtomicUsize::new(0));
+        for thread_index in 0..threads {
+            let is_mining = self.is_mining.clone();
+            let hash_count = self.hash_count.clone();
+            let thread_hashes = self.thread_hashes.clone();
+            let nonce_pool = self.nonce_pool.clone();
+            let vectorized_pool = self.vectorized_miner_pool.clone();
+            let simd_pool = self.simd_miner_pool.clone();
+            let result_tx = result_tx.clone();
+            let work = work.clone();
+            let hash_algorithm = hash_algorithm.clone();
+            let midstate = midstate.clone();
+            let warmup = self.warmup.clone();
+            let nonces_searched = self.nonces_searched.clone();
+            let exhausted = self.exhausted.clone();
+            let finished_stripes = finished_stripes.clone();
+            let progress = progress.clone();
+
+            spawn_blocking_monitored("cpu worker", move || {
+                // Runtime blocking threads keep a lowered priority after the
+                // task ends; they are only reused for blocking work
+                if let Err(e) = set_current_thread_priority(thread_priority) {
+                    warn!("{}, mining at normal priority", e);
                 }
 
-                // Use appropriate mining method based on SIMD support
-                let mining_result = if let Some(ref mut simd_miner) = simd_miner {
-                    Self::mine_batch_simd_optimized(
-                        &work_bytes,
-                        &target,
-                        current_nonce,
-                        batch_size,
-                        simd_miner,
-                        &is_mining,
-                    )
-                } else if let Some(ref mut vectorized_miner) = vectorized_miner {
-                    Self::mine_batch_simd(
-                        &work_bytes,
-                        &target,
-                        current_nonce,
-                        batch_size,
-                        vectorized_miner,
-                        &is_mining,
-                    )
+                let mut stripe = NonceStripe::new(start_nonce, thread_index, threads, batch_size);
+                let mut batches = 0u64;
+                let nonce_buffer = nonce_pool.get_buffer();
+
+                // Get appropriate miner based on SIMD support; a custom hash
+                // algorithm or the midstate doesn't use the Blake2s miners
+                let batch_miner = hash_algorithm.is_none() && midstate.is_none();
+                let mut vectorized_miner = if !use_simd && batch_miner {
+                    Some({
+                        let mut pool = vectorized_pool.lock();
+                        pool.pop()
+                            .unwrap_or_else(|| VectorizedMiner::new(batch_size as usize))
+                    })
                 } else {
                     None
                 };
 
-                if let Some((nonce, hash)) = mining_result {
-                    info!("Found solution! Nonce: {} ({})", nonce,
-                          if use_simd { "AVX2/SIMD" } else { "standard" });
-
-                    // Record solution found
-                    monitoring.record_solution();
-
-                    // Create solved work only when solution is found
-                    let mut solved_work = work;
-                    solved_work.set_nonce(nonce);
+                let mut simd_miner = if use_simd && batch_miner {
+                    Some({
+                        let mut pool = simd_pool.lock();
+                        pool.pop()
+                            .unwrap_or_else(|| SimdMiner::new(batch_size as usize))
+                    })
+                } else {
+                    None
+                };
 
-                    let result = MiningResult {
-                        work: solved_work,
-                        nonce,
-                        hash,
+                // Initialize monitoring; the first thread reports for all of them
+                let monitoring = global_monitoring();
+                let start_time = Instant::now();
+                let mut last_hash_rate_update = Instant::now();
+                let mut last_thread_stats_log = Instant::now();
+
+                let mining_result = loop {
+                    if !is_mining.load(Ordering::Relaxed) {
+                        break None;
+                    }
+
+                    let Some((current_nonce, batch_len)) = stripe.next() else {
+                        progress.next_batches[thread_index].store(u64::MAX, Ordering::Relaxed);
+                        // The stripe ended at the top of the nonce space; once
+                        // every thread got there, the work is used up
+                        if finished_stripes.fetch_add(1, Ordering::Relaxed) + 1 == threads
+                            && is_mining
+                                .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
+                                .is_ok()
+                        {
+                            info!("Searched the whole nonce space, requesting fresh work");
+                            exhausted.notify_one();
+                        }
+                        break None;
                     };
 
-                    // Stop mining after finding solution
-                    is_mining.store(false, Ordering::Relaxed);
-                    break Some(result);
-                }
-
-                // Update counters
-                hash_count.fetch_add(batch_size, Ordering::Relaxed);
-                current_nonce = current_nonce.wrapping_add(batch_size);
-
-                // Update monitoring metrics periodically
-                let now = Instant::now();
-                if now.duration_since(last_hash_rate_update) >= Duration::from_secs(1) {
-                    let total_hashes = hash_count.load(Ordering::Relaxed);
-                    let elapsed = now.duration_since(start_time).as_secs_f64();
-                    let current_hash_rate = if elapsed > 0.0 {
-                        total_hashes as f64 / elapsed
+                    // Use appropriate mining method based on SIMD support
+                    let mining_result = if let Some(ref algorithm) = hash_algorithm {
+                        Self::mine_batch_with_algorithm(
+                            algorithm.as_ref(),
+                            &work_bytes,
+                            &target,
+                            current_nonce,
+                            batch_len,
+                            &is_mining,
+                        )
+                    } else if let Some(ref midstate) = midstate {
+                        midstate.mine_batch(&target, current_nonce, batch_len, &is_mining)
+                    } else if let Some(ref mut simd_miner) = simd_miner {
+                        Self::mine_batch_simd_optimized(
+                            &work_bytes,
+                            &target,
+                            current_nonce,
+                            batch_len,
+                            simd_miner,
+                            &is_mining,
+                        )
+                    } else if let Some(ref mut vectorized_miner) = vectorized_miner {
+                        Self::mine_batch_simd(
+                            &work_bytes,
+                            &target,
+                            current_nonce,
+                            batch_len,
+                            vectorized_miner,
+                            &is_mining,
+                        )
                     } else {
-                        0.0
+                        None
                     };
 
-                    monitoring.record_hash_rate(current_hash_rate);
-                    last_hash_rate_update = now;
-                }
+                    if let Some((nonce, hash)) = mining_result {
+                        // Stop mining after finding solution; only the first
+                        // thread to get here reports it
+                        if is_mining
+                            .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
+                            .is_err()
+                        {
+                            break None;
+                        }
+
+                        info!(
+                            "Found solution! Nonce: {} (le {}, thread {}, {})",
+                            nonce,
+                            nonce.to_hex_le(),
+                            thread_index,
+                            optimizations
+                        );
+
+                        // Create solved work only when solution is found
+                        let mut solved_work = work;
+                        solved_work.set_nonce(nonce);
+
+                        break Some(MiningResult {
+                            work: solved_work,
+                            nonce,
+                            hash,
+                        });
+                    }
+
+                    // Update counters; a batch cut short by a stop doesn't
+                    // count as searched
+                    if is_mining.load(Ordering::Relaxed) {
+                        progress.next_batches[thread_index]
+                            .store(current_nonce.saturating_add(batch_len), Ordering::Relaxed);
+                    }
+                    hash_count.fetch_add(batch_len, Ordering::Relaxed);
+                    thread_hashes[thread_index].fetch_add(batch_len, Ordering::Relaxed);
+                    batches += 1;
+
+                    // Give up on the work once the threads together searched
+                    // the budget; the first thread to notice reports it
+                    if max_nonces > 0
+                        && nonces_searched.fetch_add(batch_len, Ordering::Relaxed) + batch_len
+                            >= max_nonces
+                    {
+                        if is_mining
+                            .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
+                            .is_ok()
+                        {
+                            info!(
+                                "Searched {} nonces without a solution, requesting fresh work",
+                                max_nonces
+                            );
+                            exhausted.notify_one();
+                        }
+                        break None;
+                    }
+
+                    // Update monitoring metrics periodically
+                    let now = Instant::now();
+                    if thread_index == 0
+                        && !warmup.is_warming_up()
+                        && now.duration_since(last_hash_rate_update) >= Duration::from_secs(1)
+                    {
+                        let elapsed = now.duration_since(start_time).as_secs_f64();
+                        let thread_rates: Vec<f64> = thread_hashes
+                            .iter()
+                            .map(|count| count.load(Ordering::Relaxed) as f64 / elapsed)
+                            .collect();
+
+                        monitoring.record_hash_rate(thread_rates.iter().sum());
+                        monitoring.record_thread_hash_rates(&thread_rates);
+                        last_hash_rate_update = now;
+
+                        if now.duration_since(last_thread_stats_log) >= THREAD_STATS_LOG_INTERVAL {
+                            let rates: Vec<String> = thread_rates
+                                .iter()
+                                .map(|rate| format!("{:.0}", rate))
+                                .collect();
+                            info!("CPU per-thread hashrate (H/s): [{}]", rates.join(", "));
+                            last_thread_stats_log = now;
+                        }
+                    }
+
+                    // Yield occasionally to prevent blocking
+                    if batches.is_multiple_of(100) {
+                        std::thread::yield_now();
+                    }
+                };
 
-                // Yield occasionally to prevent blocking
-                if current_nonce % (batch_size * 100) == 0 {
-                    std::thread::yield_now();
+                // Return miners to their pools
+                if let Some(vectorized_miner) = vectorized_miner {
+                    let mut pool = vectorized_pool.lock();
+                    if pool.len() < 16 {
+                        // Limit pool size
+                        pool.push(vectorized_miner);
+                    }
                 }
-            };
 
-            // Return miners to their pools
-            if let Some(vectorized_miner) = vectorized_miner {
-                let mut pool = vectorized_pool.lock();
-                if pool.len() < 16 {
-                    // Limit pool size
-                    pool.push(vectorized_miner);
-                }
-            }
-            
-            if let Some(simd_miner) = simd_miner {
-                let mut pool = simd_pool.lock();
-                if pool.len() < 16 {
-                    // Limit pool size
-                    pool.push(simd_miner);
+                if let Some(simd_miner) = simd_miner {
+                    let mut pool = simd_pool.lock();
+                    if pool.len() < 16 {
+                        // Limit pool size
+                        pool.push(simd_miner);
+                    }
                 }
-            }
 
-            // Return buffer to pool before sending result
-            nonce_pool.return_buffer(nonce_buffer);
+                // Return buffer to pool before sending result
+                nonce_pool.return_buffer(nonce_buffer);
 
-            // Send result if found
-            if let Some(result) = mining_result {
-                let _ = result_tx.blocking_send(result);
-            }
+                // Send result if found
+                if let Some(result) = mining_result {
+                    let _ = result_tx.blocking_send(result);
+                }
 
-            debug!("CPU mining stopped");
-        });
+                debug!("CPU mining thread {} stopped", thread_index);
+            });
+        }
 
         Ok(())
     }
+}
+
+#[async_trait]
+impl Worker for CpuWorker {
+    async fn mine(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+    ) -> Result<()> {
+        self.start_mining(work, target, result_tx, 0)
+    }
+
+    async fn resume(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+        start_nonce: u64,
+    ) -> Result<()> {
+        self.start_mining(work, target, result_tx, start_nonce)
+    }
+
+    fn nonce_position(&self) -> Option<NoncePosition> {
+        let progress = self.progress.lock().clone()?;
+        let nonce = progress
+            .next_batches
+            .iter()
+            .map(|next| next.load(Ordering::Relaxed))
+            .min()?;
+        Some(NoncePosition {
+            work: progress.work.clone(),
+            nonce,
+        })
+    }
 
     async fn stop(&self) -> Result<()> {
         self.is_mining.store(false, Ordering::Relaxed);
         Ok(())
     }
 
+    async fn work_exhausted(&self) {
+        self.exhausted.notified().await
+    }
+
     fn worker_type(&self) -> &str {
         "CPU"
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities::compute()
+    }
+
     async fn hashrate(&self) -> u64 {
         let hashes = self.hash_count.load(Ordering::Relaxed);
         let elapsed = self.last_hashrate_time.lock().elapsed();
@@ -444,14 +808,14 @@ impl Worker for CpuWorker {
         self.hash_count.store(0, Ordering::Relaxed);
         *self.last_hashrate_time.lock() = Instant::now();
 
-        hashes / elapsed.as_secs()
+        self.warmup.report(hashes / elapsed.as_secs())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::constants::WORK_SIZE;
+    use crate::core::constants::{NONCE_OFFSET, WORK_SIZE};
 
     #[tokio::test]
     async fn test_cpu_worker_creation() {
@@ -467,6 +831,11 @@ mod tests {
             threads: 2,
             batch_size: 1000,
             update_interval: Duration::from_millis(100),
+            warmup: Duration::ZERO,
+            thread_priority: ThreadPriority::Normal,
+            midstate: true,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
         };
         let worker = CpuWorker::new(config);
 
@@ -519,6 +888,265 @@ mod tests {
         assert!(!worker.is_mining.load(Ordering::Relaxed));
     }
 
+    /// Hash algorithm whose digest is all zeros, so any non-zero target is met
+    #[derive(Debug)]
+    struct TrivialHash;
+
+    impl HashAlgorithm for TrivialHash {
+        fn name(&self) -> &str {
+            "trivial"
+        }
+
+        fn hash(&self, _data: &[u8]) -> [u8; 32] {
+            [0u8; 32]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cpu_worker_with_mock_hash_algorithm() {
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 2,
+            batch_size: 1000,
+            update_interval: Duration::from_millis(100),
+            warmup: Duration::ZERO,
+            thread_priority: ThreadPriority::Normal,
+            midstate: true,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
+        })
+        .with_hash_algorithm(Arc::new(TrivialHash));
+
+        // Smallest non-zero target: practically impossible with Blake2s
+        let mut target_bytes = [0u8; 32];
+        target_bytes[0] = 1;
+        let target = Target::from_bytes(target_bytes);
+        let work = Work::from_bytes([0u8; WORK_SIZE]);
+        let (tx, mut rx) = mpsc::channel(1);
+
+        worker.mine(work, target, tx).await.unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .expect("No solution found");
+        assert_eq!(result.hash, [0u8; 32]);
+        assert_eq!(result.work.nonce(), result.nonce);
+        // Every thread solves its first nonce; one of them wins
+        assert_eq!(result.nonce.value() % 1000, 0);
+    }
+
+    #[tokio::test]
+    async fn test_hashrate_suppressed_during_warmup() {
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 1,
+            batch_size: 1000,
+            update_interval: Duration::from_millis(100),
+            warmup: Duration::from_millis(1500),
+            thread_priority: ThreadPriority::Normal,
+            midstate: true,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
+        });
+
+        // Very hard target (won't find solution)
+        let target = Target::from_bytes([0x00; 32]);
+        let work = Work::from_bytes([0u8; WORK_SIZE]);
+        let (tx, _rx) = mpsc::channel(1);
+
+        worker.mine(work, target, tx).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(worker.warmup.is_warming_up());
+        assert_eq!(worker.hashrate().await, 0);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(!worker.warmup.is_warming_up());
+        assert!(worker.hashrate().await > 0);
+        worker.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_thread_hashes_sum_to_aggregate() {
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 3,
+            batch_size: 1000,
+            update_interval: Duration::from_millis(100),
+            warmup: Duration::ZERO,
+            thread_priority: ThreadPriority::Normal,
+            midstate: true,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
+        });
+
+        // Very hard target (won't find solution)
+        let target = Target::from_bytes([0x00; 32]);
+        let work = Work::from_bytes([0u8; WORK_SIZE]);
+        let (tx, _rx) = mpsc::channel(1);
+
+        worker.mine(work, target, tx).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        worker.stop().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let stats = worker.stats();
+        assert_eq!(stats.thread_hashes.len(), 3);
+        assert!(stats.thread_hashes.iter().all(|&hashes| hashes > 0));
+        assert_eq!(
+            stats.total_hashes(),
+            worker.hash_count.load(Ordering::Relaxed)
+        );
+        assert_eq!(stats.thread_hashrates().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_worker_yields_after_nonce_budget() {
+        const BUDGET: u64 = 20_000;
+        const BATCH: u64 = 1000;
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 2,
+            batch_size: BATCH,
+            update_interval: Duration::from_millis(100),
+            warmup: Duration::ZERO,
+            thread_priority: ThreadPriority::Normal,
+            midstate: true,
+            max_nonces_per_work: BUDGET,
+            max_memory_mb: 0,
+        });
+
+        // Impossible target: only the budget ends the search
+        let target = Target::from_bytes([0x00; 32]);
+        let (tx, mut rx) = mpsc::channel(1);
+        worker
+            .mine(Work::from_bytes([0u8; WORK_SIZE]), target, tx)
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), worker.work_exhausted())
+            .await
+            .expect("worker kept mining past its nonce budget");
+        assert!(!worker.is_mining.load(Ordering::Relaxed));
+
+        // Threads finish the batch they are on, at most one each
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let searched = worker.stats().total_hashes();
+        assert!(
+            (BUDGET..BUDGET + 2 * BATCH).contains(&searched),
+            "searched {}",
+            searched
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// Hash algorithm that never meets a target and records every nonce it
+    /// hashes along with the thread hashing it
+    #[derive(Debug, Default)]
+    struct RecordingHash {
+        hashed: parking_lot::Mutex<Vec<(std::thread::ThreadId, u64)>>,
+    }
+
+    impl HashAlgorithm for RecordingHash {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn hash(&self, data: &[u8]) -> [u8; 32] {
+            let nonce =
+                u64::from_le_bytes(data[NONCE_OFFSET..NONCE_OFFSET + 8].try_into().unwrap());
+            self.hashed
+                .lock()
+                .push((std::thread::current().id(), nonce));
+            [0xFF; 32]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_threads_search_disjoint_stripes_without_gaps() {
+        const THREADS: usize = 3;
+        const BATCH: u64 = 7;
+        const START: u64 = 1_000;
+        let recording = Arc::new(RecordingHash::default());
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: THREADS,
+            batch_size: BATCH,
+            update_interval: Duration::from_millis(100),
+            warmup: Duration::ZERO,
+            thread_priority: ThreadPriority::Normal,
+            midstate: false,
+            max_nonces_per_work: 20 * THREADS as u64 * BATCH,
+            max_memory_mb: 0,
+        })
+        .with_hash_algorithm(recording.clone());
+
+        let (tx, _rx) = mpsc::channel(1);
+        let target = Target::from_bytes([0x00; 32]);
+        worker
+            .resume(Work::from_bytes([0u8; WORK_SIZE]), target, tx, START)
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), worker.work_exhausted())
+            .await
+            .expect("worker kept mining past its nonce budget");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // No nonce is hashed twice, and none before the resume position
+        let hashed = recording.hashed.lock().clone();
+        let nonces: std::collections::BTreeSet<u64> =
+            hashed.iter().map(|&(_, nonce)| nonce).collect();
+        assert_eq!(nonces.len(), hashed.len());
+        assert!(nonces.iter().all(|&nonce| nonce >= START));
+
+        // Each thread hashes a gapless prefix of its own stripe
+        let mut by_thread = std::collections::HashMap::<_, Vec<u64>>::new();
+        for (thread, nonce) in hashed {
+            by_thread.entry(thread).or_default().push(nonce);
+        }
+        let mut stripes = std::collections::HashSet::new();
+        for mut searched in by_thread.into_values() {
+            searched.sort_unstable();
+            let stripe = ((searched[0] - START) / BATCH) as usize;
+            assert!(stripe < THREADS && stripes.insert(stripe));
+            let expected: Vec<u64> = NonceStripe::new(START, stripe, THREADS, BATCH)
+                .flat_map(|(first, len)| first..first + len)
+                .take(searched.len())
+                .collect();
+            assert_eq!(searched, expected);
+        }
+
+        // Resuming at the reported position skips no nonce
+        let position = worker.nonce_position().unwrap().nonce;
+        assert!((START..position).all(|nonce| nonces.contains(&nonce)));
+    }
+
+    #[tokio::test]
+    async fn test_stop_halts_every_thread() {
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 3,
+            batch_size: 1000,
+            update_interval: Duration::from_millis(100),
+            warmup: Duration::ZERO,
+            thread_priority: ThreadPriority::Normal,
+            midstate: false,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
+        });
+
+        // Very hard target (won't find solution)
+        let target = Target::from_bytes([0x00; 32]);
+        let (tx, _rx) = mpsc::channel(1);
+        worker
+            .mine(Work::from_bytes([0u8; WORK_SIZE]), target, tx)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        worker.stop().await.unwrap();
+
+        // Threads finish the batch they are on, then no thread hashes any more
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let stopped = worker.stats();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(worker.stats().thread_hashes, stopped.thread_hashes);
+        assert!(stopped.thread_hashes.iter().all(|&hashes| hashes > 0));
+    }
+
     #[test]
     fn test_mine_batch() {
         let work = Work::from_bytes([0u8; WORK_SIZE]);
@@ -544,4 +1172,42 @@ mod tests {
             assert!(nonce.value() < 1000);
         }
     }
+
+    #[test]
+    fn test_resolve_threads() {
+        use crate::config::MAX_AUTO_THREADS;
+
+        // 0 selects all cores, an explicit count is kept as is
+        assert_eq!(resolve_threads(0, 8), 8);
+        assert_eq!(resolve_threads(3, 8), 3);
+        assert_eq!(resolve_threads(2048, 8), 2048);
+
+        // Degenerate core detection
+        assert_eq!(resolve_threads(0, 0), 1);
+        assert_eq!(resolve_threads(0, 1_000_000), MAX_AUTO_THREADS);
+        assert_eq!(resolve_threads(3, 0), 3);
+    }
+
+    #[test]
+    fn test_batch_size_is_clamped_to_memory_limit() {
+        let worker = CpuWorker::new(CpuWorkerConfig {
+            threads: 4,
+            batch_size: 100_000,
+            max_memory_mb: 1,
+            ..Default::default()
+        });
+        let batch_size = worker.config.batch_size;
+        assert_eq!(batch_size, 1024 * 1024 / (4 * BUFFER_BYTES_PER_NONCE));
+        assert!(projected_buffer_memory(4, batch_size) <= 1024 * 1024);
+        assert_eq!(
+            worker.simd_miner_pool.lock()[0].batch_size(),
+            batch_size as usize
+        );
+
+        // Configs within the limit, or without one, are left alone
+        assert_eq!(fit_batch_size(4, 100, 1), 100);
+        assert_eq!(fit_batch_size(64, 100_000, 0), 100_000);
+        // A limit too small for a single nonce per thread still mines
+        assert_eq!(fit_batch_size(10_000, 1000, 1), 1);
+    }
 }
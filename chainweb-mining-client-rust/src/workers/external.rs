@@ -2,17 +2,18 @@
 
 use crate::core::{Nonce, Target, Work};
 use crate::error::{Error, Result};
-use crate::workers::{MiningResult, Worker};
-use async_process::{Child, Command, Stdio};
+use crate::utils::monitoring::global_monitoring;
+use crate::utils::panic::spawn_monitored;
+use crate::workers::{CancellationReason, MiningResult, Worker, WorkerCapabilities};
+use async_process::{Child, ChildStdout, Command, Stdio};
 use async_trait::async_trait;
 use futures::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use parking_lot::Mutex;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::time::Instant;
-use tokio::sync::mpsc;
-use tokio::task;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc, watch};
 use tracing::{debug, error, info};
 
 /// External worker configuration
@@ -26,12 +27,24 @@ pub struct ExternalWorkerConfig {
     pub env: Vec<(String, String)>,
     /// Timeout for receiving results (seconds)
     pub timeout_secs: u64,
+    /// Maximum number of processes running at once
+    pub max_concurrent: usize,
 }
 
 /// External worker for GPU mining
+///
+/// Each call to [`Worker::mine`] supersedes the previous invocations: their
+/// processes are killed, and the new process waits for a free slot, so that
+/// no more than [`ExternalWorkerConfig::max_concurrent`] processes ever run
+/// at once. An invocation superseded while it waits is dropped.
 pub struct ExternalWorker {
     config: ExternalWorkerConfig,
-    process: Arc<Mutex<Option<Child>>>,
+    /// Number of the latest invocation, bumped to cancel earlier ones
+    generation: watch::Sender<u64>,
+    /// Slots for running processes, held until a process has been reaped
+    slots: Arc<Semaphore>,
+    /// Number of processes started and not yet reaped
+    running: Arc<AtomicUsize>,
     is_mining: Arc<AtomicBool>,
     hash_count: Arc<AtomicU64>,
     start_time: Arc<Mutex<Option<Instant>>>,
@@ -46,14 +59,46 @@ impl ExternalWorker {
         );
 
         Self {
+            generation: watch::Sender::new(0),
+            slots: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+            running: Arc::new(AtomicUsize::new(0)),
             config,
-            process: Arc::new(Mutex::new(None)),
             is_mining: Arc::new(AtomicBool::new(false)),
             hash_count: Arc::new(AtomicU64::new(0)),
             start_time: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Write `work` to the stdin of `child` and close it, returning its stdout
+    async fn send_work(child: &mut Child, work: &Work) -> Result<ChildStdout> {
+        // Get process streams
+        let mut stdin = child.stdin.take().ok_or_else(|| {
+            Error::worker_initialization_failed(
+                "External",
+                "Failed to get stdin handle from external process",
+            )
+        })?;
+
+        let stdout = child.stdout.take().ok_or_else(|| {
+            Error::worker_initialization_failed(
+                "External",
+                "Failed to get stdout handle from external process",
+            )
+        })?;
+
+        // Write work to stdin
+        stdin.write_all(work.as_bytes()).await.map_err(|e| {
+            Error::communication_channel_send_failed(
+                "external_process_stdin",
+                format!("Failed to write work data: {}", e),
+            )
+        })?;
+        stdin.flush().await?;
+        drop(stdin); // Close stdin
+
+        Ok(stdout)
+    }
+
     /// Parse nonce from external miner output
     fn parse_nonce(line: &str) -> Option<Nonce> {
         // Try to parse as decimal
@@ -71,6 +116,42 @@ impl ExternalWorker {
     }
 }
 
+/// A running external process holding one of the worker's slots
+struct Invocation {
+    child: Child,
+    _slot: OwnedSemaphorePermit,
+    running: Arc<AtomicUsize>,
+}
+
+impl Invocation {
+    fn new(child: Child, slot: OwnedSemaphorePermit, running: Arc<AtomicUsize>) -> Self {
+        running.fetch_add(1, Ordering::Relaxed);
+        Self {
+            child,
+            _slot: slot,
+            running,
+        }
+    }
+
+    /// Kill the process, if still running, and reap it before the slot is
+    /// released
+    async fn terminate(mut self) {
+        if let Err(e) = self.child.kill() {
+            // The process has usually exited on its own already
+            debug!("Failed to kill external worker: {}", e);
+        }
+        if let Err(e) = self.child.status().await {
+            error!("Failed to reap external worker: {}", e);
+        }
+    }
+}
+
+impl Drop for Invocation {
+    fn drop(&mut self) {
+        self.running.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 #[async_trait]
 impl Worker for ExternalWorker {
     async fn mine(
@@ -79,8 +160,18 @@ impl Worker for ExternalWorker {
         target: Target,
         result_tx: mpsc::Sender<MiningResult>,
     ) -> Result<()> {
-        if self.is_mining.load(Ordering::Relaxed) {
-            return Err(Error::worker_mining_failed("Worker is already mining"));
+        // Supersede running invocations and wait for a slot
+        self.generation.send_modify(|generation| *generation += 1);
+        let generation = *self.generation.borrow();
+        let slot = self
+            .slots
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::worker_mining_failed("External worker slots closed"))?;
+        if *self.generation.borrow() != generation {
+            debug!("External worker invocation superseded before it started");
+            return Ok(());
         }
 
         self.is_mining.store(true, Ordering::Relaxed);
@@ -104,60 +195,38 @@ impl Worker for ExternalWorker {
         // Configure process pipes
         cmd.stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
 
         // Start the external process
-        let mut child = cmd
-            .spawn()
-            .map_err(|e| {
-                Error::worker_initialization_failed(
-                    "External",
-                    format!("Failed to start command {}: {}", self.config.command.display(), e)
-                )
-            })?;
-
-        // Get process streams
-        let mut stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| {
-                Error::worker_initialization_failed(
-                    "External",
-                    "Failed to get stdin handle from external process"
-                )
-            })?;
-
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| {
-                Error::worker_initialization_failed(
-                    "External",
-                    "Failed to get stdout handle from external process"
-                )
-            })?;
-
-        // Write work to stdin
-        stdin
-            .write_all(work.as_bytes())
-            .await
-            .map_err(|e| {
-                Error::communication_channel_send_failed(
-                    "external_process_stdin",
-                    format!("Failed to write work data: {}", e)
-                )
-            })?;
-        stdin.flush().await?;
-        drop(stdin); // Close stdin
-
-        // Store process handle
-        *self.process.lock() = Some(child);
+        let child = cmd.spawn().map_err(|e| {
+            self.is_mining.store(false, Ordering::Relaxed);
+            Error::worker_initialization_failed(
+                "External",
+                format!(
+                    "Failed to start command {}: {}",
+                    self.config.command.display(),
+                    e
+                ),
+            )
+        })?;
+        let mut invocation = Invocation::new(child, slot, self.running.clone());
+
+        let stdout = match Self::send_work(&mut invocation.child, &work).await {
+            Ok(stdout) => stdout,
+            Err(e) => {
+                self.is_mining.store(false, Ordering::Relaxed);
+                invocation.terminate().await;
+                return Err(e);
+            }
+        };
 
         let is_mining = self.is_mining.clone();
+        let mut cancel = self.generation.subscribe();
         let timeout_secs = self.config.timeout_secs;
 
         // Spawn task to read results
-        task::spawn(async move {
+        spawn_monitored("external worker", async move {
             let mut reader = BufReader::new(stdout);
             let mut line = String::new();
 
@@ -166,12 +235,17 @@ impl Worker for ExternalWorker {
             loop {
                 line.clear();
 
-                // Read with timeout
-                let read_result = tokio::time::timeout(
-                    std::time::Duration::from_secs(timeout_secs),
-                    reader.read_line(&mut line),
-                )
-                .await;
+                // Read with timeout, unless superseded
+                let read_result = tokio::select! {
+                    _ = cancel.wait_for(|current| *current != generation) => {
+                        debug!("External worker process superseded");
+                        break;
+                    }
+                    read_result = tokio::time::timeout(
+                        std::time::Duration::from_secs(timeout_secs),
+                        reader.read_line(&mut line),
+                    ) => read_result,
+                };
 
                 match read_result {
                     Ok(Ok(0)) => {
@@ -184,10 +258,13 @@ impl Worker for ExternalWorker {
 
                         // Try to parse nonce from output
                         if let Some(nonce) = Self::parse_nonce(&line) {
-                            info!("External worker found solution: {}", nonce);
+                            info!(
+                                "External worker found solution: {} (le {})",
+                                nonce,
+                                nonce.to_hex_le()
+                            );
 
-                            let mut solved_work = work.clone();
-                            solved_work.set_nonce(nonce);
+                            let solved_work = work.with_nonce(nonce);
                             let hash = solved_work.hash();
 
                             // Verify the solution
@@ -200,7 +277,6 @@ impl Worker for ExternalWorker {
 
                                 // Send result
                                 let _ = result_tx.send(result).await;
-                                is_mining.store(false, Ordering::Relaxed);
                                 break;
                             } else {
                                 error!("External worker provided invalid solution");
@@ -212,17 +288,21 @@ impl Worker for ExternalWorker {
                         break;
                     }
                     Err(_) => {
-                        error!("External worker timeout");
+                        error!(
+                            "External worker produced no output in {}s, cancelling",
+                            timeout_secs
+                        );
+                        global_monitoring().record_cancellation(CancellationReason::Timeout);
                         break;
                     }
                 }
-
-                if !is_mining.load(Ordering::Relaxed) {
-                    break;
-                }
             }
 
-            is_mining.store(false, Ordering::Relaxed);
+            // A superseding invocation owns the mining state
+            if *cancel.borrow() == generation {
+                is_mining.store(false, Ordering::Relaxed);
+            }
+            invocation.terminate().await;
         });
 
         Ok(())
@@ -231,17 +311,10 @@ impl Worker for ExternalWorker {
     async fn stop(&self) -> Result<()> {
         self.is_mining.store(false, Ordering::Relaxed);
 
-        // Kill the external process
-        if let Some(mut child) = self.process.lock().take() {
-            match child.kill() {
-                Ok(()) => {
-                    debug!("External worker process killed");
-                }
-                Err(e) => {
-                    error!("Failed to kill external worker: {}", e);
-                }
-            }
-        }
+        // Cancel all invocations and wait until their processes are reaped
+        self.generation.send_modify(|generation| *generation += 1);
+        let slots = u32::try_from(self.config.max_concurrent.max(1)).unwrap_or(u32::MAX);
+        let _ = self.slots.acquire_many(slots).await;
 
         Ok(())
     }
@@ -250,6 +323,14 @@ impl Worker for ExternalWorker {
         "External"
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities {
+            // No standard protocol for the external process to report its hashrate
+            reports_hashrate: false,
+            ..WorkerCapabilities::compute()
+        }
+    }
+
     async fn hashrate(&self) -> u64 {
         // External workers typically report their own hashrate
         // For now, return 0 as we don't have a standard protocol
@@ -297,6 +378,7 @@ mod tests {
             args: vec!["--threads".to_string(), "4".to_string()],
             env: vec![("GPU_ID".to_string(), "0".to_string())],
             timeout_secs: 60,
+            max_concurrent: 1,
         };
 
         let worker = ExternalWorker::new(config);
@@ -327,6 +409,7 @@ mod tests {
             },
             env: vec![],
             timeout_secs: 1,
+            max_concurrent: 1,
         };
 
         let worker = ExternalWorker::new(config);
@@ -341,7 +424,10 @@ mod tests {
         let result = worker.mine(work, target, tx).await;
         if result.is_err() {
             // Expected for some external processes that exit immediately
-            println!("Mining failed as expected for quick-exit process: {:?}", result);
+            println!(
+                "Mining failed as expected for quick-exit process: {:?}",
+                result
+            );
             return;
         }
 
@@ -359,4 +445,120 @@ mod tests {
         worker.stop().await.unwrap();
         assert!(!worker.is_mining.load(Ordering::Relaxed));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_silent_worker_is_cancelled_for_timeout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A miner that never prints a nonce (the target is its first argument)
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("silent-miner");
+        std::fs::write(&script, "#!/bin/sh\nsleep 5\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let timeouts = || {
+            global_monitoring()
+                .get_cancellation_reasons()
+                .get(CancellationReason::Timeout.as_str())
+                .copied()
+                .unwrap_or(0)
+        };
+        let before = timeouts();
+
+        let worker = ExternalWorker::new(ExternalWorkerConfig {
+            command: script,
+            args: vec![],
+            env: vec![],
+            timeout_secs: 1,
+            max_concurrent: 1,
+        });
+        let (tx, _rx) = mpsc::channel(1);
+        worker
+            .mine(
+                Work::from_bytes([0u8; 286]),
+                Target::from_bytes([0xFF; 32]),
+                tx,
+            )
+            .await
+            .unwrap();
+
+        tokio::time::timeout(std::time::Duration::from_secs(3), async {
+            while worker.is_mining.load(Ordering::Relaxed) {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(timeouts() > before);
+        worker.stop().await.unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_rapid_work_changes_respect_concurrency_limit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A miner that records its pid and never finds a solution
+        let dir = tempfile::tempdir().unwrap();
+        let pids = dir.path().join("pids");
+        let script = dir.path().join("slow-miner");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\necho $$ >> {}\nexec sleep 30\n", pids.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        for max_concurrent in [1, 2] {
+            let worker = Arc::new(ExternalWorker::new(ExternalWorkerConfig {
+                command: script.clone(),
+                args: vec![],
+                env: vec![],
+                timeout_secs: 60,
+                max_concurrent,
+            }));
+
+            let (tx, _rx) = mpsc::channel(1);
+            for i in 0..10u8 {
+                let mut work = Work::from_bytes([0u8; 286]);
+                work.as_bytes_mut()[0] = i;
+                worker
+                    .mine(work, Target::from_bytes([0xFF; 32]), tx.clone())
+                    .await
+                    .unwrap();
+                let running = worker.running.load(Ordering::Relaxed);
+                assert!(
+                    (1..=max_concurrent).contains(&running),
+                    "{} running over {}",
+                    running,
+                    max_concurrent
+                );
+            }
+
+            // Let the latest process record its pid before stopping it
+            tokio::time::timeout(std::time::Duration::from_secs(5), async {
+                while std::fs::read_to_string(&pids)
+                    .unwrap_or_default()
+                    .is_empty()
+                {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap();
+            worker.stop().await.unwrap();
+            assert_eq!(worker.running.load(Ordering::Relaxed), 0);
+        }
+
+        // Every superseded or stopped process has been terminated
+        let pids = std::fs::read_to_string(&pids).unwrap();
+        for pid in pids.lines() {
+            assert!(
+                !Path::new(&format!("/proc/{}", pid)).exists(),
+                "process {} still running",
+                pid
+            );
+        }
+    }
 }
@@ -5,17 +5,24 @@
 
 use crate::core::{Nonce, Target, Work};
 use crate::error::{Error, Result};
-use crate::workers::{MiningResult, Worker};
+use crate::utils::panic::spawn_monitored;
+use crate::workers::{
+    DEFAULT_HASHRATE_WARMUP, HashrateWarmup, MiningResult, NoncePosition, Worker,
+    WorkerCapabilities,
+};
 use async_trait::async_trait;
 use parking_lot::Mutex;
 use std::mem::size_of;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 use wgpu::util::DeviceExt;
 
+/// How often a paused mining loop checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// GPU mining configuration
 #[derive(Debug, Clone)]
 pub struct GpuConfig {
@@ -29,6 +36,8 @@ pub struct GpuConfig {
     pub batch_size: u32,
     /// Enable GPU performance monitoring
     pub enable_monitoring: bool,
+    /// Time after mining starts during which hashrate samples are suppressed
+    pub warmup: Duration,
 }
 
 impl Default for GpuConfig {
@@ -39,6 +48,7 @@ impl Default for GpuConfig {
             workgroup_count: 1024,
             batch_size: 256 * 1024, // 256k nonces per batch
             enable_monitoring: true,
+            warmup: DEFAULT_HASHRATE_WARMUP,
         }
     }
 }
@@ -56,10 +66,10 @@ struct GpuWorkData {
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct GpuMiningParams {
-    target: [u32; 8],    // 256-bit target
+    target: [u32; 8], // 256-bit target
     start_nonce: u32,
     nonce_count: u32,
-    nonce_offset: u32,   // Offset where nonce should be placed (278/4 = 69)
+    nonce_offset: u32, // Offset where nonce should be placed (278/4 = 69)
     padding: u32,
 }
 
@@ -67,9 +77,9 @@ struct GpuMiningParams {
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct GpuMiningResult {
-    found: u32,          // 0 = not found, 1 = found
+    found: u32, // 0 = not found, 1 = found
     nonce: u32,
-    hash: [u32; 8],      // 256-bit hash
+    hash: [u32; 8], // 256-bit hash
 }
 
 /// Built-in GPU mining worker using wgpu
@@ -83,36 +93,41 @@ pub struct GpuWorker {
     is_mining: Arc<AtomicBool>,
     hash_count: Arc<AtomicU64>,
     last_hashrate_time: Arc<Mutex<Instant>>,
+    warmup: Arc<HashrateWarmup>,
     adapter_name: String,
+    /// Nonce search progress on the current work
+    position: Arc<Mutex<Option<NoncePosition>>>,
+    /// Whether batches are held back, e.g. while the GPU is too hot
+    paused: Arc<AtomicBool>,
 }
 
 impl GpuWorker {
     /// Create a new GPU worker
     pub async fn new(config: GpuConfig) -> Result<Self> {
         info!("Initializing wgpu GPU worker");
-        
+
         // Create wgpu instance
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             ..Default::default()
         });
-        
+
         // Enumerate available adapters
         let adapters: Vec<_> = instance.enumerate_adapters(wgpu::Backends::all());
-        
+
         if adapters.is_empty() {
             return Err(Error::worker_initialization_failed(
                 "GPU",
-                "No GPU adapters found"
+                "No GPU adapters found",
             ));
         }
-        
+
         // Select adapter
         let adapter = if let Some(index) = config.device_index {
             adapters.get(index).ok_or_else(|| {
                 Error::worker_initialization_failed(
                     "GPU",
-                    format!("GPU device index {} not found", index)
+                    format!("GPU device index {} not found", index),
                 )
             })?
         } else {
@@ -122,13 +137,13 @@ impl GpuWorker {
                 .find(|a| a.get_info().device_type == wgpu::DeviceType::DiscreteGpu)
                 .unwrap_or(&adapters[0])
         };
-        
+
         let adapter_info = adapter.get_info();
         info!(
             "Selected GPU: {} ({:?})",
             adapter_info.name, adapter_info.device_type
         );
-        
+
         // Request device and queue
         let (device, queue) = adapter
             .request_device(
@@ -144,19 +159,19 @@ impl GpuWorker {
             .map_err(|e| {
                 Error::worker_initialization_failed(
                     "GPU",
-                    format!("Failed to request GPU device: {}", e)
+                    format!("Failed to request GPU device: {}", e),
                 )
             })?;
-        
+
         let device: Arc<wgpu::Device> = Arc::new(device);
         let queue: Arc<wgpu::Queue> = Arc::new(queue);
-        
+
         // Load shader
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Blake2s Mining Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/blake2s.wgsl").into()),
         });
-        
+
         // Create bind group layout
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Mining Bind Group Layout"),
@@ -193,14 +208,14 @@ impl GpuWorker {
                 },
             ],
         });
-        
+
         // Create pipeline layout
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Mining Pipeline Layout"),
             bind_group_layouts: &[&bind_group_layout],
             push_constant_ranges: &[],
         });
-        
+
         // Create compute pipeline
         let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("Blake2s Mining Pipeline"),
@@ -210,8 +225,9 @@ impl GpuWorker {
             compilation_options: Default::default(),
             cache: None,
         });
-        
+
         Ok(Self {
+            warmup: Arc::new(HashrateWarmup::new(config.warmup)),
             config,
             device,
             queue,
@@ -221,15 +237,17 @@ impl GpuWorker {
             hash_count: Arc::new(AtomicU64::new(0)),
             last_hashrate_time: Arc::new(Mutex::new(Instant::now())),
             adapter_name: adapter_info.name,
+            position: Arc::new(Mutex::new(None)),
+            paused: Arc::new(AtomicBool::new(false)),
         })
     }
-    
+
     /// Prepare work data for GPU
     fn prepare_work_data(&self, work: &Work) -> GpuWorkData {
         let mut data_part1 = [0u32; 64];
         let mut data_part2 = [0u32; 8];
         let work_bytes = work.as_bytes();
-        
+
         // Convert work bytes to u32 arrays (little-endian)
         for i in 0..72 {
             let offset = i * 4;
@@ -249,22 +267,25 @@ impl GpuWorker {
             } else {
                 0
             };
-            
+
             if i < 64 {
                 data_part1[i] = value;
             } else {
                 data_part2[i - 64] = value;
             }
         }
-        
-        GpuWorkData { data_part1, data_part2 }
+
+        GpuWorkData {
+            data_part1,
+            data_part2,
+        }
     }
-    
+
     /// Prepare target for GPU
     fn prepare_target(&self, target: &Target) -> [u32; 8] {
         let mut gpu_target = [0u32; 8];
         let target_bytes = target.as_bytes();
-        
+
         // Convert target bytes to u32 array (little-endian)
         for i in 0..8 {
             let offset = i * 4;
@@ -275,10 +296,10 @@ impl GpuWorker {
                 target_bytes[offset + 3],
             ]);
         }
-        
+
         gpu_target
     }
-    
+
     /// Mine a batch of nonces on GPU
     async fn mine_batch(
         &self,
@@ -297,30 +318,36 @@ impl GpuWorker {
             nonce_offset: 69, // 278 / 4 = 69.5, rounded down
             padding: 0,
         };
-        
+
         // Create buffers
-        let work_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Work Buffer"),
-            contents: bytemuck::cast_slice(&[work_data]),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-        
-        let params_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Params Buffer"),
-            contents: bytemuck::cast_slice(&[params]),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-        
-        let result_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Result Buffer"),
-            contents: bytemuck::cast_slice(&[GpuMiningResult {
-                found: 0,
-                nonce: 0,
-                hash: [0; 8],
-            }]),
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
-        });
-        
+        let work_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Work Buffer"),
+                contents: bytemuck::cast_slice(&[work_data]),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+
+        let params_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Params Buffer"),
+                contents: bytemuck::cast_slice(&[params]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let result_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Result Buffer"),
+                contents: bytemuck::cast_slice(&[GpuMiningResult {
+                    found: 0,
+                    nonce: 0,
+                    hash: [0; 8],
+                }]),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+
         // Create staging buffer for reading results
         let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Staging Buffer"),
@@ -328,7 +355,7 @@ impl GpuWorker {
             usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
-        
+
         // Create bind group
         let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Mining Bind Group"),
@@ -348,30 +375,33 @@ impl GpuWorker {
                 },
             ],
         });
-        
+
         // Create command encoder
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Mining Encoder"),
-        });
-        
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Mining Encoder"),
+            });
+
         // Dispatch compute work
         {
             let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                 label: Some("Mining Pass"),
                 timestamp_writes: None,
             });
-            
+
             compute_pass.set_pipeline(&self.pipeline);
             compute_pass.set_bind_group(0, &bind_group, &[]);
-            
+
             // Calculate dispatch size
             let threads_per_workgroup = self.config.workgroup_size;
             let total_threads = batch_size;
-            let num_workgroups = (total_threads + threads_per_workgroup - 1) / threads_per_workgroup;
-            
+            let num_workgroups =
+                (total_threads + threads_per_workgroup - 1) / threads_per_workgroup;
+
             compute_pass.dispatch_workgroups(num_workgroups, 1, 1);
         }
-        
+
         // Copy result to staging buffer
         encoder.copy_buffer_to_buffer(
             &result_buffer,
@@ -380,46 +410,44 @@ impl GpuWorker {
             0,
             size_of::<GpuMiningResult>() as u64,
         );
-        
+
         // Submit work
         self.queue.submit(std::iter::once(encoder.finish()));
-        
+
         // Read results
         let buffer_slice = staging_buffer.slice(..);
         let (tx, rx) = tokio::sync::oneshot::channel();
-        
+
         buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
             let _ = tx.send(result);
         });
-        
+
         self.device.poll(wgpu::Maintain::Wait);
-        
+
         rx.await
             .map_err(|_| Error::worker("GPU result channel closed"))?
             .map_err(|e| Error::worker(format!("Failed to map GPU buffer: {:?}", e)))?;
-        
+
         let data = buffer_slice.get_mapped_range();
         let result: &GpuMiningResult = bytemuck::from_bytes(&data);
-        
+
         // Update hash count
-        self.hash_count.fetch_add(batch_size as u64, Ordering::Relaxed);
-        
+        self.hash_count
+            .fetch_add(batch_size as u64, Ordering::Relaxed);
+
         if result.found != 0 {
             // Found a solution!
             let nonce = Nonce::new(result.nonce as u64);
             let mut hash = [0u8; 32];
-            
+
             // Convert hash from u32 array to byte array
             for i in 0..8 {
                 let bytes = result.hash[i].to_le_bytes();
                 hash[i * 4..(i + 1) * 4].copy_from_slice(&bytes);
             }
-            
-            let mut solved_work = work.clone();
-            solved_work.set_nonce(nonce);
-            
+
             Ok(Some(MiningResult {
-                work: solved_work,
+                work: work.with_nonce(nonce),
                 nonce,
                 hash,
             }))
@@ -429,42 +457,65 @@ impl GpuWorker {
     }
 }
 
-#[async_trait]
-impl Worker for GpuWorker {
-    async fn mine(
+impl GpuWorker {
+    /// Start mining `work` in GPU batches, searching from `start_nonce`
+    fn start_mining(
         &self,
         work: Work,
         target: Target,
         result_tx: mpsc::Sender<MiningResult>,
+        start_nonce: u64,
     ) -> Result<()> {
         if self.is_mining.load(Ordering::Relaxed) {
             return Err(Error::worker("Already mining"));
         }
-        
+
         self.is_mining.store(true, Ordering::Relaxed);
         self.hash_count.store(0, Ordering::Relaxed);
         *self.last_hashrate_time.lock() = Instant::now();
-        
-        info!("Starting GPU mining on {}", self.adapter_name);
-        
+        self.warmup.start();
+
+        info!(
+            "Starting GPU mining on {} at nonce {}",
+            self.adapter_name, start_nonce
+        );
+        *self.position.lock() = Some(NoncePosition {
+            work: work.clone(),
+            nonce: start_nonce,
+        });
+
         let is_mining = self.is_mining.clone();
         let batch_size = self.config.batch_size;
         let worker = self.clone();
-        
-        tokio::spawn(async move {
-            let mut nonce = 0u64;
-            
+        let position = self.position.clone();
+        let paused = self.paused.clone();
+
+        spawn_monitored("gpu worker", async move {
+            let mut nonce = start_nonce;
+
             while is_mining.load(Ordering::Relaxed) {
+                if paused.load(Ordering::Relaxed) {
+                    tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+                    continue;
+                }
                 match worker.mine_batch(&work, &target, nonce, batch_size).await {
                     Ok(Some(result)) => {
-                        info!("GPU found solution: nonce={}", result.nonce);
+                        info!(
+                            "GPU found solution: nonce={} (le {})",
+                            result.nonce,
+                            result.nonce.to_hex_le()
+                        );
                         let _ = result_tx.send(result).await;
                         break;
                     }
                     Ok(None) => {
                         // No solution in this batch, continue
                         nonce += batch_size as u64;
-                        
+                        if let Some(position) = position.lock().as_mut().filter(|p| p.work == work)
+                        {
+                            position.nonce = nonce;
+                        }
+
                         // Check for nonce overflow
                         if nonce > u64::MAX - batch_size as u64 {
                             warn!("Nonce space exhausted");
@@ -477,35 +528,80 @@ impl Worker for GpuWorker {
                     }
                 }
             }
-            
+
             is_mining.store(false, Ordering::Relaxed);
         });
-        
+
         Ok(())
     }
-    
+}
+
+#[async_trait]
+impl Worker for GpuWorker {
+    async fn mine(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+    ) -> Result<()> {
+        self.start_mining(work, target, result_tx, 0)
+    }
+
+    async fn resume(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+        start_nonce: u64,
+    ) -> Result<()> {
+        self.start_mining(work, target, result_tx, start_nonce)
+    }
+
+    fn nonce_position(&self) -> Option<NoncePosition> {
+        self.position.lock().clone()
+    }
+
     async fn stop(&self) -> Result<()> {
         self.is_mining.store(false, Ordering::Relaxed);
         Ok(())
     }
-    
+
+    async fn set_paused(&self, paused: bool) -> Result<()> {
+        if self.paused.swap(paused, Ordering::Relaxed) != paused {
+            info!(
+                "GPU mining on {} {}",
+                self.adapter_name,
+                if paused { "paused" } else { "resumed" }
+            );
+        }
+        Ok(())
+    }
+
     fn worker_type(&self) -> &str {
         "GPU"
     }
-    
+
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities {
+            supports_pause: true,
+            ..WorkerCapabilities::compute()
+        }
+    }
+
     async fn hashrate(&self) -> u64 {
         let hashes = self.hash_count.load(Ordering::Relaxed);
         let elapsed = self.last_hashrate_time.lock().elapsed();
-        
+
         if elapsed.as_secs() == 0 {
             return 0;
         }
-        
+
         // Reset counters for next measurement
         self.hash_count.store(0, Ordering::Relaxed);
         *self.last_hashrate_time.lock() = Instant::now();
-        
-        (hashes as f64 / elapsed.as_secs_f64()) as u64
+
+        self.warmup
+            .report((hashes as f64 / elapsed.as_secs_f64()) as u64)
     }
 }
 
@@ -515,7 +611,7 @@ pub async fn enumerate_gpus() -> Vec<(usize, String, wgpu::DeviceType)> {
         backends: wgpu::Backends::all(),
         ..Default::default()
     });
-    
+
     instance
         .enumerate_adapters(wgpu::Backends::all())
         .into_iter()
@@ -530,7 +626,7 @@ pub async fn enumerate_gpus() -> Vec<(usize, String, wgpu::DeviceType)> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[tokio::test]
     async fn test_enumerate_gpus() {
         let gpus = enumerate_gpus().await;
@@ -539,11 +635,11 @@ mod tests {
             println!("GPU {}: {} ({:?})", index, name, device_type);
         }
     }
-    
+
     #[test]
     fn test_gpu_config() {
         let config = GpuConfig::default();
         assert_eq!(config.workgroup_size, 256);
         assert_eq!(config.workgroup_count, 1024);
     }
-}
\ No newline at end of file
+}
@@ -4,17 +4,22 @@
 //! strategies, including CPU mining, GPU mining, Stratum protocol support, and more.
 
 use crate::core::{Nonce, Target, Work};
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::utils::monitoring::global_monitoring;
 use async_trait::async_trait;
 use tokio::sync::mpsc;
+use tracing::debug;
 
 pub mod constant_delay;
 pub mod cpu;
 pub mod external;
 pub mod gpu;
 pub mod on_demand;
+pub mod preflight;
 pub mod simulation;
 pub mod stratum;
+pub mod supervisor;
+pub mod warmup;
 
 pub use constant_delay::ConstantDelayWorker;
 pub use cpu::CpuWorker;
@@ -23,6 +28,8 @@ pub use gpu::GpuWorker;
 pub use on_demand::OnDemandWorker;
 pub use simulation::SimulationWorker;
 pub use stratum::StratumServer;
+pub use supervisor::{SupervisedWorker, SupervisorConfig};
+pub use warmup::{DEFAULT_HASHRATE_WARMUP, HashrateWarmup};
 
 /// Result of a mining operation
 #[derive(Debug, Clone)]
@@ -35,6 +42,127 @@ pub struct MiningResult {
     pub hash: [u8; 32],
 }
 
+impl MiningResult {
+    /// Recompute the Blake2s hash of the solved work and check it against
+    /// `target`, independent of what the worker reported
+    pub fn verify(&self, target: &Target) -> bool {
+        self.work.meets_target(target)
+    }
+}
+
+/// Work handed to a worker together with the target its solutions have to
+/// meet; both are replaced at once, so solutions are never checked against
+/// the target of earlier work
+#[derive(Debug, Clone)]
+pub struct CurrentWork {
+    /// Work being mined
+    pub work: Work,
+    /// Target of the work
+    pub target: Target,
+}
+
+impl CurrentWork {
+    /// Work mined against `target`
+    pub fn new(work: Work, target: Target) -> Self {
+        Self { work, target }
+    }
+
+    /// Switch to new work and its target
+    pub fn replace(&mut self, work: Work, target: Target) {
+        self.work = work;
+        self.target = target;
+    }
+
+    /// Whether `result` meets the target of the current work
+    pub fn accepts(&self, result: &MiningResult) -> bool {
+        result.verify(&self.target)
+    }
+}
+
+/// How far a worker got searching the nonces of its work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoncePosition {
+    /// Work being searched
+    pub work: Work,
+    /// Nonce to continue the search at; the nonces from where the search
+    /// started up to this one have all been checked
+    pub nonce: u64,
+}
+
+/// Capabilities a worker declares to the mining coordinator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerCapabilities {
+    /// Worker serves external miners (stratum, on-demand) instead of computing hashes itself
+    pub is_server: bool,
+    /// Worker can be stopped and restarted with new work when the chain advances
+    pub supports_preemption: bool,
+    /// Worker reports a meaningful hashrate
+    pub reports_hashrate: bool,
+    /// Worker can be paused and resumed without losing its work
+    pub supports_pause: bool,
+}
+
+impl WorkerCapabilities {
+    /// Capabilities of a worker that computes hashes locally
+    pub fn compute() -> Self {
+        Self {
+            is_server: false,
+            supports_preemption: true,
+            reports_hashrate: true,
+            supports_pause: false,
+        }
+    }
+
+    /// Capabilities of a worker that hands out work to external miners
+    pub fn server() -> Self {
+        Self {
+            is_server: true,
+            supports_preemption: false,
+            reports_hashrate: false,
+            supports_pause: false,
+        }
+    }
+
+    /// Whether the hash rate is measured from hashes computed locally, so
+    /// the hash count advances for as long as the worker mines; a server's
+    /// hash rate is only estimated from the shares miners happen to submit
+    pub fn counts_hashes(&self) -> bool {
+        self.reports_hashrate && !self.is_server
+    }
+}
+
+/// Why a worker's mining was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CancellationReason {
+    /// New work preempted the current work immediately
+    Preemption,
+    /// The worker gave up on work that produced nothing in time
+    Timeout,
+    /// The miner is shutting down
+    Shutdown,
+    /// The worker switched to new work once its batch or the preemption
+    /// delay was over
+    NewWork,
+}
+
+impl CancellationReason {
+    /// Label used in logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancellationReason::Preemption => "preemption",
+            CancellationReason::Timeout => "timeout",
+            CancellationReason::Shutdown => "shutdown",
+            CancellationReason::NewWork => "new-work",
+        }
+    }
+}
+
+impl std::fmt::Display for CancellationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Trait for all worker implementations
 #[async_trait]
 pub trait Worker: Send + Sync {
@@ -46,14 +174,62 @@ pub trait Worker: Send + Sync {
         result_tx: mpsc::Sender<MiningResult>,
     ) -> Result<()>;
 
+    /// Like [`mine`](Self::mine), but starting the nonce search at
+    /// `start_nonce`, e.g. to continue unchanged work where a preempted
+    /// search left off; workers without an ordered search start over
+    async fn resume(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+        start_nonce: u64,
+    ) -> Result<()> {
+        let _ = start_nonce;
+        self.mine(work, target, result_tx).await
+    }
+
+    /// Progress of the nonce search on the current work, for workers that
+    /// search the nonce space in order
+    fn nonce_position(&self) -> Option<NoncePosition> {
+        None
+    }
+
     /// Stop the current mining operation
     async fn stop(&self) -> Result<()>;
 
+    /// Stop the current mining operation for `reason`, which is logged and
+    /// counted per reason in the monitoring system
+    async fn cancel(&self, reason: CancellationReason) -> Result<()> {
+        debug!("Cancelling {} worker: {}", self.worker_type(), reason);
+        global_monitoring().record_cancellation(reason);
+        self.stop().await
+    }
+
+    /// Resolves once the worker gave up on its work after searching its
+    /// nonce budget without a solution, so fresh work can be fetched;
+    /// never resolves for workers without a budget
+    async fn work_exhausted(&self) {
+        std::future::pending::<()>().await
+    }
+
+    /// Pause the current mining operation, keeping its work, or continue
+    /// it; only for workers with [`WorkerCapabilities::supports_pause`]
+    async fn set_paused(&self, paused: bool) -> Result<()> {
+        let _ = paused;
+        Err(Error::worker(format!(
+            "{} worker cannot be paused",
+            self.worker_type()
+        )))
+    }
+
     /// Get the worker type name
     fn worker_type(&self) -> &str;
 
     /// Get current hashrate (hashes per second)
     async fn hashrate(&self) -> u64;
+
+    /// Capabilities of this worker
+    fn capabilities(&self) -> WorkerCapabilities;
 }
 
 /// Available worker types
@@ -127,6 +303,80 @@ impl std::fmt::Display for WorkerType {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_cancel_counts_reason() {
+        let shutdowns = || {
+            global_monitoring()
+                .get_cancellation_reasons()
+                .get(CancellationReason::Shutdown.as_str())
+                .copied()
+                .unwrap_or(0)
+        };
+        let before = shutdowns();
+
+        let worker = SimulationWorker::new(simulation::SimulationWorkerConfig { hash_rate: 1.0 });
+        let (tx, _rx) = mpsc::channel(1);
+        worker
+            .mine(
+                Work::from_bytes([0x11; 286]),
+                Target::from_bytes([0x00; 32]),
+                tx,
+            )
+            .await
+            .unwrap();
+        worker.cancel(CancellationReason::Shutdown).await.unwrap();
+
+        assert!(shutdowns() > before);
+        assert_eq!(worker.hashrate().await, 0);
+    }
+
+    #[test]
+    fn test_verify_solution() {
+        let work = Work::from_bytes([0x11; 286]);
+        let hash = work.hash();
+        let result = MiningResult {
+            nonce: work.nonce(),
+            work,
+            hash,
+        };
+
+        // Any hash meets the maximum target and none meets the zero target
+        assert!(result.verify(&Target::from_bytes([0xFF; 32])));
+        assert!(!result.verify(&Target::from_bytes([0x00; 32])));
+
+        // A worker claiming a hash that meets the target doesn't matter
+        let bogus = MiningResult {
+            hash: [0u8; 32],
+            ..result
+        };
+        let mut just_above = [0u8; 32];
+        just_above[0] = 1;
+        assert!(!bogus.verify(&Target::from_bytes(just_above)));
+    }
+
+    #[test]
+    fn test_solution_checked_against_target_of_current_work() {
+        let work = Work::from_bytes([0x11; 286]);
+        let result = MiningResult {
+            nonce: work.nonce(),
+            hash: work.hash(),
+            work: work.clone(),
+        };
+        let easy = Target::from_bytes([0xFF; 32]);
+        let hard = Target::from_bytes([0x00; 32]);
+
+        // The target got harder before the solution arrived
+        let mut current = CurrentWork::new(work.clone(), easy);
+        assert!(current.accepts(&result));
+        current.replace(Work::from_bytes([0x22; 286]), hard);
+        assert!(!current.accepts(&result));
+
+        // The target got easier before the solution arrived
+        current.replace(work, easy);
+        assert!(current.accepts(&result));
+        assert_eq!(current.target, easy);
+    }
+
     #[test]
     fn test_worker_type_name() {
         assert_eq!(WorkerType::Cpu.name(), "cpu");
@@ -154,6 +404,62 @@ mod tests {
         assert!(all.contains(&WorkerType::External));
     }
 
+    // The GPU worker needs an adapter to be constructed and is not covered here
+    #[test]
+    fn test_worker_capabilities() {
+        let cpu = CpuWorker::new(cpu::CpuWorkerConfig::default());
+        assert_eq!(cpu.capabilities(), WorkerCapabilities::compute());
+
+        let simulation =
+            SimulationWorker::new(simulation::SimulationWorkerConfig { hash_rate: 1000.0 });
+        assert_eq!(simulation.capabilities(), WorkerCapabilities::compute());
+
+        let external = ExternalWorker::new(external::ExternalWorkerConfig {
+            command: "miner".into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            timeout_secs: 10,
+            max_concurrent: 1,
+        });
+        let caps = external.capabilities();
+        assert!(!caps.is_server && caps.supports_preemption && !caps.reports_hashrate);
+
+        let constant_delay = ConstantDelayWorker::new(constant_delay::ConstantDelayWorkerConfig {
+            block_time_secs: 30,
+        });
+        let caps = constant_delay.capabilities();
+        assert!(!caps.is_server && caps.supports_preemption && !caps.reports_hashrate);
+
+        let on_demand = OnDemandWorker::new(on_demand::OnDemandWorkerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(on_demand.capabilities(), WorkerCapabilities::server());
+
+        let stratum = StratumServer::new(stratum::StratumServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            max_connections: 10,
+            difficulty: crate::config::StratumDifficulty::Block,
+            mode: None,
+            min_difficulty_level: crate::config::DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+            hashrate_shift_factor: crate::config::DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+            rate_ms: 1000,
+            always_clean_jobs: false,
+            job_history_size: 4,
+            session_idle_timeout_secs: 600,
+            extranonce1_size: 4,
+            fixed_extranonce1: None,
+            authorize_callback: None,
+            audit_log: None,
+        });
+        let caps = stratum.capabilities();
+        assert!(caps.is_server && !caps.supports_preemption && caps.reports_hashrate);
+        assert!(!caps.counts_hashes());
+        assert!(WorkerCapabilities::compute().counts_hashes());
+    }
+
     #[test]
     fn test_mining_result() {
         let work = Work::from_bytes([0u8; 286]);
@@ -1,14 +1,21 @@
 //! On-demand mining worker with HTTP interface
+//!
+//! A request may carry an `Idempotency-Key` header; a request repeating the
+//! key of an earlier one within the replay window is rejected with
+//! `409 Conflict` instead of producing the blocks again. Requests arriving
+//! faster than the configured minimum interval are rejected with
+//! `429 Too Many Requests`.
 
 use crate::core::{Nonce, Target, Work};
 use crate::error::{Error, Result};
-use crate::workers::{MiningResult, Worker};
+use crate::utils::panic::spawn_monitored;
+use crate::workers::{MiningResult, Worker, WorkerCapabilities};
 use async_trait::async_trait;
 use axum::{
     Router,
     extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    http::{HeaderMap, StatusCode},
+    response::Json,
     routing::post,
 };
 use serde::{Deserialize, Serialize};
@@ -16,8 +23,15 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::{RwLock, mpsc};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// Header carrying the trigger id of a request
+pub const TRIGGER_ID_HEADER: &str = "idempotency-key";
+
+/// Default time during which a repeated trigger id is rejected
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(300);
 
 /// Configuration for on-demand mining
 #[derive(Debug, Clone)]
@@ -26,6 +40,77 @@ pub struct OnDemandWorkerConfig {
     pub port: u16,
     /// HTTP server host
     pub host: String,
+    /// Time during which a request repeating an earlier trigger id is rejected
+    pub replay_window: Duration,
+    /// Minimum time between requests (zero for no limit)
+    pub min_interval: Duration,
+}
+
+impl Default for OnDemandWorkerConfig {
+    fn default() -> Self {
+        Self {
+            port: 1917,
+            host: "0.0.0.0".to_string(),
+            replay_window: DEFAULT_REPLAY_WINDOW,
+            min_interval: Duration::ZERO,
+        }
+    }
+}
+
+/// Why a trigger was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TriggerRejection {
+    /// The trigger id was seen within the replay window
+    Duplicate,
+    /// The previous trigger was too recent
+    TooFast,
+}
+
+/// Replay protection and rate limiting of triggers
+#[derive(Debug)]
+struct TriggerGuard {
+    replay_window: Duration,
+    min_interval: Duration,
+    /// Trigger ids accepted within the replay window
+    seen: HashMap<String, Instant>,
+    last: Option<Instant>,
+}
+
+impl TriggerGuard {
+    fn new(replay_window: Duration, min_interval: Duration) -> Self {
+        Self {
+            replay_window,
+            min_interval,
+            seen: HashMap::new(),
+            last: None,
+        }
+    }
+
+    /// Accept a trigger with the optional `id` at `now`, or say why not
+    fn admit(
+        &mut self,
+        id: Option<&str>,
+        now: Instant,
+    ) -> std::result::Result<(), TriggerRejection> {
+        let window = self.replay_window;
+        self.seen.retain(|_, at| now.duration_since(*at) < window);
+
+        if id.is_some_and(|id| self.seen.contains_key(id)) {
+            return Err(TriggerRejection::Duplicate);
+        }
+        if self
+            .last
+            .is_some_and(|last| now.duration_since(last) < self.min_interval)
+        {
+            return Err(TriggerRejection::TooFast);
+        }
+
+        self.last = Some(now);
+        if let Some(id) = id {
+            self.seen.insert(id.to_string(), now);
+        }
+        Ok(())
+    }
 }
 
 /// Request to mine blocks
@@ -54,6 +139,8 @@ struct ServerState {
     result_tx: Arc<RwLock<Option<mpsc::Sender<MiningResult>>>>,
     /// Block counter
     block_counter: Arc<AtomicU64>,
+    /// Replay protection and rate limiting
+    triggers: Arc<parking_lot::Mutex<TriggerGuard>>,
 }
 
 /// On-demand mining worker
@@ -72,13 +159,17 @@ impl OnDemandWorker {
         );
 
         Self {
-            config,
             running: Arc::new(AtomicBool::new(false)),
             server_state: ServerState {
                 work_info: Arc::new(RwLock::new(None)),
                 result_tx: Arc::new(RwLock::new(None)),
                 block_counter: Arc::new(AtomicU64::new(0)),
+                triggers: Arc::new(parking_lot::Mutex::new(TriggerGuard::new(
+                    config.replay_window,
+                    config.min_interval,
+                ))),
             },
+            config,
         }
     }
 
@@ -109,8 +200,9 @@ impl OnDemandWorker {
 /// Handler for /make-blocks endpoint
 async fn make_blocks_handler(
     State(state): State<ServerState>,
+    headers: HeaderMap,
     Json(request): Json<MakeBlocksRequest>,
-) -> impl IntoResponse {
+) -> (StatusCode, Json<MakeBlocksResponse>) {
     let mut blocks_mined = HashMap::new();
     let mut errors = Vec::new();
 
@@ -128,6 +220,30 @@ async fn make_blocks_handler(
         );
     }
 
+    let trigger_id = headers
+        .get(TRIGGER_ID_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if let Err(rejection) = state.triggers.lock().admit(trigger_id, Instant::now()) {
+        let (status, message) = match rejection {
+            TriggerRejection::Duplicate => (
+                StatusCode::CONFLICT,
+                format!("Duplicate trigger {}", trigger_id.unwrap_or_default()),
+            ),
+            TriggerRejection::TooFast => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many triggers, slow down".to_string(),
+            ),
+        };
+        warn!("Rejected on-demand request: {}", message);
+        return (
+            status,
+            Json(MakeBlocksResponse {
+                blocks_mined,
+                errors: vec![message],
+            }),
+        );
+    }
+
     let (work, _target) = work_info.as_ref().unwrap();
     let tx = result_tx.as_ref().unwrap();
 
@@ -197,11 +313,11 @@ impl Worker for OnDemandWorker {
         let running = Arc::clone(&self.running);
         let worker = self.clone();
 
-        tokio::spawn(async move {
+        spawn_monitored("on-demand server", async move {
             while running.load(Ordering::Relaxed) {
                 if let Err(e) = worker.start_server().await {
                     error!("On-demand server error: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    tokio::time::sleep(Duration::from_secs(5)).await;
                 }
             }
         });
@@ -220,6 +336,10 @@ impl Worker for OnDemandWorker {
         "OnDemand"
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities::server()
+    }
+
     async fn hashrate(&self) -> u64 {
         // On-demand doesn't have a meaningful hashrate
         0
@@ -246,6 +366,7 @@ mod tests {
         let config = OnDemandWorkerConfig {
             port: 0, // Use random port for testing
             host: "127.0.0.1".to_string(),
+            ..Default::default()
         };
 
         let worker = OnDemandWorker::new(config);
@@ -264,4 +385,78 @@ mod tests {
         // Stop mining
         worker.stop().await.unwrap();
     }
+
+    async fn trigger(worker: &OnDemandWorker, id: Option<&str>) -> (StatusCode, Vec<String>) {
+        let mut headers = HeaderMap::new();
+        if let Some(id) = id {
+            headers.insert(TRIGGER_ID_HEADER, id.parse().unwrap());
+        }
+        let request = MakeBlocksRequest {
+            chains: HashMap::from([("0".to_string(), 1)]),
+        };
+        let (status, Json(response)) =
+            make_blocks_handler(State(worker.server_state.clone()), headers, Json(request)).await;
+        (status, response.errors)
+    }
+
+    async fn ready_worker(
+        config: OnDemandWorkerConfig,
+    ) -> (OnDemandWorker, mpsc::Receiver<MiningResult>) {
+        let worker = OnDemandWorker::new(config);
+        let (tx, rx) = mpsc::channel(10);
+        let state = &worker.server_state;
+        *state.work_info.write().await =
+            Some((Work::from_bytes([0u8; 286]), Target::from_bytes([0xFF; 32])));
+        *state.result_tx.write().await = Some(tx);
+        (worker, rx)
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_trigger_produces_one_block() {
+        let (worker, mut rx) = ready_worker(OnDemandWorkerConfig::default()).await;
+
+        assert_eq!(trigger(&worker, Some("trigger-1")).await.0, StatusCode::OK);
+        let (status, errors) = trigger(&worker, Some("trigger-1")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(errors[0].contains("trigger-1"));
+
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+
+        // Other ids and requests without an id still go through
+        assert_eq!(trigger(&worker, Some("trigger-2")).await.0, StatusCode::OK);
+        assert_eq!(trigger(&worker, None).await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_fast_triggers_are_rate_limited() {
+        let (worker, mut rx) = ready_worker(OnDemandWorkerConfig {
+            min_interval: Duration::from_secs(3600),
+            ..Default::default()
+        })
+        .await;
+
+        assert_eq!(trigger(&worker, Some("a")).await.0, StatusCode::OK);
+        assert_eq!(
+            trigger(&worker, Some("b")).await.0,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_trigger_ids_expire_after_window() {
+        let mut guard = TriggerGuard::new(Duration::from_secs(60), Duration::ZERO);
+        let start = Instant::now();
+        assert_eq!(guard.admit(Some("x"), start), Ok(()));
+        assert_eq!(
+            guard.admit(Some("x"), start + Duration::from_secs(59)),
+            Err(TriggerRejection::Duplicate)
+        );
+        assert_eq!(
+            guard.admit(Some("x"), start + Duration::from_secs(61)),
+            Ok(())
+        );
+    }
 }
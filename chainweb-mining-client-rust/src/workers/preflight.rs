@@ -0,0 +1,160 @@
+//! Pre-flight checks for server workers
+//!
+//! The stratum and on-demand workers listen on a port, and the stratum
+//! server holds a socket per connected miner. A busy or privileged port, or
+//! a file descriptor limit below the connection limit, would otherwise only
+//! show up as an opaque error deep in server startup (or as refused miners
+//! later on). These checks run once before the worker is created.
+
+use crate::error::{Error, Result};
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use tracing::{info, warn};
+
+/// File descriptors needed besides the client connections (node
+/// connections, listener, log files, ...)
+const FD_HEADROOM: u64 = 64;
+
+/// Check that a server worker can listen on `host:port` and serve
+/// `max_connections` clients
+///
+/// Failing to bind is an error; a privileged port without root and a file
+/// descriptor limit that can't be raised far enough are only warned about.
+pub fn check_server_worker(
+    worker_type: &str,
+    host: &str,
+    port: u16,
+    max_connections: usize,
+) -> Result<()> {
+    let addr: SocketAddr = format!("{}:{}", host, port).parse().map_err(|e| {
+        Error::worker_startup_failed(
+            worker_type,
+            format!("invalid listen address {}:{}: {}", host, port, e),
+        )
+    })?;
+
+    if port != 0 && port < 1024 && !is_root() {
+        warn!(
+            "{} worker listens on privileged port {} without running as root; binding may fail",
+            worker_type, port
+        );
+    }
+    check_bindable(worker_type, addr)?;
+
+    if max_connections > 0 {
+        raise_fd_limit(worker_type, max_connections as u64 + FD_HEADROOM);
+    }
+    Ok(())
+}
+
+fn check_bindable(worker_type: &str, addr: SocketAddr) -> Result<()> {
+    // Dropped right away; std listeners set SO_REUSEADDR on Unix so the
+    // server can bind the same address next
+    TcpListener::bind(addr).map(drop).map_err(|e| {
+        let reason = match e.kind() {
+            io::ErrorKind::AddrInUse => format!(
+                "{} is already in use; stop the process listening there or configure another port",
+                addr
+            ),
+            io::ErrorKind::PermissionDenied => format!(
+                "not permitted to listen on {}; use a port of 1024 or higher, run as root or grant CAP_NET_BIND_SERVICE",
+                addr
+            ),
+            io::ErrorKind::AddrNotAvailable => format!(
+                "{} is not an address of this machine; listen on 0.0.0.0 or a local interface",
+                addr
+            ),
+            _ => format!("cannot listen on {}: {}", addr, e),
+        };
+        Error::worker_startup_failed(worker_type, reason)
+    })
+}
+
+#[cfg(unix)]
+fn is_root() -> bool {
+    rustix::process::geteuid().is_root()
+}
+
+#[cfg(not(unix))]
+fn is_root() -> bool {
+    // No privileged ports to warn about
+    true
+}
+
+/// Raise the soft file descriptor limit to `needed`, up to the hard limit
+#[cfg(unix)]
+fn raise_fd_limit(worker_type: &str, needed: u64) {
+    use rustix::process::{Resource, Rlimit, getrlimit, setrlimit};
+
+    let limit = getrlimit(Resource::Nofile);
+    let current = limit.current.unwrap_or(u64::MAX);
+    if current >= needed {
+        return;
+    }
+
+    let raised = limit.maximum.map_or(needed, |maximum| needed.min(maximum));
+    let available = match setrlimit(
+        Resource::Nofile,
+        Rlimit {
+            current: Some(raised),
+            ..limit
+        },
+    ) {
+        Ok(()) => {
+            info!(
+                "Raised the file descriptor limit from {} to {}",
+                current, raised
+            );
+            raised
+        }
+        Err(e) => {
+            warn!(
+                "Failed to raise the file descriptor limit from {}: {}",
+                current, e
+            );
+            current
+        }
+    };
+    if available < needed {
+        warn!(
+            "{} worker may only serve about {} of its {} connections: the file descriptor limit is {} (raise it with `ulimit -n {}`)",
+            worker_type,
+            available.saturating_sub(FD_HEADROOM),
+            needed - FD_HEADROOM,
+            available,
+            needed
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn raise_fd_limit(_worker_type: &str, _needed: u64) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_in_use_fails_preflight() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let err = check_server_worker("Stratum", "127.0.0.1", port, 10).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Stratum"), "{}", message);
+        assert!(
+            message.contains(&format!("127.0.0.1:{} is already in use", port)),
+            "{}",
+            message
+        );
+
+        drop(listener);
+        check_server_worker("Stratum", "127.0.0.1", port, 10).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_host_fails_preflight() {
+        let err = check_server_worker("On-demand", "not an address", 1917, 0).unwrap_err();
+        assert!(err.to_string().contains("invalid listen address"));
+    }
+}
@@ -1,8 +1,9 @@
 //! Simulated mining worker for testing
 
-use crate::core::{Nonce, Target, Work};
+use crate::core::{Nonce, Target, TargetArithmetic, TargetWords, Work};
 use crate::error::Result;
-use crate::workers::{MiningResult, Worker};
+use crate::utils::panic::spawn_monitored;
+use crate::workers::{MiningResult, Worker, WorkerCapabilities};
 use async_trait::async_trait;
 use rand::rng;
 use rand_distr::{Distribution, Exp};
@@ -44,11 +45,12 @@ impl SimulationWorker {
 
     /// Calculate expected time to find a block
     fn calculate_block_time(&self, target: &Target) -> Duration {
-        // Get the difficulty from the target
-        let difficulty = target.to_difficulty();
-
-        // Expected time = difficulty / hash_rate
-        let expected_seconds = difficulty / self.config.hash_rate;
+        // Expected time = difficulty / hash_rate, with the difficulty taken
+        // over the whole target so simulated runs have realistic block times
+        let expected_seconds = TargetArithmetic::expected_block_time(
+            &TargetWords::from(target),
+            self.config.hash_rate,
+        );
 
         // Use exponential distribution for realistic mining simulation
         let exp = Exp::new(1.0 / expected_seconds).unwrap();
@@ -74,7 +76,7 @@ impl Worker for SimulationWorker {
         let work = work.clone();
         let block_time = self.calculate_block_time(&target);
 
-        tokio::spawn(async move {
+        spawn_monitored("simulation worker", async move {
             info!("Starting simulated mining");
             debug!("Simulated block time: {:?}", block_time);
 
@@ -99,7 +101,7 @@ impl Worker for SimulationWorker {
                                 hash: [0u8; 32], // Fake hash
                             };
 
-                            info!("Simulation found block with nonce: {}", nonce);
+                            info!("Simulation found block with nonce: {} (le {})", nonce, nonce.to_hex_le());
 
                             if let Err(e) = result_tx.send(result).await {
                                 debug!("Failed to send mining result: {}", e);
@@ -131,6 +133,10 @@ impl Worker for SimulationWorker {
         "Simulation"
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities::compute()
+    }
+
     async fn hashrate(&self) -> u64 {
         self.current_hashrate.load(Ordering::Relaxed)
     }
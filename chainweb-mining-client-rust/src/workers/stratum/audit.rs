@@ -0,0 +1,193 @@
+//! Audit trail of accepted stratum shares
+//!
+//! With `--stratum-audit-file`, every accepted share is appended to the file
+//! as one JSON line and flushed right away, so the record survives a crash
+//! and can settle disputes with pool participants later. Once the file grows
+//! past its size limit it is rotated: `FILE` becomes `FILE.1`, `FILE.1`
+//! becomes `FILE.2` and so on, keeping [`AUDIT_ROTATIONS`] old files.
+
+use crate::error::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+/// Number of rotated audit files kept besides the current one
+pub const AUDIT_ROTATIONS: usize = 5;
+
+/// One accepted share as written to the audit file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareAuditRecord {
+    /// Unix time in milliseconds when the share was accepted
+    pub timestamp_ms: u64,
+    /// Worker name the session authorized with
+    pub worker_name: String,
+    /// Stratum session that submitted the share
+    pub session_id: String,
+    /// Job the share was submitted for
+    pub job_id: String,
+    /// Submitted nonce (hex, as sent by the miner)
+    pub nonce: String,
+    /// Difficulty of the session target the share met
+    pub share_difficulty: f64,
+    /// Whether the share also met the block target
+    pub is_block: bool,
+}
+
+impl ShareAuditRecord {
+    /// Stamp a share with the current time
+    pub fn now(
+        worker_name: &str,
+        session_id: &str,
+        job_id: &str,
+        nonce: &str,
+        share_difficulty: f64,
+        is_block: bool,
+    ) -> Self {
+        Self {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            worker_name: worker_name.to_string(),
+            session_id: session_id.to_string(),
+            job_id: job_id.to_string(),
+            nonce: nonce.to_string(),
+            share_difficulty,
+            is_block,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AuditFile {
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+/// Append-only audit file of accepted shares, rotated by size
+#[derive(Debug)]
+pub struct ShareAuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    file: Mutex<AuditFile>,
+}
+
+impl ShareAuditLog {
+    /// Open `path` for appending, creating it if it doesn't exist; the file
+    /// is rotated once it exceeds `max_bytes` (0 disables rotation)
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = Self::open_file(&path)?;
+        info!("Recording accepted stratum shares in {}", path.display());
+        Ok(Self {
+            path,
+            max_bytes,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn open_file(path: &Path) -> Result<AuditFile> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(AuditFile {
+            writer: BufWriter::new(file),
+            size,
+        })
+    }
+
+    /// Path of the current audit file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one record and flush it, rotating the file first if the
+    /// record would take it past the size limit
+    pub fn write(&self, record: &ShareAuditRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock();
+        if self.max_bytes > 0 && file.size > 0 && file.size + line.len() as u64 > self.max_bytes {
+            file.writer.flush()?;
+            self.rotate()?;
+            *file = Self::open_file(&self.path)?;
+        }
+        file.writer.write_all(&line)?;
+        file.writer.flush()?;
+        file.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `FILE.N` to `FILE.N+1`, dropping the oldest, and `FILE` to `FILE.1`
+    fn rotate(&self) -> Result<()> {
+        for index in (1..AUDIT_ROTATIONS).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))?;
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(job_id: &str) -> ShareAuditRecord {
+        ShareAuditRecord::now(
+            "miner.rig1",
+            "session",
+            job_id,
+            "0102030405060708",
+            1.5,
+            false,
+        )
+    }
+
+    fn read_records(path: &Path) -> Vec<ShareAuditRecord> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_file_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shares.jsonl");
+        let line_len = serde_json::to_vec(&record("1")).unwrap().len() as u64 + 1;
+
+        // Room for two records per file
+        let log = ShareAuditLog::open(&path, 2 * line_len).unwrap();
+        for job in 1..=5 {
+            log.write(&record(&job.to_string())).unwrap();
+        }
+
+        let jobs = |path: &Path| -> Vec<String> {
+            read_records(path).into_iter().map(|r| r.job_id).collect()
+        };
+        assert_eq!(jobs(&path), ["5"]);
+        assert_eq!(jobs(&path.with_extension("jsonl.1")), ["3", "4"]);
+        assert_eq!(jobs(&path.with_extension("jsonl.2")), ["1", "2"]);
+        assert!(!path.with_extension("jsonl.3").exists());
+
+        // Reopening appends to the current file
+        drop(log);
+        let log = ShareAuditLog::open(&path, 2 * line_len).unwrap();
+        log.write(&record("6")).unwrap();
+        assert_eq!(jobs(&path), ["5", "6"]);
+    }
+}
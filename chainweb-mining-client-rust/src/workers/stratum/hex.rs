@@ -2,7 +2,9 @@
 //!
 //! Provides functions for encoding and decoding hex strings used in Stratum messages.
 
-use crate::error::{Error, Result};
+use super::nonce::{Nonce2, NonceSize};
+use crate::core::{Nonce, trim_hex};
+use crate::error::{Error, Result, StratumError};
 
 /// Encode bytes as a lowercase hex string
 pub fn encode_hex(bytes: &[u8]) -> String {
@@ -19,14 +21,67 @@ pub fn encode_hex_prefixed(bytes: &[u8]) -> String {
     format!("0x{}", encode_hex(bytes))
 }
 
-/// Decode a hex string that may have "0x" prefix
+/// Decode a hex string that may have "0x" prefix and surrounding whitespace
 pub fn decode_hex_flexible(hex_str: &str) -> Result<Vec<u8>> {
-    let cleaned = if hex_str.starts_with("0x") || hex_str.starts_with("0X") {
-        &hex_str[2..]
-    } else {
-        hex_str
-    };
-    decode_hex(cleaned)
+    decode_hex(trim_hex(hex_str))
+}
+
+fn invalid_field(field: &'static str, value: &str, reason: impl Into<String>) -> StratumError {
+    StratumError::InvalidField {
+        field,
+        value: value.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Decode the hex of a `mining.submit` field into exactly `len` bytes
+fn decode_field(
+    field: &'static str,
+    hex_str: &str,
+    len: usize,
+) -> std::result::Result<Vec<u8>, StratumError> {
+    let bytes = hex::decode(hex_str).map_err(|e| invalid_field(field, hex_str, e.to_string()))?;
+    if bytes.len() != len {
+        return Err(invalid_field(
+            field,
+            hex_str,
+            format!("expected {} bytes, got {}", len, bytes.len()),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Parse the submitted `nTime`, the hex encoded (big-endian) seconds since
+/// the Unix epoch as sent in `mining.notify`
+pub fn parse_ntime(hex_str: &str) -> std::result::Result<u64, StratumError> {
+    if hex_str.is_empty() || hex_str.len() > 16 || !hex_str.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid_field(
+            "ntime",
+            hex_str,
+            "expected 1 to 16 hex digits",
+        ));
+    }
+    u64::from_str_radix(hex_str, 16).map_err(|e| invalid_field("ntime", hex_str, e.to_string()))
+}
+
+/// Parse the submitted nonce, the 8 hex encoded bytes of the full
+/// (little-endian) nonce
+pub fn parse_submit_nonce(hex_str: &str) -> std::result::Result<Nonce, StratumError> {
+    let bytes = decode_field("nonce", hex_str, 8)?;
+    let mut nonce = [0u8; 8];
+    nonce.copy_from_slice(&bytes);
+    Ok(Nonce::from_le_bytes(nonce))
+}
+
+/// Parse the submitted extranonce2, the hex encoded (big-endian) bytes of
+/// the session's extranonce2 size
+pub fn parse_extranonce2(
+    hex_str: &str,
+    size: NonceSize,
+) -> std::result::Result<Nonce2, StratumError> {
+    let bytes = decode_field("extranonce2", hex_str, size.as_bytes() as usize)?;
+    Nonce2::from_bytes(size, &bytes)
+        .map_err(|e| invalid_field("extranonce2", hex_str, e.to_string()))
 }
 
 #[cfg(test)]
@@ -72,6 +127,7 @@ mod tests {
         assert_eq!(decode_hex_flexible("abcd").unwrap(), data);
         assert_eq!(decode_hex_flexible("0xabcd").unwrap(), data);
         assert_eq!(decode_hex_flexible("0Xabcd").unwrap(), data);
+        assert_eq!(decode_hex_flexible(" 0xabcd\n").unwrap(), data);
     }
 
     #[test]
@@ -90,4 +146,48 @@ mod tests {
         let decoded = decode_hex(&encoded).unwrap();
         assert_eq!(decoded, empty);
     }
+
+    #[test]
+    fn test_parse_ntime() {
+        assert_eq!(parse_ntime("6553f100").unwrap(), 0x6553f100);
+        assert_eq!(parse_ntime("1").unwrap(), 1);
+
+        for malformed in ["", "+1", "6553f10g", "0x6553f100", "00000000000000001"] {
+            let err = parse_ntime(malformed).unwrap_err();
+            assert!(err.to_string().starts_with("Invalid ntime"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_submit_nonce() {
+        assert_eq!(
+            parse_submit_nonce("0100000000000080").unwrap().value(),
+            0x8000_0000_0000_0001
+        );
+
+        let err = parse_submit_nonce("01000000").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid nonce \"01000000\": expected 8 bytes, got 4"
+        );
+        assert!(parse_submit_nonce("010000000000008g").is_err());
+        assert!(parse_submit_nonce("010000000000008").is_err());
+    }
+
+    #[test]
+    fn test_parse_extranonce2() {
+        let size = NonceSize::new(4).unwrap();
+        assert_eq!(
+            parse_extranonce2("01020304", size).unwrap().value(),
+            0x01020304
+        );
+
+        let err = parse_extranonce2("010203", size).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid extranonce2 \"010203\": expected 4 bytes, got 3"
+        );
+        assert!(parse_extranonce2("0102030g", size).is_err());
+        assert!(parse_extranonce2("", size).is_err());
+    }
 }
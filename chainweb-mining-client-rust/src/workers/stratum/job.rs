@@ -11,14 +11,19 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 
+/// Longest job id in hex characters; the job counter wraps beyond it
+pub const JOB_ID_MAX_HEX_LEN: usize = 8;
+
+const JOB_ID_MASK: u64 = (1 << (JOB_ID_MAX_HEX_LEN * 4)) - 1;
+
 /// Job identifier with hex encoding support
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct JobId(String);
 
 impl JobId {
-    /// Create a new job ID from integer
+    /// Create a new job ID from integer, wrapped to [`JOB_ID_MAX_HEX_LEN`] hex characters
     pub fn new(id: u64) -> Self {
-        JobId(format!("{:x}", id))
+        JobId(format!("{:x}", id & JOB_ID_MASK))
     }
 
     /// Create job ID from string
@@ -197,9 +202,16 @@ impl JobManager {
     }
 
     /// Generate next job ID
+    ///
+    /// After the counter wraps, ids still held by an active job are skipped.
     pub fn next_job_id(&self) -> JobId {
-        let id = self.job_counter.fetch_add(1, Ordering::Relaxed);
-        JobId::new(id)
+        let jobs = self.jobs.read();
+        loop {
+            let id = JobId::new(self.job_counter.fetch_add(1, Ordering::Relaxed));
+            if !jobs.contains_key(&id) {
+                return id;
+            }
+        }
     }
 
     /// Add a new job
@@ -327,6 +339,44 @@ mod tests {
 
         let parsed = JobId::from_hex("ff").unwrap();
         assert_eq!(parsed, job_id);
+
+        // Ids wrap instead of growing past the maximum width
+        assert_eq!(JobId::new(JOB_ID_MASK).as_str(), "ffffffff");
+        assert_eq!(JobId::new(JOB_ID_MASK + 1).as_str(), "0");
+        assert_eq!(JobId::new(u64::MAX).as_str().len(), JOB_ID_MAX_HEX_LEN);
+    }
+
+    #[test]
+    fn test_job_id_wrap_skips_active_jobs() {
+        let manager = JobManager::new(10, 60);
+        let add = |id: JobId| {
+            manager.add_job(MiningJob::new(
+                id,
+                ChainId::new(0),
+                Target::from_bytes([0xFF; 32]),
+                Work::from_bytes([0x42; 286]),
+                Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap(),
+                false,
+            ))
+        };
+
+        let first = manager.next_job_id();
+        add(first.clone());
+
+        // Wrap the counter around so it lands on the still active first job
+        manager.job_counter.store(JOB_ID_MASK, Ordering::Relaxed);
+        let last = manager.next_job_id();
+        assert_eq!(last.as_str(), "ffffffff");
+        add(last.clone());
+
+        let wrapped = manager.next_job_id();
+        assert_eq!(wrapped.as_str(), "1");
+        assert_ne!(wrapped, first);
+        add(wrapped.clone());
+
+        for id in [&first, &last, &wrapped] {
+            assert_eq!(&manager.get_job(id).unwrap().job_id, id);
+        }
     }
 
     #[test]
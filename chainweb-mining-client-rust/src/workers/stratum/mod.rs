@@ -1,5 +1,6 @@
 //! Stratum protocol server implementation for ASIC miners
 
+mod audit;
 mod difficulty;
 mod hex;
 mod job;
@@ -7,16 +8,22 @@ mod nonce;
 mod protocol;
 mod server;
 mod session;
+mod worker_registry;
 
+pub use audit::{AUDIT_ROTATIONS, ShareAuditLog, ShareAuditRecord};
 pub use difficulty::{difficulty_to_target, target_to_difficulty};
-pub use hex::{decode_hex, decode_hex_flexible, encode_hex, encode_hex_prefixed};
+pub use hex::{
+    decode_hex, decode_hex_flexible, encode_hex, encode_hex_prefixed, parse_extranonce2,
+    parse_ntime, parse_submit_nonce,
+};
 pub use job::{ClientWorker, JobId, JobManager, MiningJob, SharedJobManager};
-pub use nonce::{Nonce1, Nonce2, NonceSize, compose_nonce, split_nonce};
+pub use nonce::{Nonce1, Nonce2, NonceSize, compose_nonce, nonce_split, split_nonce};
 pub use protocol::{
-    StratumMessage, StratumMethod, StratumNotification, StratumRequest, StratumResponse,
+    JobParams, StratumMessage, StratumMethod, StratumNotification, StratumRequest, StratumResponse,
 };
 pub use server::{StratumServer, StratumServerConfig};
 pub use session::{SessionId, StratumSession};
+pub use worker_registry::WorkerRegistry;
 
 #[cfg(test)]
 mod tests {
@@ -194,6 +194,23 @@ impl Nonce2 {
     }
 }
 
+/// Split the 8 byte nonce into extranonce1 and extranonce2 sizes
+///
+/// Both parts need at least one byte, so the extranonce1 size must be 1-7;
+/// the extranonce2 size is the complement and the two always sum to 8.
+pub fn nonce_split(extranonce1_size: u8) -> Result<(NonceSize, NonceSize)> {
+    if extranonce1_size == 0 || extranonce1_size >= 8 {
+        return Err(Error::config_invalid_value(
+            "stratum_extranonce1_size",
+            extranonce1_size.to_string(),
+            "1 to 7 bytes",
+        ));
+    }
+
+    let nonce1_size = NonceSize::new(extranonce1_size)?;
+    Ok((nonce1_size, nonce1_size.complement()))
+}
+
 /// Compose Nonce1 and Nonce2 into final nonce
 pub fn compose_nonce(nonce1: Nonce1, nonce2: Nonce2) -> Result<Nonce> {
     let total_size = nonce1.size().as_bytes() + nonce2.size().as_bytes();
@@ -326,6 +343,39 @@ mod tests {
         assert_eq!(nonce2.value(), 255);
     }
 
+    #[test]
+    fn test_configurable_nonce_splits() {
+        for (nonce1_bytes, nonce2_bytes) in [(2u8, 6u8), (4, 4), (6, 2)] {
+            let (nonce1_size, nonce2_size) = nonce_split(nonce1_bytes).unwrap();
+            assert_eq!(nonce1_size.as_bytes(), nonce1_bytes);
+            assert_eq!(nonce2_size.as_bytes(), nonce2_bytes);
+
+            let nonce1 = Nonce1::new(nonce1_size, nonce1_size.max_value()).unwrap();
+            assert_eq!(nonce1.nonce2_size(), nonce2_size);
+            assert_eq!(nonce1.to_hex().len(), nonce1_bytes as usize * 2);
+
+            let nonce2_wire = vec![0xA5u8; nonce2_bytes as usize];
+            let nonce2 = Nonce2::from_bytes(nonce2_size, &nonce2_wire).unwrap();
+
+            let composed = compose_nonce(nonce1, nonce2).unwrap();
+            let (split1, split2) = split_nonce(composed, nonce1_size).unwrap();
+            assert_eq!(split1, nonce1);
+            assert_eq!(split2, nonce2);
+
+            // All 8 bytes are covered: nonce1 fills the low bytes, nonce2 the high ones
+            let bytes = composed.value().to_le_bytes();
+            assert!(bytes[..nonce1_bytes as usize].iter().all(|&b| b == 0xFF));
+            assert!(bytes[nonce1_bytes as usize..].iter().all(|&b| b == 0xA5));
+        }
+    }
+
+    #[test]
+    fn test_invalid_nonce_split() {
+        assert!(nonce_split(0).is_err());
+        assert!(nonce_split(8).is_err());
+        assert!(nonce_split(9).is_err());
+    }
+
     #[test]
     fn test_invalid_composition() {
         let nonce1 = Nonce1::new(NonceSize::new(3).unwrap(), 0x123456).unwrap();
@@ -1,5 +1,6 @@
 //! Stratum protocol message definitions
 
+use crate::core::Work;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -18,6 +19,8 @@ pub enum StratumErrorCode {
     UnauthorizedWorker = 24,
     /// Not subscribed
     NotSubscribed = 25,
+    /// Method the server doesn't implement (JSON-RPC "method not found")
+    UnknownMethod = -32601,
 }
 
 impl StratumErrorCode {
@@ -25,7 +28,7 @@ impl StratumErrorCode {
     pub fn code(&self) -> i64 {
         *self as i64
     }
-    
+
     /// Get a standard error message
     pub fn message(&self) -> &'static str {
         match self {
@@ -35,6 +38,7 @@ impl StratumErrorCode {
             Self::LowDifficultyShare => "Low difficulty share",
             Self::UnauthorizedWorker => "Unauthorized worker",
             Self::NotSubscribed => "Not subscribed",
+            Self::UnknownMethod => "Method not found",
         }
     }
 }
@@ -150,14 +154,18 @@ impl StratumResponse {
             ])),
         }
     }
-    
+
     /// Create an error response using a standard error code
     pub fn error_with_code(id: Value, error_code: StratumErrorCode) -> Self {
         Self::error(id, error_code.code() as i32, error_code.message())
     }
-    
+
     /// Create an error response with a custom message but standard error code
-    pub fn error_with_code_and_message(id: Value, error_code: StratumErrorCode, message: &str) -> Self {
+    pub fn error_with_code_and_message(
+        id: Value,
+        error_code: StratumErrorCode,
+        message: &str,
+    ) -> Self {
         Self::error(id, error_code.code() as i32, message)
     }
 }
@@ -227,50 +235,68 @@ impl StratumMessage {
     }
 }
 
-/// Job parameters for mining.notify
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Job parameters of `mining.notify` for Kadena
+///
+/// Kadena work is a complete block header ending in the 8 byte nonce, so
+/// unlike Bitcoin there is no coinbase or merkle tree for the miner to
+/// assemble. A job is sent as `[job_id, header, clean_jobs]`:
+///
+/// - `job_id`: id the miner quotes in `mining.submit`
+/// - `header`: hex of the work header; the miner completes it with its
+///   nonce, extranonce1 followed by extranonce2, in the final 8 bytes
+/// - `clean_jobs`: whether work on previous jobs should be abandoned
+///
+/// The target shares must meet is sent separately with `mining.set_target`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct JobParams {
     /// Job ID
     pub job_id: String,
-    /// Previous block hash
-    pub prevhash: String,
-    /// Coinbase part 1
-    pub coinb1: String,
-    /// Coinbase part 2
-    pub coinb2: String,
-    /// Merkle branches
-    pub merkle_branch: Vec<String>,
-    /// Block version
-    pub version: String,
-    /// nBits (target)
-    pub nbits: String,
-    /// nTime
-    pub ntime: String,
+    /// Hex encoded work header
+    pub header: String,
     /// Clean jobs flag
     pub clean_jobs: bool,
 }
 
 impl JobParams {
+    /// Parameters of job `job_id` on `work`
+    pub fn new(job_id: &str, work: &Work, clean_jobs: bool) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            header: hex::encode(work.as_bytes()),
+            clean_jobs,
+        }
+    }
+
     /// Convert to params array for notification
-    #[allow(dead_code)]
     pub fn to_params(&self) -> Vec<Value> {
         vec![
             Value::String(self.job_id.clone()),
-            Value::String(self.prevhash.clone()),
-            Value::String(self.coinb1.clone()),
-            Value::String(self.coinb2.clone()),
-            Value::Array(
-                self.merkle_branch
-                    .iter()
-                    .map(|s| Value::String(s.clone()))
-                    .collect(),
-            ),
-            Value::String(self.version.clone()),
-            Value::String(self.nbits.clone()),
-            Value::String(self.ntime.clone()),
+            Value::String(self.header.clone()),
             Value::Bool(self.clean_jobs),
         ]
     }
+
+    /// Parse the params array of a notification
+    pub fn from_params(params: &[Value]) -> Option<Self> {
+        match params {
+            [
+                Value::String(job_id),
+                Value::String(header),
+                Value::Bool(clean_jobs),
+            ] => Some(Self {
+                job_id: job_id.clone(),
+                header: header.clone(),
+                clean_jobs: *clean_jobs,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Decode the work header
+    pub fn work(&self) -> Option<Work> {
+        let bytes = hex::decode(&self.header).ok()?;
+        Work::from_slice(&bytes).ok()
+    }
 }
 
 #[cfg(test)]
@@ -333,21 +359,18 @@ mod tests {
 
     #[test]
     fn test_job_params() {
-        let job = JobParams {
-            job_id: "123".to_string(),
-            prevhash: "00000000".to_string(),
-            coinb1: "01000000".to_string(),
-            coinb2: "ffffffff".to_string(),
-            merkle_branch: vec!["aabbccdd".to_string()],
-            version: "20000000".to_string(),
-            nbits: "1a2b3c4d".to_string(),
-            ntime: "5a1b2c3d".to_string(),
-            clean_jobs: true,
-        };
+        let work = Work::from_bytes([0xab; 286]);
+        let job = JobParams::new("123", &work, true);
 
         let params = job.to_params();
-        assert_eq!(params.len(), 9);
+        assert_eq!(params.len(), 3);
         assert_eq!(params[0], Value::String("123".to_string()));
-        assert_eq!(params[8], Value::Bool(true));
+        assert_eq!(params[1], Value::String("ab".repeat(286)));
+        assert_eq!(params[2], Value::Bool(true));
+
+        let parsed = JobParams::from_params(&params).unwrap();
+        assert_eq!(parsed, job);
+        assert_eq!(parsed.work().unwrap(), work);
+        assert!(JobParams::from_params(&params[..2]).is_none());
     }
 }
@@ -1,34 +1,55 @@
 //! Stratum server implementation
 
-use crate::config::StratumDifficulty;
-use crate::core::{adjust_difficulty, Difficulty, HashRate, Nonce, Period, Target, Work};
-use crate::error::{Error, Result};
-use crate::utils::monitoring::global_monitoring;
-use crate::workers::{MiningResult, Worker};
+use crate::config::{StratumDifficulty, StratumMode};
+use crate::core::{Difficulty, HashRate, Period, Target, Work, adjust_difficulty};
+use crate::error::{Error, Result, StratumError};
+use crate::utils::events::{MiningEvent, emit_event};
+use crate::utils::monitoring::{RejectReason, global_monitoring};
+use crate::utils::panic::spawn_monitored;
+use crate::workers::{MiningResult, Worker, WorkerCapabilities};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use serde_json::Value;
+use std::collections::VecDeque;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream, tcp::OwnedWriteHalf};
-use tokio::sync::{RwLock, broadcast, mpsc};
-use tokio::time::interval;
-use tracing::{error, info, warn, debug};
-
-use super::nonce::{Nonce1, Nonce2, NonceSize, compose_nonce};
+use tokio::sync::{RwLock, broadcast, mpsc, watch};
+use tokio::task::JoinSet;
+use tokio::time::{Instant, interval};
+use tracing::{debug, error, info, warn};
+
+use super::audit::{ShareAuditLog, ShareAuditRecord};
+use super::hex::{parse_extranonce2, parse_ntime, parse_submit_nonce};
+use super::job::JobId;
+use super::nonce::{Nonce1, NonceSize, compose_nonce, nonce_split};
 use super::protocol::{StratumErrorCode, *};
 use super::session::*;
+use super::worker_registry::WorkerRegistry;
+
+/// Dead band for period based difficulty ([`StratumDifficulty::Period`])
+///
+/// There is no built-in share period: sessions always aim at the configured
+/// one. A session's difficulty only changes once its share interval
+/// (difficulty / estimated hash rate) is off from that period by more than
+/// this fraction, e.g. outside 3.75s..6.25s for a 5s period.
+const PERIOD_TOLERANCE: f64 = 0.25;
+
+/// Difficulty level a period difficulty session starts at, before its hash
+/// rate is known
+const INITIAL_SESSION_TARGET_LEVEL: u8 = 20;
 
-/// Constants for dynamic difficulty adjustment
-const PERIOD_TOLERANCE: f64 = 0.25;  // 25% tolerance before adjusting
-const MAX_SESSION_TARGET_LEVEL: u8 = 42;  // Minimum difficulty level
+/// Time connections get on shutdown to finish the line they are writing; a
+/// miner that stops reading is dropped afterwards
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
 
 /// Authorization callback type
 /// Returns Ok(()) if authorized, Err(message) if not
-pub type AuthorizeCallback = Box<dyn Fn(&str, &str) -> std::result::Result<(), String> + Send + Sync>;
+pub type AuthorizeCallback =
+    Box<dyn Fn(&str, &str) -> std::result::Result<(), String> + Send + Sync>;
 
 /// Stratum server configuration
 pub struct StratumServerConfig {
@@ -40,10 +61,33 @@ pub struct StratumServerConfig {
     pub max_connections: usize,
     /// Difficulty setting
     pub difficulty: StratumDifficulty,
+    /// Solo or pool semantics, overriding `difficulty` (see
+    /// [`StratumMode::difficulty`]); `difficulty` applies as given if unset
+    pub mode: Option<StratumMode>,
+    /// Easiest difficulty level (leading zero bits of the target) sessions
+    /// are handed by a period difficulty
+    pub min_difficulty_level: u8,
+    /// Hash rate change factor after which a period difficulty is
+    /// recalculated from the latest shares only (zero disables)
+    pub hashrate_shift_factor: f64,
     /// Job emission rate in milliseconds
     pub rate_ms: u64,
+    /// Always set `clean_jobs` in `mining.notify`, even for time-only updates
+    pub always_clean_jobs: bool,
+    /// Number of recent jobs (including the current one) accepted for submits
+    pub job_history_size: usize,
+    /// Disconnect sessions that submit no shares for this many seconds (0 disables)
+    pub session_idle_timeout_secs: u64,
+    /// Bytes of the nonce assigned to each session as extranonce1 (1-7);
+    /// miners roll the remaining bytes as extranonce2
+    pub extranonce1_size: u8,
+    /// Extranonce1 handed to every session instead of a random one, which
+    /// also serves as the subscription id; only for reproducible tests
+    pub fixed_extranonce1: Option<u64>,
     /// Optional authorization callback
     pub authorize_callback: Option<AuthorizeCallback>,
+    /// Optional audit trail of accepted shares
+    pub audit_log: Option<Arc<ShareAuditLog>>,
 }
 
 /// Current mining job
@@ -55,6 +99,8 @@ struct MiningJob {
     work: Work,
     /// Target
     target: Target,
+    /// Whether miners must discard in-progress work for this job
+    clean_jobs: bool,
 }
 
 impl MiningJob {
@@ -69,20 +115,44 @@ impl MiningJob {
 struct ServerState {
     /// Active sessions
     sessions: DashMap<SessionId, Arc<RwLock<StratumSession>>>,
+    /// Extranonce1 changes queued for the connection of each session
+    extranonce_updates: DashMap<SessionId, mpsc::UnboundedSender<Nonce1>>,
     /// Current job
     current_job: RwLock<Option<MiningJob>>,
-    /// Job counter
+    /// Recently superseded jobs, newest first
+    job_history: RwLock<VecDeque<MiningJob>>,
+    /// Number of jobs (including the current one) accepted for submits
+    job_history_size: usize,
+    /// Job counter, wrapped into job ids of bounded width
     job_counter: AtomicU64,
-    /// Total hashrate estimate
-    total_hashrate: AtomicU64,
-    /// Shutdown flag
-    shutdown: AtomicBool,
+    /// Share statistics by worker name, across sessions
+    workers: WorkerRegistry,
+    /// Set once the server shuts down
+    shutdown: watch::Sender<bool>,
     /// Result channel for submitted shares
     result_tx: RwLock<Option<mpsc::Sender<MiningResult>>>,
     /// Difficulty configuration
     difficulty_config: StratumDifficulty,
+    /// Only accept shares meeting the block target
+    solo: bool,
+    /// Easiest target sessions are handed by a period difficulty
+    min_session_target: Target,
+    /// Hash rate change factor that resets a session's estimate (zero disables)
+    hashrate_shift_factor: f64,
+    /// Force `clean_jobs` on every notify
+    always_clean_jobs: bool,
+    /// Idle time after which a session without submits is disconnected (zero disables)
+    session_idle_timeout: Duration,
+    /// Size of the extranonce1 assigned to each session
+    extranonce1_size: NonceSize,
+    /// Extranonce1 of every session, if not random
+    fixed_extranonce1: Option<Nonce1>,
+    /// Source of random extranonce1 values
+    fill_random: FillRandom,
     /// Authorization callback
     authorize_callback: Option<AuthorizeCallback>,
+    /// Audit trail of accepted shares
+    audit_log: Option<Arc<ShareAuditLog>>,
 }
 
 /// Stratum server for ASIC miners
@@ -103,24 +173,70 @@ impl StratumServer {
 
         let (job_tx, _) = broadcast::channel(100);
 
+        let extranonce1_size = match nonce_split(config.extranonce1_size) {
+            Ok((nonce1_size, nonce2_size)) => {
+                info!(
+                    "Stratum nonce split: {} byte extranonce1, {} byte extranonce2",
+                    nonce1_size.as_bytes(),
+                    nonce2_size.as_bytes()
+                );
+                nonce1_size
+            }
+            Err(e) => {
+                warn!("{}, using the default split", e);
+                NonceSize::default()
+            }
+        };
+
+        let difficulty = config.mode.map_or(config.difficulty.clone(), |mode| {
+            mode.difficulty(&config.difficulty)
+        });
+
+        let fixed_extranonce1 = config.fixed_extranonce1.and_then(|value| {
+            Nonce1::new(extranonce1_size, value)
+                .inspect_err(|e| warn!("Ignoring fixed extranonce1: {}", e))
+                .ok()
+        });
+
         Self {
             config: StratumServerConfig {
                 port: config.port,
                 host: config.host.clone(),
                 max_connections: config.max_connections,
-                difficulty: config.difficulty.clone(),
+                difficulty: difficulty.clone(),
+                mode: config.mode,
+                min_difficulty_level: config.min_difficulty_level,
+                hashrate_shift_factor: config.hashrate_shift_factor,
                 rate_ms: config.rate_ms,
+                always_clean_jobs: config.always_clean_jobs,
+                job_history_size: config.job_history_size,
+                session_idle_timeout_secs: config.session_idle_timeout_secs,
+                extranonce1_size: extranonce1_size.as_bytes(),
+                fixed_extranonce1: config.fixed_extranonce1,
                 authorize_callback: None, // Callbacks can't be cloned, so we don't store it here
+                audit_log: None,
             },
             state: Arc::new(ServerState {
                 sessions: DashMap::new(),
+                extranonce_updates: DashMap::new(),
                 current_job: RwLock::new(None),
+                job_history: RwLock::new(VecDeque::new()),
+                job_history_size: config.job_history_size.max(1),
                 job_counter: AtomicU64::new(0),
-                total_hashrate: AtomicU64::new(0),
-                shutdown: AtomicBool::new(false),
+                workers: WorkerRegistry::new(),
+                shutdown: watch::Sender::new(false),
                 result_tx: RwLock::new(None),
-                difficulty_config: config.difficulty.clone(),
+                difficulty_config: difficulty,
+                solo: config.mode == Some(StratumMode::Solo),
+                min_session_target: Target::mk_target_level(config.min_difficulty_level),
+                hashrate_shift_factor: config.hashrate_shift_factor,
+                always_clean_jobs: config.always_clean_jobs,
+                session_idle_timeout: Duration::from_secs(config.session_idle_timeout_secs),
+                extranonce1_size,
+                fixed_extranonce1,
+                fill_random: getrandom::fill,
                 authorize_callback: config.authorize_callback,
+                audit_log: config.audit_log,
             }),
             job_tx,
             result_tx: None,
@@ -141,9 +257,11 @@ impl StratumServer {
 
         // Start job emitter
         let job_emitter = self.start_job_emitter();
+        let mut clients = JoinSet::new();
+        let mut shutdown = self.state.shutdown.subscribe();
 
         // Accept connections
-        while !self.state.shutdown.load(Ordering::Relaxed) {
+        loop {
             tokio::select! {
                 Ok((stream, addr)) = listener.accept() => {
                     if self.state.sessions.len() >= self.config.max_connections {
@@ -155,21 +273,42 @@ impl StratumServer {
                     let job_rx = self.job_tx.subscribe();
                     let result_tx = self.result_tx.clone();
 
-                    tokio::spawn(async move {
+                    clients.spawn(async move {
                         if let Err(e) = handle_client(stream, addr, state, job_rx, result_tx).await {
                             error!("Client {} error: {}", addr, e);
                         }
                     });
                 }
+                Some(_) = clients.join_next(), if !clients.is_empty() => {}
+                _ = shutting_down(&mut shutdown) => {
+                    info!("Shutting down Stratum server");
+                    break;
+                }
                 _ = tokio::signal::ctrl_c() => {
                     info!("Shutting down Stratum server");
                     break;
                 }
             }
         }
+        drop(listener);
 
-        // Stop job emitter
-        job_emitter.abort();
+        // The job emitter and the connections stop between messages, so no
+        // miner is left with a partially written line
+        let _ = self.state.shutdown.send_replace(true);
+        if let Err(e) = job_emitter.await {
+            error!("Stratum job emitter failed: {}", e);
+        }
+        let closed = tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, async {
+            while clients.join_next().await.is_some() {}
+        })
+        .await;
+        if closed.is_err() {
+            warn!(
+                "Dropping {} stratum connections that didn't close within {}s",
+                clients.len(),
+                SHUTDOWN_GRACE_PERIOD.as_secs()
+            );
+        }
 
         Ok(())
     }
@@ -180,40 +319,48 @@ impl StratumServer {
         let job_tx = self.job_tx.clone();
         let rate = Duration::from_millis(self.config.rate_ms);
 
-        tokio::spawn(async move {
+        let mut shutdown = state.shutdown.subscribe();
+
+        spawn_monitored("stratum job emitter", async move {
             let mut ticker = interval(rate);
             ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-            
-            let mut last_job_update = std::time::Instant::now();
+
+            // Job time advances with the monotonic clock, so stepping the
+            // system clock neither rewinds nor skips it
+            let mut last_job_update = Instant::now();
             let job_update_interval = Duration::from_secs(30); // Update job time every 30 seconds
 
             loop {
-                ticker.tick().await;
-
-                if state.shutdown.load(Ordering::Relaxed) {
-                    break;
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutting_down(&mut shutdown) => break,
                 }
 
                 // Check if we need to update job time
-                let now = std::time::Instant::now();
-                let should_update_time = now.duration_since(last_job_update) >= job_update_interval;
+                let now = Instant::now();
+                let elapsed = now.duration_since(last_job_update);
+                let should_update_time = elapsed >= job_update_interval;
 
                 // Get current job
                 let job = state.current_job.read().await.clone();
 
                 if let Some(mut current_job) = job {
+                    // Re-emissions and time bumps keep building on the same
+                    // parent, so miners don't need to drop their work
+                    current_job.clean_jobs = state.always_clean_jobs;
+
                     // Update job time if needed
                     if should_update_time {
-                        let micros_to_add = job_update_interval.as_micros() as i64;
+                        let micros_to_add = elapsed.as_micros() as i64;
                         current_job.increment_job_time(micros_to_add);
-                        
+
                         // Update the stored job
                         *state.current_job.write().await = Some(current_job.clone());
                         last_job_update = now;
-                        
+
                         debug!("Updated job time by {} microseconds", micros_to_add);
                     }
-                    
+
                     // Emit job to all clients
                     let _ = job_tx.send(current_job);
                 }
@@ -223,20 +370,58 @@ impl StratumServer {
 
     /// Update current work
     async fn update_work(&self, work: Work, target: Target) {
-        let job_id = self.state.job_counter.fetch_add(1, Ordering::Relaxed);
+        let mut current_job = self.state.current_job.write().await;
+        let mut history = self.state.job_history.write().await;
+
+        // After the counter wraps, skip ids still held by an active job
+        let id = loop {
+            let id = JobId::new(self.state.job_counter.fetch_add(1, Ordering::Relaxed)).to_string();
+            if current_job
+                .iter()
+                .chain(history.iter())
+                .all(|job| job.id != id)
+            {
+                break id;
+            }
+        };
+
+        let clean_jobs =
+            self.state.always_clean_jobs || needs_clean_jobs(current_job.as_ref(), &work, &target);
 
         let job = MiningJob {
-            id: format!("{:x}", job_id),
-            work: work.clone(),
+            id,
+            work,
             target,
+            clean_jobs,
         };
 
-        // Update current job
-        *self.state.current_job.write().await = Some(job.clone());
+        // Update current job, keeping the superseded one for late submits
+        if let Some(previous) = current_job.replace(job.clone()) {
+            history.push_front(previous);
+            history.truncate(self.state.job_history_size - 1);
+        }
+        drop(history);
+        drop(current_job);
 
         // Broadcast to all clients
         let _ = self.job_tx.send(job);
     }
+
+    /// Move a connected session to a new extranonce1
+    ///
+    /// A subscribed miner is told with `mining.set_extranonce`; shares it
+    /// submits afterwards have to be composed with the new extranonce1.
+    pub fn set_session_extranonce1(
+        &self,
+        session_id: SessionId,
+        extranonce1: Nonce1,
+    ) -> Result<()> {
+        self.state
+            .extranonce_updates
+            .get(&session_id)
+            .and_then(|updates| updates.send(extranonce1).ok())
+            .ok_or_else(|| Error::stratum(format!("No connected stratum session {}", session_id)))
+    }
 }
 
 /// Handle a client connection
@@ -253,13 +438,18 @@ async fn handle_client(
     let mut reader = BufReader::new(reader);
 
     // Create session with initial difficulty based on config
-    let extranonce1 = generate_extranonce1();
+    let mut extranonce1 = match state.fixed_extranonce1 {
+        Some(extranonce1) => extranonce1,
+        None => generate_extranonce1(state.extranonce1_size, state.fill_random).map_err(|e| {
+            Error::stratum_client_connection_failed(addr.to_string(), format!("refused: {}", e))
+        })?,
+    };
     let initial_difficulty = match &state.difficulty_config {
         StratumDifficulty::Block => 1.0, // Will be updated with actual work
         StratumDifficulty::Fixed(level) => 2f64.powi(*level as i32),
         StratumDifficulty::Period(_) => 1.0, // Start with low difficulty, will adjust
     };
-    
+
     let session = Arc::new(RwLock::new(StratumSession::new(
         extranonce1,
         initial_difficulty,
@@ -267,10 +457,21 @@ async fn handle_client(
     let session_id = session.read().await.id;
 
     state.sessions.insert(session_id, Arc::clone(&session));
+    let (extranonce_tx, mut extranonce_rx) = mpsc::unbounded_channel();
+    state.extranonce_updates.insert(session_id, extranonce_tx);
 
     // Client state
     let mut authorized = false;
     let mut subscribed = false;
+    let mut shutdown = state.shutdown.subscribe();
+
+    // Idle monitor; checks a few times per timeout so disconnects aren't late
+    let idle_timeout = state.session_idle_timeout;
+    let mut idle_check = interval(if idle_timeout.is_zero() {
+        Duration::from_secs(3600)
+    } else {
+        (idle_timeout / 4).max(Duration::from_millis(10))
+    });
 
     loop {
         let mut line = String::new();
@@ -297,8 +498,7 @@ async fn handle_client(
                                     &mut writer,
                                 ).await;
 
-                                let json = serde_json::to_string(&response)? + "\n";
-                                writer.write_all(json.as_bytes()).await?;
+                                write_message(&mut writer, &response).await?;
                             }
                             Ok(_) => {
                                 warn!("Unexpected message type from {}", addr);
@@ -315,16 +515,79 @@ async fn handle_client(
                 }
             }
 
+            // Messages are written in the branches, so a shutdown closes the
+            // connection between two lines
+            _ = shutting_down(&mut shutdown) => {
+                info!("Closing stratum connection of {} for shutdown", addr);
+                break;
+            }
+
+            // Reassigned extranonce1; before subscribing the miner learns it
+            // from the subscribe response instead
+            Some(new_extranonce1) = extranonce_rx.recv() => {
+                if new_extranonce1 == extranonce1 {
+                    continue;
+                }
+                info!(
+                    "Changing extranonce1 of stratum session {} from {} to {}",
+                    session_id,
+                    extranonce1.to_hex(),
+                    new_extranonce1.to_hex()
+                );
+                extranonce1 = new_extranonce1;
+                session.write().await.extranonce1 = extranonce1;
+                if subscribed {
+                    send_set_extranonce(&mut writer, &extranonce1).await?;
+                }
+            }
+
+            // Disconnect sessions that stopped submitting shares
+            _ = idle_check.tick(), if !idle_timeout.is_zero() => {
+                let idle = session.read().await.idle_duration();
+                if idle >= idle_timeout {
+                    warn!(
+                        "Disconnecting idle stratum session {} from {}: no shares for {}s",
+                        session_id,
+                        addr,
+                        idle.as_secs()
+                    );
+                    let message = StratumNotification::new(
+                        "client.show_message",
+                        vec![Value::String(format!(
+                            "Disconnected: no shares submitted for {} seconds",
+                            idle.as_secs()
+                        ))],
+                    );
+                    let _ = write_message(&mut writer, &message).await;
+                    break;
+                }
+            }
+
             // Receive job updates
-            Ok(job) = job_rx.recv() => {
+            job = job_rx.recv() => {
+                let job = match job {
+                    Ok(job) => job,
+                    // A client that fell behind only needs the newest job;
+                    // the skipped ones are superseded anyway
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        let Some(job) = latest_job(&mut job_rx) else {
+                            continue;
+                        };
+                        warn!(
+                            "Stratum client {} lagged behind by {} jobs, skipping to job {}",
+                            addr, skipped, job.id
+                        );
+                        job
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
                 if subscribed && authorized {
                     // Send mining.notify
                     let params = create_job_params(&job);
                     let notify = StratumNotification::new("mining.notify", params);
 
-                    let json = serde_json::to_string(&notify)? + "\n";
-                    writer.write_all(json.as_bytes()).await?;
-                    
+                    write_message(&mut writer, &notify).await?;
+
                     // If this is the first job and we're using period-based difficulty,
                     // set initial session target
                     let mut session = session.write().await;
@@ -344,7 +607,11 @@ async fn handle_client(
                             }
                             StratumDifficulty::Period(_) => {
                                 // Start with a reasonable initial difficulty
-                                let initial_target = Target::mk_target_level(20); // Reasonable starting point
+                                let initial_target = clamp_session_target(
+                                    Target::mk_target_level(INITIAL_SESSION_TARGET_LEVEL),
+                                    &state.min_session_target,
+                                    &job.target,
+                                );
                                 session.session_target = Some(initial_target);
                                 session.difficulty = Difficulty::from(initial_target).0;
                                 // Send initial difficulty
@@ -360,10 +627,26 @@ async fn handle_client(
 
     // Remove session
     state.sessions.remove(&session_id);
+    state.extranonce_updates.remove(&session_id);
+    if let Some(name) = &session.read().await.worker_name {
+        state.workers.remove_session(name, session_id);
+    }
 
     Ok(())
 }
 
+/// Newest job queued on `job_rx`, dropping the older ones
+fn latest_job(job_rx: &mut broadcast::Receiver<MiningJob>) -> Option<MiningJob> {
+    let mut latest = None;
+    loop {
+        match job_rx.try_recv() {
+            Ok(job) => latest = Some(job),
+            Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+            Err(_) => return latest,
+        }
+    }
+}
+
 /// Handle a Stratum request
 async fn handle_request(
     req: StratumRequest,
@@ -380,7 +663,10 @@ async fn handle_request(
             *subscribed = true;
 
             // Response: [["mining.notify", "subscription_id"], "extranonce1", extranonce2_size]
-            let subscription_id = format!("{:x}", rand::random::<u32>());
+            let subscription_id = match state.fixed_extranonce1 {
+                Some(fixed) => fixed.to_hex(),
+                None => format!("{:x}", rand::random::<u32>()),
+            };
             let result = Value::Array(vec![
                 Value::Array(vec![Value::Array(vec![
                     Value::String("mining.notify".to_string()),
@@ -400,41 +686,47 @@ async fn handle_request(
                     Some(u) => u,
                     None => return StratumResponse::error(req.id, 20, "Invalid username"),
                 };
-                
-                let password = req.params.get(1)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                
+
+                let password = req.params.get(1).and_then(|v| v.as_str()).unwrap_or("");
+
                 // Check authorization if callback is provided
                 if let Some(ref callback) = state.authorize_callback {
                     match callback(username, password) {
                         Ok(()) => {
-                            let mut session = session.write().await;
-                            session.worker_name = Some(username.to_string());
+                            authorize_worker(state, &mut *session.write().await, username);
                             *authorized = true;
                             StratumResponse::success(req.id, Value::Bool(true))
                         }
-                        Err(msg) => {
-                            StratumResponse::error_with_code_and_message(req.id, StratumErrorCode::UnauthorizedWorker, &msg)
-                        }
+                        Err(msg) => StratumResponse::error_with_code_and_message(
+                            req.id,
+                            StratumErrorCode::UnauthorizedWorker,
+                            &msg,
+                        ),
                     }
                 } else {
                     // No callback, always authorize
-                    let mut session = session.write().await;
-                    session.worker_name = Some(username.to_string());
+                    authorize_worker(state, &mut *session.write().await, username);
                     *authorized = true;
                     StratumResponse::success(req.id, Value::Bool(true))
                 }
             } else {
-                StratumResponse::error_with_code_and_message(req.id, StratumErrorCode::Other, "Missing username")
+                StratumResponse::error_with_code_and_message(
+                    req.id,
+                    StratumErrorCode::Other,
+                    "Missing username",
+                )
             }
         }
 
         StratumMethod::Submit => {
-            // mining.submit("username", "job_id", "extranonce2", "ntime", "nonce")
+            // mining.submit("username", "job_id", "extranonce2", "ntime", "nonce"[, "version_bits"])
             if !*authorized {
-                return StratumResponse::error_with_code(req.id, StratumErrorCode::UnauthorizedWorker);
+                return StratumResponse::error_with_code(
+                    req.id,
+                    StratumErrorCode::UnauthorizedWorker,
+                );
             }
+            session.write().await.record_submit();
 
             // Parse submit parameters
             if req.params.len() < 5 {
@@ -442,7 +734,7 @@ async fn handle_request(
             }
 
             let params = &req.params;
-            let _username = match params[0].as_str() {
+            let username = match params[0].as_str() {
                 Some(u) => u,
                 None => return StratumResponse::error(req.id, 20, "Invalid username"),
             };
@@ -454,7 +746,7 @@ async fn handle_request(
                 Some(e) => e,
                 None => return StratumResponse::error(req.id, 20, "Invalid extranonce2"),
             };
-            let _ntime = match params[3].as_str() {
+            let ntime_hex = match params[3].as_str() {
                 Some(n) => n,
                 None => return StratumResponse::error(req.id, 20, "Invalid ntime"),
             };
@@ -462,154 +754,323 @@ async fn handle_request(
                 Some(n) => n,
                 None => return StratumResponse::error(req.id, 20, "Invalid nonce"),
             };
+            let version_bits_hex = match params.get(5) {
+                None => None,
+                Some(v) => match v.as_str() {
+                    Some(bits) => Some(bits),
+                    None => return StratumResponse::error(req.id, 20, "Invalid version bits"),
+                },
+            };
 
             // Update share statistics
             let mut session = session.write().await;
             session.shares_submitted += 1;
 
-            // Get the current job
-            let current_job = state.current_job.read().await;
-            let job = match &*current_job {
-                Some(j) if j.id == job_id => j.clone(),
-                _ => {
-                    drop(current_job);
+            // Find the job: the current one, or a recently superseded one
+            let (job, stale) = match find_job(state, job_id).await {
+                Some(found) => found,
+                None => {
+                    record_share(state, &session, false);
+                    global_monitoring().record_rejection(RejectReason::JobNotFound);
+                    emit_share_event(username, Some(RejectReason::JobNotFound.as_str()));
                     return StratumResponse::error_with_code(req.id, StratumErrorCode::JobNotFound);
                 }
             };
-            drop(current_job);
 
-            // Parse extranonce2
-            let extranonce2_bytes = match hex::decode(extranonce2_hex) {
-                Ok(b) => b,
-                Err(_) => return StratumResponse::error(req.id, 20, "Invalid extranonce2 hex"),
+            // Parse the hex fields; the nTime is validated but not rolled
+            let parsed = (|| {
+                Ok::<_, StratumError>((
+                    parse_extranonce2(extranonce2_hex, extranonce1.nonce2_size())?,
+                    parse_ntime(ntime_hex)?,
+                    parse_submit_nonce(nonce_hex)?,
+                ))
+            })();
+            let (nonce2, _ntime, submitted_nonce) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    record_share(state, &session, false);
+                    global_monitoring().record_rejection(RejectReason::Malformed);
+                    emit_share_event(username, Some(RejectReason::Malformed.as_str()));
+                    return StratumResponse::error(req.id, 20, &e.to_string());
+                }
             };
 
-            if extranonce2_bytes.len() != extranonce1.nonce2_size().as_bytes() as usize {
-                // Record share rejected
-                global_monitoring().record_share_submitted(false);
-                return StratumResponse::error(req.id, 20, "Invalid extranonce2 size");
+            // The nonce has to be the session's extranonce1 composed with the
+            // submitted extranonce2, so miners stay within their nonce space
+            match compose_nonce(*extranonce1, nonce2) {
+                Ok(nonce) if nonce == submitted_nonce => {}
+                composed => {
+                    record_share(state, &session, false);
+                    global_monitoring().record_rejection(RejectReason::Malformed);
+                    emit_share_event(username, Some(RejectReason::Malformed.as_str()));
+                    let message = match composed {
+                        Ok(_) => "Nonce doesn't match extranonce1 and extranonce2",
+                        Err(_) => "Failed to compose nonce",
+                    };
+                    return StratumResponse::error(req.id, 20, message);
+                }
             }
 
-            // Create Nonce2
-            let nonce2_size = extranonce1.nonce2_size();
-            let nonce2 = match Nonce2::from_bytes(nonce2_size, &extranonce2_bytes) {
-                Ok(n) => n,
-                Err(_) => return StratumResponse::error(req.id, 20, "Invalid extranonce2 format"),
+            // Rolled version bits are only accepted within the negotiated mask
+            let work = match version_bits_hex {
+                None => job.work.clone(),
+                Some(bits_hex) => {
+                    let rolled = match (
+                        session.version_rolling_mask,
+                        u32::from_str_radix(bits_hex, 16),
+                    ) {
+                        (None, _) => Err("Version rolling not negotiated"),
+                        (Some(_), Err(_)) => Err("Invalid version bits hex"),
+                        (Some(mask), Ok(bits)) if bits & !mask != 0 => {
+                            Err("Version bits outside the negotiated mask")
+                        }
+                        (Some(mask), Ok(bits)) => Ok(job.work.with_rolled_version(mask, bits)),
+                    };
+                    match rolled {
+                        Ok(work) => work,
+                        Err(msg) => {
+                            record_share(state, &session, false);
+                            global_monitoring().record_rejection(RejectReason::Malformed);
+                            emit_share_event(username, Some(RejectReason::Malformed.as_str()));
+                            return StratumResponse::error(req.id, 20, msg);
+                        }
+                    }
+                }
             };
 
-            // Compose the full nonce
-            let _full_nonce = match compose_nonce(*extranonce1, nonce2) {
-                Ok(n) => n,
-                Err(_) => return StratumResponse::error(req.id, 20, "Failed to compose nonce"),
-            };
+            // Apply the submitted nonce to the job's (fixed size) header
+            let modified_work = work.with_nonce(submitted_nonce);
 
-            // Parse the submitted nonce
-            let submitted_nonce = match hex::decode(nonce_hex) {
-                Ok(b) if b.len() == 8 => {
-                    let mut arr = [0u8; 8];
-                    arr.copy_from_slice(&b);
-                    u64::from_le_bytes(arr)
-                }
-                _ => return StratumResponse::error(req.id, 20, "Invalid nonce hex"),
+            // Compute the hash for the modified work
+            let hash = modified_work.hash();
+
+            // Get session target (or job target if not set); in solo mode
+            // only blocks count, whatever the session was told
+            let session_target = if state.solo {
+                &job.target
+            } else {
+                session.session_target.as_ref().unwrap_or(&job.target)
             };
 
-            // Create a modified work with the composed nonce
-            let mut work_bytes = job.work.as_bytes().to_vec();
-
-            // The nonce should be at the correct offset in the work
-            if work_bytes.len() >= crate::core::constants::WORK_SIZE {
-                // Update the nonce in the work
-                work_bytes[crate::core::constants::NONCE_OFFSET..]
-                    .copy_from_slice(&submitted_nonce.to_le_bytes());
-
-                // Create new work from the modified bytes
-                let mut work_array = [0u8; 286];
-                work_array.copy_from_slice(&work_bytes[..286]);
-                let modified_work = Work::from_bytes(work_array);
-
-                // Compute the hash for the modified work
-                let hash = modified_work.hash();
-
-                // Get session target (or job target if not set)
-                let session_target = session.session_target.as_ref().unwrap_or(&job.target);
-                
-                // Check if share meets session difficulty
-                if !session_target.meets_target(&hash.into()) {
-                    // Share doesn't meet session difficulty
-                    global_monitoring().record_share_submitted(false);
-                    return StratumResponse::error_with_code(req.id, StratumErrorCode::LowDifficultyShare);
-                }
+            // Check if share meets session difficulty
+            if !session_target.meets_target(&hash.into()) {
+                // Share doesn't meet session difficulty
+                record_share(state, &session, false);
+                global_monitoring().record_rejection(RejectReason::LowDifficulty);
+                emit_share_event(username, Some(RejectReason::LowDifficulty.as_str()));
+                return StratumResponse::error_with_code(
+                    req.id,
+                    StratumErrorCode::LowDifficultyShare,
+                );
+            }
+            let share_difficulty = Difficulty::from(*session_target).0;
+
+            // Check if share meets job target (potential block). A stale job only
+            // yields a block if it still builds on the current parent.
+            let meets_job_target = job.target.meets_target(&hash.into());
+            let is_block =
+                meets_job_target && (stale.is_none() || stale == Some(StaleJob::TimeOnly));
+            if meets_job_target && !is_block {
+                debug!("Dropping block solution for outdated job {}", job.id);
+                global_monitoring().record_rejection(RejectReason::Stale);
+            }
 
-                // Check if share meets job target (potential block)
-                let is_block = job.target.meets_target(&hash.into());
+            // Submit the result if we have a channel and it's a potential block
+            if is_block {
+                if let Some(ref tx) = *state.result_tx.read().await {
+                    let result = MiningResult {
+                        work: modified_work,
+                        nonce: submitted_nonce,
+                        hash,
+                    };
+
+                    if tx.send(result).await.is_err() {
+                        // Record share rejected in monitoring
+                        record_share(state, &session, false);
+                        emit_share_event(username, Some("submit-failed"));
+                        return StratumResponse::error(req.id, 20, "Failed to submit share");
+                    }
+                }
+            }
 
-                // Submit the result if we have a channel and it's a potential block
-                if is_block {
-                    if let Some(ref tx) = *state.result_tx.read().await {
-                        let result = MiningResult {
-                            work: modified_work,
-                            nonce: Nonce::new(submitted_nonce),
-                            hash,
-                        };
+            // Share is valid
+            session.shares_valid += 1;
+            if stale.is_some() {
+                session.shares_stale += 1;
+                debug!("Accepted stale share for superseded job {}", job.id);
+            }
 
-                        if tx.send(result).await.is_err() {
-                            // Record share rejected in monitoring
-                            global_monitoring().record_share_submitted(false);
-                            return StratumResponse::error(req.id, 20, "Failed to submit share");
-                        }
-                    }
+            // Every accepted share feeds the hash rate estimate of the session
+            let difficulty = session.difficulty;
+            session.update_hash_rate(difficulty);
+
+            // Update difficulty for dynamic adjustment
+            if matches!(state.difficulty_config, StratumDifficulty::Period(_)) {
+                // Need to clone values to avoid holding the write lock
+                let difficulty_config = state.difficulty_config.clone();
+
+                // Update session target if needed
+                if let Err(e) = update_session_target(
+                    &mut session,
+                    &job,
+                    writer,
+                    &difficulty_config,
+                    &state.min_session_target,
+                    state.hashrate_shift_factor,
+                )
+                .await
+                {
+                    warn!("Failed to update session target: {}", e);
                 }
+            }
 
-                // Share is valid
-                session.shares_valid += 1;
-
-                // Update hash rate and difficulty for dynamic adjustment
-                if matches!(state.difficulty_config, StratumDifficulty::Period(_)) {
-                    // Need to clone values to avoid holding the write lock
-                    let difficulty_config = state.difficulty_config.clone();
-                    
-                    // Update session target if needed
-                    if let Err(e) = update_session_target(
-                        &mut session,
-                        &job,
-                        writer,
-                        &difficulty_config,
-                    ).await {
-                        warn!("Failed to update session target: {}", e);
-                    }
+            // Record share accepted in monitoring
+            record_share(state, &session, true);
+            if let Some(name) = &session.worker_name {
+                state
+                    .workers
+                    .record_hash_rate(name, session.id, session.estimated_hashrate);
+            }
+            emit_share_event(username, None);
+            if let Some(audit_log) = &state.audit_log {
+                let record = ShareAuditRecord::now(
+                    session.worker_name.as_deref().unwrap_or(username),
+                    &session.id.to_string(),
+                    &job.id,
+                    nonce_hex,
+                    share_difficulty,
+                    is_block,
+                );
+                if let Err(e) = audit_log.write(&record) {
+                    warn!(
+                        "Failed to write share to audit file {}: {}",
+                        audit_log.path().display(),
+                        e
+                    );
                 }
+            }
 
-                // Record share accepted in monitoring
-                global_monitoring().record_share_submitted(true);
+            StratumResponse::success(req.id, Value::Bool(true))
+        }
 
-                StratumResponse::success(req.id, Value::Bool(true))
-            } else {
-                StratumResponse::error(req.id, 20, "Invalid work size")
-            }
+        // Vendor extensions; answered so the miner stays connected
+        StratumMethod::Unknown(method) => {
+            debug!(
+                "Unknown stratum method {} from session {}",
+                method,
+                session.read().await.id
+            );
+            StratumResponse::error_with_code(req.id, StratumErrorCode::UnknownMethod)
         }
 
-        _ => StratumResponse::error_with_code_and_message(req.id, StratumErrorCode::Other, "Method not supported"),
+        _ => StratumResponse::error_with_code_and_message(
+            req.id,
+            StratumErrorCode::Other,
+            "Method not supported",
+        ),
     }
 }
 
-/// Generate extranonce1 for a new session
-fn generate_extranonce1() -> Nonce1 {
-    let mut bytes = [0u8; 4];
-    getrandom::fill(&mut bytes).unwrap();
-    // Convert bytes to u64 (big-endian)
-    let value = u32::from_be_bytes(bytes) as u64;
-    Nonce1::new(NonceSize::new(4).unwrap(), value).unwrap()
+/// Name the session after the authorized worker, moving it between workers
+/// if it authorizes again under a different name
+fn authorize_worker(state: &ServerState, session: &mut StratumSession, username: &str) {
+    if let Some(previous) = session.worker_name.replace(username.to_string())
+        && previous != username
+    {
+        state.workers.remove_session(&previous, session.id);
+    }
+    state.workers.add_session(username, session.id);
+}
+
+/// Count a share in the global monitoring and for the session's worker name
+fn record_share(state: &ServerState, session: &StratumSession, accepted: bool) {
+    global_monitoring().record_share_submitted(accepted);
+    if let Some(name) = &session.worker_name {
+        state.workers.record_share(name, accepted);
+    }
+}
+
+/// Emit a share event; `reject_reason` is `None` for accepted shares
+fn emit_share_event(worker: &str, reject_reason: Option<&str>) {
+    emit_event(MiningEvent::ShareSubmitted {
+        worker: worker.to_string(),
+        accepted: reject_reason.is_none(),
+        reason: reject_reason.map(str::to_string),
+    });
+}
+
+/// How a superseded job relates to the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StaleJob {
+    /// Only the time changed since, the job still builds on the current parent
+    TimeOnly,
+    /// The parent or payload changed, solutions can't become blocks anymore
+    Outdated,
+}
+
+/// Look up a job by id in the current job and recent job history
+async fn find_job(state: &ServerState, job_id: &str) -> Option<(MiningJob, Option<StaleJob>)> {
+    let current_job = state.current_job.read().await;
+    let current = current_job.as_ref()?;
+    if current.id == job_id {
+        return Some((current.clone(), None));
+    }
+
+    let history = state.job_history.read().await;
+    let job = history.iter().find(|j| j.id == job_id)?;
+    let stale =
+        if current.target == job.target && job.work.changed_fields(&current.work).is_time_only() {
+            StaleJob::TimeOnly
+        } else {
+            StaleJob::Outdated
+        };
+    Some((job.clone(), Some(stale)))
+}
+
+/// Fills a buffer with random bytes, like [`getrandom::fill`]
+type FillRandom = fn(&mut [u8]) -> std::result::Result<(), getrandom::Error>;
+
+/// Generate a random extranonce1 of the given size for a new session
+fn generate_extranonce1(size: NonceSize, fill_random: FillRandom) -> Result<Nonce1> {
+    let mut bytes = [0u8; 8];
+    fill_random(&mut bytes)
+        .map_err(|e| Error::stratum(format!("Failed to generate a random extranonce1: {}", e)))?;
+    // Convert bytes to u64 (big-endian) and keep the low `size` bytes
+    let value = u64::from_be_bytes(bytes) & size.max_value();
+    Nonce1::new(size, value)
 }
 
 /// Send mining.set_target notification to client
 async fn send_set_target(writer: &mut OwnedWriteHalf, target: &Target) -> Result<()> {
     let params = vec![Value::String(target.to_hex())];
     let notify = StratumNotification::new("mining.set_target", params);
-    
-    let json = serde_json::to_string(&notify)? + "\n";
-    writer.write_all(json.as_bytes()).await?;
+    write_message(writer, &notify).await
+}
+
+/// Send `mining.set_extranonce` with the extranonce1 and the extranonce2 size
+async fn send_set_extranonce(writer: &mut OwnedWriteHalf, extranonce1: &Nonce1) -> Result<()> {
+    let params = vec![
+        Value::String(extranonce1.to_hex()),
+        Value::Number(extranonce1.nonce2_size().as_bytes().into()),
+    ];
+    let notify = StratumNotification::new("mining.set_extranonce", params);
+    write_message(writer, &notify).await
+}
+
+/// Resolves once the server shuts down
+async fn shutting_down(shutdown: &mut watch::Receiver<bool>) {
+    let _ = shutdown.wait_for(|stopping| *stopping).await;
+}
+
+/// Write `message` to the miner as one JSON line
+///
+/// The line goes out in a single buffer, and the writes of a connection are
+/// never cancelled midway, so the miner receives whole lines only.
+async fn write_message(writer: &mut OwnedWriteHalf, message: &impl serde::Serialize) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
     writer.flush().await?;
-    
     Ok(())
 }
 
@@ -619,6 +1080,7 @@ fn get_new_session_target(
     current_hash_rate: HashRate,
     current_target: &Target,
     job_target: &Target,
+    min_session_target: &Target,
 ) -> Option<Target> {
     match difficulty_config {
         StratumDifficulty::Block => None, // Use job target
@@ -632,28 +1094,18 @@ fn get_new_session_target(
         }
         StratumDifficulty::Period(target_period) => {
             // Calculate new target based on hash rate
-            let current_difficulty = Difficulty::from(*current_target);
-            let new_difficulty = adjust_difficulty(
-                PERIOD_TOLERANCE,
+            let new_difficulty = period_difficulty(
+                *target_period,
                 current_hash_rate,
-                Period(*target_period),
-                current_difficulty,
+                Difficulty::from(*current_target),
+            )?;
+
+            let new_target = clamp_session_target(
+                new_difficulty.to_target().leveled(),
+                min_session_target,
+                job_target,
             );
-            
-            let candidate = new_difficulty.to_target().leveled();
-            
-            // Ensure target is between job target and max session target
-            let max_session_target = Target::mk_target_level(MAX_SESSION_TARGET_LEVEL);
-            
-            // The final target must be harder than max_session_target but easier than job_target
-            let new_target = if candidate.meets_target(job_target.as_bytes()) {
-                *job_target
-            } else if max_session_target.meets_target(candidate.as_bytes()) {
-                max_session_target
-            } else {
-                candidate
-            };
-            
+
             if &new_target != current_target {
                 Some(new_target)
             } else {
@@ -663,60 +1115,95 @@ fn get_new_session_target(
     }
 }
 
+/// `target` bounded by the session minimum and the block target
+///
+/// Sessions aren't handed targets easier than `min_session_target`, and
+/// shares are never harder than a block.
+fn clamp_session_target(
+    target: Target,
+    min_session_target: &Target,
+    job_target: &Target,
+) -> Target {
+    let target = if target.is_easier_than(min_session_target) {
+        *min_session_target
+    } else {
+        target
+    };
+    if target.is_harder_than(job_target) {
+        *job_target
+    } else {
+        target
+    }
+}
+
+/// Difficulty moving the share interval towards `target_period` seconds, or
+/// `None` while the interval is within [`PERIOD_TOLERANCE`] of it
+fn period_difficulty(
+    target_period: f64,
+    hash_rate: HashRate,
+    current_difficulty: Difficulty,
+) -> Option<Difficulty> {
+    let new_difficulty = adjust_difficulty(
+        PERIOD_TOLERANCE,
+        hash_rate,
+        Period(target_period),
+        current_difficulty,
+    );
+    (new_difficulty != current_difficulty).then_some(new_difficulty)
+}
+
 /// Update session target after valid share
 async fn update_session_target(
     session: &mut StratumSession,
     job: &MiningJob,
     writer: &mut OwnedWriteHalf,
     difficulty_config: &StratumDifficulty,
+    min_session_target: &Target,
+    hashrate_shift_factor: f64,
 ) -> Result<()> {
-    // Update hash rate estimate
-    session.update_hash_rate(session.difficulty);
-    
+    if hashrate_shift_factor > 0.0 && session.reset_on_hash_rate_shift(hashrate_shift_factor) {
+        info!(
+            "Hash rate of stratum session {} shifted to {:.2} H/s, recalculating its difficulty",
+            session.id, session.estimated_hashrate
+        );
+    }
+
     // Check if we need to adjust difficulty
     if let Some(new_target) = get_new_session_target(
         difficulty_config,
         HashRate(session.estimated_hashrate),
         session.session_target.as_ref().unwrap_or(&job.target),
         &job.target,
+        min_session_target,
     ) {
         // Update session target
         session.session_target = Some(new_target);
         session.difficulty = Difficulty::from(new_target).0;
-        
+
         // Send mining.set_target notification
         send_set_target(writer, &new_target).await?;
-        
+
         debug!(
             "Updated session {} difficulty to {} (hashrate: {})",
             session.id, session.difficulty, session.estimated_hashrate
         );
     }
-    
+
     Ok(())
 }
 
-/// Create job parameters for mining.notify
+/// Whether new work invalidates the previous job, i.e. anything other than
+/// the time or nonce changed
+fn needs_clean_jobs(previous: Option<&MiningJob>, work: &Work, target: &Target) -> bool {
+    match previous {
+        Some(prev) => prev.target != *target || !prev.work.changed_fields(work).is_time_only(),
+        None => true,
+    }
+}
+
+/// Create job parameters for mining.notify, see [`JobParams`]
 fn create_job_params(job: &MiningJob) -> Vec<Value> {
-    // For Kadena, we need to adapt the work format
-    // This is a simplified version - real implementation would need proper conversion
-    vec![
-        Value::String(job.id.clone()),
-        Value::String(hex::encode(&job.work.as_bytes()[0..32])), // Previous hash
-        Value::String(hex::encode(&job.work.as_bytes()[32..64])), // Coinbase 1
-        Value::String(hex::encode(&job.work.as_bytes()[64..96])), // Coinbase 2
-        Value::Array(vec![]),                                    // Merkle branches (empty for now)
-        Value::String("00000020".to_string()),                   // Version
-        Value::String(hex::encode(job.target.0)),                // nBits
-        Value::String(format!(
-            "{:x}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        )), // nTime
-        Value::Bool(true),                                       // Clean jobs
-    ]
+    JobParams::new(&job.id, &job.work, job.clean_jobs).to_params()
 }
 
 #[async_trait]
@@ -740,8 +1227,17 @@ impl Worker for StratumServer {
                 host: self.config.host.clone(),
                 max_connections: self.config.max_connections,
                 difficulty: self.config.difficulty.clone(),
+                mode: self.config.mode,
+                min_difficulty_level: self.config.min_difficulty_level,
+                hashrate_shift_factor: self.config.hashrate_shift_factor,
                 rate_ms: self.config.rate_ms,
+                always_clean_jobs: self.config.always_clean_jobs,
+                job_history_size: self.config.job_history_size,
+                session_idle_timeout_secs: self.config.session_idle_timeout_secs,
+                extranonce1_size: self.config.extranonce1_size,
+                fixed_extranonce1: self.config.fixed_extranonce1,
                 authorize_callback: None,
+                audit_log: None,
             },
             state: Arc::clone(&self.state),
             job_tx: self.job_tx.clone(),
@@ -752,7 +1248,7 @@ impl Worker for StratumServer {
         server.update_work(work, target).await;
 
         // Start server if not already running
-        tokio::spawn(async move {
+        spawn_monitored("stratum server", async move {
             if let Err(e) = server.start_server().await {
                 error!("Stratum server error: {}", e);
             }
@@ -762,7 +1258,7 @@ impl Worker for StratumServer {
     }
 
     async fn stop(&self) -> Result<()> {
-        self.state.shutdown.store(true, Ordering::Relaxed);
+        let _ = self.state.shutdown.send_replace(true);
         Ok(())
     }
 
@@ -770,7 +1266,1387 @@ impl Worker for StratumServer {
         "Stratum"
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities {
+            // Aggregated from the share rate of connected miners
+            reports_hashrate: true,
+            ..WorkerCapabilities::server()
+        }
+    }
+
     async fn hashrate(&self) -> u64 {
-        self.state.total_hashrate.load(Ordering::Relaxed)
+        self.state.workers.total_hash_rate() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR, DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+    };
+    use crate::core::Nonce;
+    use crate::core::constants::NONCE_OFFSET;
+    use crate::workers::stratum::Nonce2;
+
+    fn test_server_config() -> StratumServerConfig {
+        StratumServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            max_connections: 10,
+            difficulty: StratumDifficulty::Block,
+            mode: None,
+            min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+            hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+            rate_ms: 1000,
+            always_clean_jobs: false,
+            job_history_size: 4,
+            session_idle_timeout_secs: 0,
+            extranonce1_size: 4,
+            fixed_extranonce1: None,
+            authorize_callback: None,
+            audit_log: None,
+        }
+    }
+
+    fn test_server(always_clean_jobs: bool) -> StratumServer {
+        StratumServer::new(StratumServerConfig {
+            always_clean_jobs,
+            ..test_server_config()
+        })
+    }
+
+    fn clean_jobs_param(job: &MiningJob) -> Value {
+        create_job_params(job)[2].clone()
+    }
+
+    #[test]
+    fn test_period_difficulty_uses_configured_period() {
+        // 1000 H/s at difficulty 10000: one share every 10s
+        let hash_rate = HashRate(1000.0);
+        let difficulty = Difficulty(10000.0);
+
+        // A 10s period is already met
+        assert_eq!(period_difficulty(10.0, hash_rate, difficulty), None);
+
+        // A 5s period halves the difficulty, i.e. 5s shares at this hash rate
+        let adjusted = period_difficulty(5.0, hash_rate, difficulty).unwrap();
+        assert!((adjusted.0 / hash_rate.0 - 5.0).abs() < 1e-9);
+
+        // Within the 25% dead band around 5s nothing changes
+        assert_eq!(period_difficulty(5.0, hash_rate, Difficulty(5500.0)), None);
+        assert!(period_difficulty(5.0, hash_rate, Difficulty(6500.0)).is_some());
+    }
+
+    #[test]
+    fn test_session_target_only_changes_off_period() {
+        let current = Target::mk_target_level(20);
+        let job_target = Target::mk_target_level(60);
+        let current_difficulty = Difficulty::from(current).0;
+        // Hash rate producing one share every 10s at the current target
+        let hash_rate = HashRate(current_difficulty / 10.0);
+        let min_session_target = Target::mk_target_level(DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL);
+        let new_target = |period| {
+            get_new_session_target(
+                &StratumDifficulty::Period(period),
+                hash_rate,
+                &current,
+                &job_target,
+                &min_session_target,
+            )
+        };
+
+        assert_eq!(new_target(10.0), None);
+        assert!(new_target(5.0).is_some());
+    }
+
+    #[test]
+    fn test_session_target_is_clamped() {
+        let max_session_target = Target::mk_target_level(DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL);
+        let current = Target::mk_target_level(45);
+        let period = StratumDifficulty::Period(10.0);
+        let new_target = |job_target| {
+            get_new_session_target(
+                &period,
+                HashRate(1e-3),
+                &current,
+                job_target,
+                &max_session_target,
+            )
+        };
+
+        // A slow miner gets no easier target than the session maximum
+        let job_target = Target::mk_target_level(50);
+        assert_eq!(new_target(&job_target).unwrap(), max_session_target);
+
+        // On an easy network the session maximum would be harder than a block
+        let job_target = Target::mk_target_level(2);
+        assert!(max_session_target.is_harder_than(&job_target));
+        assert_eq!(new_target(&job_target).unwrap(), job_target);
+    }
+
+    #[test]
+    fn test_session_target_respects_configured_floor() {
+        let period = StratumDifficulty::Period(10.0);
+        let job_target = Target::mk_target_level(60);
+
+        for level in [8, 30, 50] {
+            let server = StratumServer::new(StratumServerConfig {
+                difficulty: period.clone(),
+                min_difficulty_level: level,
+                ..test_server_config()
+            });
+            let floor = server.state.min_session_target;
+            assert_eq!(floor, Target::mk_target_level(level));
+
+            // However slow the miner, repeated adjustments stop at the floor
+            let mut current = Target::mk_target_level(55);
+            while let Some(target) =
+                get_new_session_target(&period, HashRate(1e-3), &current, &job_target, &floor)
+            {
+                assert!(!target.is_easier_than(&floor));
+                current = target;
+            }
+            assert_eq!(current, floor);
+
+            // Nor does a session start below it
+            let initial = clamp_session_target(
+                Target::mk_target_level(INITIAL_SESSION_TARGET_LEVEL),
+                &floor,
+                &job_target,
+            );
+            assert!(!initial.is_easier_than(&floor));
+        }
+    }
+
+    #[test]
+    fn test_hash_rate_shift_resets_estimate() {
+        let current = Target::mk_target_level(40);
+        let job_target = Target::mk_target_level(60);
+        let min_session_target = Target::mk_target_level(DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL);
+        let period = StratumDifficulty::Period(10.0);
+        let difficulty = Difficulty::from(current).0;
+        let new_target = |hash_rate| {
+            get_new_session_target(
+                &period,
+                HashRate(hash_rate),
+                &current,
+                &job_target,
+                &min_session_target,
+            )
+        };
+
+        // A share every 10s matches the period, then the rig gets 10x faster
+        let extranonce1 = Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap();
+        let mut shifting = StratumSession::new(extranonce1, difficulty);
+        let mut lagging = StratumSession::new(extranonce1, difficulty);
+        let mut now = std::time::Instant::now();
+        let mut share = |interval: u64| {
+            now += Duration::from_secs(interval);
+            shifting.update_hash_rate_at(difficulty, now);
+            lagging.update_hash_rate_at(difficulty, now);
+            shifting.reset_on_hash_rate_shift(DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR)
+        };
+        let steady: Vec<bool> = (0..10).map(|_| share(10)).collect();
+        let jumped: Vec<bool> = (0..HASH_RATE_SHIFT_WINDOW).map(|_| share(1)).collect();
+        assert!(steady.iter().all(|reset| !reset));
+        assert!(jumped.iter().any(|reset| *reset));
+
+        assert!((shifting.estimated_hashrate - difficulty).abs() < difficulty * 1e-9);
+        // Without the reset the estimate still trails the new hash rate
+        assert!(lagging.estimated_hashrate < 0.8 * difficulty);
+
+        let adjusted = new_target(shifting.estimated_hashrate).expect("retargeted");
+        assert!(adjusted.is_harder_than(&current));
+        assert_eq!(Some(adjusted), new_target(difficulty));
+    }
+
+    #[tokio::test]
+    async fn test_time_only_update_does_not_clean_jobs() {
+        let server = test_server(false);
+        let target = Target::from_bytes([0xFF; 32]);
+        let work = Work::from_bytes([0x11; 286]);
+        let mut rx = server.job_tx.subscribe();
+
+        server.update_work(work.clone(), target).await;
+        assert_eq!(
+            clean_jobs_param(&rx.recv().await.unwrap()),
+            Value::Bool(true)
+        );
+
+        let mut later = work;
+        later.increment_time_micros(1_000_000);
+        server.update_work(later, target).await;
+        assert_eq!(
+            clean_jobs_param(&rx.recv().await.unwrap()),
+            Value::Bool(false)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_job_time_follows_monotonic_clock() {
+        let server = test_server(false);
+        let work = Work::from_bytes([0x11; 286]);
+        server
+            .update_work(work.clone(), Target::from_bytes([0xFF; 32]))
+            .await;
+        let mut rx = server.job_tx.subscribe();
+        let emitter = server.start_job_emitter();
+
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        let mut job = rx.recv().await.unwrap();
+        while job.work.get_timestamp() == work.get_timestamp() {
+            job = rx.recv().await.unwrap();
+        }
+        assert_eq!(job.work.get_timestamp(), work.get_timestamp() + 30_000_000);
+        emitter.abort();
+    }
+
+    #[tokio::test]
+    async fn test_notify_carries_work_header() {
+        let server = test_server(false);
+        let work = Work::from_bytes([0x11; 286]);
+        let mut rx = server.job_tx.subscribe();
+
+        server
+            .update_work(work.clone(), Target::from_bytes([0xFF; 32]))
+            .await;
+        let job = rx.recv().await.unwrap();
+        let params = JobParams::from_params(&create_job_params(&job)).unwrap();
+        assert_eq!(params.job_id, job.id);
+        assert_eq!(params.work(), Some(job.work));
+        assert!(params.clean_jobs);
+    }
+
+    #[tokio::test]
+    async fn test_parent_change_cleans_jobs() {
+        let server = test_server(false);
+        let target = Target::from_bytes([0xFF; 32]);
+        let work = Work::from_bytes([0x11; 286]);
+        let mut rx = server.job_tx.subscribe();
+
+        server.update_work(work.clone(), target).await;
+        rx.recv().await.unwrap();
+
+        let mut new_parent = work;
+        new_parent.as_bytes_mut()[20] ^= 0xFF;
+        server.update_work(new_parent, target).await;
+        assert_eq!(
+            clean_jobs_param(&rx.recv().await.unwrap()),
+            Value::Bool(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_always_clean_jobs_override() {
+        let server = test_server(true);
+        let target = Target::from_bytes([0xFF; 32]);
+        let work = Work::from_bytes([0x11; 286]);
+        let mut rx = server.job_tx.subscribe();
+
+        server.update_work(work.clone(), target).await;
+        rx.recv().await.unwrap();
+
+        let mut later = work;
+        later.increment_time_micros(1_000_000);
+        server.update_work(later, target).await;
+        assert_eq!(
+            clean_jobs_param(&rx.recv().await.unwrap()),
+            Value::Bool(true)
+        );
+    }
+
+    /// Connected socket pair so request handlers get a real write half
+    async fn socket_pair() -> (OwnedWriteHalf, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server_side, _) = listener.accept().await.unwrap();
+        let (_, writer) = server_side.into_split();
+        (writer, client)
+    }
+
+    /// Nonce of the share with extranonce2 42 within the nonce space of `extranonce1`
+    fn share_nonce(extranonce1: &Nonce1) -> (Nonce2, Nonce) {
+        let nonce2 = Nonce2::new(extranonce1.nonce2_size(), 42).unwrap();
+        (nonce2, compose_nonce(*extranonce1, nonce2).unwrap())
+    }
+
+    fn submit_request(job_id: &str, extranonce1: &Nonce1) -> StratumRequest {
+        let (nonce2, nonce) = share_nonce(extranonce1);
+        StratumRequest {
+            id: Value::from(1),
+            method: "mining.submit".to_string(),
+            params: vec![
+                Value::String("miner.worker".to_string()),
+                Value::String(job_id.to_string()),
+                Value::String(nonce2.to_hex()),
+                Value::String("00000000".to_string()),
+                Value::String(nonce.to_hex_le()),
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_against_previous_job_is_stale_but_valid() {
+        let server = test_server(false);
+        let target = Target::from_bytes([0xFF; 32]);
+        let work = Work::from_bytes([0x11; 286]);
+
+        server.update_work(work.clone(), target).await;
+        let first_job_id = server
+            .state
+            .current_job
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .id
+            .clone();
+
+        // A new notify supersedes the first job
+        let mut new_parent = work;
+        new_parent.as_bytes_mut()[20] ^= 0xFF;
+        server.update_work(new_parent, target).await;
+
+        let extranonce1 = Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap();
+        let session = Arc::new(RwLock::new(StratumSession::new(extranonce1, 1.0)));
+        let (mut writer, _client) = socket_pair().await;
+        let (mut authorized, mut subscribed) = (true, true);
+
+        let response = handle_request(
+            submit_request(&first_job_id, &extranonce1),
+            &mut authorized,
+            &mut subscribed,
+            &session,
+            &extranonce1,
+            &server.state,
+            &mut writer,
+        )
+        .await;
+
+        assert_eq!(response.result, Some(Value::Bool(true)));
+        let session = session.read().await;
+        assert_eq!(session.shares_valid, 1);
+        assert_eq!(session.shares_stale, 1);
+    }
+
+    /// Submit `request` as an authorized session with the given rolling mask,
+    /// returning the response and the block solution it produced
+    /// Extranonce1 of the session submitting in [`submit_with_mask`]
+    fn masked_extranonce1() -> Nonce1 {
+        Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap()
+    }
+
+    async fn submit_with_mask(
+        request: StratumRequest,
+        version_rolling_mask: Option<u32>,
+    ) -> (StratumResponse, Option<MiningResult>) {
+        let server = test_server(false);
+        let (tx, mut rx) = mpsc::channel(1);
+        *server.state.result_tx.write().await = Some(tx);
+        let mut work = Work::from_bytes([0x11; 286]);
+        work.as_bytes_mut()[266..270].copy_from_slice(&0x2000_0000u32.to_le_bytes());
+        server
+            .update_work(work, Target::from_bytes([0xFF; 32]))
+            .await;
+
+        let extranonce1 = masked_extranonce1();
+        let mut session = StratumSession::new(extranonce1, 1.0);
+        session.version_rolling_mask = version_rolling_mask;
+        let session = Arc::new(RwLock::new(session));
+        let (mut writer, _client) = socket_pair().await;
+        let (mut authorized, mut subscribed) = (true, true);
+
+        let response = handle_request(
+            request,
+            &mut authorized,
+            &mut subscribed,
+            &session,
+            &extranonce1,
+            &server.state,
+            &mut writer,
+        )
+        .await;
+        (response, rx.try_recv().ok())
+    }
+
+    fn rolled_submit_request(version_bits: &str) -> StratumRequest {
+        let mut request = submit_request("0", &masked_extranonce1());
+        request.params.push(Value::String(version_bits.to_string()));
+        request
+    }
+
+    #[tokio::test]
+    async fn test_submit_without_version_bits() {
+        let (response, result) =
+            submit_with_mask(submit_request("0", &masked_extranonce1()), None).await;
+        assert_eq!(response.result, Some(Value::Bool(true)));
+        let result = result.unwrap();
+        assert_eq!(result.work.version(), 0x2000_0000);
+        assert_eq!(result.hash, result.work.hash());
+    }
+
+    #[tokio::test]
+    async fn test_submitted_nonce_hex_is_little_endian() {
+        let nonce = Nonce::new(0x0123_4567_0000_0001);
+        let mut request = submit_request("0", &masked_extranonce1());
+        request.params[2] = Value::String("01234567".to_string());
+        request.params[4] = Value::String(nonce.to_hex_le());
+        let (response, result) = submit_with_mask(request, None).await;
+        assert_eq!(response.result, Some(Value::Bool(true)));
+        let result = result.unwrap();
+        assert_eq!(result.nonce, nonce);
+        assert_eq!(result.work.nonce(), nonce);
+        assert_eq!(
+            hex::encode(&result.work.as_bytes()[NONCE_OFFSET..]),
+            nonce.to_hex_le()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_with_rolled_version_bits() {
+        let mask = 0x1fff_e000;
+        let (response, result) =
+            submit_with_mask(rolled_submit_request("00ffe000"), Some(mask)).await;
+        assert_eq!(response.result, Some(Value::Bool(true)));
+        let result = result.unwrap();
+        assert_eq!(result.work.version(), 0x20ff_e000);
+        assert_eq!(result.hash, result.work.hash());
+
+        // Bits outside the mask are refused
+        let (response, result) =
+            submit_with_mask(rolled_submit_request("e0000000"), Some(mask)).await;
+        assert!(response.error.is_some());
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rolled_submit_requires_negotiation() {
+        let (response, result) = submit_with_mask(rolled_submit_request("00ffe000"), None).await;
+        assert!(response.result.is_none());
+        assert!(
+            response
+                .error
+                .unwrap()
+                .to_string()
+                .contains("not negotiated")
+        );
+        assert!(result.is_none());
+    }
+
+    /// Submit a share that meets an easy session target but not the block
+    /// target to a server in `mode`, returning the response and whether a
+    /// block was produced
+    async fn submit_sub_block_share(mode: StratumMode) -> (StratumResponse, bool) {
+        let server = StratumServer::new(StratumServerConfig {
+            mode: Some(mode),
+            ..test_server_config()
+        });
+        let (tx, mut rx) = mpsc::channel(1);
+        *server.state.result_tx.write().await = Some(tx);
+        // No hash meets an all-zero block target
+        server
+            .update_work(Work::from_bytes([0x11; 286]), Target::from_bytes([0; 32]))
+            .await;
+
+        let extranonce1 = Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap();
+        let mut session = StratumSession::new(extranonce1, 1.0);
+        session.session_target = Some(Target::from_bytes([0xFF; 32]));
+        let session = Arc::new(RwLock::new(session));
+        let (mut writer, _client) = socket_pair().await;
+        let (mut authorized, mut subscribed) = (true, true);
+
+        let response = handle_request(
+            submit_request("0", &extranonce1),
+            &mut authorized,
+            &mut subscribed,
+            &session,
+            &extranonce1,
+            &server.state,
+            &mut writer,
+        )
+        .await;
+        (response, rx.try_recv().is_ok())
+    }
+
+    #[tokio::test]
+    async fn test_solo_mode_rejects_sub_block_shares() {
+        let (response, block) = submit_sub_block_share(StratumMode::Solo).await;
+        assert!(response.result.is_none());
+        assert!(response.error.is_some());
+        assert!(!block);
+    }
+
+    #[tokio::test]
+    async fn test_pool_mode_accepts_sub_block_shares() {
+        let (response, block) = submit_sub_block_share(StratumMode::Pool).await;
+        assert_eq!(response.result, Some(Value::Bool(true)));
+        assert!(!block);
+    }
+
+    #[test]
+    fn test_mode_sets_difficulty() {
+        let server = |mode, difficulty| {
+            StratumServer::new(StratumServerConfig {
+                difficulty,
+                mode,
+                ..test_server_config()
+            })
+        };
+        let solo = server(Some(StratumMode::Solo), StratumDifficulty::Block);
+        assert!(solo.state.solo);
+        assert!(matches!(
+            solo.state.difficulty_config,
+            StratumDifficulty::Block
+        ));
+
+        // Pool mode adjusts the difficulty unless one is configured
+        let pool = server(Some(StratumMode::Pool), StratumDifficulty::Block);
+        assert!(!pool.state.solo);
+        assert!(matches!(
+            pool.state.difficulty_config,
+            StratumDifficulty::Period(period) if period == crate::config::DEFAULT_POOL_SHARE_PERIOD
+        ));
+        let pool = server(Some(StratumMode::Pool), StratumDifficulty::Fixed(30));
+        assert!(matches!(
+            pool.state.difficulty_config,
+            StratumDifficulty::Fixed(30)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_accepted_shares_are_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shares.jsonl");
+        let server = StratumServer::new(StratumServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            max_connections: 10,
+            difficulty: StratumDifficulty::Block,
+            mode: None,
+            min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+            hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+            rate_ms: 1000,
+            always_clean_jobs: false,
+            job_history_size: 4,
+            session_idle_timeout_secs: 0,
+            extranonce1_size: 4,
+            fixed_extranonce1: None,
+            authorize_callback: None,
+            audit_log: Some(Arc::new(ShareAuditLog::open(&path, 0).unwrap())),
+        });
+        let target = Target::from_bytes([0xFF; 32]);
+        server
+            .update_work(Work::from_bytes([0x11; 286]), target)
+            .await;
+        let job_id = server
+            .state
+            .current_job
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .id
+            .clone();
+
+        let extranonce1 = Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap();
+        let session = Arc::new(RwLock::new(StratumSession::new(extranonce1, 1.0)));
+        let (mut writer, _client) = socket_pair().await;
+        let (mut authorized, mut subscribed) = (true, true);
+
+        // One accepted share, one rejected for an unknown job
+        for job in [job_id.as_str(), "missing"] {
+            handle_request(
+                submit_request(job, &extranonce1),
+                &mut authorized,
+                &mut subscribed,
+                &session,
+                &extranonce1,
+                &server.state,
+                &mut writer,
+            )
+            .await;
+        }
+
+        let records: Vec<ShareAuditRecord> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.worker_name, "miner.worker");
+        assert_eq!(record.session_id, session.read().await.id.to_string());
+        assert_eq!(record.job_id, job_id);
+        assert_eq!(record.nonce, share_nonce(&extranonce1).1.to_hex_le());
+        assert_eq!(record.share_difficulty, Difficulty::from(target).0);
+        assert!(record.is_block);
+        assert!(record.timestamp_ms > 0);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_sharing_a_worker_name_aggregate() {
+        let server = test_server(false);
+        server
+            .update_work(
+                Work::from_bytes([0x11; 286]),
+                Target::from_bytes([0xFF; 32]),
+            )
+            .await;
+        let job_id = server
+            .state
+            .current_job
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .id
+            .clone();
+        let (mut writer, _client) = socket_pair().await;
+
+        let mut sessions = Vec::new();
+        for value in 1..=2 {
+            let extranonce1 = Nonce1::new(NonceSize::new(4).unwrap(), value).unwrap();
+            let session = Arc::new(RwLock::new(StratumSession::new(extranonce1, 1.0)));
+            let (mut authorized, mut subscribed) = (false, true);
+            let authorize = StratumRequest {
+                id: Value::from(1),
+                method: "mining.authorize".to_string(),
+                params: vec![Value::String("miner.rig1".to_string())],
+            };
+            handle_request(
+                authorize,
+                &mut authorized,
+                &mut subscribed,
+                &session,
+                &extranonce1,
+                &server.state,
+                &mut writer,
+            )
+            .await;
+            assert!(authorized);
+            sessions.push((session, extranonce1, authorized, subscribed));
+        }
+
+        // Two accepted shares from the first session, one unknown job from the second
+        for (index, job) in [(0, job_id.as_str()), (0, job_id.as_str()), (1, "missing")] {
+            let (session, extranonce1, authorized, subscribed) = &mut sessions[index];
+            handle_request(
+                submit_request(job, extranonce1),
+                authorized,
+                subscribed,
+                session,
+                extranonce1,
+                &server.state,
+                &mut writer,
+            )
+            .await;
+        }
+
+        let stats = server.state.workers.stats("miner.rig1").unwrap();
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.shares_submitted, 3);
+        assert_eq!(stats.shares_accepted, 2);
+        assert_eq!(stats.shares_rejected(), 1);
+        assert!((stats.reject_rate() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(server.state.workers.snapshot().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_hashrate_sums_share_rate_of_sessions() {
+        let server = test_server(false);
+        server
+            .update_work(
+                Work::from_bytes([0x11; 286]),
+                Target::from_bytes([0xFF; 32]),
+            )
+            .await;
+        let job_id = server
+            .state
+            .current_job
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .id
+            .clone();
+        assert_eq!(server.hashrate().await, 0);
+
+        let extranonce1 = Nonce1::new(NonceSize::new(4).unwrap(), 1).unwrap();
+        let mut session = StratumSession::new(extranonce1, 1000.0);
+        session.last_share_time = Some(std::time::Instant::now() - Duration::from_secs(10));
+        let session = Arc::new(RwLock::new(session));
+        let (mut writer, _client) = socket_pair().await;
+        let (mut authorized, mut subscribed) = (false, true);
+        let authorize = StratumRequest {
+            id: Value::from(1),
+            method: "mining.authorize".to_string(),
+            params: vec![Value::String("miner.rig1".to_string())],
+        };
+        for request in [authorize, submit_request(&job_id, &extranonce1)] {
+            handle_request(
+                request,
+                &mut authorized,
+                &mut subscribed,
+                &session,
+                &extranonce1,
+                &server.state,
+                &mut writer,
+            )
+            .await;
+        }
+
+        // One share of difficulty 1000 ten seconds after the previous one
+        let hashrate = server.hashrate().await;
+        assert!((90..=100).contains(&hashrate), "hashrate {}", hashrate);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_reports_configured_nonce_split() {
+        let server = StratumServer::new(StratumServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            max_connections: 10,
+            difficulty: StratumDifficulty::Block,
+            mode: None,
+            min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+            hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+            rate_ms: 1000,
+            always_clean_jobs: false,
+            job_history_size: 4,
+            session_idle_timeout_secs: 0,
+            extranonce1_size: 6,
+            fixed_extranonce1: None,
+            authorize_callback: None,
+            audit_log: None,
+        });
+
+        let extranonce1 =
+            generate_extranonce1(server.state.extranonce1_size, getrandom::fill).unwrap();
+        let session = Arc::new(RwLock::new(StratumSession::new(extranonce1, 1.0)));
+        let (mut writer, _client) = socket_pair().await;
+        let (mut authorized, mut subscribed) = (false, false);
+
+        let request = StratumRequest {
+            id: Value::from(1),
+            method: "mining.subscribe".to_string(),
+            params: vec![Value::String("test-miner/1.0".to_string())],
+        };
+        let response = handle_request(
+            request,
+            &mut authorized,
+            &mut subscribed,
+            &session,
+            &extranonce1,
+            &server.state,
+            &mut writer,
+        )
+        .await;
+
+        let result = response.result.unwrap();
+        assert_eq!(result[1].as_str().unwrap().len(), 12);
+        assert_eq!(result[2], Value::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_fixed_extranonce1_subscribe_response() {
+        let server = StratumServer::new(StratumServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            max_connections: 10,
+            difficulty: StratumDifficulty::Block,
+            mode: None,
+            min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+            hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+            rate_ms: 1000,
+            always_clean_jobs: false,
+            job_history_size: 4,
+            session_idle_timeout_secs: 0,
+            extranonce1_size: 4,
+            fixed_extranonce1: Some(0x0a0b0c0d),
+            authorize_callback: None,
+            audit_log: None,
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::clone(&server.state);
+        let job_rx = server.job_tx.subscribe();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            handle_client(stream, peer, state, job_rx, None).await
+        });
+
+        let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(
+                b"{\"id\":1,\"method\":\"mining.subscribe\",\"params\":[\"test-miner/1.0\"]}\n",
+            )
+            .await
+            .unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(
+            line,
+            "{\"id\":1,\"result\":[[[\"mining.notify\",\"0a0b0c0d\"]],\"0a0b0c0d\",4],\"error\":null}\n"
+        );
+
+        // Shares of the session compose their nonce with the fixed extranonce1
+        let nonce2 = Nonce2::new(NonceSize::new(4).unwrap(), 0x01020304).unwrap();
+        let nonce = compose_nonce(server.state.fixed_extranonce1.unwrap(), nonce2).unwrap();
+        assert_eq!(nonce.value(), 0x01020304_0a0b0c0d);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_shutdown_leaves_only_whole_lines() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = Arc::new(StratumServer::new(StratumServerConfig {
+            port,
+            rate_ms: 1,
+            ..test_server_config()
+        }));
+        let target = Target::from_bytes([0xFF; 32]);
+        server
+            .update_work(Work::from_bytes([0x11; 286]), target)
+            .await;
+        let running = tokio::spawn({
+            let server = Arc::clone(&server);
+            async move { server.start_server().await }
+        });
+
+        let stream = loop {
+            match TcpStream::connect(("127.0.0.1", port)).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        writer
+            .write_all(b"{\"id\":1,\"method\":\"mining.subscribe\",\"params\":[]}\n{\"id\":2,\"method\":\"mining.authorize\",\"params\":[\"miner\",\"x\"]}\n")
+            .await
+            .unwrap();
+        let mut line = String::new();
+        while !line.contains("mining.notify") {
+            line.clear();
+            reader.read_line(&mut line).await.unwrap();
+        }
+        let received = tokio::spawn(async move {
+            let mut received = Vec::new();
+            tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut received)
+                .await
+                .unwrap();
+            received
+        });
+
+        // Shut down abruptly while jobs are being broadcast
+        for i in 0..200u8 {
+            let mut work = Work::from_bytes([0x11; 286]);
+            work.as_bytes_mut()[20] = i;
+            server.update_work(work, target).await;
+        }
+        server.stop().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(10), running)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(10), received)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(received.is_empty() || received.ends_with(b"\n"));
+        let received = String::from_utf8(received).unwrap();
+        for line in received.lines() {
+            let message: Value = serde_json::from_str(line).unwrap();
+            assert!(message.is_object(), "{}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rng_failure_refuses_connection() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut server = StratumServer::new(StratumServerConfig {
+            port,
+            ..test_server_config()
+        });
+        Arc::get_mut(&mut server.state).unwrap().fill_random =
+            |_| Err(getrandom::Error::UNSUPPORTED);
+        assert!(
+            generate_extranonce1(server.state.extranonce1_size, server.state.fill_random).is_err()
+        );
+        let server = Arc::new(server);
+        let running = tokio::spawn({
+            let server = Arc::clone(&server);
+            async move { server.start_server().await }
+        });
+
+        // Each connection is closed without a session, the server keeps running
+        for _ in 0..2 {
+            let mut stream = loop {
+                match TcpStream::connect(("127.0.0.1", port)).await {
+                    Ok(stream) => break stream,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            };
+            let mut received = Vec::new();
+            let read = tokio::io::AsyncReadExt::read_to_end(&mut stream, &mut received);
+            tokio::time::timeout(Duration::from_secs(10), read)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(received.is_empty());
+            assert!(server.state.sessions.is_empty());
+        }
+        assert!(!running.is_finished());
+
+        server.stop().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(10), running)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_job_history_is_bounded() {
+        let server = test_server(false);
+        let target = Target::from_bytes([0xFF; 32]);
+
+        for i in 0..6u8 {
+            server.update_work(Work::from_bytes([i; 286]), target).await;
+        }
+
+        // Current job plus three superseded ones
+        assert_eq!(server.state.job_history.read().await.len(), 3);
+        assert!(find_job(&server.state, "5").await.unwrap().1.is_none());
+        assert_eq!(
+            find_job(&server.state, "2").await.unwrap().1,
+            Some(StaleJob::Outdated)
+        );
+        assert!(find_job(&server.state, "1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_job_ids_wrap_without_colliding() {
+        let server = test_server(false);
+        let target = Target::from_bytes([0xFF; 32]);
+
+        // Jobs "0" and "1" are still active when the counter comes around
+        server.update_work(Work::from_bytes([0; 286]), target).await;
+        server.update_work(Work::from_bytes([1; 286]), target).await;
+        server
+            .state
+            .job_counter
+            .store(0xFFFF_FFFF, Ordering::Relaxed);
+        server.update_work(Work::from_bytes([2; 286]), target).await;
+        server.update_work(Work::from_bytes([3; 286]), target).await;
+
+        let history = server.state.job_history.read().await;
+        let ids: Vec<String> = history.iter().map(|job| job.id.clone()).collect();
+        assert_eq!(ids, ["ffffffff", "1", "0"]);
+        drop(history);
+
+        let current = find_job(&server.state, "2").await.unwrap();
+        assert!(current.1.is_none());
+        assert_eq!(current.0.work, Work::from_bytes([3; 286]));
+
+        // Lookups still find the job each id was handed out for
+        let (job, stale) = find_job(&server.state, "0").await.unwrap();
+        assert_eq!(job.work, Work::from_bytes([0; 286]));
+        assert_eq!(stale, Some(StaleJob::Outdated));
+        let (job, _) = find_job(&server.state, "ffffffff").await.unwrap();
+        assert_eq!(job.work, Work::from_bytes([2; 286]));
+        assert!(
+            ids.iter()
+                .all(|id| id.len() <= crate::workers::stratum::job::JOB_ID_MAX_HEX_LEN)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_is_disconnected() {
+        let server = StratumServer::new(StratumServerConfig {
+            port: 0,
+            host: "127.0.0.1".to_string(),
+            max_connections: 10,
+            difficulty: StratumDifficulty::Block,
+            mode: None,
+            min_difficulty_level: DEFAULT_STRATUM_MIN_DIFFICULTY_LEVEL,
+            hashrate_shift_factor: DEFAULT_STRATUM_HASHRATE_SHIFT_FACTOR,
+            rate_ms: 1000,
+            always_clean_jobs: false,
+            job_history_size: 4,
+            session_idle_timeout_secs: 1,
+            extranonce1_size: 4,
+            fixed_extranonce1: None,
+            authorize_callback: None,
+            audit_log: None,
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::clone(&server.state);
+        let job_tx = server.job_tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, peer) = listener.accept().await.unwrap();
+                tokio::spawn(handle_client(
+                    stream,
+                    peer,
+                    Arc::clone(&state),
+                    job_tx.subscribe(),
+                    None,
+                ));
+            }
+        });
+
+        let idle = TcpStream::connect(addr).await.unwrap();
+        let mut active = TcpStream::connect(addr).await.unwrap();
+        active
+            .write_all(b"{\"id\":1,\"method\":\"mining.authorize\",\"params\":[\"miner\",\"x\"]}\n")
+            .await
+            .unwrap();
+
+        // The active miner keeps submitting (rejected, unknown job) shares
+        let submitter = tokio::spawn(async move {
+            for id in 2..10 {
+                let submit = format!(
+                    "{{\"id\":{},\"method\":\"mining.submit\",\"params\":[\"miner\",\"none\",\"00000000\",\"00000000\",\"0000000000000000\"]}}\n",
+                    id
+                );
+                active.write_all(submit.as_bytes()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+            active
+        });
+
+        // The idle miner gets a message and is disconnected
+        let mut lines = BufReader::new(idle).lines();
+        let message = tokio::time::timeout(Duration::from_secs(3), lines.next_line())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(message.contains("client.show_message"));
+        let eof = tokio::time::timeout(Duration::from_secs(1), lines.next_line())
+            .await
+            .unwrap();
+        assert!(matches!(eof, Ok(None)));
+
+        let _active = submitter.await.unwrap();
+        assert_eq!(server.state.sessions.len(), 1);
+    }
+    #[tokio::test]
+    async fn test_changed_extranonce1_is_sent_to_miner() {
+        let server = test_server(false);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::clone(&server.state);
+        let job_tx = server.job_tx.clone();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            handle_client(stream, peer, state, job_tx.subscribe(), None).await
+        });
+
+        let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut next_message = async || {
+            let line = tokio::time::timeout(Duration::from_secs(1), lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .expect("connection closed");
+            serde_json::from_str::<Value>(&line).unwrap()
+        };
+
+        writer
+            .write_all(b"{\"id\":1,\"method\":\"mining.subscribe\",\"params\":[]}\n")
+            .await
+            .unwrap();
+        let reply = next_message().await;
+        let old_extranonce1 = reply["result"][1].as_str().unwrap().to_string();
+
+        let session_id = *server.state.sessions.iter().next().unwrap().key();
+        let new_extranonce1 = Nonce1::new(NonceSize::new(3).unwrap(), 0xabcdef).unwrap();
+        assert_ne!(new_extranonce1.to_hex(), old_extranonce1);
+        server
+            .set_session_extranonce1(session_id, new_extranonce1)
+            .unwrap();
+
+        let notification = next_message().await;
+        assert_eq!(notification["method"], "mining.set_extranonce");
+        assert_eq!(notification["params"][0], "abcdef");
+        assert_eq!(notification["params"][1], 5);
+
+        let session = server.state.sessions.get(&session_id).unwrap().clone();
+        assert_eq!(session.read().await.extranonce1, new_extranonce1);
+
+        // Unknown sessions can't be changed
+        assert!(
+            server
+                .set_session_extranonce1(SessionId::new(), new_extranonce1)
+                .is_err()
+        );
+    }
+
+    /// Miner connected to a session of `server`, subscribed and authorized,
+    /// with the extranonce1 it was assigned
+    async fn subscribed_miner(
+        server: &StratumServer,
+    ) -> (
+        OwnedWriteHalf,
+        tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        Nonce1,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::clone(&server.state);
+        let job_tx = server.job_tx.clone();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            handle_client(stream, peer, state, job_tx.subscribe(), None).await
+        });
+
+        let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let subscribe = StratumRequest {
+            id: Value::from(1),
+            method: "mining.subscribe".to_string(),
+            params: Vec::new(),
+        };
+        let reply = call(&mut writer, &mut lines, subscribe).await;
+        let nonce2_size = reply["result"][2].as_u64().unwrap() as u8;
+        let size = NonceSize::new(8 - nonce2_size).unwrap();
+        let extranonce1 = Nonce1::from_hex(size, reply["result"][1].as_str().unwrap()).unwrap();
+        let authorize = StratumRequest {
+            id: Value::from(2),
+            method: "mining.authorize".to_string(),
+            params: vec![
+                Value::String("miner.worker".to_string()),
+                Value::String("x".to_string()),
+            ],
+        };
+        assert_eq!(
+            call(&mut writer, &mut lines, authorize).await["result"],
+            true
+        );
+        (writer, lines, extranonce1)
+    }
+
+    /// Send `request` and wait for its reply, skipping notifications
+    async fn call(
+        writer: &mut OwnedWriteHalf,
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        request: StratumRequest,
+    ) -> Value {
+        let id = request.id.clone();
+        let line = serde_json::to_string(&request).unwrap() + "\n";
+        writer.write_all(line.as_bytes()).await.unwrap();
+        loop {
+            let line = tokio::time::timeout(Duration::from_secs(1), lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .expect("connection closed");
+            let message: Value = serde_json::from_str(&line).unwrap();
+            if message["id"] == id {
+                return message;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_after_set_extranonce_needs_new_extranonce1() {
+        let server = test_server(false);
+        server
+            .update_work(
+                Work::from_bytes([0x11; 286]),
+                Target::from_bytes([0xFF; 32]),
+            )
+            .await;
+        let job_id = server
+            .state
+            .current_job
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .id
+            .clone();
+        let (mut writer, mut lines, old_extranonce1) = subscribed_miner(&server).await;
+
+        let session_id = *server.state.sessions.iter().next().unwrap().key();
+        let new_extranonce1 = Nonce1::new(NonceSize::new(3).unwrap(), 0xabcdef).unwrap();
+        server
+            .set_session_extranonce1(session_id, new_extranonce1)
+            .unwrap();
+
+        // A share from the old nonce space is no longer valid
+        let mut stale = submit_request(&job_id, &new_extranonce1);
+        stale.id = Value::from(3);
+        stale.params[4] = Value::String(share_nonce(&old_extranonce1).1.to_hex_le());
+        let reply = call(&mut writer, &mut lines, stale).await;
+        assert_eq!(reply["result"], Value::Null);
+        assert_eq!(
+            reply["error"][1],
+            "Nonce doesn't match extranonce1 and extranonce2"
+        );
+
+        let mut share = submit_request(&job_id, &new_extranonce1);
+        share.id = Value::from(4);
+        assert_eq!(call(&mut writer, &mut lines, share).await["result"], true);
+
+        let session = server.state.sessions.get(&session_id).unwrap().clone();
+        let session = session.read().await;
+        assert_eq!((session.shares_submitted, session.shares_valid), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_submit_needs_extranonce1_of_every_split() {
+        for extranonce1_size in 1..=7 {
+            let server = StratumServer::new(StratumServerConfig {
+                extranonce1_size,
+                ..test_server_config()
+            });
+            server
+                .update_work(
+                    Work::from_bytes([0x11; 286]),
+                    Target::from_bytes([0xFF; 32]),
+                )
+                .await;
+            let job_id = server
+                .state
+                .current_job
+                .read()
+                .await
+                .as_ref()
+                .unwrap()
+                .id
+                .clone();
+            let (mut writer, mut lines, extranonce1) = subscribed_miner(&server).await;
+            assert_eq!(extranonce1.size().as_bytes(), extranonce1_size);
+
+            let mut share = submit_request(&job_id, &extranonce1);
+            share.id = Value::from(3);
+            assert_eq!(
+                call(&mut writer, &mut lines, share).await["result"],
+                true,
+                "split {}",
+                extranonce1_size
+            );
+
+            // The same extranonce2 with another extranonce1
+            let other = Nonce1::new(extranonce1.size(), extranonce1.value() ^ 1).unwrap();
+            let mut foreign = submit_request(&job_id, &extranonce1);
+            foreign.id = Value::from(4);
+            foreign.params[4] = Value::String(share_nonce(&other).1.to_hex_le());
+            let reply = call(&mut writer, &mut lines, foreign).await;
+            assert_eq!(reply["result"], Value::Null, "split {}", extranonce1_size);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_keeps_client_connected() {
+        let server = test_server(false);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::clone(&server.state);
+        let job_tx = server.job_tx.clone();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            handle_client(stream, peer, state, job_tx.subscribe(), None).await
+        });
+
+        let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut request = async |line: &str| {
+            writer.write_all(line.as_bytes()).await.unwrap();
+            let reply = tokio::time::timeout(Duration::from_secs(1), lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .expect("connection closed");
+            serde_json::from_str::<Value>(&reply).unwrap()
+        };
+
+        let reply =
+            request("{\"id\":1,\"method\":\"mining.extranonce.subscribe\",\"params\":[]}\n").await;
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"][0], StratumErrorCode::UnknownMethod.code());
+        assert_eq!(reply["error"][1], "Method not found");
+
+        // Still connected and served
+        let reply =
+            request("{\"id\":2,\"method\":\"mining.authorize\",\"params\":[\"miner\",\"x\"]}\n")
+                .await;
+        assert_eq!(reply["id"], 2);
+        assert_eq!(reply["result"], true);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_client_skips_to_latest_job() {
+        let server = test_server(false);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::clone(&server.state);
+        let job_tx = server.job_tx.clone();
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            handle_client(stream, peer, state, job_tx.subscribe(), None).await
+        });
+
+        let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut next_message = async || {
+            let line = tokio::time::timeout(Duration::from_secs(1), lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .expect("connection closed");
+            serde_json::from_str::<Value>(&line).unwrap()
+        };
+        writer
+            .write_all(b"{\"id\":1,\"method\":\"mining.subscribe\",\"params\":[]}\n")
+            .await
+            .unwrap();
+        assert_eq!(next_message().await["id"], 1);
+        writer
+            .write_all(b"{\"id\":2,\"method\":\"mining.authorize\",\"params\":[\"miner\",\"x\"]}\n")
+            .await
+            .unwrap();
+        assert_eq!(next_message().await["id"], 2);
+
+        // The session task doesn't get to run while the jobs are sent, so
+        // it falls more than the channel capacity behind
+        let job = |id: usize| MiningJob {
+            id: id.to_string(),
+            work: Work::from_bytes([id as u8; 286]),
+            target: Target::from_bytes([0xFF; 32]),
+            clean_jobs: true,
+        };
+        for id in 0..250 {
+            server.job_tx.send(job(id)).unwrap();
+        }
+
+        let notify = next_message().await;
+        assert_eq!(notify["method"], "mining.notify");
+        assert_eq!(notify["params"][0], "249");
+
+        // Updates keep flowing after the lag
+        server.job_tx.send(job(250)).unwrap();
+        let notify = next_message().await;
+        assert_eq!(notify["params"][0], "250");
     }
 }
@@ -2,9 +2,13 @@
 
 use super::nonce::Nonce1;
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+/// Number of latest shares whose hash rate is compared with the earlier ones
+/// to detect a hash rate shift
+pub const HASH_RATE_SHIFT_WINDOW: usize = 4;
+
 /// Session ID type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SessionId(Uuid);
@@ -42,8 +46,12 @@ pub struct StratumSession {
     pub shares_submitted: u64,
     /// Valid shares
     pub shares_valid: u64,
+    /// Valid shares for jobs that had already been superseded
+    pub shares_stale: u64,
     /// Last share submission time
     pub last_share_time: Option<Instant>,
+    /// Time of the last `mining.submit` (valid or not), or of connecting
+    pub last_submit_time: Instant,
     /// Sum of hash rates for averaging
     pub hash_rate_sum: f64,
     /// Number of share intervals counted
@@ -54,6 +62,8 @@ pub struct StratumSession {
     pub estimated_hashrate: f64,
     /// Session-specific target (may differ from work target)
     pub session_target: Option<crate::core::Target>,
+    /// Version bits the miner may roll; `None` until version rolling is negotiated
+    pub version_rolling_mask: Option<u32>,
 }
 
 impl StratumSession {
@@ -66,19 +76,35 @@ impl StratumSession {
             extranonce1,
             shares_submitted: 0,
             shares_valid: 0,
+            shares_stale: 0,
             last_share_time: None,
+            last_submit_time: Instant::now(),
             hash_rate_sum: 0.0,
             share_count: 0,
             recent_shares: VecDeque::with_capacity(10),
             estimated_hashrate: 0.0,
             session_target: None,
+            version_rolling_mask: None,
         }
     }
 
+    /// Record a `mining.submit` from the miner
+    pub fn record_submit(&mut self) {
+        self.last_submit_time = Instant::now();
+    }
+
+    /// Time since the miner last submitted a share (or connected)
+    pub fn idle_duration(&self) -> Duration {
+        self.last_submit_time.elapsed()
+    }
+
     /// Update hash rate estimation based on a new share
     pub fn update_hash_rate(&mut self, current_difficulty: f64) {
-        let now = Instant::now();
-        
+        self.update_hash_rate_at(current_difficulty, Instant::now());
+    }
+
+    /// Update hash rate estimation based on a share found at `now`
+    pub fn update_hash_rate_at(&mut self, current_difficulty: f64, now: Instant) {
         if let Some(last_time) = self.last_share_time {
             let elapsed = now.duration_since(last_time).as_secs_f64();
             
@@ -115,4 +141,30 @@ impl StratumSession {
         
         self.last_share_time = Some(now);
     }
+
+    /// Restart the hash rate estimate from the latest
+    /// [`HASH_RATE_SHIFT_WINDOW`] shares if their hash rate differs from the
+    /// one of the earlier shares by more than `factor`, e.g. after units were
+    /// added to or removed from the rig; returns whether it did
+    ///
+    /// The weighted estimate would otherwise take several more shares to
+    /// follow the change.
+    pub fn reset_on_hash_rate_shift(&mut self, factor: f64) -> bool {
+        if self.recent_shares.len() <= HASH_RATE_SHIFT_WINDOW {
+            return false;
+        }
+        // Harmonic means, i.e. the difficulty over the average share interval
+        let rate = |shares: &[(Instant, f64)]| {
+            shares.len() as f64 / shares.iter().map(|(_, rate)| rate.recip()).sum::<f64>()
+        };
+        let split = self.recent_shares.len() - HASH_RATE_SHIFT_WINDOW;
+        let shares = self.recent_shares.make_contiguous();
+        let (earlier, latest) = (rate(&shares[..split]), rate(&shares[split..]));
+        if latest <= earlier * factor && latest * factor >= earlier {
+            return false;
+        }
+        self.recent_shares.drain(..split);
+        self.estimated_hashrate = latest;
+        true
+    }
 }
@@ -0,0 +1,137 @@
+//! Statistics of named stratum workers
+//!
+//! A rig may open several sessions under the same worker name (e.g. one per
+//! hashing board, or after reconnecting). Shares are counted per name across
+//! all of its sessions, and the hash rate is the sum over the sessions that
+//! are still connected.
+
+use super::session::SessionId;
+use crate::utils::monitoring::{NamedWorkerStats, global_monitoring};
+use dashmap::DashMap;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Default)]
+struct WorkerEntry {
+    shares_submitted: u64,
+    shares_accepted: u64,
+    session_hash_rates: HashMap<SessionId, f64>,
+}
+
+impl WorkerEntry {
+    fn stats(&self) -> NamedWorkerStats {
+        NamedWorkerStats {
+            shares_submitted: self.shares_submitted,
+            shares_accepted: self.shares_accepted,
+            hash_rate: self.session_hash_rates.values().sum(),
+            sessions: self.session_hash_rates.len(),
+        }
+    }
+}
+
+/// Stratum worker statistics keyed by worker name
+#[derive(Debug, Default)]
+pub struct WorkerRegistry {
+    workers: DashMap<String, WorkerEntry>,
+}
+
+impl WorkerRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a session authorized as worker `name`
+    pub fn add_session(&self, name: &str, session: SessionId) {
+        self.update(name, |entry| {
+            entry.session_hash_rates.entry(session).or_insert(0.0);
+        });
+    }
+
+    /// Remove a disconnected session; the worker's share counts are kept
+    pub fn remove_session(&self, name: &str, session: SessionId) {
+        self.update(name, |entry| {
+            entry.session_hash_rates.remove(&session);
+        });
+    }
+
+    /// Count a share submitted by worker `name`
+    pub fn record_share(&self, name: &str, accepted: bool) {
+        self.update(name, |entry| {
+            entry.shares_submitted += 1;
+            if accepted {
+                entry.shares_accepted += 1;
+            }
+        });
+    }
+
+    /// Set the estimated hash rate of one of the worker's sessions
+    pub fn record_hash_rate(&self, name: &str, session: SessionId, hash_rate: f64) {
+        self.update(name, |entry| {
+            entry.session_hash_rates.insert(session, hash_rate);
+        });
+    }
+
+    /// Statistics of worker `name`
+    pub fn stats(&self, name: &str) -> Option<NamedWorkerStats> {
+        self.workers.get(name).map(|entry| entry.stats())
+    }
+
+    /// Estimated hash rate of all connected sessions
+    pub fn total_hash_rate(&self) -> f64 {
+        self.workers
+            .iter()
+            .map(|entry| entry.stats().hash_rate)
+            .sum()
+    }
+
+    /// Statistics of all workers, ordered by name
+    pub fn snapshot(&self) -> BTreeMap<String, NamedWorkerStats> {
+        self.workers
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.stats()))
+            .collect()
+    }
+
+    /// Apply `change` to the entry of `name` and publish the result
+    fn update(&self, name: &str, change: impl FnOnce(&mut WorkerEntry)) {
+        let stats = {
+            let mut entry = self.workers.entry(name.to_string()).or_default();
+            change(&mut entry);
+            entry.stats()
+        };
+        global_monitoring().record_named_worker(name, stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_rate_sums_connected_sessions() {
+        let registry = WorkerRegistry::new();
+        let (first, second) = (SessionId::new(), SessionId::new());
+        registry.add_session("rig", first);
+        registry.add_session("rig", second);
+        registry.record_hash_rate("rig", first, 100.0);
+        registry.record_hash_rate("rig", second, 250.0);
+        registry.record_share("rig", true);
+        registry.record_share("other", false);
+
+        let stats = registry.stats("rig").unwrap();
+        assert_eq!(stats.hash_rate, 350.0);
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(registry.total_hash_rate(), 350.0);
+
+        // A disconnect drops the session's hash rate but not its shares
+        registry.remove_session("rig", first);
+        let stats = registry.stats("rig").unwrap();
+        assert_eq!(stats.hash_rate, 250.0);
+        assert_eq!(stats.sessions, 1);
+        assert_eq!(stats.shares_accepted, 1);
+        assert_eq!(registry.total_hash_rate(), 250.0);
+
+        let names: Vec<_> = registry.snapshot().into_keys().collect();
+        assert_eq!(names, ["other", "rig"]);
+    }
+}
@@ -0,0 +1,654 @@
+//! Supervised worker with automatic restart on repeated mining failures
+//!
+//! Wraps any [`Worker`] and tracks consecutive `mine` errors. Once the
+//! configured threshold is reached the underlying worker is torn down and
+//! recreated from its factory (re-initializing GPU devices, respawning
+//! external processes, ...). Restarts run in the background so the caller
+//! keeps reacting to new work and shutdown; work handed over meanwhile is
+//! mined by the recreated worker. Restarts back off exponentially and are
+//! capped; when the cap is exhausted a critical monitoring alert is raised.
+
+use crate::core::{Target, Work};
+use crate::error::{Error, Result};
+use crate::utils::monitoring::global_monitoring;
+use crate::utils::panic::spawn_monitored;
+use crate::workers::{CancellationReason, MiningResult, NoncePosition, Worker, WorkerCapabilities};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::{Notify, mpsc};
+use tracing::{error, info, warn};
+
+/// Factory used to (re)create the supervised worker
+pub type WorkerFactory = Arc<dyn Fn() -> BoxFuture<'static, Result<Arc<dyn Worker>>> + Send + Sync>;
+
+/// Restart policy for a supervised worker
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// Consecutive mining errors before the worker is restarted
+    pub failure_threshold: u32,
+    /// Maximum number of restarts without a successful `mine` in between
+    pub max_restarts: u32,
+    /// Delay before the first restart attempt
+    pub initial_backoff: Duration,
+    /// Upper bound for the exponential restart delay
+    pub max_backoff: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            max_restarts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// Backoff delay before the given (zero-based) restart attempt
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Work handed to the supervised worker
+#[derive(Clone)]
+struct MineRequest {
+    work: Work,
+    target: Target,
+    result_tx: mpsc::Sender<MiningResult>,
+    start_nonce: Option<u64>,
+}
+
+/// Worker wrapper that restarts the inner worker after repeated failures
+pub struct SupervisedWorker {
+    this: Weak<Self>,
+    config: SupervisorConfig,
+    factory: WorkerFactory,
+    inner: RwLock<Arc<dyn Worker>>,
+    worker_type: String,
+    consecutive_failures: AtomicU32,
+    restarts: AtomicU32,
+    exhausted: AtomicBool,
+    /// Signalled once the restart budget is used up
+    exhausted_notify: Notify,
+    /// Whether a restart is scheduled or running
+    restarting: AtomicBool,
+    /// Latest work to mine once the scheduled restart completes
+    pending: Mutex<Option<MineRequest>>,
+    /// Whether mining is paused, carried over to recreated workers
+    paused: AtomicBool,
+}
+
+impl SupervisedWorker {
+    /// Create the initial worker from the factory and wrap it
+    pub async fn new(factory: WorkerFactory, config: SupervisorConfig) -> Result<Arc<Self>> {
+        let inner = factory().await?;
+        let worker_type = inner.worker_type().to_string();
+
+        Ok(Arc::new_cyclic(|this| Self {
+            this: this.clone(),
+            config,
+            factory,
+            inner: RwLock::new(inner),
+            worker_type,
+            consecutive_failures: AtomicU32::new(0),
+            restarts: AtomicU32::new(0),
+            exhausted: AtomicBool::new(false),
+            exhausted_notify: Notify::new(),
+            restarting: AtomicBool::new(false),
+            pending: Mutex::new(None),
+            paused: AtomicBool::new(false),
+        }))
+    }
+
+    /// Number of restarts performed since the last successful `mine`
+    pub fn restart_count(&self) -> u32 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Whether mining is currently paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Whether the restart budget has been used up
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+
+    /// Wait until the restart budget is used up by a background restart
+    pub async fn restarts_exhausted(&self) {
+        self.exhausted_notify.notified().await
+    }
+
+    /// Start mining `work`, retrying failures below the threshold after the
+    /// initial backoff until the worker mines or its restarts are exhausted
+    ///
+    /// Unlike [`Worker::mine`], which leaves retrying to the next work
+    /// update and restarts in the background, this suits the first `mine`
+    /// of a run, which no update follows, so restarts are awaited here.
+    pub async fn start(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+    ) -> Result<()> {
+        let request = MineRequest {
+            work,
+            target,
+            result_tx,
+            start_nonce: None,
+        };
+        let mut worker = self.current();
+        loop {
+            match self.try_mine(&worker, &request).await {
+                Ok(()) => return Ok(()),
+                Err(_) if self.needs_restart() => worker = self.restart().await?,
+                Err(_) => tokio::time::sleep(self.config.initial_backoff).await,
+            }
+        }
+    }
+
+    fn current(&self) -> Arc<dyn Worker> {
+        Arc::clone(&self.inner.read())
+    }
+
+    /// Tear down the current worker and recreate it, backing off between attempts
+    async fn restart(&self) -> Result<Arc<dyn Worker>> {
+        if let Err(e) = self.current().stop().await {
+            warn!(
+                "Failed to stop {} worker before restart: {}",
+                self.worker_type, e
+            );
+        }
+
+        loop {
+            let attempt = self.restarts.load(Ordering::Relaxed);
+            if attempt >= self.config.max_restarts {
+                self.exhausted.store(true, Ordering::Relaxed);
+                self.exhausted_notify.notify_one();
+                global_monitoring().record_worker_restart_exhausted(&self.worker_type, attempt);
+                return Err(Error::worker_restart_exhausted(&self.worker_type, attempt));
+            }
+
+            let delay = self.config.backoff(attempt);
+            warn!(
+                "Restarting {} worker in {:?} (attempt {}/{})",
+                self.worker_type,
+                delay,
+                attempt + 1,
+                self.config.max_restarts
+            );
+            tokio::time::sleep(delay).await;
+            self.restarts.fetch_add(1, Ordering::Relaxed);
+
+            match (self.factory)().await {
+                Ok(worker) => {
+                    if self.paused.load(Ordering::Relaxed)
+                        && let Err(e) = worker.set_paused(true).await
+                    {
+                        warn!(
+                            "Failed to pause restarted {} worker: {}",
+                            self.worker_type, e
+                        );
+                    }
+                    *self.inner.write() = Arc::clone(&worker);
+                    self.consecutive_failures.store(0, Ordering::Relaxed);
+                    info!("{} worker restarted", self.worker_type);
+                    return Ok(worker);
+                }
+                Err(e) => {
+                    error!("Failed to recreate {} worker: {}", self.worker_type, e);
+                }
+            }
+        }
+    }
+
+    /// Mine `request` on `worker`, counting consecutive failures
+    async fn try_mine(&self, worker: &Arc<dyn Worker>, request: &MineRequest) -> Result<()> {
+        let MineRequest {
+            work,
+            target,
+            result_tx,
+            start_nonce,
+        } = request.clone();
+        let mined = match start_nonce {
+            Some(start_nonce) => worker.resume(work, target, result_tx, start_nonce).await,
+            None => worker.mine(work, target, result_tx).await,
+        };
+        match mined {
+            Ok(()) => {
+                self.consecutive_failures.store(0, Ordering::Relaxed);
+                self.restarts.store(0, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                error!(
+                    "{} worker mining failed ({}/{}): {}",
+                    self.worker_type, failures, self.config.failure_threshold, e
+                );
+                Err(e)
+            }
+        }
+    }
+
+    /// Whether the failures since the last successful `mine` call for a restart
+    fn needs_restart(&self) -> bool {
+        self.consecutive_failures.load(Ordering::Relaxed) >= self.config.failure_threshold
+    }
+
+    /// Hand `request` to the restarted worker, returning whether no restart
+    /// was scheduled yet
+    fn queue_restart(&self, request: MineRequest) -> bool {
+        let mut pending = self.pending.lock();
+        pending.get_or_insert(request);
+        !self.restarting.swap(true, Ordering::Relaxed)
+    }
+
+    /// Recreate the worker and mine the queued work on it, until it mines
+    /// or the restart budget is used up
+    async fn run_restarts(self: Arc<Self>) {
+        loop {
+            let worker = match self.restart().await {
+                Ok(worker) => worker,
+                Err(_) => {
+                    self.clear_pending();
+                    self.restarting.store(false, Ordering::Relaxed);
+                    return;
+                }
+            };
+
+            let request = {
+                let mut pending = self.pending.lock();
+                self.restarting.store(false, Ordering::Relaxed);
+                pending.take()
+            };
+            // Mining was cancelled while the worker restarted
+            let Some(request) = request else {
+                return;
+            };
+
+            match self.try_mine(&worker, &request).await {
+                Err(_) if self.needs_restart() => {
+                    if !self.queue_restart(request) {
+                        return;
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Mine `request` on the current worker, scheduling a restart once its
+    /// failures reach the threshold
+    async fn mine_supervised(&self, request: MineRequest) -> Result<()> {
+        if self.is_exhausted() {
+            return Err(Error::worker_restart_exhausted(
+                &self.worker_type,
+                self.restart_count(),
+            ));
+        }
+
+        // The worker is being recreated: it mines the latest work once it is back
+        {
+            let mut pending = self.pending.lock();
+            if self.restarting.load(Ordering::Relaxed) {
+                *pending = Some(request);
+                return Ok(());
+            }
+        }
+
+        let mined = self.try_mine(&self.current(), &request).await;
+        if mined.is_err()
+            && self.needs_restart()
+            && self.queue_restart(request)
+            && let Some(this) = self.this.upgrade()
+        {
+            spawn_monitored("worker restart", this.run_restarts());
+        }
+        mined
+    }
+
+    /// Drop work queued for a restarted worker
+    fn clear_pending(&self) {
+        self.pending.lock().take();
+    }
+}
+
+#[async_trait]
+impl Worker for SupervisedWorker {
+    async fn mine(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+    ) -> Result<()> {
+        self.mine_supervised(MineRequest {
+            work,
+            target,
+            result_tx,
+            start_nonce: None,
+        })
+        .await
+    }
+
+    async fn resume(
+        &self,
+        work: Work,
+        target: Target,
+        result_tx: mpsc::Sender<MiningResult>,
+        start_nonce: u64,
+    ) -> Result<()> {
+        self.mine_supervised(MineRequest {
+            work,
+            target,
+            result_tx,
+            start_nonce: Some(start_nonce),
+        })
+        .await
+    }
+
+    async fn stop(&self) -> Result<()> {
+        self.clear_pending();
+        self.current().stop().await
+    }
+
+    async fn cancel(&self, reason: CancellationReason) -> Result<()> {
+        self.clear_pending();
+        self.current().cancel(reason).await
+    }
+
+    async fn work_exhausted(&self) {
+        self.current().work_exhausted().await
+    }
+
+    fn nonce_position(&self) -> Option<NoncePosition> {
+        self.current().nonce_position()
+    }
+
+    async fn set_paused(&self, paused: bool) -> Result<()> {
+        self.current().set_paused(paused).await?;
+        self.paused.store(paused, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn worker_type(&self) -> &str {
+        &self.worker_type
+    }
+
+    async fn hashrate(&self) -> u64 {
+        self.current().hashrate().await
+    }
+
+    fn capabilities(&self) -> WorkerCapabilities {
+        self.current().capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Worker stub whose first generation always fails to mine
+    struct FlakyWorker {
+        generation: u32,
+        failed_mines: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl Worker for FlakyWorker {
+        async fn mine(
+            &self,
+            _work: Work,
+            _target: Target,
+            _result_tx: mpsc::Sender<MiningResult>,
+        ) -> Result<()> {
+            if self.generation == 0 {
+                self.failed_mines.fetch_add(1, Ordering::Relaxed);
+                Err(Error::worker_mining_failed("device lost"))
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn worker_type(&self) -> &str {
+            "Flaky"
+        }
+
+        async fn hashrate(&self) -> u64 {
+            self.generation as u64
+        }
+
+        fn capabilities(&self) -> WorkerCapabilities {
+            WorkerCapabilities::compute()
+        }
+    }
+
+    fn flaky_factory(created: Arc<AtomicU32>, healthy_after: u32) -> WorkerFactory {
+        let failed_mines = Arc::new(AtomicU32::new(0));
+        flaky_factory_counting(created, healthy_after, failed_mines)
+    }
+
+    fn flaky_factory_counting(
+        created: Arc<AtomicU32>,
+        healthy_after: u32,
+        failed_mines: Arc<AtomicU32>,
+    ) -> WorkerFactory {
+        Arc::new(move || {
+            let created = Arc::clone(&created);
+            let failed_mines = Arc::clone(&failed_mines);
+            Box::pin(async move {
+                let n = created.fetch_add(1, Ordering::Relaxed);
+                let generation = if n >= healthy_after { n } else { 0 };
+                Ok(Arc::new(FlakyWorker {
+                    generation,
+                    failed_mines,
+                }) as Arc<dyn Worker>)
+            })
+        })
+    }
+
+    fn fast_config(failure_threshold: u32, max_restarts: u32) -> SupervisorConfig {
+        SupervisorConfig {
+            failure_threshold,
+            max_restarts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    /// Wait for the scheduled restart to recreate the worker and mine
+    async fn wait_for_restart(worker: &SupervisedWorker) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while worker.restarting.load(Ordering::Relaxed) || worker.restart_count() > 0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("worker restarted");
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let config = SupervisorConfig::default();
+        assert_eq!(config.backoff(0), Duration::from_secs(1));
+        assert_eq!(config.backoff(3), Duration::from_secs(8));
+        assert_eq!(config.backoff(10), Duration::from_secs(60));
+        assert_eq!(config.backoff(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_worker_recovers_after_restart() {
+        let created = Arc::new(AtomicU32::new(0));
+        let failed_mines = Arc::new(AtomicU32::new(0));
+        let factory = flaky_factory_counting(Arc::clone(&created), 1, Arc::clone(&failed_mines));
+        let worker = SupervisedWorker::new(factory, fast_config(2, 3))
+            .await
+            .unwrap();
+        let (tx, _rx) = mpsc::channel(1);
+        let work = Work::default();
+        let target = Target::from_bytes([0xFF; 32]);
+
+        // First failure is below the threshold and is reported to the caller
+        assert!(worker.mine(work.clone(), target, tx.clone()).await.is_err());
+        assert_eq!(created.load(Ordering::Relaxed), 1);
+
+        // Second failure schedules a restart and the fresh worker mines the same work
+        assert!(worker.mine(work, target, tx).await.is_err());
+        wait_for_restart(&worker).await;
+        assert_eq!(failed_mines.load(Ordering::Relaxed), 2);
+        assert_eq!(created.load(Ordering::Relaxed), 2);
+        assert_eq!(worker.hashrate().await, 1);
+        assert_eq!(worker.worker_type(), "Flaky");
+        assert!(!worker.is_exhausted());
+        assert_eq!(worker.restart_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_start_retries_until_restarted_worker_mines() {
+        let created = Arc::new(AtomicU32::new(0));
+        let failed_mines = Arc::new(AtomicU32::new(0));
+        let factory = flaky_factory_counting(Arc::clone(&created), 1, Arc::clone(&failed_mines));
+        let worker = SupervisedWorker::new(factory, fast_config(2, 3))
+            .await
+            .unwrap();
+        let (tx, _rx) = mpsc::channel(1);
+
+        // Fails twice, the second failure restarts the worker, which then mines
+        worker
+            .start(Work::default(), Target::from_bytes([0xFF; 32]), tx)
+            .await
+            .unwrap();
+        assert_eq!(failed_mines.load(Ordering::Relaxed), 2);
+        assert_eq!(created.load(Ordering::Relaxed), 2);
+        assert_eq!(worker.hashrate().await, 1);
+        assert!(!worker.is_exhausted());
+    }
+
+    #[tokio::test]
+    async fn test_start_gives_up_once_restarts_are_exhausted() {
+        let created = Arc::new(AtomicU32::new(0));
+        let worker = SupervisedWorker::new(
+            flaky_factory(Arc::clone(&created), u32::MAX),
+            fast_config(2, 2),
+        )
+        .await
+        .unwrap();
+        let (tx, _rx) = mpsc::channel(1);
+
+        assert!(
+            worker
+                .start(Work::default(), Target::from_bytes([0xFF; 32]), tx)
+                .await
+                .is_err()
+        );
+        assert!(worker.is_exhausted());
+        assert_eq!(created.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_restart_attempts_exhausted() {
+        let created = Arc::new(AtomicU32::new(0));
+        let worker = SupervisedWorker::new(
+            flaky_factory(Arc::clone(&created), u32::MAX),
+            fast_config(1, 2),
+        )
+        .await
+        .unwrap();
+        let (tx, _rx) = mpsc::channel(1);
+
+        assert!(
+            worker
+                .mine(Work::default(), Target::from_bytes([0xFF; 32]), tx.clone())
+                .await
+                .is_err()
+        );
+        tokio::time::timeout(Duration::from_secs(5), worker.restarts_exhausted())
+            .await
+            .expect("restarts exhausted");
+        assert!(worker.is_exhausted());
+        // Initial worker plus two restarts
+        assert_eq!(created.load(Ordering::Relaxed), 3);
+
+        // Further mining is refused without recreating the worker again
+        let err = worker
+            .mine(Work::default(), Target::from_bytes([0xFF; 32]), tx)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Worker(crate::error::WorkerError::RestartExhausted { attempts: 2, .. })
+        ));
+        assert_eq!(created.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_restart_backoff_does_not_block_mine() {
+        let created = Arc::new(AtomicU32::new(0));
+        let failed_mines = Arc::new(AtomicU32::new(0));
+        let factory = flaky_factory_counting(Arc::clone(&created), 1, Arc::clone(&failed_mines));
+        let config = SupervisorConfig {
+            initial_backoff: Duration::from_secs(60),
+            ..fast_config(1, 3)
+        };
+        let worker = SupervisedWorker::new(factory, config).await.unwrap();
+        let (tx, _rx) = mpsc::channel(1);
+        let target = Target::from_bytes([0xFF; 32]);
+
+        // The failure schedules the restart and returns before its backoff ends
+        let mined = tokio::time::timeout(
+            Duration::from_secs(1),
+            worker.mine(Work::default(), target, tx.clone()),
+        )
+        .await
+        .expect("mine returns during the restart backoff");
+        assert!(mined.is_err());
+        assert_eq!(created.load(Ordering::Relaxed), 1);
+
+        // Work handed over meanwhile is kept for the recreated worker
+        worker.mine(Work::default(), target, tx).await.unwrap();
+        assert_eq!(failed_mines.load(Ordering::Relaxed), 1);
+
+        wait_for_restart(&worker).await;
+        assert_eq!(created.load(Ordering::Relaxed), 2);
+        assert_eq!(failed_mines.load(Ordering::Relaxed), 1);
+        assert_eq!(worker.hashrate().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_drops_work_queued_for_restart() {
+        let created = Arc::new(AtomicU32::new(0));
+        let worker = SupervisedWorker::new(
+            flaky_factory(Arc::clone(&created), 1),
+            SupervisorConfig {
+                initial_backoff: Duration::from_millis(50),
+                ..fast_config(1, 3)
+            },
+        )
+        .await
+        .unwrap();
+        let (tx, _rx) = mpsc::channel(1);
+
+        assert!(
+            worker
+                .mine(Work::default(), Target::from_bytes([0xFF; 32]), tx)
+                .await
+                .is_err()
+        );
+        worker.cancel(CancellationReason::Shutdown).await.unwrap();
+        assert!(worker.pending.lock().is_none());
+    }
+}
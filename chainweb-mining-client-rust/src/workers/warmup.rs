@@ -0,0 +1,92 @@
+//! Hashrate warm-up window
+//!
+//! The first hashrate samples after mining starts include thread start-up and
+//! buffer setup, so they are far below the steady state. During the warm-up
+//! window workers report the last stable value instead (0 right after
+//! startup, which callers treat as "no sample yet") and skip feeding the
+//! monitoring system, so low-hashrate alerts don't fire on every (re)start.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Default warm-up window after mining starts
+pub const DEFAULT_HASHRATE_WARMUP: Duration = Duration::from_secs(5);
+
+/// Tracks the warm-up window of a mining worker
+#[derive(Debug)]
+pub struct HashrateWarmup {
+    duration: Duration,
+    started: Mutex<Option<Instant>>,
+    last_stable: AtomicU64,
+}
+
+impl HashrateWarmup {
+    /// Create a warm-up tracker; mining counts as not started yet
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            started: Mutex::new(None),
+            last_stable: AtomicU64::new(0),
+        }
+    }
+
+    /// Mining (re)started: open a new warm-up window
+    pub fn start(&self) {
+        *self.started.lock() = Some(Instant::now());
+    }
+
+    /// Whether hashrate samples should currently be suppressed
+    pub fn is_warming_up(&self) -> bool {
+        self.started
+            .lock()
+            .is_some_and(|started| started.elapsed() < self.duration)
+    }
+
+    /// Filter a hashrate sample: the last stable value while warming up,
+    /// otherwise the sample itself
+    pub fn report(&self, sample: u64) -> u64 {
+        if self.is_warming_up() {
+            return self.last_stable.load(Ordering::Relaxed);
+        }
+        self.last_stable.store(sample, Ordering::Relaxed);
+        sample
+    }
+}
+
+impl Default for HashrateWarmup {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASHRATE_WARMUP)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_suppressed_during_warmup() {
+        let warmup = HashrateWarmup::new(Duration::from_millis(50));
+        assert!(!warmup.is_warming_up());
+
+        warmup.start();
+        assert!(warmup.is_warming_up());
+        assert_eq!(warmup.report(10), 0);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!warmup.is_warming_up());
+        assert_eq!(warmup.report(1_000), 1_000);
+
+        // A restart reports the previous stable value until warmed up again
+        warmup.start();
+        assert_eq!(warmup.report(5), 1_000);
+    }
+
+    #[test]
+    fn test_zero_warmup_reports_immediately() {
+        let warmup = HashrateWarmup::new(Duration::ZERO);
+        warmup.start();
+        assert!(!warmup.is_warming_up());
+        assert_eq!(warmup.report(42), 42);
+    }
+}
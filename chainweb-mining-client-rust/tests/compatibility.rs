@@ -97,12 +97,10 @@ fn test_configuration_compatibility() {
     assert_eq!(config.node.timeout_secs, 30);
     assert_eq!(config.mining.update_interval_secs, 5);
 
-    match config.worker {
-        WorkerConfig::Cpu { batch_size, .. } => {
-            assert_eq!(batch_size, 100_000);
-        }
-        _ => panic!("Default should be CPU worker"),
-    }
+    assert!(
+        matches!(config.worker, WorkerConfig::Stratum { .. }),
+        "Default should be stratum worker"
+    );
 }
 
 #[test]
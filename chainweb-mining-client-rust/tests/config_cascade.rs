@@ -130,7 +130,9 @@ logLevel = "info""#
     )
     .unwrap();
 
-    // Simulate CLI args that would override config file
+    // Simulate CLI args that would override config file; the key of the
+    // final config has to be well-formed
+    const CLI_KEY: &str = "368820f80c324bbc7c2b0610688a7da43e39f91d118732671cd9c7500ff43cca";
     let args = Args::parse_from([
         "chainweb-mining-client",
         "--config-file",
@@ -139,7 +141,7 @@ logLevel = "info""#
         "cli.chainweb.com",
         "--no-tls",
         "--public-key",
-        "cli_key",
+        CLI_KEY,
         "--log-level",
         "debug",
     ]);
@@ -150,8 +152,8 @@ logLevel = "info""#
     // Verify CLI args take precedence
     assert_eq!(config.node.url, "cli.chainweb.com"); // CLI override
     assert!(!config.node.use_tls); // CLI override (--no-tls)
-    assert_eq!(config.mining.public_key, "cli_key"); // CLI override
-    assert_eq!(config.mining.account, "k:cli_key"); // Auto-generated from CLI key
+    assert_eq!(config.mining.public_key, CLI_KEY); // CLI override
+    assert_eq!(config.mining.account, format!("k:{}", CLI_KEY)); // Auto-generated from CLI key
     assert_eq!(config.logging.level, "debug"); // CLI override
 }
 
@@ -160,10 +160,11 @@ async fn test_config_url_error_handling() {
 
     mock_404.assert_async().await;
 
-    // Test 500 error
+    // Test 500 error, retried before giving up
     let mock_500 = server
         .mock("GET", "/error.yaml")
         .with_status(500)
+        .expect(3)
         .create_async()
         .await;
 
@@ -175,6 +176,54 @@ async fn test_config_url_error_handling() {
     mock_500.assert_async().await;
 }
 
+#[tokio::test]
+async fn test_config_url_retries_transient_failure() {
+    use mockito::Server;
+
+    let mut server = Server::new_async().await;
+    let failure = server
+        .mock("GET", "/config.yaml")
+        .with_status(503)
+        .expect(1)
+        .create_async()
+        .await;
+    let success = server
+        .mock("GET", "/config.yaml")
+        .with_status(200)
+        .with_body("node: retried.chainweb.com\npublicKey: test\n")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let url = format!("{}/config.yaml", server.url());
+    let config = Config::from_url_async(&url).await.unwrap();
+    assert_eq!(config.node.url, "retried.chainweb.com");
+
+    failure.assert_async().await;
+    success.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_config_url_unreachable() {
+    use mockito::Server;
+
+    // The response breaks off while the body is read
+    let mut server = Server::new_async().await;
+    let broken = server
+        .mock("GET", "/config.yaml")
+        .with_chunked_body(|_| Err(std::io::Error::other("connection lost")))
+        .expect(3)
+        .create_async()
+        .await;
+
+    let url = format!("{}/config.yaml", server.url());
+    let err = Config::from_url_async(&url).await.unwrap_err().to_string();
+    assert!(err.contains("Failed to fetch config"), "{}", err);
+    assert!(err.contains("after 3 attempt(s)"), "{}", err);
+
+    broken.assert_async().await;
+}
+
 #[tokio::test]
 async fn test_config_url_invalid_content() {
     use mockito::Server;
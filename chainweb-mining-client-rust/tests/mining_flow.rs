@@ -2,7 +2,8 @@
 
 use chainweb_mining_client::{
     config::{Config, LoggingConfig, MiningConfig, NodeConfig, WorkerConfig},
-    core::{Nonce, Target, Work},
+    core::{ChainId, Nonce, Target, Work},
+    protocol::chainweb::NodeInfo,
     workers::{
         Worker,
         cpu::{CpuWorker, CpuWorkerConfig},
@@ -27,6 +28,11 @@ async fn test_cpu_mining_workflow() {
         threads: 2,
         batch_size: 1000,
         update_interval: Duration::from_millis(100),
+        warmup: Duration::ZERO,
+        thread_priority: Default::default(),
+        midstate: true,
+        max_nonces_per_work: 0,
+        max_memory_mb: 0,
     };
     let worker = CpuWorker::new(cpu_config);
 
@@ -55,22 +61,47 @@ fn test_config_creation() {
             use_tls: true,
             timeout_secs: 30,
             chain_id: Some(0),
+            exclude_chains: Vec::new(),
             insecure: false,
+            fallback_urls: Vec::new(),
+            relay_urls: Vec::new(),
+            submit_encoding: Default::default(),
+            on_node_loss: Default::default(),
+            network: None,
+            dns_refresh_secs: None,
+            work_fetch_timeout_secs: None,
+            submit_timeout_secs: None,
+            http_headers: Vec::new(),
         },
         mining: MiningConfig {
             account: "test-account".to_string(),
             public_key: "test-key".to_string(),
             update_interval_secs: 5,
+            hashrate_warmup_secs: 0,
+            fallback_poll_interval_secs: 10,
+            max_concurrent_work_fetches: 4,
+            confirm_blocks: false,
+            confirm_delay_secs: 60,
+            rotation_accounts: Vec::new(),
+            account_rotation: None,
+            chain_accounts: Default::default(),
+            keyset: None,
         },
         worker: WorkerConfig::Cpu {
             threads: 4,
             batch_size: 10000,
+            thread_priority: Default::default(),
+            midstate: true,
+            max_nonces_per_work: 0,
+            max_memory_mb: 0,
         },
         logging: LoggingConfig {
             level: "info".to_string(),
             format: "plain".to_string(),
             file: None,
+            hashrate_log_interval_secs: 10,
         },
+        worker_defaulted: false,
     };
 
     assert!(config.validate().is_ok());
@@ -78,10 +109,13 @@ fn test_config_creation() {
 
 #[test]
 fn test_invalid_chain_id() {
-    let mut config = Config::default();
-    config.node.chain_id = Some(20); // Invalid
+    let info: NodeInfo = serde_json::from_str(
+        r#"{"nodeVersion": "mainnet01", "nodeApiVersion": "0.0", "nodeNumberOfChains": 20}"#,
+    )
+    .unwrap();
 
-    assert!(config.validate().is_err());
+    assert!(info.validate_chain_id(ChainId::new(19)).is_ok());
+    assert!(info.validate_chain_id(ChainId::new(20)).is_err()); // Invalid
 }
 
 #[tokio::test]
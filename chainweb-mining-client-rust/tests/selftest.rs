@@ -0,0 +1,22 @@
+//! Runs the `--selftest` command of the built binary
+
+use std::process::Command;
+
+#[test]
+fn test_selftest_command_passes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_chainweb-mining-client"))
+        .arg("--selftest")
+        .output()
+        .expect("failed to run the mining client");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "self-test failed:\n{}\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("PASS  blake2s known answers"));
+    assert!(stdout.contains("PASS  simd and scalar hashes agree"));
+    assert!(!stdout.contains("FAIL"));
+}